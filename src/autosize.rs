@@ -0,0 +1,132 @@
+//! Shrinking text to fit its element for [`Config::autosize_text`](crate::Config::autosize_text).
+
+use crate::error::{Error, Result};
+use blitz_dom::{local_name, ns, BaseDocument, QualName};
+use blitz_html::HtmlDocument;
+
+/// Smallest fraction of its original font size text is shrunk to.
+const MIN_SCALE: f32 = 0.1;
+
+/// Binary search steps, each laying the document out again.
+const SEARCH_STEPS: usize = 8;
+
+/// Widths within this many CSS pixels of the content box still fit.
+const TOLERANCE: f32 = 0.5;
+
+/// An element being fitted.
+struct Target {
+    node_id: usize,
+    /// The element's `style` attribute before fitting.
+    style: Option<String>,
+    /// Computed font size in CSS pixels before fitting.
+    font_size: f32,
+    /// Largest scale known to fit.
+    fits: f32,
+    /// Smallest scale known to overflow.
+    overflows: f32,
+}
+
+/// Shrink the font size of the elements matching `selector` until their
+/// text fits their width.
+///
+/// Each trial size is set through the element's `style` attribute and
+/// checked after `resolve` lays the document out again. All matched
+/// elements are searched together, so every step costs one layout. Styles
+/// must already be resolved; the document is left resolved at the final
+/// sizes.
+pub(crate) fn autosize_text(
+    document: &mut HtmlDocument,
+    selector: &str,
+    mut resolve: impl FnMut(&mut HtmlDocument),
+) -> Result<()> {
+    let doc: &BaseDocument = document;
+    let elements = doc.query_selector_all(selector).map_err(|_| {
+        Error::InvalidConfig(format!("autosize_text selector is invalid: {}", selector))
+    })?;
+
+    let mut targets: Vec<Target> = elements
+        .into_iter()
+        .filter(|&node_id| !text_fits(doc, node_id))
+        .filter_map(|node_id| {
+            let node = doc.get_node(node_id)?;
+            let font_size = node
+                .primary_styles()?
+                .get_font()
+                .font_size
+                .computed_size()
+                .px();
+            Some(Target {
+                node_id,
+                style: node.attr(local_name!("style")).map(str::to_string),
+                font_size,
+                fits: MIN_SCALE,
+                overflows: 1.0,
+            })
+        })
+        .collect();
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    for _ in 0..SEARCH_STEPS {
+        let trials: Vec<f32> = targets
+            .iter()
+            .map(|target| (target.fits + target.overflows) / 2.0)
+            .collect();
+        set_font_sizes(document, &targets, &trials);
+        resolve(document);
+        for (target, trial) in targets.iter_mut().zip(trials) {
+            if text_fits(document, target.node_id) {
+                target.fits = trial;
+            } else {
+                target.overflows = trial;
+            }
+        }
+    }
+
+    let scales: Vec<f32> = targets.iter().map(|target| target.fits).collect();
+    set_font_sizes(document, &targets, &scales);
+    resolve(document);
+    Ok(())
+}
+
+/// Set each target's font size to its original size times its scale.
+fn set_font_sizes(document: &mut HtmlDocument, targets: &[Target], scales: &[f32]) {
+    let mut mutator = document.mutate();
+    for (target, scale) in targets.iter().zip(scales) {
+        let declaration = format!("font-size: {}px !important", target.font_size * scale);
+        let style = match &target.style {
+            Some(style) => format!("{}; {}", style, declaration),
+            None => declaration,
+        };
+        mutator.set_attribute(
+            target.node_id,
+            QualName::new(None, ns!(), local_name!("style")),
+            &style,
+        );
+    }
+}
+
+/// Whether every line of text laid out inside the element fits the width
+/// of the box it is laid out in.
+fn text_fits(doc: &BaseDocument, node_id: usize) -> bool {
+    let Some(node) = doc.get_node(node_id) else {
+        return true;
+    };
+    if let Some(text_layout) = node
+        .element_data()
+        .and_then(|element| element.inline_layout_data.as_ref())
+    {
+        let layout = &node.final_layout;
+        let content_width = layout.size.width
+            - layout.padding.left
+            - layout.padding.right
+            - layout.border.left
+            - layout.border.right;
+        let text_width = text_layout.layout.width() / doc.viewport().scale();
+        if text_width > content_width + TOLERANCE {
+            return false;
+        }
+    }
+    node.children.iter().all(|&child| text_fits(doc, child))
+}
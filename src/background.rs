@@ -0,0 +1,100 @@
+//! Decoding and placement of [`Config::background_image`](crate::Config::background_image).
+
+use crate::config::{BackgroundFit, Config};
+use crate::error::{Error, Result};
+use blitz_dom::Node;
+use image::imageops::FilterType;
+use std::sync::Arc;
+use style::values::specified::image::ImageRendering;
+
+/// A decoded background image, shared by every paint of a render.
+#[cfg_attr(not(any(feature = "png", feature = "pdf")), allow(dead_code))]
+pub(crate) struct DecodedBackground {
+    /// RGBA8 pixels with straight (unpremultiplied) alpha.
+    pub pixels: Arc<Vec<u8>>,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Width in CSS pixels, from the source image before any downscaling.
+    pub natural_width: u32,
+    /// Height in CSS pixels, from the source image before any downscaling.
+    pub natural_height: u32,
+    /// How the image is fitted to the page.
+    pub fit: BackgroundFit,
+}
+
+/// Where a single copy of the image is drawn, in page units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Placement {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Decode the configured background image, if there is one, downscaling it
+/// to fit within [`Config::max_decoded_image_dimension`] pixels in each
+/// direction.
+///
+/// Renders decode it once up front and hand it to each paint, rather than
+/// decoding it again for every pass and frame.
+#[cfg_attr(not(any(feature = "png", feature = "pdf")), allow(dead_code))]
+pub(crate) fn decode(config: &Config) -> Result<Option<DecodedBackground>> {
+    let Some(image) = &config.background_image else {
+        return Ok(None);
+    };
+    let mut decoded = image::load_from_memory(&image.data).map_err(|e| {
+        Error::InvalidConfig(format!("background image could not be decoded: {}", e))
+    })?;
+    let (natural_width, natural_height) = (decoded.width(), decoded.height());
+    if let Some(limit) = config.max_decoded_image_dimension {
+        if natural_width > limit || natural_height > limit {
+            decoded = decoded.resize(limit, limit, FilterType::Lanczos3);
+        }
+    }
+    let decoded = decoded.into_rgba8();
+    Ok(Some(DecodedBackground {
+        width: decoded.width(),
+        height: decoded.height(),
+        natural_width,
+        natural_height,
+        pixels: Arc::new(decoded.into_raw()),
+        fit: image.fit,
+    }))
+}
+
+#[cfg_attr(not(any(feature = "png", feature = "pdf")), allow(dead_code))]
+impl DecodedBackground {
+    /// Placement of the image on a `page_width` x `page_height` page, or
+    /// `None` for [`BackgroundFit::Tile`], which repeats from the origin at
+    /// the image's natural size.
+    pub fn placement(&self, page_width: f32, page_height: f32) -> Option<Placement> {
+        let (width, height) = (self.natural_width as f32, self.natural_height as f32);
+        let scale = match self.fit {
+            BackgroundFit::Tile => return None,
+            BackgroundFit::Cover => (page_width / width).max(page_height / height),
+            BackgroundFit::Contain => (page_width / width).min(page_height / height),
+            BackgroundFit::Center => 1.0,
+        };
+        let (width, height) = (width * scale, height * scale);
+        Some(Placement {
+            x: (page_width - width) / 2.0,
+            y: (page_height - height) / 2.0,
+            width,
+            height,
+        })
+    }
+}
+
+/// Whether the node's `image-rendering` asks for its images to be scaled
+/// without smoothing, as `pixelated` and `crisp-edges` do.
+#[cfg_attr(not(any(feature = "png", feature = "pdf")), allow(dead_code))]
+pub(crate) fn is_pixelated(node: &Node) -> bool {
+    node.primary_styles().is_some_and(|style| {
+        matches!(
+            style.clone_image_rendering(),
+            ImageRendering::Pixelated | ImageRendering::CrispEdges
+        )
+    })
+}
@@ -0,0 +1,185 @@
+//! Resource limits for a single render.
+
+use crate::config::{Config, OutputFormat};
+use crate::error::{Error, Result};
+use blitz_dom::BaseDocument;
+use std::time::{Duration, Instant};
+
+/// Hard limits on the resources a single render may use.
+///
+/// Set with [`Config::budget`](crate::Config::budget). Every bound is
+/// optional; the [`Default`] values are generous enough for ordinary pages
+/// while stopping pathological input early. Exceeding a bound fails the
+/// render with the matching [`Error`] variant.
+///
+/// # Example
+///
+/// ```rust
+/// use hyper_render::{Config, RenderBudget};
+/// use std::time::Duration;
+///
+/// let budget = RenderBudget::default()
+///     .max_pixels(4_000_000)
+///     .deadline(Duration::from_secs(5));
+/// let config = Config::new().budget(budget);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderBudget {
+    /// Maximum number of output pixels in device pixels (default: 100 million).
+    ///
+    /// Counted across every frame for animated output.
+    pub max_pixels: Option<u64>,
+
+    /// Maximum output height in CSS pixels (default: 50,000).
+    ///
+    /// Mostly relevant with [`Config::auto_height`](crate::Config::auto_height),
+    /// where the height comes from the content.
+    pub max_height: Option<u32>,
+
+    /// Maximum total size in bytes of the data URIs embedded in the HTML
+    /// (default: 16 MiB).
+    pub max_resource_bytes: Option<usize>,
+
+    /// Maximum nesting depth of the DOM tree (default: 512).
+    pub max_depth: Option<usize>,
+
+    /// Maximum wall-clock time for the render (default: 30 seconds).
+    ///
+    /// Checked between pipeline stages, while blurring backdrops and before
+    /// rasterizing each 3D transform for PDF output; other stages already
+    /// in progress (such as rasterization) are not interrupted.
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RenderBudget {
+    fn default() -> Self {
+        Self {
+            max_pixels: Some(100_000_000),
+            max_height: Some(50_000),
+            max_resource_bytes: Some(16 * 1024 * 1024),
+            max_depth: Some(512),
+            deadline: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl RenderBudget {
+    /// Create a budget with the default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a budget with no limits, to opt into individual bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::RenderBudget;
+    ///
+    /// let budget = RenderBudget::unlimited().max_depth(64);
+    /// assert_eq!(budget.max_pixels, None);
+    /// ```
+    pub fn unlimited() -> Self {
+        Self {
+            max_pixels: None,
+            max_height: None,
+            max_resource_bytes: None,
+            max_depth: None,
+            deadline: None,
+        }
+    }
+
+    /// Set the maximum number of output pixels.
+    pub fn max_pixels(mut self, pixels: u64) -> Self {
+        self.max_pixels = Some(pixels);
+        self
+    }
+
+    /// Set the maximum output height in CSS pixels.
+    pub fn max_height(mut self, height: u32) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Set the maximum total size of embedded data URIs in bytes.
+    pub fn max_resource_bytes(mut self, bytes: usize) -> Self {
+        self.max_resource_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the maximum DOM nesting depth.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Set the maximum wall-clock time for the render.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Check the parsed document against the depth and resource bounds.
+    pub(crate) fn check_document(&self, doc: &BaseDocument) -> Result<()> {
+        if let Some(limit) = self.max_depth {
+            let depth = document_depth(doc);
+            if depth > limit {
+                return Err(Error::DepthLimitExceeded { depth, limit });
+            }
+        }
+        if let Some(limit) = self.max_resource_bytes {
+            let bytes = crate::resources::data_uri_bytes(doc);
+            if bytes > limit {
+                return Err(Error::ResourceLimitExceeded { bytes, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the output size, with `height` in CSS pixels, against the
+    /// height and pixel bounds.
+    pub(crate) fn check_output(&self, height: u32, config: &Config) -> Result<()> {
+        if let Some(limit) = self.max_height {
+            if height > limit {
+                return Err(Error::HeightLimitExceeded { height, limit });
+            }
+        }
+        if let Some(limit) = self.max_pixels {
+            let scale = config.device_scale() as f64;
+            let frames = match config.format {
+                OutputFormat::Gif => config.frames as u64,
+                _ => 1,
+            };
+            let pixels = (config.width as f64 * scale) as u64 * (height as f64 * scale) as u64;
+            let pixels = pixels.saturating_mul(frames);
+            if pixels > limit {
+                return Err(Error::PixelLimitExceeded { pixels, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if more than the allowed time has passed since `started`.
+    pub(crate) fn check_deadline(&self, started: Instant) -> Result<()> {
+        match self.deadline {
+            Some(deadline) if started.elapsed() > deadline => {
+                Err(Error::DeadlineExceeded(deadline))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Depth of the deepest node below the document root.
+fn document_depth(doc: &BaseDocument) -> usize {
+    // Iterative, since the trees being guarded against can overflow the stack
+    let mut max_depth = 0;
+    let mut stack = vec![(doc.root_node().id, 0)];
+    while let Some((node_id, depth)) = stack.pop() {
+        max_depth = max_depth.max(depth);
+        if let Some(node) = doc.get_node(node_id) {
+            stack.extend(node.children.iter().map(|&child| (child, depth + 1)));
+        }
+    }
+    max_depth
+}
@@ -0,0 +1,117 @@
+//! Table captions, which Blitz doesn't lay out.
+
+use blitz_dom::{local_name, BaseDocument};
+use style::values::computed::table::CaptionSide;
+use style::values::computed::Display;
+use style_traits::ToCss;
+
+/// A table with captions to lay out around it.
+struct CaptionedTable {
+    table_id: usize,
+    /// Captions with `caption-side: top`, in document order.
+    top: Vec<usize>,
+    /// Captions with `caption-side: bottom`, in document order.
+    bottom: Vec<usize>,
+    /// Width for the captions: the table's percentage, or else the width it
+    /// was laid out at.
+    width: String,
+    /// The table's margins, as top, right, bottom and left.
+    margin: [String; 4],
+}
+
+/// Lay out the `<caption>`s of tables above or below them.
+///
+/// Blitz lays out no boxes for `display: table-caption`, so captions are
+/// never drawn. This moves each caption out of its table to sit beside it,
+/// before or after it following its `caption-side`, set as a block as wide
+/// as the table and at the same horizontal margins. The table itself stays
+/// where it is in the tree, so selectors matching it or its rows still
+/// apply; its top or bottom margin moves to the outermost caption on that
+/// side, so the captions sit flush against it.
+///
+/// Styles must already be resolved; returns whether any caption was moved,
+/// in which case the document must be resolved again.
+pub(crate) fn place_captions(doc: &mut BaseDocument) -> bool {
+    let mut tables = Vec::new();
+    for (table_id, node) in doc.tree().iter() {
+        let Some(style) = node.primary_styles() else {
+            continue;
+        };
+        if node
+            .element_data()
+            .is_none_or(|element| element.name.local != local_name!("table"))
+            || node.parent.is_none()
+        {
+            continue;
+        }
+        let (mut top, mut bottom) = (Vec::new(), Vec::new());
+        for &child_id in &node.children {
+            let Some(child) = doc.get_node(child_id) else {
+                continue;
+            };
+            let is_caption = child
+                .element_data()
+                .is_some_and(|element| element.name.local == local_name!("caption"));
+            match child.primary_styles() {
+                Some(caption) if is_caption && caption.get_box().display != Display::None => {
+                    match caption.get_inherited_table().caption_side {
+                        CaptionSide::Top => top.push(child_id),
+                        CaptionSide::Bottom => bottom.push(child_id),
+                    }
+                }
+                _ => {}
+            }
+        }
+        if top.is_empty() && bottom.is_empty() {
+            continue;
+        }
+
+        let margin = style.get_margin();
+        let width = style.get_position().width.to_css_string();
+        tables.push(CaptionedTable {
+            table_id,
+            top,
+            bottom,
+            width: match width.ends_with('%') {
+                true => width,
+                false => format!("{}px", node.final_layout.size.width),
+            },
+            margin: [
+                margin.margin_top.to_css_string(),
+                margin.margin_right.to_css_string(),
+                margin.margin_bottom.to_css_string(),
+                margin.margin_left.to_css_string(),
+            ],
+        });
+    }
+
+    let changed = !tables.is_empty();
+    let mut mutator = doc.mutate();
+    for table in tables {
+        let [top, right, bottom, left] = &table.margin;
+        // Captions neither widen nor come out narrower than the table
+        for &caption_id in table.top.iter().chain(&table.bottom) {
+            mutator.set_style_property(caption_id, "display", "block");
+            mutator.set_style_property(caption_id, "box-sizing", "border-box");
+            mutator.set_style_property(caption_id, "width", &table.width);
+            mutator.set_style_property(caption_id, "margin-right", right);
+            mutator.set_style_property(caption_id, "margin-left", left);
+        }
+        if let Some(&first) = table.top.first() {
+            mutator.set_style_property(first, "margin-top", top);
+            mutator.set_style_property(table.table_id, "margin-top", "0");
+        }
+        if let Some(&last) = table.bottom.last() {
+            mutator.set_style_property(last, "margin-bottom", bottom);
+            mutator.set_style_property(table.table_id, "margin-bottom", "0");
+        }
+
+        if !table.top.is_empty() {
+            mutator.insert_nodes_before(table.table_id, &table.top);
+        }
+        if !table.bottom.is_empty() {
+            mutator.insert_nodes_after(table.table_id, &table.bottom);
+        }
+    }
+    changed
+}
@@ -0,0 +1,97 @@
+//! Locating the element cropped out by [`Config::capture`](crate::Config::capture),
+//! and the content captured by [`Config::capture_overhang`](crate::Config::capture_overhang).
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use blitz_dom::BaseDocument;
+
+/// The area of the page a capture crops to, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CaptureRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CaptureRect {
+    /// The rect in whole device pixels at `scale`, rounded outwards, as
+    /// `(x, y, width, height)`.
+    pub fn device_pixels(&self, scale: f32) -> (u32, u32, u32, u32) {
+        let left = (self.x * scale).floor().max(0.0) as u32;
+        let top = (self.y * scale).floor().max(0.0) as u32;
+        let right = ((self.x + self.width) * scale).ceil().max(0.0) as u32;
+        let bottom = ((self.y + self.height) * scale).ceil().max(0.0) as u32;
+        (
+            left,
+            top,
+            right.saturating_sub(left),
+            bottom.saturating_sub(top),
+        )
+    }
+}
+
+/// The border box of the first element matching [`Config::capture`],
+/// grown by [`Config::capture_padding`] and clipped to the page's left and
+/// top edges, or `None` if no capture is configured.
+///
+/// Fails with [`Error::InvalidConfig`] if the selector is invalid, matches
+/// no element, or matches one that takes up no space.
+pub(crate) fn capture_rect(doc: &BaseDocument, config: &Config) -> Result<Option<CaptureRect>> {
+    let Some(selector) = &config.capture else {
+        return Ok(None);
+    };
+    let node_id = doc
+        .query_selector(selector)
+        .map_err(|_| Error::InvalidConfig(format!("capture selector is invalid: {}", selector)))?
+        .ok_or_else(|| {
+            Error::InvalidConfig(format!("capture selector matches no element: {}", selector))
+        })?;
+    let node = doc
+        .get_node(node_id)
+        .ok_or_else(|| Error::InvalidConfig(format!("capture element is missing: {}", selector)))?;
+
+    let position = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return Err(Error::InvalidConfig(format!(
+            "capture element has no size: {}",
+            selector
+        )));
+    }
+
+    let padding = config.capture_padding as f32;
+    let x = (position.x - padding).max(0.0);
+    let y = (position.y - padding).max(0.0);
+    Ok(Some(CaptureRect {
+        x,
+        y,
+        width: position.x + size.width + padding - x,
+        height: position.y + size.height + padding - y,
+    }))
+}
+
+/// The page of `page_height` CSS pixels grown to hold all of the
+/// document's content, in whole CSS pixels, or `None` if
+/// [`Config::capture_overhang`] is off or a [`Config::capture`] is set.
+///
+/// Content overhanging the page's left or top edge gives the rect a
+/// negative `x` or `y`.
+pub(crate) fn overhang_rect(
+    doc: &BaseDocument,
+    config: &Config,
+    page_height: u32,
+) -> Option<CaptureRect> {
+    if !config.capture_overhang || config.capture.is_some() {
+        return None;
+    }
+    let [left, top, right, bottom] =
+        crate::render::content_bounds(doc).unwrap_or([0.0, 0.0, 0.0, 0.0]);
+    let (x, y) = (left.floor().min(0.0), top.floor().min(0.0));
+    Some(CaptureRect {
+        x,
+        y,
+        width: right.ceil().max(config.width as f32) - x,
+        height: bottom.ceil().max(page_height as f32) - y,
+    })
+}
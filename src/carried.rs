@@ -0,0 +1,141 @@
+//! Properties Stylo's servo build drops, carried through the cascade in
+//! custom properties so author rules in stylesheets reach them.
+
+use crate::counter_style::shorthand_type;
+use crate::first_letter::{split_top_level, top_level_rules};
+use blitz_dom::{local_name, ns, BaseDocument, QualName};
+use style::properties::ComputedValues;
+
+/// Custom property carrying author `list-style-type` values, as Stylo drops
+/// the declarations of types it doesn't parse.
+pub(crate) const LIST_STYLE_TYPE: &str = "--hyper-render-list-style-type";
+
+/// Custom property carrying `content-visibility`.
+pub(crate) const CONTENT_VISIBILITY: &str = "--hyper-render-content-visibility";
+
+/// Custom property carrying `line-clamp` and `-webkit-line-clamp`.
+pub(crate) const LINE_CLAMP: &str = "--hyper-render-line-clamp";
+
+/// Custom property carrying `tab-size`.
+pub(crate) const TAB_SIZE: &str = "--hyper-render-tab-size";
+
+/// Custom property carrying `hyphens` and `-webkit-hyphens`.
+pub(crate) const HYPHENS: &str = "--hyper-render-hyphens";
+
+/// Custom property carrying `font-variant-numeric`.
+pub(crate) const FONT_VARIANT_NUMERIC: &str = "--hyper-render-font-variant-numeric";
+
+/// Longhands copied as they are, and the custom property carrying each.
+const LONGHANDS: &[(&str, &str)] = &[
+    ("list-style-type", LIST_STYLE_TYPE),
+    ("content-visibility", CONTENT_VISIBILITY),
+    ("line-clamp", LINE_CLAMP),
+    ("-webkit-line-clamp", LINE_CLAMP),
+    ("tab-size", TAB_SIZE),
+    ("hyphens", HYPHENS),
+    ("-webkit-hyphens", HYPHENS),
+    ("font-variant-numeric", FONT_VARIANT_NUMERIC),
+];
+
+/// Rules resetting the carried values ahead of the copied author
+/// declarations. Custom properties inherit, so those of properties that
+/// don't are reset on every element, and the list style type where the
+/// user agent stylesheet sets `list-style-type`.
+const RESET_CSS: &str = "\
+:where(ol, ul, menu, dir) { --hyper-render-list-style-type: initial; }
+:where(*) { --hyper-render-content-visibility: initial; --hyper-render-line-clamp: initial; }
+";
+
+/// Carry author declarations of the properties above through the cascade.
+///
+/// This copies every such declaration in the document's `<style>` elements
+/// into its custom property on the same selectors, in source order, and
+/// appends the copies to `style` attributes declaring one. Returns the
+/// stylesheet holding the copies, or `None` if there are no declarations;
+/// it must be injected before styles are resolved.
+///
+/// Rules inside `@media` and `@supports` are copied; other at-rules are not.
+pub(crate) fn carry_properties(doc: &mut BaseDocument) -> Option<String> {
+    let mut css = String::new();
+    let mut inline = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        if element.name.local == local_name!("style") {
+            css.push_str(&copy_rules(&node.text_content()));
+        } else if let Some(style) = element.attr(local_name!("style")) {
+            if let Some(declarations) = copy_declarations(style) {
+                inline.push((node_id, format!("{}; {}", style, declarations)));
+            }
+        }
+    }
+
+    let mut mutator = doc.mutate();
+    for (node_id, style) in &inline {
+        mutator.set_attribute(
+            *node_id,
+            QualName::new(None, ns!(), local_name!("style")),
+            style,
+        );
+    }
+    // Inline copies need the resets too, to keep them from inheriting
+    (!css.is_empty() || !inline.is_empty()).then(|| format!("{}{}", RESET_CSS, css))
+}
+
+/// The value carried in `property` for an element with `style`, if any
+/// author declaration set one.
+pub(crate) fn value(style: &ComputedValues, property: &str) -> Option<String> {
+    style
+        .custom_properties()
+        .inherited
+        .get(&style::Atom::from(&property[2..]))
+        .and_then(|value| value.as_universal())
+        .map(|value| value.css.trim().to_string())
+}
+
+/// Rules setting the carried values for the rules in `css` that declare one.
+fn copy_rules(css: &str) -> String {
+    let mut copies = String::new();
+    for (prelude, block) in top_level_rules(css) {
+        let lower = prelude.to_ascii_lowercase();
+        if lower.starts_with("@media") || lower.starts_with("@supports") {
+            let nested = copy_rules(block);
+            if !nested.is_empty() {
+                copies.push_str(&format!("{} {{\n{}}}\n", prelude, nested));
+            }
+        } else if !prelude.starts_with('@') {
+            if let Some(declarations) = copy_declarations(block) {
+                copies.push_str(&format!("{} {{ {} }}\n", prelude, declarations));
+            }
+        }
+    }
+    copies
+}
+
+/// Declarations of the carried values for the carried declarations in
+/// `block`, in order, so the last of each still wins.
+fn copy_declarations(block: &str) -> Option<String> {
+    let mut copies = Vec::new();
+    for declaration in split_top_level(block, ';') {
+        let Some((name, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        let (value, important) = match value.rfind('!') {
+            Some(bang) if value[bang + 1..].trim().eq_ignore_ascii_case("important") => {
+                (value[..bang].trim(), " !important")
+            }
+            _ => (value.trim(), ""),
+        };
+        let (property, value) = match LONGHANDS.iter().find(|(longhand, _)| *longhand == name) {
+            Some(&(_, property)) => (property, value.to_string()),
+            None if name == "list-style" => (LIST_STYLE_TYPE, shorthand_type(value)),
+            None => continue,
+        };
+        if !value.is_empty() {
+            copies.push(format!("{}: {}{}", property, value, important));
+        }
+    }
+    (!copies.is_empty()).then(|| copies.join("; "))
+}
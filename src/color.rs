@@ -0,0 +1,51 @@
+//! CSS color parsing for configuration values.
+
+use crate::error::{Error, Result};
+use blitz_traits::net::Url;
+use cssparser::ParserInput;
+use style::color::{AbsoluteColor, ColorSpace};
+use style::context::QuirksMode;
+use style::parser::ParserContext;
+use style::stylesheets::{CssRuleType, Origin, UrlExtraData};
+use style::values::computed::Color as ComputedColor;
+use style::values::specified::Color as SpecifiedColor;
+use style::values::Parser;
+use style_traits::ParsingMode;
+
+/// Parse a CSS color string (`"navy"`, `"#ff8800"`, `"rgba(0, 0, 0, 0.5)"`, ...)
+/// into RGBA bytes using Stylo's color parser.
+///
+/// Colors outside the sRGB gamut are clamped. `currentcolor` is rejected since
+/// there is no element to take it from, as is anything after the color.
+pub(crate) fn parse_css_color(css: &str) -> Result<[u8; 4]> {
+    let invalid = || Error::InvalidConfig(format!("invalid CSS color: {:?}", css));
+
+    let url_data = UrlExtraData::from(Url::parse("about:blank").expect("valid URL"));
+    let context = ParserContext::new(
+        Origin::Author,
+        &url_data,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        Default::default(),
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+
+    let color = match SpecifiedColor::parse_and_compute(&context, &mut parser, None) {
+        Some(ComputedColor::CurrentColor) | None => return Err(invalid()),
+        Some(color) => color.resolve_to_absolute(&AbsoluteColor::BLACK),
+    };
+    parser.expect_exhausted().map_err(|_| invalid())?;
+
+    let srgb = color.to_color_space(ColorSpace::Srgb);
+    let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Ok([
+        to_byte(srgb.components.0),
+        to_byte(srgb.components.1),
+        to_byte(srgb.components.2),
+        to_byte(srgb.alpha),
+    ])
+}
@@ -1,15 +1,30 @@
 //! Configuration types for rendering.
 
+use crate::budget::RenderBudget;
+use crate::color::parse_css_color;
 use crate::error::{Error, Result};
+use crate::icc;
+use crate::overlay::{Overlay, OverlayTarget};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Output format for rendered content.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum OutputFormat {
     /// PNG image format (raster).
     #[default]
     Png,
     /// PDF document format (vector).
     Pdf,
+    /// Animated GIF format (raster, requires the `gif` feature).
+    Gif,
+    /// SVG image format (vector, requires the `svg` feature).
+    Svg,
+    /// Multi-page TIFF format (lossless raster, requires the `tiff` feature).
+    Tiff,
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -17,6 +32,9 @@ impl std::fmt::Display for OutputFormat {
         match self {
             OutputFormat::Png => write!(f, "png"),
             OutputFormat::Pdf => write!(f, "pdf"),
+            OutputFormat::Gif => write!(f, "gif"),
+            OutputFormat::Svg => write!(f, "svg"),
+            OutputFormat::Tiff => write!(f, "tiff"),
         }
     }
 }
@@ -40,6 +58,365 @@ impl From<ColorScheme> for blitz_traits::shell::ColorScheme {
     }
 }
 
+/// PDF specification version to target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum PdfVersion {
+    /// PDF 1.4.
+    Pdf14,
+    /// PDF 1.5.
+    Pdf15,
+    /// PDF 1.6.
+    Pdf16,
+    /// PDF 1.7.
+    #[default]
+    Pdf17,
+    /// PDF 2.0.
+    Pdf20,
+}
+
+impl std::fmt::Display for PdfVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdfVersion::Pdf14 => write!(f, "1.4"),
+            PdfVersion::Pdf15 => write!(f, "1.5"),
+            PdfVersion::Pdf16 => write!(f, "1.6"),
+            PdfVersion::Pdf17 => write!(f, "1.7"),
+            PdfVersion::Pdf20 => write!(f, "2.0"),
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<PdfVersion> for krilla::configure::PdfVersion {
+    fn from(version: PdfVersion) -> Self {
+        match version {
+            PdfVersion::Pdf14 => krilla::configure::PdfVersion::Pdf14,
+            PdfVersion::Pdf15 => krilla::configure::PdfVersion::Pdf15,
+            PdfVersion::Pdf16 => krilla::configure::PdfVersion::Pdf16,
+            PdfVersion::Pdf17 => krilla::configure::PdfVersion::Pdf17,
+            PdfVersion::Pdf20 => krilla::configure::PdfVersion::Pdf20,
+        }
+    }
+}
+
+/// PDF/A archival conformance level.
+///
+/// Conforming output embeds all fonts and an sRGB output intent. Content
+/// that the level forbids (such as transparency under PDF/A-1) makes the
+/// render fail with [`Error::PdfCreate`] rather than produce an invalid file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfA {
+    /// PDF/A-1b (PDF 1.4 only).
+    A1b,
+    /// PDF/A-2b (PDF 1.4 to 1.7).
+    A2b,
+    /// PDF/A-2u: PDF/A-2b plus Unicode mappings for all text (PDF 1.4 to 1.7).
+    A2u,
+    /// PDF/A-3b: PDF/A-2b that also permits embedded files (PDF 1.4 to 1.7).
+    A3b,
+}
+
+impl PdfA {
+    /// Whether this conformance level can be used with the given PDF version.
+    pub fn supports_version(self, version: PdfVersion) -> bool {
+        match self {
+            PdfA::A1b => version == PdfVersion::Pdf14,
+            PdfA::A2b | PdfA::A2u | PdfA::A3b => version <= PdfVersion::Pdf17,
+        }
+    }
+}
+
+impl std::fmt::Display for PdfA {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdfA::A1b => write!(f, "PDF/A-1b"),
+            PdfA::A2b => write!(f, "PDF/A-2b"),
+            PdfA::A2u => write!(f, "PDF/A-2u"),
+            PdfA::A3b => write!(f, "PDF/A-3b"),
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<PdfA> for krilla::configure::Validator {
+    fn from(level: PdfA) -> Self {
+        match level {
+            PdfA::A1b => krilla::configure::Validator::A1_B,
+            PdfA::A2b => krilla::configure::Validator::A2_B,
+            PdfA::A2u => krilla::configure::Validator::A2_U,
+            PdfA::A3b => krilla::configure::Validator::A3_B,
+        }
+    }
+}
+
+/// Numbering style of a [`PageLabelRange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageLabelStyle {
+    /// Arabic numerals (1, 2, 3, ...).
+    #[default]
+    Decimal,
+    /// Lowercase Roman numerals (i, ii, iii, ...).
+    RomanLower,
+    /// Uppercase Roman numerals (I, II, III, ...).
+    RomanUpper,
+    /// Lowercase letters (a to z, then aa to zz, ...).
+    LettersLower,
+    /// Uppercase letters (A to Z, then AA to ZZ, ...).
+    LettersUpper,
+}
+
+#[cfg(feature = "pdf")]
+impl From<PageLabelStyle> for krilla::page::NumberingStyle {
+    fn from(style: PageLabelStyle) -> Self {
+        match style {
+            PageLabelStyle::Decimal => krilla::page::NumberingStyle::Arabic,
+            PageLabelStyle::RomanLower => krilla::page::NumberingStyle::LowerRoman,
+            PageLabelStyle::RomanUpper => krilla::page::NumberingStyle::UpperRoman,
+            PageLabelStyle::LettersLower => krilla::page::NumberingStyle::LowerAlpha,
+            PageLabelStyle::LettersUpper => krilla::page::NumberingStyle::UpperAlpha,
+        }
+    }
+}
+
+/// How the PDF pages from `start` up to the next range are labeled.
+///
+/// Used by [`Config::page_labels`]. PDF viewers show a page's label in
+/// place of its position, so front matter can be numbered i, ii, ...
+/// before the body restarts at 1.
+///
+/// ```rust
+/// use hyper_render::{PageLabelRange, PageLabelStyle};
+///
+/// let appendix = PageLabelRange::new(10, PageLabelStyle::Decimal).prefix("A-");
+/// assert_eq!(appendix.first_number, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageLabelRange {
+    /// Zero-based index of the first page in the range.
+    pub start: usize,
+
+    /// Numbering style of the pages.
+    pub style: PageLabelStyle,
+
+    /// Text put before each page's number (default: none).
+    pub prefix: Option<String>,
+
+    /// Number of the first page in the range (default: 1).
+    pub first_number: u32,
+}
+
+impl PageLabelRange {
+    /// Create a range starting at page index `start`, numbered from 1.
+    pub fn new(start: usize, style: PageLabelStyle) -> Self {
+        Self {
+            start,
+            style,
+            prefix: None,
+            first_number: 1,
+        }
+    }
+
+    /// Put `prefix` before each page's number (e.g. `"A-"` for A-1, A-2, ...).
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Number the first page in the range `number` rather than 1.
+    pub fn first_number(mut self, number: u32) -> Self {
+        self.first_number = number;
+        self
+    }
+}
+
+/// How to handle resources referenced by URL that fail to load.
+///
+/// Applies to images, stylesheets and fonts requested by the document.
+/// hyper-render does not load resources over the network, so every such
+/// URL fails; `data:` URIs are embedded in the document and unaffected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourcePolicy {
+    /// Render without the resource and report a
+    /// [`Warning::ResourceNotLoaded`](crate::Warning::ResourceNotLoaded).
+    #[default]
+    BestEffort,
+    /// Fail the render with [`Error::Network`] on the first resource that
+    /// could not be loaded.
+    Strict,
+}
+
+/// What a render returns when it fails.
+///
+/// Used by [`Config::on_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Return the [`Error`].
+    #[default]
+    Propagate,
+    /// Return a placeholder of the configured size and format showing
+    /// "Render failed" and the error message, and report a
+    /// [`Warning::RenderFailed`](crate::Warning::RenderFailed).
+    Placeholder,
+}
+
+/// Clockwise rotation applied to the finished output.
+///
+/// Used by [`Config::rotate_output`]. Quarter turns swap the output's width
+/// and height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// No rotation.
+    #[default]
+    None,
+    /// 90 degrees clockwise.
+    Deg90,
+    /// 180 degrees.
+    Deg180,
+    /// 270 degrees clockwise (90 degrees counterclockwise).
+    Deg270,
+}
+
+impl Rotation {
+    /// Whether the rotation swaps width and height.
+    pub fn swaps_dimensions(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+
+    /// Output size of a `width` x `height` page after rotating.
+    pub(crate) fn rotated_size<T>(self, width: T, height: T) -> (T, T) {
+        if self.swaps_dimensions() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Affine transform `[a, b, c, d, e, f]` mapping a point `(x, y)` on a
+    /// `width` x `height` page to `(a*x + c*y + e, b*x + d*y + f)` on the
+    /// rotated page.
+    #[cfg_attr(not(any(feature = "pdf", feature = "svg")), allow(dead_code))]
+    pub(crate) fn transform(self, width: f32, height: f32) -> [f32; 6] {
+        match self {
+            Rotation::None => [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            Rotation::Deg90 => [0.0, 1.0, -1.0, 0.0, height, 0.0],
+            Rotation::Deg180 => [-1.0, 0.0, 0.0, -1.0, width, height],
+            Rotation::Deg270 => [0.0, -1.0, 1.0, 0.0, 0.0, width],
+        }
+    }
+}
+
+/// Pixel format of PNG output.
+///
+/// Used by [`Config::png_color_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngColorType {
+    /// 8-bit RGBA.
+    #[default]
+    Rgba8,
+    /// 8-bit indexed color with a palette of at most 256 colors.
+    ///
+    /// Renders with 256 colors or fewer use them exactly; others are
+    /// quantized, with Floyd-Steinberg dithering when `dither` is set.
+    Indexed {
+        /// Diffuse quantization error to neighboring pixels, trading banding
+        /// in gradients for noise.
+        dither: bool,
+    },
+}
+
+/// How CSS pixels map to output pixels under [`Config::dpi`].
+///
+/// | Mode | Device pixels per CSS pixel | DPI recorded in PNG output |
+/// |------|-----------------------------|----------------------------|
+/// | [`CssPx`](PixelMode::CssPx) | `scale` | `dpi`, if set |
+/// | [`Physical`](PixelMode::Physical) | `scale * dpi / 96` | `dpi`, if set |
+///
+/// PDF output is measured in points, one per CSS pixel, in either mode;
+/// only the images it embeds for [`Config::rasterize_gradients`] and
+/// [`Config::flatten_transforms_for_pdf`] follow the device pixel ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelMode {
+    /// One CSS pixel is `scale` device pixels whatever the DPI, as in
+    /// browser screenshots.
+    #[default]
+    CssPx,
+    /// CSS pixels keep their physical size of 1/96 inch at the configured
+    /// DPI, as when printing.
+    Physical,
+}
+
+/// How [`Config::background_image`] is fitted to the page.
+///
+/// Images are sized at one CSS pixel per image pixel before fitting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackgroundFit {
+    /// Repeat the image at its natural size from the top-left corner.
+    Tile,
+    /// Scale the image to cover the page, cropping the overflow.
+    #[default]
+    Cover,
+    /// Scale the image to fit within the page, centered.
+    Contain,
+    /// Center the image at its natural size.
+    Center,
+}
+
+/// An encoded raster image drawn beneath the document content.
+///
+/// Set with [`Config::background_image`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackgroundImage {
+    /// Encoded PNG, JPEG, GIF or WebP bytes.
+    pub data: Arc<[u8]>,
+    /// How the image is fitted to the page.
+    pub fit: BackgroundFit,
+}
+
+/// A linear gradient described by an angle and a list of color stops.
+///
+/// Used by [`Config::page_gradient`] to paint the page background. The angle
+/// follows CSS conventions: `0.0` points to the top, `90.0` to the right and
+/// `180.0` (the default) to the bottom.
+///
+/// ```rust
+/// use hyper_render::LinearGradientSpec;
+///
+/// let gradient = LinearGradientSpec::new(135.0)
+///     .stop(0.0, [102, 126, 234, 255])
+///     .stop(1.0, [118, 75, 162, 255]);
+/// assert_eq!(gradient.stops.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearGradientSpec {
+    /// Gradient angle in degrees (CSS convention).
+    pub angle: f32,
+
+    /// Color stops as `(offset, rgba)` pairs, with offsets in `0.0..=1.0`.
+    pub stops: Vec<(f32, [u8; 4])>,
+}
+
+impl Default for LinearGradientSpec {
+    fn default() -> Self {
+        Self::new(180.0)
+    }
+}
+
+impl LinearGradientSpec {
+    /// Create a gradient with the given angle and no stops.
+    pub fn new(angle: f32) -> Self {
+        Self {
+            angle,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Append a color stop at `offset` (clamped to `0.0..=1.0` when drawn).
+    pub fn stop(mut self, offset: f32, rgba: [u8; 4]) -> Self {
+        self.stops.push((offset, rgba));
+        self
+    }
+}
+
 /// Configuration for HTML rendering.
 ///
 /// Use the builder pattern to construct a configuration:
@@ -68,7 +445,19 @@ pub struct Config {
     /// Scale factor for rendering (e.g., 2.0 for retina displays).
     pub scale: f32,
 
-    /// Output format (PNG or PDF).
+    /// Largest scale factor accepted by validation (default: [`Config::DEFAULT_MAX_SCALE`]).
+    pub max_scale: f32,
+
+    /// Output resolution in dots per inch (default: none, meaning 96).
+    pub dpi: Option<f32>,
+
+    /// How CSS pixels map to output pixels under `dpi` (default: [`PixelMode::CssPx`]).
+    pub pixel_mode: PixelMode,
+
+    /// Whether box edges are moved onto device pixel boundaries (default: false).
+    pub pixel_snap: bool,
+
+    /// Output format (PNG, PDF, GIF, SVG or TIFF).
     pub format: OutputFormat,
 
     /// Color scheme preference (light or dark mode).
@@ -80,8 +469,186 @@ pub struct Config {
     /// and use that instead of the configured height.
     pub auto_height: bool,
 
+    /// Whether the output keeps the configured size even with `auto_height`
+    /// (default: false).
+    pub force_page_size: bool,
+
+    /// Width to height ratio the height is derived from, replacing the
+    /// configured height (default: none).
+    pub aspect_ratio: Option<f32>,
+
     /// Background color as RGBA (default: white).
     pub background: [u8; 4],
+
+    /// Device pixels to crop off the final PNG as `[top, right, bottom, left]`
+    /// (default: none).
+    pub crop_margins: [u32; 4],
+
+    /// CSS selector for the element PNG output is cropped to (default: none).
+    pub capture: Option<String>,
+
+    /// CSS pixels of the page kept around the [`capture`](Config::capture)d
+    /// element (default: 0).
+    pub capture_padding: u32,
+
+    /// Grow PNG output to hold content overhanging the page's edges
+    /// (default: false).
+    pub capture_overhang: bool,
+
+    /// Clockwise rotation of the finished output (default: none).
+    pub rotation: Rotation,
+
+    /// Pixel format of PNG output (default: [`PngColorType::Rgba8`]).
+    pub png_color_type: PngColorType,
+
+    /// Background color as a CSS color string, overriding `background` when set.
+    ///
+    /// Parsed during validation; see [`Config::background_css`].
+    pub background_css: Option<String>,
+
+    /// Optional gradient painted over the page background (PDF only).
+    ///
+    /// Drawn before any document content, on top of `background`.
+    pub page_gradient: Option<LinearGradientSpec>,
+
+    /// Optional image painted beneath the document content.
+    pub background_image: Option<BackgroundImage>,
+
+    /// Directory of font files to register before rendering.
+    ///
+    /// Scanned recursively for `.ttf`, `.otf` and `.woff2` files.
+    pub fonts_dir: Option<PathBuf>,
+
+    /// Whether the fonts installed on the system are available (default: true).
+    ///
+    /// When disabled, text is set only in fonts from [`Config::fonts_dir`]
+    /// and `@font-face` rules, so output doesn't depend on the machine it
+    /// is rendered on.
+    pub system_fonts: bool,
+
+    /// Whether to report content that overflows the output bounds.
+    ///
+    /// When enabled, a [`Warning::ContentClipped`](crate::Warning::ContentClipped)
+    /// is raised if laid-out content exceeds the configured width, or the
+    /// configured height when `auto_height` is disabled.
+    pub overflow_guard: bool,
+
+    /// Whether warnings should fail the render.
+    ///
+    /// When enabled, the first warning is returned as
+    /// [`Error::Strict`](crate::Error::Strict) instead of being reported.
+    pub strict: bool,
+
+    /// Time in seconds that CSS animations are sampled at (default: 0).
+    pub resolve_timestamp: f64,
+
+    /// Number of frames to capture for animated output (GIF only).
+    pub frames: u32,
+
+    /// Time between captured frames for animated output (GIF only).
+    pub frame_duration: Duration,
+
+    /// Whether rendering fails when a character can't be shaped by any font.
+    ///
+    /// When enabled, text that falls back to the `.notdef` glyph ("tofu")
+    /// produces an [`Error::Font`] naming the missing characters.
+    pub require_full_coverage: bool,
+
+    /// Language of the document as a BCP 47 tag, replacing the `<html lang>`
+    /// attribute when set (default: none).
+    pub lang: Option<String>,
+
+    /// Whether `<script>` elements and inline event handlers are removed
+    /// from the HTML before rendering (default: false).
+    pub sanitize: bool,
+
+    /// Whether the HTML is rendered as a bare fragment, with no default
+    /// body margin (default: false).
+    pub fragment_mode: bool,
+
+    /// Whether each element's layout boxes are outlined on the output
+    /// (default: false).
+    pub debug_layout: bool,
+
+    /// Whether fonts form their default ligatures, such as `ffi` (default: true).
+    pub ligatures: bool,
+
+    /// CSS selector for elements whose text is shrunk to fit their width
+    /// (default: none).
+    pub autosize_text: Option<String>,
+
+    /// Light and dark stylesheets, one of which is applied based on `color_scheme`.
+    ///
+    /// See [`Config::theme_css`] for how these interact with the document's own styles.
+    pub theme_css: Option<(String, String)>,
+
+    /// Maximum size in bytes of a data URI embedded in the HTML (default: unlimited).
+    ///
+    /// Larger data URIs are removed before rendering and reported as
+    /// [`Warning::ResourceTooLarge`](crate::Warning::ResourceTooLarge).
+    pub max_resource_bytes: Option<usize>,
+
+    /// Largest width or height in CSS pixels any element may lay out to, set
+    /// by [`assert_dimensions`](Config::assert_dimensions) (default: unlimited).
+    pub max_element_px: Option<u32>,
+
+    /// Largest width or height in pixels of a decoded image
+    /// (default: unlimited).
+    pub max_decoded_image_dimension: Option<u32>,
+
+    /// How resources referenced by URL that fail to load are handled
+    /// (default: [`ResourcePolicy::BestEffort`]).
+    pub resource_policy: ResourcePolicy,
+
+    /// What a failed render returns (default: [`ErrorPolicy::Propagate`]).
+    pub on_error: ErrorPolicy,
+
+    /// Whether text is laid out again with `@font-face` fonts once they
+    /// load (default: true).
+    pub retry_layout_on_font_load: bool,
+
+    /// PDF version to write (default: 1.7).
+    pub pdf_version: PdfVersion,
+
+    /// PDF/A conformance level to enforce, if any (default: none).
+    pub pdf_conformance: Option<PdfA>,
+
+    /// Whether PDF output gets a bookmark outline of its headings (default: false).
+    pub generate_outline: bool,
+
+    /// Whether PDF output starts with a table of contents page linking to
+    /// its headings (default: false).
+    pub toc: bool,
+
+    /// Whether PDF content is clipped to the page (default: true).
+    pub clip_to_page: bool,
+
+    /// Labels of PDF pages by range, sorted by start page (default: none).
+    pub page_labels: Vec<PageLabelRange>,
+
+    /// ICC profile embedded as the PDF output intent (default: none).
+    pub icc_profile: Option<Arc<[u8]>>,
+
+    /// Whether PDF output draws gradient backgrounds as images painted by
+    /// the raster renderer (default: false).
+    pub rasterize_gradients: bool,
+
+    /// Whether PDF output draws elements with 3D transforms as images
+    /// painted by the raster renderer (default: false).
+    ///
+    /// Each element takes a paint pass of its own, so only the first 32 are
+    /// drawn as images; the rest are drawn as their 2D part.
+    pub flatten_transforms_for_pdf: bool,
+
+    /// Most threads [`render_batch`](crate::render_batch) renders on at once
+    /// (default: none, one per CPU).
+    pub concurrency: Option<usize>,
+
+    /// Hard limits on the render's resource use (default: none).
+    pub budget: Option<RenderBudget>,
+
+    /// Hook drawing on top of the painted document before encoding (default: none).
+    pub overlay: Option<Overlay>,
 }
 
 impl Default for Config {
@@ -90,10 +657,58 @@ impl Default for Config {
             width: 800,
             height: 600,
             scale: 1.0,
+            max_scale: Self::DEFAULT_MAX_SCALE,
+            dpi: None,
+            pixel_mode: PixelMode::CssPx,
+            pixel_snap: false,
             format: OutputFormat::Png,
             color_scheme: ColorScheme::Light,
             auto_height: false,
+            force_page_size: false,
+            aspect_ratio: None,
             background: [255, 255, 255, 255], // White
+            crop_margins: [0; 4],
+            capture: None,
+            capture_padding: 0,
+            capture_overhang: false,
+            rotation: Rotation::None,
+            png_color_type: PngColorType::Rgba8,
+            page_gradient: None,
+            background_image: None,
+            fonts_dir: None,
+            system_fonts: true,
+            overflow_guard: false,
+            strict: false,
+            frames: 1,
+            frame_duration: Duration::from_millis(100),
+            resolve_timestamp: 0.0,
+            require_full_coverage: false,
+            lang: None,
+            sanitize: false,
+            fragment_mode: false,
+            debug_layout: false,
+            ligatures: true,
+            theme_css: None,
+            autosize_text: None,
+            background_css: None,
+            max_resource_bytes: None,
+            max_element_px: None,
+            max_decoded_image_dimension: None,
+            resource_policy: ResourcePolicy::default(),
+            on_error: ErrorPolicy::default(),
+            retry_layout_on_font_load: true,
+            pdf_version: PdfVersion::default(),
+            pdf_conformance: None,
+            generate_outline: false,
+            toc: false,
+            clip_to_page: true,
+            page_labels: Vec::new(),
+            icc_profile: None,
+            rasterize_gradients: false,
+            flatten_transforms_for_pdf: false,
+            concurrency: None,
+            budget: None,
+            overlay: None,
         }
     }
 }
@@ -160,7 +775,9 @@ impl Config {
 
     /// Set the scale factor for rendering.
     ///
-    /// Use 2.0 for retina/HiDPI displays to get crisp output.
+    /// Use 2.0 for retina/HiDPI displays to get crisp output. The scale is
+    /// the device pixel ratio seen by `@media (resolution)` queries, so
+    /// `(min-resolution: 2dppx)` rules apply from 2.0.
     ///
     /// # Example
     ///
@@ -175,6 +792,103 @@ impl Config {
         self
     }
 
+    /// Set the largest scale factor accepted by validation.
+    ///
+    /// Output buffers grow with the square of the scale, so a typo such as
+    /// `scale(100.0)` would otherwise allocate gigabytes. Raise the limit only
+    /// when very high resolutions are intended.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().max_scale(16.0).scale(12.0);
+    /// assert!(config.validate().is_ok());
+    /// ```
+    pub fn max_scale(mut self, max_scale: f32) -> Self {
+        self.max_scale = max_scale;
+        self
+    }
+
+    /// Set the output resolution in dots per inch.
+    ///
+    /// PNG output records it in its `pHYs` chunk, which image editors and
+    /// print tools use for the physical size. Whether it also changes the
+    /// pixel dimensions depends on [`pixel_mode`](Config::pixel_mode): with
+    /// the default [`PixelMode::CssPx`], it doesn't.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, PixelMode};
+    ///
+    /// // A 4x6 inch print: 384x576 CSS pixels at 300 DPI
+    /// let config = Config::new()
+    ///     .size(384, 576)
+    ///     .dpi(300.0)
+    ///     .pixel_mode(PixelMode::Physical);
+    /// assert_eq!(config.device_scale(), 3.125);
+    /// ```
+    pub fn dpi(mut self, dpi: f32) -> Self {
+        self.dpi = Some(dpi);
+        self
+    }
+
+    /// Set how CSS pixels map to output pixels under [`dpi`](Config::dpi).
+    ///
+    /// See [`PixelMode`] for how the two combine with [`scale`](Config::scale).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, PixelMode};
+    ///
+    /// // Pixel-for-pixel with a browser screenshot, tagged as 300 DPI
+    /// let config = Config::new().dpi(300.0).pixel_mode(PixelMode::CssPx);
+    /// assert_eq!(config.device_scale(), 1.0);
+    /// ```
+    pub fn pixel_mode(mut self, mode: PixelMode) -> Self {
+        self.pixel_mode = mode;
+        self
+    }
+
+    /// Device pixels per CSS pixel, from [`scale`](Config::scale) and,
+    /// under [`PixelMode::Physical`], [`dpi`](Config::dpi).
+    ///
+    /// This is the device pixel ratio the document is rendered at, and the
+    /// one seen by `@media (resolution)` queries.
+    pub fn device_scale(&self) -> f32 {
+        match self.pixel_mode {
+            PixelMode::CssPx => self.scale,
+            PixelMode::Physical => self.scale * self.dpi.unwrap_or(Self::CSS_DPI) / Self::CSS_DPI,
+        }
+    }
+
+    /// Snap box edges to device pixel boundaries for crisp hairlines.
+    ///
+    /// Boxes can be laid out at fractional positions, for example below a
+    /// line of text whose line height isn't a whole number of pixels, or at
+    /// any position under a fractional [`scale`](Config::scale). A 1px
+    /// border or `<hr>` there is drawn blurred across two rows of
+    /// half-covered pixels. With snapping, every box edge is rounded to the
+    /// nearest device pixel, and border widths to whole device pixels, in
+    /// both PNG and PDF output. Boxes may move or change size by up to half
+    /// a device pixel; text keeps its position within its box.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().pixel_snap(true);
+    /// assert!(config.pixel_snap);
+    /// ```
+    pub fn pixel_snap(mut self, enabled: bool) -> Self {
+        self.pixel_snap = enabled;
+        self
+    }
+
     /// Set the output format.
     ///
     /// # Example
@@ -223,6 +937,64 @@ impl Config {
         self
     }
 
+    /// Keep the output at the configured `width` x `height`, ignoring the
+    /// content height with [`auto_height`](Config::auto_height).
+    ///
+    /// For configs that measure content without changing the output, such as
+    /// a fixed-size preview of a document of any length: content below the
+    /// configured height is clipped, and the height it would have taken is
+    /// still reported as [`RenderOutput::content_height`](crate::RenderOutput::content_height)
+    /// by [`render_detailed`](crate::render_detailed).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().size(400, 300).auto_height(true).force_page_size(true);
+    /// ```
+    pub fn force_page_size(mut self, enabled: bool) -> Self {
+        self.force_page_size = enabled;
+        self
+    }
+
+    /// Derive the height from the width at a fixed width to height ratio.
+    ///
+    /// For outputs with a set shape whatever their content, such as 1200x630
+    /// social cards: the height is the configured width divided by `ratio`,
+    /// rounded to a whole pixel, whenever the width is set. The document is
+    /// laid out in that viewport as usual, so content starts at the top and
+    /// anything below the derived height is cropped. Can't be combined with
+    /// [`auto_height`](Config::auto_height), which derives the height from
+    /// the content instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().width(1200).aspect_ratio(1200.0 / 630.0);
+    /// assert_eq!(config.aspect_height(), 630);
+    /// ```
+    pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// The height in CSS pixels: the configured height, or the one derived
+    /// from the width by [`aspect_ratio`](Config::aspect_ratio).
+    ///
+    /// An invalid ratio is left for [`validate`](Config::validate) to
+    /// reject, keeping the configured height.
+    pub fn aspect_height(&self) -> u32 {
+        match self.aspect_ratio {
+            Some(ratio) if ratio.is_finite() && ratio > 0.0 => {
+                (self.width as f32 / ratio).round() as u32
+            }
+            _ => self.height,
+        }
+    }
+
     /// Set the background color as RGBA values.
     ///
     /// # Example
@@ -238,28 +1010,1025 @@ impl Config {
     /// ```
     pub fn background(mut self, rgba: [u8; 4]) -> Self {
         self.background = rgba;
+        self.background_css = None;
         self
     }
 
-    /// Set a transparent background.
+    /// Set the background color from a CSS color string.
+    ///
+    /// Accepts anything Stylo parses as a color: named colors, hex notation,
+    /// and functions such as `rgb()`, `rgba()` or `hsl()`. Invalid input is
+    /// reported as [`Error::InvalidConfig`](crate::Error::InvalidConfig) by
+    /// [`Config::validate`] and at render time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().background_css("rgba(0, 0, 0, 0.5)");
+    /// assert_eq!(config.background_rgba().unwrap(), [0, 0, 0, 128]);
+    /// ```
+    pub fn background_css(mut self, css: impl Into<String>) -> Self {
+        self.background_css = Some(css.into());
+        self
+    }
+
+    /// Get the effective background color as RGBA.
+    ///
+    /// Returns the parsed `background_css` color if one is set, otherwise
+    /// `background`.
+    pub fn background_rgba(&self) -> Result<[u8; 4]> {
+        match &self.background_css {
+            Some(css) => parse_css_color(css),
+            None => Ok(self.background),
+        }
+    }
+
+    /// Set a transparent background.
     ///
     /// Shorthand for `.background([0, 0, 0, 0])`.
     pub fn transparent(self) -> Self {
         self.background([0, 0, 0, 0])
     }
 
+    /// Set a gradient to paint as the page background.
+    ///
+    /// The gradient covers the whole page and is drawn before any document
+    /// content, so full-bleed output doesn't need a viewport-sized body
+    /// element. Currently only applied to PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, LinearGradientSpec};
+    ///
+    /// let config = Config::new().page_gradient(Some(
+    ///     LinearGradientSpec::new(90.0)
+    ///         .stop(0.0, [255, 0, 0, 255])
+    ///         .stop(1.0, [0, 0, 255, 255]),
+    /// ));
+    /// ```
+    pub fn page_gradient(mut self, gradient: Option<LinearGradientSpec>) -> Self {
+        self.page_gradient = gradient;
+        self
+    }
+
+    /// Draw an image as the page background, beneath all document content.
+    ///
+    /// For branded exports such as a logo watermark or a texture, without
+    /// injecting CSS. `data` is an encoded PNG, JPEG, GIF or WebP image,
+    /// fitted to the page by `fit`. It shows through wherever the document
+    /// is transparent, so it is hidden by an opaque `html` or `body`
+    /// background. In PDF output it is drawn over `background` and any
+    /// [`page_gradient`](Config::page_gradient). Rendering fails with
+    /// [`Error::InvalidConfig`] if the image can't be decoded.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use hyper_render::{BackgroundFit, Config};
+    ///
+    /// let logo = std::fs::read("watermark.png")?;
+    /// let config = Config::new().background_image(logo, BackgroundFit::Center);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn background_image(mut self, data: impl Into<Vec<u8>>, fit: BackgroundFit) -> Self {
+        self.background_image = Some(BackgroundImage {
+            data: data.into().into(),
+            fit,
+        });
+        self
+    }
+
+    /// Load every font in a directory for use by the document.
+    ///
+    /// The directory is scanned recursively for `.ttf`, `.otf` and `.woff2`
+    /// files, which are registered alongside the system fonts so their
+    /// families can be referenced from `font-family`. Other files are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().fonts_dir("assets/fonts");
+    /// ```
+    pub fn fonts_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fonts_dir = Some(dir.into());
+        self
+    }
+
+    /// Make the fonts installed on the system available, or not.
+    ///
+    /// With system fonts disabled, the fonts from [`Config::fonts_dir`] are
+    /// the only ones available besides `@font-face` rules, and also stand in
+    /// for the generic families (`serif`, `sans-serif`, `monospace`, ...).
+    /// A bundled copy of Open Sans backs them, so text is drawn even without
+    /// a fonts directory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().fonts_dir("assets/fonts").system_fonts(false);
+    /// ```
+    pub fn system_fonts(mut self, enabled: bool) -> Self {
+        self.system_fonts = enabled;
+        self
+    }
+
+    /// Tune the config for reproducible snapshot tests.
+    ///
+    /// Disables [`Config::system_fonts`] so text is set in the same fonts on
+    /// every platform, and samples CSS animations at time zero. Rendering
+    /// is otherwise deterministic: the same HTML and config always give the
+    /// same bytes, on any thread, as nothing is output in hash map order. Text falls back to the bundled Open Sans unless
+    /// [`Config::fonts_dir`] provides fonts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().fonts_dir("assets/fonts").snapshot_mode();
+    /// ```
+    pub fn snapshot_mode(self) -> Self {
+        self.system_fonts(false).resolve_timestamp(0.0)
+    }
+
+    /// Enable detection of content clipped by the output bounds.
+    ///
+    /// Catches mis-sized viewports: after layout, content wider than
+    /// `width` (or taller than `height` when `auto_height` is off) raises a
+    /// [`Warning::ContentClipped`](crate::Warning::ContentClipped).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().overflow_guard(true);
+    /// ```
+    pub fn overflow_guard(mut self, enabled: bool) -> Self {
+        self.overflow_guard = enabled;
+        self
+    }
+
+    /// Treat warnings as errors.
+    ///
+    /// When enabled, rendering fails with [`Error::Strict`](crate::Error::Strict)
+    /// on the first warning rather than reporting it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().overflow_guard(true).strict(true);
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Render the document as it looks `seconds` into its CSS animations.
+    ///
+    /// Styles and layout are resolved at this animation time, so an element
+    /// can be captured mid-transition or in its final animated state.
+    /// Animated output captures its first frame at this time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// // Past a one-second fade-in
+    /// let config = Config::new().resolve_timestamp(1.0);
+    /// ```
+    pub fn resolve_timestamp(mut self, seconds: f64) -> Self {
+        self.resolve_timestamp = seconds;
+        self
+    }
+
+    /// Set the number of frames to capture for animated output.
+    ///
+    /// Frames are captured at evenly spaced timestamps `frame_duration`
+    /// apart, advancing CSS animations between frames. Only used by
+    /// [`OutputFormat::Gif`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    /// use std::time::Duration;
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Gif)
+    ///     .frames(12)
+    ///     .frame_duration(Duration::from_millis(80));
+    /// assert_eq!(config.frames, 12);
+    /// ```
+    pub fn frames(mut self, count: u32) -> Self {
+        self.frames = count;
+        self
+    }
+
+    /// Set the time between captured frames for animated output.
+    ///
+    /// This is both the animation timestep and the GIF frame delay, which
+    /// GIF stores in hundredths of a second.
+    pub fn frame_duration(mut self, duration: Duration) -> Self {
+        self.frame_duration = duration;
+        self
+    }
+
+    /// Fail rendering when any character has no glyph in the available fonts.
+    ///
+    /// Useful in CI to catch missing CJK or emoji fonts: after layout, any
+    /// text shaped to the `.notdef` glyph results in an [`Error::Font`]
+    /// listing the uncovered characters.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().require_full_coverage(true);
+    /// ```
+    pub fn require_full_coverage(mut self, required: bool) -> Self {
+        self.require_full_coverage = required;
+        self
+    }
+
+    /// Set the document language, such as `"de"` or `"en-GB"`.
+    ///
+    /// The tag replaces the `lang` attribute of the root `<html>` element, so
+    /// templates without one (or with the wrong one) render in the intended
+    /// language: it is seen by `[lang]` attribute selectors and picks the
+    /// quotation marks used for `<q>` under `quotes: auto` and the
+    /// hyphenation patterns for `hyphens: auto`. Elements with their own
+    /// `lang` attribute keep it.
+    ///
+    /// Blitz does not yet match `:lang()` selectors or pass the language to
+    /// text shaping and line breaking.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().lang("de");
+    /// assert_eq!(config.lang.as_deref(), Some("de"));
+    /// ```
+    pub fn lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    /// Remove scripting from the HTML before it is styled and laid out.
+    ///
+    /// hyper-render never runs JavaScript, so scripts can't change the
+    /// output, but a `<script>` element still holds its source as text,
+    /// which author CSS could reveal. When rendering untrusted HTML this
+    /// makes the no-script guarantee explicit: `<script>` elements (HTML
+    /// and SVG) are removed along with every `on*` event handler attribute,
+    /// such as `<style onload>` or `<img onerror>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().sanitize(true);
+    /// ```
+    pub fn sanitize(mut self, enabled: bool) -> Self {
+        self.sanitize = enabled;
+        self
+    }
+
+    /// Render the HTML as a bare fragment, such as a component's markup.
+    ///
+    /// Fragments like `<div class="card">...</div>` already parse, into an
+    /// implicit `<html>` and `<body>`, but the body's default 8px margin
+    /// offsets them from the output's top-left corner. In fragment mode the
+    /// implicit document chrome adds nothing: the body has no margin, and
+    /// the root and body are transparent. These are defaults, so the
+    /// fragment's own styles still override them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().fragment_mode(true).auto_height(true);
+    /// ```
+    pub fn fragment_mode(mut self, enabled: bool) -> Self {
+        self.fragment_mode = enabled;
+        self
+    }
+
+    /// Outline each element's layout boxes on the output, like the box
+    /// model view of browser developer tools.
+    ///
+    /// For finding out why content lands where it does: every element's
+    /// border box is outlined in yellow, and where they differ from it, its
+    /// margin box in orange, padding box in green and content box in blue.
+    /// The outlines are drawn over the painted document, one device pixel
+    /// wide in raster output and half a point wide in PDF output. SVG output
+    /// is not outlined.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().debug_layout(true);
+    /// ```
+    pub fn debug_layout(mut self, enabled: bool) -> Self {
+        self.debug_layout = enabled;
+        self
+    }
+
+    /// Set whether text forms ligatures, in both PNG and PDF output.
+    ///
+    /// On by default, so fonts join sequences like `ffi` and `fl` into a
+    /// single glyph as browsers do. Turn it off for code, where a
+    /// programming font's `->` or `!=` ligatures would hide the characters
+    /// typed. The characters of each sequence the fonts shaped as a
+    /// ligature are separated with a zero width non-joiner (U+200C), which
+    /// is left out of the PDF's text layer; other text is not changed. Text
+    /// set in a font without a glyph for the non-joiner keeps its ligatures
+    /// when system fonts are on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().ligatures(false);
+    /// assert!(!config.ligatures);
+    /// ```
+    pub fn ligatures(mut self, enabled: bool) -> Self {
+        self.ligatures = enabled;
+        self
+    }
+
+    /// Shrink the text of elements matching `selector` until it fits their width.
+    ///
+    /// For fixed-size labels and cards whose text varies in length: the
+    /// font size of each matched element is scaled down, found by binary
+    /// search with the document laid out again at each trial size, until
+    /// no line of text inside it is wider than its content box. Text that
+    /// already fits keeps its size, and text is never shrunk below a tenth
+    /// of it.
+    ///
+    /// The scaled size is set on the matched element, so descendants with
+    /// relative font sizes (`em`, `%`) shrink along with it, while ones
+    /// with absolute sizes don't. Only text laid out inside the matched
+    /// element is measured, so it should be a block container rather than
+    /// an inline element. An invalid selector fails rendering with
+    /// [`Error::InvalidConfig`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().autosize_text(".card-title");
+    /// assert_eq!(config.autosize_text.as_deref(), Some(".card-title"));
+    /// ```
+    pub fn autosize_text(mut self, selector: impl Into<String>) -> Self {
+        self.autosize_text = Some(selector.into());
+        self
+    }
+
+    /// Provide separate light and dark stylesheets.
+    ///
+    /// For apps that ship theme files instead of `prefers-color-scheme` media
+    /// queries: the stylesheet matching [`Config::color_scheme`] is injected
+    /// into the document and the other is ignored.
+    ///
+    /// The injected stylesheet is ordered after all of the document's own
+    /// stylesheets, so it wins over author rules of equal specificity —
+    /// including rules inside the page's `@media (prefers-color-scheme)`
+    /// blocks. More specific author selectors, inline `style` attributes and
+    /// `!important` declarations still take precedence.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{ColorScheme, Config};
+    ///
+    /// let config = Config::new()
+    ///     .color_scheme(ColorScheme::Dark)
+    ///     .theme_css("body { background: white; }", "body { background: #111; }");
+    /// ```
+    pub fn theme_css(mut self, light: impl Into<String>, dark: impl Into<String>) -> Self {
+        self.theme_css = Some((light.into(), dark.into()));
+        self
+    }
+
+    /// Crop exact pixel margins off the final PNG image.
+    ///
+    /// The amounts are in device pixels (after `scale`) and are removed from
+    /// the rendered raster, including any `auto_height` growth. Useful for
+    /// cutting sprites out of a larger render. Rendering fails with
+    /// [`Error::InvalidConfig`] if the margins would leave no pixels, or if
+    /// the output format isn't PNG.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// // Crop 10px off every side of a 100x100 render, giving 80x80
+    /// let config = Config::new().size(100, 100).crop_margins(10, 10, 10, 10);
+    /// ```
+    pub fn crop_margins(mut self, top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        self.crop_margins = [top, right, bottom, left];
+        self
+    }
+
+    /// Crop PNG output to the border box of the first element matching
+    /// `selector`.
+    ///
+    /// The shortcut for screenshotting one element: the document is laid out
+    /// at the configured size as usual, and the image is cut to the element's
+    /// box (rounded out to whole device pixels), wherever it is on the page,
+    /// even below the configured height. [`capture_padding`](Config::capture_padding)
+    /// keeps some of the page around it, and [`crop_margins`](Config::crop_margins) then
+    /// applies to the captured image. Rendering fails with
+    /// [`Error::InvalidConfig`] if the selector is invalid, matches nothing,
+    /// or matches an element with no size. Only PNG output is captured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().capture(".badge").capture_padding(8);
+    /// ```
+    pub fn capture(mut self, selector: impl Into<String>) -> Self {
+        self.capture = Some(selector.into());
+        self
+    }
+
+    /// Keep `padding` CSS pixels of the page around the
+    /// [`capture`](Config::capture)d element, up to the page's top and left
+    /// edges.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().capture("#chart").capture_padding(16);
+    /// ```
+    pub fn capture_padding(mut self, padding: u32) -> Self {
+        self.capture_padding = padding;
+        self
+    }
+
+    /// Grow PNG output to hold content overhanging the page's edges.
+    ///
+    /// Elements pulled past the page by negative margins or positioning are
+    /// normally cut off at its edges. With this enabled the document is laid
+    /// out at the configured size as usual, and the image then grows on
+    /// every side to the border boxes of all the content, so content off the
+    /// left or top edge shifts right or down into view rather than being
+    /// clipped. Content clipped by an `overflow` other than `visible` only
+    /// counts up to the clipping box, and shadows and outlines past a box's
+    /// border aren't counted. Ignored when a [`capture`](Config::capture) is
+    /// set; [`crop_margins`](Config::crop_margins) applies to the grown image. Only PNG output
+    /// grows.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().capture_overhang(true);
+    /// ```
+    pub fn capture_overhang(mut self, enabled: bool) -> Self {
+        self.capture_overhang = enabled;
+        self
+    }
+
+    /// Rotate the finished output clockwise.
+    ///
+    /// For label printers and other rotated media, without authoring rotated
+    /// CSS. The document is laid out at the configured size and the result is
+    /// rotated, so a quarter turn of a 100x50 render gives a 50x100 image or
+    /// page. Raster output is rotated after [`crop_margins`](Config::crop_margins) and the
+    /// [`overlay`](Config::overlay) are applied; PDF and SVG content is drawn
+    /// through a rotating transform.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, Rotation};
+    ///
+    /// let config = Config::new().size(400, 200).rotate_output(Rotation::Deg90);
+    /// ```
+    pub fn rotate_output(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set the pixel format of PNG output.
+    ///
+    /// [`PngColorType::Indexed`] writes an 8-bit palette image, which is much
+    /// smaller for flat graphics such as icons, badges and charts. Only PNG
+    /// output is affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, PngColorType};
+    ///
+    /// let config = Config::new().png_color_type(PngColorType::Indexed { dither: true });
+    /// ```
+    pub fn png_color_type(mut self, color_type: PngColorType) -> Self {
+        self.png_color_type = color_type;
+        self
+    }
+
+    /// Limit the size of data URIs embedded in the HTML.
+    ///
+    /// Protects services that render untrusted HTML from memory spikes caused
+    /// by huge inline resources such as base64-encoded images. Any element
+    /// attribute holding a `data:` URI longer than `bytes` is removed before
+    /// styles are resolved, so the resource is never decoded or drawn, and a
+    /// [`Warning::ResourceTooLarge`](crate::Warning::ResourceTooLarge) is
+    /// reported. With [`Config::strict`] enabled the render fails instead.
+    ///
+    /// Data URIs inside stylesheets (`url(data:...)`) are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().max_resource_bytes(1024 * 1024);
+    /// ```
+    pub fn max_resource_bytes(mut self, bytes: usize) -> Self {
+        self.max_resource_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail when an element's layout box grows beyond `max_element_px` in
+    /// either dimension.
+    ///
+    /// Pathological CSS such as `width: 10000000px` makes layout produce
+    /// enormous boxes that are slow to paint. With this guard, the laid-out
+    /// border box of every element is checked before painting and
+    /// [`Error::ElementTooLarge`] is returned for the first one that is too
+    /// wide or too tall.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().assert_dimensions(100_000);
+    /// ```
+    pub fn assert_dimensions(mut self, max_element_px: u32) -> Self {
+        self.max_element_px = Some(max_element_px);
+        self
+    }
+
+    /// Limit decoded images to `px` pixels in either dimension.
+    ///
+    /// A huge photo drawn into a small box otherwise holds its full
+    /// resolution in memory and, in PDF output, is embedded at that
+    /// resolution.
+    ///
+    /// The document's images and CSS background images lay out at their
+    /// decoded size, so ones over the limit are skipped instead: their size
+    /// is read from the image header and their pixels are never decoded.
+    /// Each one skipped is reported as [`Warning::ImageTooLarge`](crate::Warning::ImageTooLarge),
+    /// which fails the render under [`Config::strict`].
+    ///
+    /// [`Config::background_image`] is resized to fit within the limit,
+    /// keeping its aspect ratio, before it is composited or embedded. It
+    /// still draws at its original size, so a limit below the size it is
+    /// drawn at (times [`Config::scale`]) trades sharpness for the savings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().max_decoded_image_dimension(2048);
+    /// ```
+    pub fn max_decoded_image_dimension(mut self, px: u32) -> Self {
+        self.max_decoded_image_dimension = Some(px);
+        self
+    }
+
+    /// Set how resources referenced by URL that fail to load are handled.
+    ///
+    /// Batch jobs can choose between resilience and correctness: with
+    /// [`ResourcePolicy::BestEffort`] the document renders without the
+    /// missing images, stylesheets and fonts and a
+    /// [`Warning::ResourceNotLoaded`](crate::Warning::ResourceNotLoaded) is
+    /// reported for each, while [`ResourcePolicy::Strict`] fails with
+    /// [`Error::Network`] on the first one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, ResourcePolicy};
+    ///
+    /// let config = Config::new().resource_policy(ResourcePolicy::Strict);
+    /// ```
+    pub fn resource_policy(mut self, policy: ResourcePolicy) -> Self {
+        self.resource_policy = policy;
+        self
+    }
+
+    /// Set what a render returns when it fails.
+    ///
+    /// So one bad document doesn't break a batch job,
+    /// [`ErrorPolicy::Placeholder`] turns a failed render into a successful
+    /// one whose output is a plain "Render failed" image or PDF of the
+    /// configured width, height, scale and format, showing the error
+    /// message. The error is reported as a
+    /// [`Warning::RenderFailed`](crate::Warning::RenderFailed) by
+    /// [`render_with_warnings`](crate::render_with_warnings). If the
+    /// placeholder can't be rendered either, such as when the configured
+    /// size is invalid or the format isn't enabled, the original error is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, ErrorPolicy};
+    ///
+    /// let config = Config::new().on_error(ErrorPolicy::Placeholder);
+    /// ```
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        self.on_error = policy;
+        self
+    }
+
+    /// Fail the render when a resource referenced by URL can't be loaded.
+    ///
+    /// Shorthand for `.resource_policy(ResourcePolicy::Strict)` when `fail`
+    /// is true and `.resource_policy(ResourcePolicy::BestEffort)` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, ResourcePolicy};
+    ///
+    /// let config = Config::new().fail_on_network(true);
+    /// assert_eq!(config.resource_policy, ResourcePolicy::Strict);
+    /// ```
+    pub fn fail_on_network(self, fail: bool) -> Self {
+        self.resource_policy(if fail {
+            ResourcePolicy::Strict
+        } else {
+            ResourcePolicy::BestEffort
+        })
+    }
+
+    /// Set whether text is laid out again once `@font-face` fonts load.
+    ///
+    /// Web fonts embedded as `data:` URIs only arrive after the first
+    /// layout, which measures text with fallback fonts. When enabled, each
+    /// font is loaded as it arrives and the document laid out again, so
+    /// text is measured and drawn with the web font's own metrics. When
+    /// disabled, web fonts are not loaded and text keeps its fallback
+    /// fonts, as a browser shows it before the fonts swap in; this skips a
+    /// layout pass. Fonts from [`fonts_dir`](Config::fonts_dir) are
+    /// registered before the first layout and always used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().retry_layout_on_font_load(false);
+    /// ```
+    pub fn retry_layout_on_font_load(mut self, enabled: bool) -> Self {
+        self.retry_layout_on_font_load = enabled;
+        self
+    }
+
+    /// Limit [`render_batch`](crate::render_batch) to rendering `threads`
+    /// documents at once.
+    ///
+    /// By default a batch is spread over one thread per CPU. A lower limit
+    /// bounds memory use, since each document is parsed, laid out and
+    /// painted in full on its thread. Single renders are unaffected.
+    /// [`Config::validate`] fails if `threads` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::Config;
+    ///
+    /// let config = Config::new().concurrency(4);
+    /// ```
+    pub fn concurrency(mut self, threads: usize) -> Self {
+        self.concurrency = Some(threads);
+        self
+    }
+
+    /// Bound the resources a render may use.
+    ///
+    /// Combines limits on output pixels, output height, embedded resource
+    /// size, DOM depth and elapsed time; see [`RenderBudget`]. Exceeding any
+    /// bound fails the render with a specific error such as
+    /// [`Error::PixelLimitExceeded`]. Unlike [`Config::max_resource_bytes`],
+    /// which skips individual oversized resources, this is a hard cap.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, RenderBudget};
+    ///
+    /// let config = Config::new().budget(RenderBudget::default().max_depth(128));
+    /// ```
+    pub fn budget(mut self, budget: RenderBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Draw on top of the painted document, before the output is encoded.
+    ///
+    /// An extension point for watermarks, timestamps or debug grids that
+    /// doesn't require re-rendering. The hook is called once per raster
+    /// (each PNG, GIF frame or TIFF page, before [`Config::crop_margins`]) with
+    /// [`OverlayTarget::Raster`], and once per PDF page with
+    /// `OverlayTarget::Pdf`, which exposes the page's Krilla surface. SVG
+    /// output and [`paint_into_scene`](crate::paint_into_scene) don't call it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OverlayTarget};
+    ///
+    /// // Paint a 4x4 red mark in the top-left corner of raster output
+    /// let config = Config::new().overlay(|target| {
+    ///     if let OverlayTarget::Raster { pixels, width, .. } = target {
+    ///         for y in 0..4 {
+    ///             for x in 0..4 {
+    ///                 let i = ((y * width + x) * 4) as usize;
+    ///                 pixels[i..i + 4].copy_from_slice(&[255, 0, 0, 255]);
+    ///             }
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn overlay(mut self, draw: impl Fn(OverlayTarget<'_, '_>) + Send + Sync + 'static) -> Self {
+        self.overlay = Some(Overlay::new(draw));
+        self
+    }
+
+    /// Set the PDF version to write.
+    ///
+    /// Only affects PDF output. Newer features are automatically downgraded
+    /// or omitted when targeting older versions.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat, PdfVersion};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .pdf_version(PdfVersion::Pdf14);
+    /// ```
+    pub fn pdf_version(mut self, version: PdfVersion) -> Self {
+        self.pdf_version = version;
+        self
+    }
+
+    /// Require PDF/A conformance for archival output.
+    ///
+    /// Only affects PDF output. The level must support the configured
+    /// [`Config::pdf_version`], otherwise [`Config::validate`] fails. PDF/A
+    /// output must embed every resource it shows, so images, stylesheets and
+    /// fonts referenced by URL fail the render with [`Error::Network`], as
+    /// under [`ResourcePolicy::Strict`]; embed them as `data:` URIs instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat, PdfA};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .pdf_conformance(Some(PdfA::A2b));
+    /// ```
+    pub fn pdf_conformance(mut self, level: Option<PdfA>) -> Self {
+        self.pdf_conformance = level;
+        self
+    }
+
+    /// Add a bookmark outline built from the document's headings to PDF output.
+    ///
+    /// Each visible `<h1>`–`<h6>` becomes an entry titled with its text
+    /// that jumps to the heading, nested under the closest preceding
+    /// heading of a higher level, so PDF viewers can show the document's
+    /// structure in their navigation pane. Only affects PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .generate_outline(true);
+    /// ```
+    pub fn generate_outline(mut self, enabled: bool) -> Self {
+        self.generate_outline = enabled;
+        self
+    }
+
+    /// Start PDF output with a table of contents page.
+    ///
+    /// The page lists each visible `<h1>`–`<h6>`, indented by level, with
+    /// the number of the page it is on, and each entry links to its
+    /// heading. It comes before the document's page, so that is page 2,
+    /// and is clipped to the page size if the list is longer. Only affects
+    /// PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let config = Config::new().format(OutputFormat::Pdf).toc(true);
+    /// ```
+    pub fn toc(mut self, enabled: bool) -> Self {
+        self.toc = enabled;
+        self
+    }
+
+    /// Clip PDF content to the page.
+    ///
+    /// PDF output is a single page, so content past its edges, such as an
+    /// element below the configured height, would be drawn outside the
+    /// MediaBox, where viewers differ in how much of it they show. Enabled
+    /// by default; disable it to leave such content in the file as bleed,
+    /// e.g. for print workflows that trim pages themselves. Only affects
+    /// PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let config = Config::new().format(OutputFormat::Pdf).clip_to_page(false);
+    /// ```
+    pub fn clip_to_page(mut self, enabled: bool) -> Self {
+        self.clip_to_page = enabled;
+        self
+    }
+
+    /// Label the pages of PDF output, such as Roman numerals for front
+    /// matter followed by Arabic numerals for the body.
+    ///
+    /// Each range labels the pages from its start up to the next range's
+    /// start; pages before the first range are unlabeled. Ranges must be
+    /// sorted by strictly increasing start page and numbered from at least
+    /// 1, otherwise [`Config::validate`] fails. Only affects PDF output,
+    /// which is currently a single page, so only a range starting at page 0
+    /// takes effect.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat, PageLabelRange, PageLabelStyle};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .page_labels(vec![
+    ///         PageLabelRange::new(0, PageLabelStyle::RomanLower),
+    ///         PageLabelRange::new(2, PageLabelStyle::Decimal),
+    ///     ]);
+    /// ```
+    pub fn page_labels(mut self, ranges: Vec<PageLabelRange>) -> Self {
+        self.page_labels = ranges;
+        self
+    }
+
+    /// Embed an ICC profile as the output intent of PDF output.
+    ///
+    /// For print workflows that target a specific CMYK or calibrated RGB
+    /// device. Colors are still authored in RGB; the profile, set as the
+    /// document's `/OutputIntents` entry, tells consumers which device
+    /// space they describe. An RGB profile also tags the content, as the
+    /// color space its RGB colors and images are drawn in; CMYK and
+    /// grayscale profiles can't describe RGB colors, so content drawn
+    /// under them stays device RGB. `data` must be a complete RGB, CMYK or
+    /// grayscale ICC profile, which [`Config::validate`] checks by its
+    /// header. It can't be combined with
+    /// [`pdf_conformance`](Config::pdf_conformance), since PDF/A output
+    /// carries its own sRGB output intent. Only affects PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let profile = std::fs::read("CoatedFOGRA39.icc")?;
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .icc_profile(profile);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn icc_profile(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.icc_profile = Some(data.into().into());
+        self
+    }
+
+    /// Draw gradient backgrounds in PDF output as images painted by the
+    /// raster renderer, rather than as vector shadings.
+    ///
+    /// PDF output converts linear gradients to shadings itself, which
+    /// approximates some angles and color hints, and skips radial and conic
+    /// gradients. Rasterized gradients look as they do in PNG output at the
+    /// cost of being resolution-bound: they are painted at
+    /// [`scale`](Config::scale) device pixels per CSS pixel. Requires the
+    /// `png` feature. Only affects PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .scale(2.0)
+    ///     .rasterize_gradients(true);
+    /// ```
+    pub fn rasterize_gradients(mut self, enabled: bool) -> Self {
+        self.rasterize_gradients = enabled;
+        self
+    }
+
+    /// Draw elements with 3D transforms in PDF output as images painted by
+    /// the raster renderer and projected through their transform.
+    ///
+    /// PDF can only transform content with 2D affine matrices, so without
+    /// this, transforms such as `rotateX()` or `perspective()` are drawn as
+    /// their 2D part, dropping depth and perspective. With it, each such
+    /// element and its contents are painted at [`scale`](Config::scale)
+    /// device pixels per CSS pixel and projected, at the cost of being
+    /// resolution-bound. 2D transforms are always drawn natively. Requires
+    /// the `png` feature. Only affects PDF output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hyper_render::{Config, OutputFormat};
+    ///
+    /// let config = Config::new()
+    ///     .format(OutputFormat::Pdf)
+    ///     .scale(2.0)
+    ///     .flatten_transforms_for_pdf(true);
+    /// ```
+    pub fn flatten_transforms_for_pdf(mut self, enabled: bool) -> Self {
+        self.flatten_transforms_for_pdf = enabled;
+        self
+    }
+
     /// Minimum supported width/height in pixels.
     ///
     /// Very small dimensions can cause overflow issues in the underlying
     /// rendering engine.
     pub const MIN_DIMENSION: u32 = 16;
 
+    /// Default upper bound on the scale factor.
+    pub const DEFAULT_MAX_SCALE: f32 = 8.0;
+
+    /// Resolution of CSS pixels in dots per inch: CSS defines an inch as 96px.
+    pub const CSS_DPI: f32 = 96.0;
+
     /// Validate the configuration.
     ///
     /// Returns an error if any configuration values are invalid:
     /// - Width must be at least 16
-    /// - Height must be at least 16
+    /// - Height (derived from `aspect_ratio`, if set) must be at least 16
+    /// - `aspect_ratio`, if set, must be finite and greater than 0, and
+    ///   can't be combined with `auto_height`
     /// - Scale must be greater than 0
+    /// - Scale must not exceed `max_scale` (8.0 by default)
+    /// - `dpi`, if set, must be finite and greater than 0
+    /// - The device scale (see [`Config::device_scale`]) must not exceed `max_scale`
+    /// - At least one frame must be captured
+    /// - `resolve_timestamp` must be finite and not negative
+    /// - `max_decoded_image_dimension`, if set, must be at least 1
+    /// - `concurrency`, if set, must be at least 1
+    /// - `page_labels` must have strictly increasing start pages and first
+    ///   numbers of at least 1
     ///
     /// This is called automatically by the render functions.
     ///
@@ -278,7 +2047,7 @@ impl Config {
                 Self::MIN_DIMENSION
             )));
         }
-        if self.height < Self::MIN_DIMENSION {
+        if self.aspect_height() < Self::MIN_DIMENSION {
             return Err(Error::InvalidConfig(format!(
                 "height must be at least {} pixels",
                 Self::MIN_DIMENSION
@@ -294,6 +2063,101 @@ impl Config {
                 "scale must be a finite number".to_string(),
             ));
         }
+        if self.scale > self.max_scale {
+            return Err(Error::InvalidConfig(format!(
+                "scale {} exceeds the maximum of {}; raise it with Config::max_scale",
+                self.scale, self.max_scale
+            )));
+        }
+        if let Some(dpi) = self.dpi {
+            if !dpi.is_finite() || dpi <= 0.0 {
+                return Err(Error::InvalidConfig(
+                    "dpi must be a finite number greater than 0".to_string(),
+                ));
+            }
+        }
+        if self.device_scale() > self.max_scale {
+            return Err(Error::InvalidConfig(format!(
+                "scale {} at {} dpi gives {} device pixels per CSS pixel, over the maximum of {}; raise it with Config::max_scale",
+                self.scale,
+                self.dpi.unwrap_or(Self::CSS_DPI),
+                self.device_scale(),
+                self.max_scale
+            )));
+        }
+        if let Some(ratio) = self.aspect_ratio {
+            if !ratio.is_finite() || ratio <= 0.0 {
+                return Err(Error::InvalidConfig(
+                    "aspect_ratio must be a finite number greater than 0".to_string(),
+                ));
+            }
+            if self.auto_height {
+                return Err(Error::InvalidConfig(
+                    "aspect_ratio can't be combined with auto_height".to_string(),
+                ));
+            }
+        }
+        if self.crop_margins != [0; 4] && self.format != OutputFormat::Png {
+            return Err(Error::InvalidConfig(format!(
+                "crop_margins only applies to png output, not {}",
+                self.format
+            )));
+        }
+        if self.frames == 0 {
+            return Err(Error::InvalidConfig(
+                "frames must be at least 1".to_string(),
+            ));
+        }
+        if let Some(level) = self.pdf_conformance {
+            if !level.supports_version(self.pdf_version) {
+                return Err(Error::InvalidConfig(format!(
+                    "{} is not compatible with PDF {}",
+                    level, self.pdf_version
+                )));
+            }
+        }
+        if !self.resolve_timestamp.is_finite() || self.resolve_timestamp < 0.0 {
+            return Err(Error::InvalidConfig(
+                "resolve_timestamp must be a finite, non-negative number of seconds".to_string(),
+            ));
+        }
+        if self.max_decoded_image_dimension == Some(0) {
+            return Err(Error::InvalidConfig(
+                "max_decoded_image_dimension must be at least 1".to_string(),
+            ));
+        }
+        if self.concurrency == Some(0) {
+            return Err(Error::InvalidConfig(
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+        if self
+            .page_labels
+            .windows(2)
+            .any(|pair| pair[0].start >= pair[1].start)
+        {
+            return Err(Error::InvalidConfig(
+                "page_labels must be sorted by strictly increasing start page".to_string(),
+            ));
+        }
+        if self.page_labels.iter().any(|range| range.first_number == 0) {
+            return Err(Error::InvalidConfig(
+                "page_labels must be numbered from at least 1".to_string(),
+            ));
+        }
+        if let Some(profile) = &self.icc_profile {
+            if icc::components(profile).is_none() {
+                return Err(Error::InvalidConfig(
+                    "icc_profile is not an RGB, CMYK or grayscale ICC profile".to_string(),
+                ));
+            }
+            if self.pdf_conformance.is_some() {
+                return Err(Error::InvalidConfig(
+                    "icc_profile can't be combined with pdf_conformance".to_string(),
+                ));
+            }
+        }
+        self.background_rgba()?;
         Ok(())
     }
 }
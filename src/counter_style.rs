@@ -0,0 +1,690 @@
+//! List markers in counter styles Blitz doesn't render, such as
+//! `decimal-leading-zero`, `lower-roman`, `lower-greek` and `cjk-decimal`,
+//! along with those defined by `@counter-style` rules, and markers styled
+//! by `::marker` rules.
+
+use crate::carried;
+use crate::first_letter::{split_top_level, top_level_rules};
+use blitz_dom::node::Attribute;
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, QualName};
+use std::collections::{HashMap, HashSet};
+use style::computed_values::list_style_position::T as ListStylePosition;
+use style_traits::ToCss;
+
+/// Attribute on list items whose marker is generated.
+const ITEM_ATTR: &str = "data-hyper-render-counter-item";
+
+/// Attribute on the generated markers, set to the marker's position.
+const MARKER_ATTR: &str = "data-hyper-render-marker";
+
+/// Rules for list items with generated markers. Blitz's own marker is
+/// turned off, and an outside marker is placed in a zero-height block at
+/// the start of the item, right-aligned against its content box.
+pub(crate) const MARKER_CSS: &str = r#"
+[data-hyper-render-counter-item] {
+    list-style-type: none !important;
+}
+[data-hyper-render-marker] {
+    white-space: pre !important;
+}
+[data-hyper-render-marker="outside"] {
+    display: block !important;
+    position: relative !important;
+    height: 0 !important;
+}
+[data-hyper-render-marker="outside"] > span {
+    position: absolute !important;
+    top: 0 !important;
+    right: 100% !important;
+}
+"#;
+
+/// Most symbols a counter representation, or its padding, may take; longer
+/// representations fall back to `decimal`, as the CSS Counter Styles
+/// specification allows, rather than growing without bound.
+const MAX_SYMBOLS: usize = 60;
+
+/// List style types Blitz renders itself.
+const NATIVE_TYPES: &[&str] = &[
+    "none",
+    "disc",
+    "circle",
+    "square",
+    "disclosure-open",
+    "disclosure-closed",
+    "decimal",
+    "lower-alpha",
+    "upper-alpha",
+];
+
+/// How a counter style represents counter values.
+#[derive(Clone)]
+enum System {
+    /// Cycles through the symbols.
+    Cyclic,
+    /// Uses each symbol once, starting at the given value.
+    Fixed(i64),
+    /// Cycles through the symbols, repeating them on each cycle.
+    Symbolic,
+    /// Counts in a bijective numeral system without a zero.
+    Alphabetic,
+    /// Counts in a positional numeral system with the first symbol as zero.
+    Numeric,
+    /// Sums the weighted symbols, like Roman numerals.
+    Additive(Vec<(i64, String)>),
+}
+
+/// A counter style, predefined or from an `@counter-style` rule.
+#[derive(Clone)]
+struct CounterStyle {
+    system: System,
+    symbols: Vec<String>,
+    prefix: String,
+    suffix: String,
+    /// Minimum length and the symbol padding shorter representations.
+    pad: Option<(usize, String)>,
+    /// Values the style represents, outside which `decimal` is used.
+    range: Option<(i64, i64)>,
+}
+
+impl CounterStyle {
+    fn new(system: System, symbols: &str, suffix: &str) -> Self {
+        Self {
+            system,
+            symbols: symbols.chars().map(String::from).collect(),
+            prefix: String::new(),
+            suffix: suffix.to_string(),
+            pad: None,
+            range: None,
+        }
+    }
+
+    /// The marker text for counter `value`, falling back to `decimal` for
+    /// values outside the style's range.
+    fn marker(&self, value: i64) -> String {
+        let representation = self.represent(value).unwrap_or_else(|| {
+            let decimal = predefined("decimal").expect("decimal is predefined");
+            decimal.represent(value).unwrap_or_default()
+        });
+        format!("{}{}{}", self.prefix, representation, self.suffix)
+    }
+
+    fn represent(&self, value: i64) -> Option<String> {
+        let symbols = &self.symbols;
+        let count = symbols.len() as i64;
+        let (negative, magnitude) = (value < 0, value.unsigned_abs());
+        if symbols.is_empty() && !matches!(self.system, System::Additive(_)) {
+            return None;
+        }
+        if self
+            .range
+            .is_some_and(|(low, high)| value < low || value > high)
+        {
+            return None;
+        }
+        let mut representation = match &self.system {
+            System::Cyclic => symbols[(value % count - 1).rem_euclid(count) as usize].clone(),
+            System::Fixed(first) => {
+                let index = value.checked_sub(*first)?;
+                symbols.get(usize::try_from(index).ok()?)?.clone()
+            }
+            System::Symbolic if value >= 1 => {
+                let symbol = &symbols[((value - 1) % count) as usize];
+                let repeats = ((value - 1) / count + 1) as usize;
+                if repeats.saturating_mul(symbol.chars().count()) > MAX_SYMBOLS {
+                    return None;
+                }
+                symbol.repeat(repeats)
+            }
+            System::Alphabetic if value >= 1 && count >= 2 => {
+                let mut digits = Vec::new();
+                let mut rest = value;
+                while rest > 0 {
+                    rest -= 1;
+                    digits.push(symbols[(rest % count) as usize].as_str());
+                    rest /= count;
+                }
+                digits.into_iter().rev().collect()
+            }
+            System::Numeric if count >= 2 => {
+                let mut digits = Vec::new();
+                let mut rest = magnitude;
+                loop {
+                    digits.push(symbols[(rest % count as u64) as usize].as_str());
+                    rest /= count as u64;
+                    if rest == 0 {
+                        break;
+                    }
+                }
+                digits.into_iter().rev().collect()
+            }
+            System::Additive(weights) if value >= 0 => {
+                let mut representation = String::new();
+                let mut rest = value;
+                for (weight, symbol) in weights {
+                    if *weight == 0 {
+                        if value == 0 {
+                            return Some(symbol.clone());
+                        }
+                        continue;
+                    }
+                    if *weight < 0 || rest < *weight {
+                        continue;
+                    }
+                    let repeats = (rest / weight) as usize;
+                    let length = representation.chars().count()
+                        + repeats.saturating_mul(symbol.chars().count());
+                    if length > MAX_SYMBOLS {
+                        return None;
+                    }
+                    representation.push_str(&symbol.repeat(repeats));
+                    rest -= weight * repeats as i64;
+                }
+                if rest != 0 || representation.is_empty() {
+                    return None;
+                }
+                representation
+            }
+            _ => return None,
+        };
+
+        if let Some((length, symbol)) = &self.pad {
+            let current = representation.chars().count() + negative as usize;
+            if current < *length {
+                representation = symbol.repeat(length - current) + &representation;
+            }
+        }
+        if negative && matches!(self.system, System::Numeric) {
+            representation.insert(0, '-');
+        }
+        Some(representation)
+    }
+}
+
+/// The predefined counter style `name`, for the types in the CSS Counter
+/// Styles specification that Stylo or Blitz don't render, and the ones
+/// Blitz does render for markers styled by `::marker` rules.
+fn predefined(name: &str) -> Option<CounterStyle> {
+    let numeric = |digits: &str, suffix: &str| CounterStyle::new(System::Numeric, digits, suffix);
+    let alphabetic =
+        |letters: &str, suffix: &str| CounterStyle::new(System::Alphabetic, letters, suffix);
+    let roman = |symbols: [&str; 13]| CounterStyle {
+        system: System::Additive(
+            [1000, 900, 500, 400, 100, 90, 50, 40, 10, 9, 5, 4, 1]
+                .into_iter()
+                .zip(symbols.map(String::from))
+                .collect(),
+        ),
+        symbols: Vec::new(),
+        prefix: String::new(),
+        suffix: ". ".to_string(),
+        pad: None,
+        // The range of the predefined Roman numeral styles
+        range: Some((1, 3999)),
+    };
+
+    let cyclic = |symbols: &str| CounterStyle::new(System::Cyclic, symbols, " ");
+
+    Some(match name {
+        "disc" => cyclic("•"),
+        "circle" => cyclic("◦"),
+        "square" => cyclic("▪"),
+        "disclosure-open" => cyclic("▾"),
+        "disclosure-closed" => cyclic("▸"),
+        "decimal" => numeric("0123456789", ". "),
+        "decimal-leading-zero" => CounterStyle {
+            pad: Some((2, "0".to_string())),
+            ..numeric("0123456789", ". ")
+        },
+        "lower-roman" => roman([
+            "m", "cm", "d", "cd", "c", "xc", "l", "xl", "x", "ix", "v", "iv", "i",
+        ]),
+        "upper-roman" => roman([
+            "M", "CM", "D", "CD", "C", "XC", "L", "XL", "X", "IX", "V", "IV", "I",
+        ]),
+        "lower-latin" | "lower-alpha" => alphabetic("abcdefghijklmnopqrstuvwxyz", ". "),
+        "upper-latin" | "upper-alpha" => alphabetic("ABCDEFGHIJKLMNOPQRSTUVWXYZ", ". "),
+        "lower-greek" => alphabetic("αβγδεζηθικλμνξοπρστυφχψω", ". "),
+        "arabic-indic" => numeric("٠١٢٣٤٥٦٧٨٩", ". "),
+        "persian" => numeric("۰۱۲۳۴۵۶۷۸۹", ". "),
+        "bengali" => numeric("০১২৩৪৫৬৭৮৯", ". "),
+        "cambodian" | "khmer" => numeric("០១២៣៤៥៦៧៨៩", ". "),
+        "devanagari" => numeric("०१२३४५६७८९", ". "),
+        "gujarati" => numeric("૦૧૨૩૪૫૬૭૮૯", ". "),
+        "gurmukhi" => numeric("੦੧੨੩੪੫੬੭੮੯", ". "),
+        "kannada" => numeric("೦೧೨೩೪೫೬೭೮೯", ". "),
+        "lao" => numeric("໐໑໒໓໔໕໖໗໘໙", ". "),
+        "malayalam" => numeric("൦൧൨൩൪൫൬൭൮൯", ". "),
+        "mongolian" => numeric("᠐᠑᠒᠓᠔᠕᠖᠗᠘᠙", ". "),
+        "myanmar" => numeric("၀၁၂၃၄၅၆၇၈၉", ". "),
+        "oriya" => numeric("୦୧୨୩୪୫୬୭୮୯", ". "),
+        "telugu" => numeric("౦౧౨౩౪౫౬౭౮౯", ". "),
+        "thai" => numeric("๐๑๒๓๔๕๖๗๘๙", ". "),
+        "tibetan" => numeric("༠༡༢༣༤༥༦༧༨༩", ". "),
+        "cjk-decimal" => numeric("〇一二三四五六七八九", "、"),
+        "cjk-earthly-branch" => alphabetic("子丑寅卯辰巳午未申酉戌亥", "、"),
+        "cjk-heavenly-stem" => alphabetic("甲乙丙丁戊己庚辛壬癸", "、"),
+        "hiragana" => alphabetic(
+            "あいうえおかきくけこさしすせそたちつてとなにぬねのはひふへほまみむめもやゆよらりるれろわゐゑをん",
+            "、",
+        ),
+        "hiragana-iroha" => alphabetic(
+            "いろはにほへとちりぬるをわかよたれそつねならむうゐのおくやまけふこえてあさきゆめみしゑひもせす",
+            "、",
+        ),
+        "katakana" => alphabetic(
+            "アイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワヰヱヲン",
+            "、",
+        ),
+        "katakana-iroha" => alphabetic(
+            "イロハニホヘトチリヌルヲワカヨタレソツネナラムウヰノオクヤマケフコエテアサキユメミシヱヒモセス",
+            "、",
+        ),
+        _ => return None,
+    })
+}
+
+/// The type set by a `list-style` shorthand value.
+pub(crate) fn shorthand_type(value: &str) -> String {
+    let mut rest = value;
+    let mut tokens = Vec::new();
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        rest = &rest[start..];
+        let end = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].find(quote).map_or(rest.len(), |i| i + 2),
+            _ => {
+                let mut depth = 0usize;
+                rest.char_indices()
+                    .find(|&(_, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth = depth.saturating_sub(1),
+                            _ => {}
+                        }
+                        depth == 0 && c.is_whitespace()
+                    })
+                    .map_or(rest.len(), |(i, _)| i)
+            }
+        };
+        tokens.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let mut list_style_type = None;
+    let mut nones = 0;
+    for token in tokens {
+        let lower = token.to_ascii_lowercase();
+        if lower == "none" {
+            nones += 1;
+        } else if lower != "inside" && lower != "outside" && !token.contains('(') {
+            list_style_type = Some(token.to_string());
+        }
+    }
+    // `none` sets the type only if it isn't taken by the image
+    match list_style_type {
+        Some(list_style_type) => list_style_type,
+        None if nones > 0 => "none".to_string(),
+        None => "disc".to_string(),
+    }
+}
+
+/// Insert generated markers into list items whose counter style Blitz
+/// doesn't render or whose marker is styled by a `::marker` rule,
+/// returning the stylesheet for them, or `None` if none were inserted.
+///
+/// The type of each item is its carried type, or its computed type if
+/// none was carried. Items whose type names an `@counter-style` rule in
+/// the document's `<style>` elements, a predefined counter style or a
+/// string get a marker; other types unknown to Blitz fall back to
+/// `decimal`. Items are numbered among the list item children of their
+/// parent, following the `start` and `reversed` attributes of `<ol>`.
+///
+/// Stylo parses `::marker` rules but never computes the marker's style,
+/// so items matching one get a generated marker even for a type Blitz
+/// renders, and the rule's font, color and white-space declarations are
+/// applied to it. Styles must already be resolved; the returned stylesheet
+/// (which starts with [`MARKER_CSS`]) must be injected and the document
+/// resolved again.
+///
+/// Only top-level `@counter-style` and `::marker` rules are found, and the
+/// `range`, `negative`, `fallback` and `speak-as` descriptors are ignored.
+pub(crate) fn insert_markers(doc: &mut BaseDocument) -> Option<String> {
+    let mut styles = HashMap::new();
+    let mut marker_rules = Vec::new();
+    for (_, node) in doc.tree().iter() {
+        if node
+            .element_data()
+            .is_some_and(|element| element.name.local == local_name!("style"))
+        {
+            let css = node.text_content();
+            collect_counter_styles(&css, &mut styles);
+            collect_marker_rules(doc, &css, &mut marker_rules);
+        }
+    }
+    let mut styled_items = HashSet::new();
+    for rule in &marker_rules {
+        if let Ok(items) = doc.query_selector_all(&rule.originating) {
+            styled_items.extend(items);
+        }
+    }
+    let mut markers = Vec::new();
+    let mut ordinals = HashMap::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(style) = node.primary_styles() else {
+            continue;
+        };
+        if !style.get_box().display.is_list_item()
+            || node.element_data().is_some_and(|element| {
+                matches!(element.name.local, local_name!("ol") | local_name!("ul"))
+            })
+        {
+            continue;
+        }
+        let list_style_type = carried::value(&style, carried::LIST_STYLE_TYPE)
+            .unwrap_or_else(|| style.clone_list_style_type().to_css_string());
+        let lower = list_style_type.to_ascii_lowercase();
+        if lower == "none"
+            || NATIVE_TYPES.contains(&lower.as_str()) && !styled_items.contains(&node_id)
+        {
+            continue;
+        }
+
+        let value = ordinal(doc, node_id, &mut ordinals);
+        let text = match list_style_type.chars().next() {
+            Some('"' | '\'') => unquote(&list_style_type),
+            _ => styles
+                .get(&list_style_type)
+                .cloned()
+                .or_else(|| predefined(&list_style_type.to_ascii_lowercase()))
+                .or_else(|| predefined("decimal"))
+                .map(|counter_style| counter_style.marker(value))
+                .unwrap_or_default(),
+        };
+        let inside = style.clone_list_style_position() == ListStylePosition::Inside;
+        markers.push((node_id, text, inside));
+    }
+
+    if markers.is_empty() {
+        return None;
+    }
+    let attr = |name: &str| QualName::new(None, ns!(), LocalName::from(name));
+    let span = || QualName::new(None, ns!(html), local_name!("span"));
+    let first_children: Vec<Option<usize>> = markers
+        .iter()
+        .map(|&(node_id, _, _)| doc.get_node(node_id)?.children.first().copied())
+        .collect();
+    let mut mutator = doc.mutate();
+    for ((node_id, text, inside), first_child) in markers.into_iter().zip(first_children) {
+        mutator.set_attribute(node_id, attr(ITEM_ATTR), "");
+        let marker_id = mutator.create_element(
+            span(),
+            vec![Attribute {
+                name: attr(MARKER_ATTR),
+                value: if inside { "inside" } else { "outside" }.to_string(),
+            }],
+        );
+        let text_id = mutator.create_text_node(&text);
+        if inside {
+            mutator.append_children(marker_id, &[text_id]);
+        } else {
+            let inner_id = mutator.create_element(span(), vec![]);
+            mutator.append_children(inner_id, &[text_id]);
+            mutator.append_children(marker_id, &[inner_id]);
+        }
+        match first_child {
+            Some(first_id) => mutator.insert_nodes_before(first_id, &[marker_id]),
+            None => mutator.append_children(node_id, &[marker_id]),
+        }
+    }
+
+    let mut css = MARKER_CSS.to_string();
+    for rule in marker_rules {
+        css.push_str(&format!(
+            ":is({}) > [{}] {{ {} }}\n",
+            rule.originating, MARKER_ATTR, rule.declarations
+        ));
+    }
+    Some(css)
+}
+
+/// A `::marker` rule found in an author stylesheet.
+struct MarkerRule {
+    /// Selector for the list items whose markers it styles.
+    originating: String,
+    /// The rule's declarations of properties that apply to markers.
+    declarations: String,
+}
+
+/// Properties that apply to `::marker`, other than `content`, by name or
+/// prefix.
+const MARKER_PROPERTIES: &[&str] = &["font", "color", "white-space", "direction", "unicode-bidi"];
+
+/// Add the top-level `::marker` rules in `css` to `rules`.
+fn collect_marker_rules(doc: &BaseDocument, css: &str, rules: &mut Vec<MarkerRule>) {
+    for (prelude, block) in top_level_rules(css) {
+        if prelude.starts_with('@') {
+            continue;
+        }
+        let declarations: Vec<&str> = split_top_level(block, ';')
+            .into_iter()
+            .filter(|declaration| {
+                let name = declaration
+                    .split_once(':')
+                    .map_or("", |(name, _)| name.trim());
+                MARKER_PROPERTIES.iter().any(|property| {
+                    name.get(..property.len())
+                        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(property))
+                })
+            })
+            .collect();
+        if declarations.is_empty() {
+            continue;
+        }
+        for selector in split_top_level(prelude, ',') {
+            let Some(base) = selector
+                .len()
+                .checked_sub("::marker".len())
+                .filter(|&end| selector[end..].eq_ignore_ascii_case("::marker"))
+                .map(|end| &selector[..end])
+            else {
+                continue;
+            };
+            let originating = match base.is_empty()
+                || base.ends_with(|c: char| c.is_whitespace() || "+>~".contains(c))
+            {
+                true => format!("{}*", base),
+                false => base.to_string(),
+            };
+            if doc.try_parse_selector_list(&originating).is_ok() {
+                rules.push(MarkerRule {
+                    originating,
+                    declarations: declarations.join("; "),
+                });
+            }
+        }
+    }
+}
+
+/// The counter value of list item `node_id`: its position among the list
+/// item children of its parent, counting from the `start` of an `<ol>`,
+/// downwards if the list is `reversed`.
+///
+/// The values of all the parent's items are found at once and kept in
+/// `ordinals`, by node id, for the items that follow.
+fn ordinal(doc: &BaseDocument, node_id: usize, ordinals: &mut HashMap<usize, i64>) -> i64 {
+    if let Some(&value) = ordinals.get(&node_id) {
+        return value;
+    }
+    let Some(parent) = doc
+        .get_node(node_id)
+        .and_then(|node| node.parent)
+        .and_then(|parent_id| doc.get_node(parent_id))
+    else {
+        return 1;
+    };
+    let items: Vec<usize> = parent
+        .children
+        .iter()
+        .copied()
+        .filter(|&child_id| {
+            doc.get_node(child_id)
+                .and_then(|child| child.primary_styles())
+                .is_some_and(|style| style.get_box().display.is_list_item())
+        })
+        .collect();
+
+    let is_ol = parent
+        .element_data()
+        .is_some_and(|element| element.name.local == local_name!("ol"));
+    let reversed = is_ol && parent.attr(local_name!("reversed")).is_some();
+    let start = parent
+        .attr(local_name!("start"))
+        .filter(|_| is_ol)
+        .and_then(|start| start.trim().parse::<i64>().ok());
+    let value = |index: usize| {
+        let index = index as i64;
+        if reversed {
+            start.unwrap_or(items.len() as i64).saturating_sub(index)
+        } else {
+            start.unwrap_or(1).saturating_add(index)
+        }
+    };
+    for (index, &item_id) in items.iter().enumerate() {
+        ordinals.insert(item_id, value(index));
+    }
+    ordinals.get(&node_id).copied().unwrap_or_else(|| value(0))
+}
+
+/// Add the `@counter-style` rules in `css` to `styles`, by name.
+fn collect_counter_styles(css: &str, styles: &mut HashMap<String, CounterStyle>) {
+    for (prelude, block) in top_level_rules(css) {
+        let Some(name) = prelude
+            .strip_prefix("@counter-style")
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+        else {
+            continue;
+        };
+
+        let mut descriptors = HashMap::new();
+        for declaration in split_top_level(block, ';') {
+            if let Some((descriptor, value)) = declaration.split_once(':') {
+                descriptors.insert(descriptor.trim().to_ascii_lowercase(), value.trim());
+            }
+        }
+        let mut system = descriptors
+            .get("system")
+            .map(|system| system.split_whitespace().collect::<Vec<_>>())
+            .unwrap_or_else(|| vec!["symbolic"]);
+
+        // `extends` reuses another style's system and symbols
+        let mut counter_style = if system[0].eq_ignore_ascii_case("extends") {
+            let Some(base) = system
+                .get(1)
+                .and_then(|base| styles.get(*base).cloned().or_else(|| predefined(base)))
+            else {
+                continue;
+            };
+            system.clear();
+            base
+        } else {
+            CounterStyle {
+                system: System::Symbolic,
+                symbols: Vec::new(),
+                prefix: String::new(),
+                suffix: ". ".to_string(),
+                pad: None,
+                range: None,
+            }
+        };
+        if let Some(kind) = system.first() {
+            counter_style.system = match kind.to_ascii_lowercase().as_str() {
+                "cyclic" => System::Cyclic,
+                "fixed" => System::Fixed(system.get(1).and_then(|v| v.parse().ok()).unwrap_or(1)),
+                "symbolic" => System::Symbolic,
+                "alphabetic" => System::Alphabetic,
+                "numeric" => System::Numeric,
+                "additive" => System::Additive(
+                    descriptors
+                        .get("additive-symbols")
+                        .map(|value| {
+                            split_top_level(value, ',')
+                                .into_iter()
+                                .filter_map(|pair| {
+                                    let tokens = symbol_tokens(pair);
+                                    let (weight, symbol) = match tokens.as_slice() {
+                                        [a, b] => a
+                                            .parse()
+                                            .map(|w| (w, b))
+                                            .or_else(|_| b.parse().map(|w| (w, a))),
+                                        _ => return None,
+                                    }
+                                    .ok()?;
+                                    Some((weight, symbol.clone()))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                ),
+                _ => continue,
+            };
+        }
+        if let Some(symbols) = descriptors.get("symbols") {
+            counter_style.symbols = symbol_tokens(symbols);
+        }
+        if let Some(prefix) = descriptors.get("prefix") {
+            counter_style.prefix = symbol_tokens(prefix).concat();
+        }
+        if let Some(suffix) = descriptors.get("suffix") {
+            counter_style.suffix = symbol_tokens(suffix).concat();
+        }
+        if let Some(pad) = descriptors.get("pad") {
+            let tokens = symbol_tokens(pad);
+            if let [length, symbol] = tokens.as_slice() {
+                counter_style.pad = length
+                    .parse::<usize>()
+                    .ok()
+                    .map(|length| (length.min(MAX_SYMBOLS), symbol.clone()));
+            }
+        }
+        styles.insert(name.to_string(), counter_style);
+    }
+}
+
+/// The strings and identifiers in a descriptor value, unquoted.
+fn symbol_tokens(value: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = value.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut token = String::new();
+            while let Some(next) = chars.next() {
+                match next {
+                    '\\' => token.extend(chars.next()),
+                    _ if next == c => break,
+                    _ => token.push(next),
+                }
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() || next == '"' || next == '\'' {
+                    break;
+                }
+                token.push(next);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// A CSS string's contents.
+fn unquote(string: &str) -> String {
+    symbol_tokens(string).concat()
+}
@@ -0,0 +1,87 @@
+//! Layout box outlines for [`Config::debug_layout`](crate::Config::debug_layout).
+
+use blitz_dom::{BaseDocument, Node};
+
+/// Outline colors for each kind of box, after the ones browser developer
+/// tools use, deepened to show up as thin lines.
+const MARGIN_COLOR: [u8; 3] = [230, 126, 34];
+const BORDER_COLOR: [u8; 3] = [212, 172, 13];
+const PADDING_COLOR: [u8; 3] = [39, 174, 96];
+const CONTENT_COLOR: [u8; 3] = [41, 128, 185];
+
+/// A box to outline, in CSS pixels from the page's top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LayoutBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub color: [u8; 3],
+}
+
+/// The boxes of every element with any area, in tree order, each
+/// element's from the outside in.
+///
+/// Every element gets its border box; its margin box if it has a margin,
+/// its padding box if it has a border, and its content box if it has
+/// padding, so no box is outlined twice in different colors.
+pub(crate) fn layout_boxes(doc: &BaseDocument) -> Vec<LayoutBox> {
+    let mut boxes = Vec::new();
+    // The tree is walked with an explicit stack, so deeply nested documents
+    // can't overflow the call stack; children are pushed in reverse to come
+    // off it in tree order
+    let mut stack = vec![(doc.root_element().id, 0.0, 0.0)];
+    while let Some((node_id, parent_x, parent_y)) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        let (x, y) = collect_boxes(node, parent_x, parent_y, &mut boxes);
+        let children = node.layout_children.borrow();
+        stack.extend(
+            children
+                .iter()
+                .flatten()
+                .rev()
+                .map(|&child_id| (child_id, x, y)),
+        );
+    }
+    boxes
+}
+
+/// Add the boxes of `node`, where its layout parent's border box starts at
+/// `(parent_x, parent_y)`, and return where its own border box starts.
+fn collect_boxes(
+    node: &Node,
+    parent_x: f32,
+    parent_y: f32,
+    boxes: &mut Vec<LayoutBox>,
+) -> (f32, f32) {
+    let layout = node.final_layout;
+    let x = parent_x + layout.location.x;
+    let y = parent_y + layout.location.y;
+
+    if node.is_element() && layout.size.width > 0.0 && layout.size.height > 0.0 {
+        let mut push = |edges: taffy::Rect<f32>, outset: bool, color| {
+            let sign = if outset { 1.0 } else { -1.0 };
+            boxes.push(LayoutBox {
+                x: x - sign * edges.left,
+                y: y - sign * edges.top,
+                width: layout.size.width + sign * (edges.left + edges.right),
+                height: layout.size.height + sign * (edges.top + edges.bottom),
+                color,
+            });
+        };
+        let (margin, border, padding) = (layout.margin, layout.border, layout.padding);
+        if margin != taffy::Rect::zero() {
+            push(margin, true, MARGIN_COLOR);
+        }
+        push(taffy::Rect::zero(), true, BORDER_COLOR);
+        if border != taffy::Rect::zero() {
+            push(border, false, PADDING_COLOR);
+        }
+        if padding != taffy::Rect::zero() {
+            push(border + padding, false, CONTENT_COLOR);
+        }
+    }
+    (x, y)
+}
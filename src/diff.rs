@@ -0,0 +1,100 @@
+//! Pixel comparison of two renders, for visual-regression tooling.
+
+use crate::error::Result;
+use crate::render::png::{encode_png, unpremultiply};
+
+/// A rectangle in device pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffBounds {
+    /// Left edge.
+    pub x: u32,
+    /// Top edge.
+    pub y: u32,
+    /// Width.
+    pub width: u32,
+    /// Height.
+    pub height: u32,
+}
+
+/// The difference between two renders, returned by
+/// [`render_diff`](crate::render_diff).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffResult {
+    /// Width of the compared area in device pixels; the larger of the two renders.
+    pub width: u32,
+
+    /// Height of the compared area in device pixels; the larger of the two renders.
+    pub height: u32,
+
+    /// Number of pixels that differ in any channel. Pixels covered by only
+    /// one render (when their sizes differ) count as changed.
+    pub changed_pixels: u64,
+
+    /// Smallest rectangle containing every changed pixel, or `None` if the
+    /// renders are identical.
+    pub bounds: Option<DiffBounds>,
+
+    /// PNG highlighting the changes in red over a faded copy of the first
+    /// render, or `None` if the renders are identical.
+    pub diff_image: Option<Vec<u8>>,
+}
+
+impl DiffResult {
+    /// Whether the two renders are pixel-identical.
+    pub fn is_identical(&self) -> bool {
+        self.changed_pixels == 0
+    }
+}
+
+/// Compare two premultiplied RGBA8 buffers, given as `(buffer, width, height)`.
+pub(crate) fn compare(a: (&[u8], u32, u32), b: (&[u8], u32, u32)) -> Result<DiffResult> {
+    let width = a.1.max(b.1);
+    let height = a.2.max(b.2);
+    let pixel = |(buffer, w, h): (&[u8], u32, u32), x: u32, y: u32| {
+        (x < w && y < h).then(|| {
+            let i = ((y * w + x) * 4) as usize;
+            [buffer[i], buffer[i + 1], buffer[i + 2], buffer[i + 3]]
+        })
+    };
+
+    let mut changed_pixels = 0;
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    let mut image = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let before = pixel(a, x, y);
+            if before != pixel(b, x, y) {
+                changed_pixels += 1;
+                bounds = Some(match bounds {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    None => (x, y, x, y),
+                });
+                image.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                // Fade unchanged pixels to a light gray so the changes stand
+                // out, in straight alpha as PNG stores it
+                let [r, g, b, alpha] = before.map(|p| unpremultiply(&p)).unwrap_or_default();
+                let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+                let gray = (luma + 3 * 255) / 4;
+                image.extend_from_slice(&[gray as u8, gray as u8, gray as u8, alpha]);
+            }
+        }
+    }
+
+    let diff_image = match bounds {
+        Some(_) => Some(encode_png(&image, width, height, None)?),
+        None => None,
+    };
+    Ok(DiffResult {
+        width,
+        height,
+        changed_pixels,
+        bounds: bounds.map(|(x0, y0, x1, y1)| DiffBounds {
+            x: x0,
+            y: y0,
+            width: x1 - x0 + 1,
+            height: y1 - y0 + 1,
+        }),
+        diff_image,
+    })
+}
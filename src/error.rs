@@ -1,5 +1,7 @@
 //! Error types for hyper-render.
 
+use crate::warning::Warning;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias for hyper-render operations.
@@ -28,6 +30,14 @@ pub enum Error {
     #[error("PNG encoding failed: {0}")]
     PngEncode(String),
 
+    /// Failed to encode GIF animation.
+    #[error("GIF encoding failed: {0}")]
+    GifEncode(String),
+
+    /// Failed to encode TIFF image.
+    #[error("TIFF encoding failed: {0}")]
+    TiffEncode(String),
+
     /// Failed to create PDF document.
     #[error("PDF creation failed: {0}")]
     PdfCreate(String),
@@ -40,6 +50,75 @@ pub enum Error {
     #[error("font error: {0}")]
     Font(String),
 
+    /// A warning was raised while [`Config::strict`](crate::Config::strict) is enabled.
+    #[error("strict mode: {0}")]
+    Strict(Warning),
+
+    /// The output exceeded [`RenderBudget::max_pixels`](crate::RenderBudget::max_pixels).
+    #[error("output of {pixels} pixels exceeds the budget of {limit}")]
+    PixelLimitExceeded {
+        /// Output size in device pixels, across all frames.
+        pixels: u64,
+        /// The configured limit.
+        limit: u64,
+    },
+
+    /// The output exceeded [`RenderBudget::max_height`](crate::RenderBudget::max_height).
+    #[error("output height of {height}px exceeds the budget of {limit}px")]
+    HeightLimitExceeded {
+        /// Output height in CSS pixels.
+        height: u32,
+        /// The configured limit.
+        limit: u32,
+    },
+
+    /// Embedded data URIs exceeded [`RenderBudget::max_resource_bytes`](crate::RenderBudget::max_resource_bytes).
+    #[error("embedded resources of {bytes} bytes exceed the budget of {limit} bytes")]
+    ResourceLimitExceeded {
+        /// Total size of the data URIs in bytes.
+        bytes: usize,
+        /// The configured limit.
+        limit: usize,
+    },
+
+    /// The DOM exceeded [`RenderBudget::max_depth`](crate::RenderBudget::max_depth).
+    #[error("document depth of {depth} exceeds the budget of {limit}")]
+    DepthLimitExceeded {
+        /// Nesting depth of the deepest node.
+        depth: usize,
+        /// The configured limit.
+        limit: usize,
+    },
+
+    /// A laid-out element exceeded the size set with
+    /// [`Config::assert_dimensions`](crate::Config::assert_dimensions).
+    #[error("<{element}> laid out at {width}x{height}px, over the {limit}px limit")]
+    ElementTooLarge {
+        /// Tag name of the oversized element.
+        element: String,
+        /// Border-box width in CSS pixels.
+        width: u32,
+        /// Border-box height in CSS pixels.
+        height: u32,
+        /// The configured limit.
+        limit: u32,
+    },
+
+    /// A resource referenced by URL could not be loaded under
+    /// [`ResourcePolicy::Strict`](crate::ResourcePolicy::Strict), or for
+    /// PDF/A output with [`Config::pdf_conformance`](crate::Config::pdf_conformance).
+    #[error("failed to load {url}: {reason}")]
+    Network {
+        /// The resolved URL of the image, stylesheet or font.
+        url: String,
+        /// Why it was not loaded.
+        reason: String,
+    },
+
+    /// The render ran past [`RenderBudget::deadline`](crate::RenderBudget::deadline).
+    #[error("render exceeded its deadline of {0:?}")]
+    DeadlineExceeded(Duration),
+
     /// I/O error occurred.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -0,0 +1,292 @@
+//! `::first-letter` styling, which Stylo doesn't parse in Servo mode.
+
+use blitz_dom::node::Attribute;
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, QualName};
+use std::collections::BTreeMap;
+use style::values::computed::Display;
+
+/// Attribute on the generated first-letter spans, listing the indices of the
+/// `::first-letter` rules that apply to them.
+const LETTER_ATTR: &str = "data-hyper-render-first-letter";
+
+/// Elements whose content can't hold a first letter, ending the search.
+const REPLACED_ELEMENTS: &[&str] = &[
+    "br", "img", "svg", "input", "textarea", "select", "button", "video", "canvas", "iframe",
+    "object",
+];
+
+/// A `::first-letter` rule found in an author stylesheet.
+struct FirstLetterRule {
+    /// Selector for the originating elements.
+    originating: String,
+    /// The rule's declarations, copied verbatim.
+    declarations: String,
+}
+
+/// Apply author `::first-letter` rules to the document.
+///
+/// Stylo drops any rule with a `::first-letter` selector, so drop caps and
+/// similar styling never reach layout. This finds those rules in the
+/// document's `<style>` elements, wraps the first letter of each matching
+/// element (along with any punctuation before it) in a generated `<span>`,
+/// and returns a stylesheet applying the rules' declarations to the spans,
+/// along with the rest of any selector lists the rules were dropped with.
+/// Returns `None` if there are no such rules to apply; otherwise the
+/// stylesheet must be injected and the document resolved again.
+///
+/// Only top-level rules are found, so rules inside `@media` and other
+/// at-rules are not applied. Styles must already be resolved.
+pub(crate) fn wrap_first_letters(doc: &mut BaseDocument) -> Option<String> {
+    let mut rules = Vec::new();
+    let mut css = String::new();
+    for (_, node) in doc.tree().iter() {
+        if node
+            .element_data()
+            .is_some_and(|element| element.name.local == local_name!("style"))
+        {
+            css.push_str(&collect_rules(doc, &node.text_content(), &mut rules));
+        }
+    }
+    if rules.is_empty() && css.is_empty() {
+        return None;
+    }
+
+    // Group the rules by the text node holding each element's first letter
+    let mut letters: BTreeMap<usize, (usize, usize, Vec<usize>)> = BTreeMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        let Ok(elements) = doc.query_selector_all(&rule.originating) else {
+            continue;
+        };
+        for element_id in elements {
+            if let Some((text_id, start, end)) = first_letter(doc, element_id) {
+                let entry = letters.entry(text_id).or_insert((start, end, Vec::new()));
+                if !entry.2.contains(&index) {
+                    entry.2.push(index);
+                }
+            }
+        }
+    }
+    let letters: Vec<_> = letters
+        .into_iter()
+        .filter_map(|(text_id, (start, end, indices))| {
+            let text = doc.get_node(text_id)?.text_data()?.content.clone();
+            Some((text_id, text, start, end, indices))
+        })
+        .collect();
+
+    let mut mutator = doc.mutate();
+    for (text_id, text, start, end, mut indices) in letters {
+        indices.sort_unstable();
+        if start > 0 {
+            let before_id = mutator.create_text_node(&text[..start]);
+            mutator.insert_nodes_before(text_id, &[before_id]);
+        }
+        let value = indices
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let span_id = mutator.create_element(
+            QualName::new(None, ns!(html), local_name!("span")),
+            vec![Attribute {
+                name: QualName::new(None, ns!(), LocalName::from(LETTER_ATTR)),
+                value,
+            }],
+        );
+        let letter_id = mutator.create_text_node(&text[start..end]);
+        mutator.append_children(span_id, &[letter_id]);
+        mutator.insert_nodes_before(text_id, &[span_id]);
+        mutator.set_node_text(text_id, &text[end..]);
+    }
+
+    for (index, rule) in rules.iter().enumerate() {
+        css.push_str(&format!(
+            "[{}~=\"{}\"] {{{}}}\n",
+            LETTER_ATTR, index, rule.declarations
+        ));
+    }
+    Some(css)
+}
+
+/// Add the `::first-letter` rules in `css` to `rules`, returning CSS for the
+/// other selectors in their selector lists.
+fn collect_rules(doc: &BaseDocument, css: &str, rules: &mut Vec<FirstLetterRule>) -> String {
+    let mut others = String::new();
+    for (prelude, block) in top_level_rules(css) {
+        if prelude.starts_with('@') {
+            continue;
+        }
+
+        let mut plain = Vec::new();
+        let mut found = false;
+        for selector in split_top_level(prelude, ',') {
+            let Some(originating) = originating_selector(selector) else {
+                plain.push(selector);
+                continue;
+            };
+            found = true;
+            if doc.try_parse_selector_list(&originating).is_ok() {
+                rules.push(FirstLetterRule {
+                    originating,
+                    declarations: block.to_string(),
+                });
+            }
+        }
+        if found && !plain.is_empty() {
+            others.push_str(&format!("{} {{{}}}\n", plain.join(", "), block));
+        }
+    }
+    others
+}
+
+/// The selector for the originating element if `selector` ends in
+/// `::first-letter` (or the legacy `:first-letter`).
+fn originating_selector(selector: &str) -> Option<String> {
+    let lower = selector.to_ascii_lowercase();
+    let suffix_len = if lower.ends_with("::first-letter") {
+        "::first-letter".len()
+    } else if lower.ends_with(":first-letter") {
+        ":first-letter".len()
+    } else {
+        return None;
+    };
+
+    let base = &selector[..selector.len() - suffix_len];
+    if base.trim().is_empty() || base.ends_with(|c: char| c.is_whitespace() || "+>~".contains(c)) {
+        Some(format!("{}*", base))
+    } else {
+        Some(base.to_string())
+    }
+}
+
+/// Split CSS `text` on `separator`, ignoring separators inside brackets,
+/// strings and comments.
+pub(crate) fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    scan(text, |i, c| match c {
+        '(' | '[' | '{' => depth += 1,
+        ')' | ']' | '}' => depth = depth.saturating_sub(1),
+        c if c == separator && depth == 0 => {
+            parts.push(text[start..i].trim());
+            start = i + c.len_utf8();
+        }
+        _ => {}
+    });
+    parts.push(text[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// The `(prelude, block contents)` of each top-level rule in a stylesheet.
+pub(crate) fn top_level_rules(css: &str) -> Vec<(&str, &str)> {
+    let mut rules = Vec::new();
+    let mut prelude_start = 0;
+    let mut block_start = 0;
+    let mut depth = 0usize;
+    scan(css, |i, c| match c {
+        '{' => {
+            if depth == 0 {
+                block_start = i + 1;
+            }
+            depth += 1;
+        }
+        '}' if depth > 0 => {
+            depth -= 1;
+            if depth == 0 {
+                rules.push((
+                    css[prelude_start..block_start - 1].trim(),
+                    &css[block_start..i],
+                ));
+                prelude_start = i + 1;
+            }
+        }
+        ';' if depth == 0 => prelude_start = i + 1,
+        _ => {}
+    });
+    rules
+}
+
+/// Call `visit` with the byte index of each character of CSS `text` outside
+/// strings and comments.
+fn scan(text: &str, mut visit: impl FnMut(usize, char)) {
+    let mut chars = text.char_indices().peekable();
+    let mut quote = None;
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '/' && chars.peek().is_some_and(|&(_, next)| next == '*') => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, c) in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            None => visit(i, c),
+        }
+    }
+}
+
+/// The text node and byte range of `element_id`'s first letter: its first
+/// letter or digit along with any punctuation before it.
+fn first_letter(doc: &BaseDocument, element_id: usize) -> Option<(usize, usize, usize)> {
+    let element = doc.get_node(element_id)?;
+    let display = element.primary_styles()?.get_box().display;
+    if display == Display::Inline || display == Display::None {
+        return None;
+    }
+    find_letter(doc, element_id).ok().flatten()
+}
+
+/// Depth-first search for the first letter under `node_id`; `Err` when
+/// content that ends the search was reached first.
+fn find_letter(doc: &BaseDocument, node_id: usize) -> Result<Option<(usize, usize, usize)>, ()> {
+    let node = doc.get_node(node_id).ok_or(())?;
+    for &child_id in &node.children {
+        let child = doc.get_node(child_id).ok_or(())?;
+        if let Some(text) = child.text_data() {
+            let content = &text.content;
+            let Some(start) = content.find(|c: char| !c.is_whitespace()) else {
+                continue;
+            };
+            let mut end = start;
+            for (i, c) in content[start..].char_indices() {
+                end = start + i + c.len_utf8();
+                if c.is_alphanumeric() {
+                    break;
+                }
+                if c.is_whitespace() {
+                    end = start + i;
+                    break;
+                }
+            }
+            return Ok(Some((child_id, start, end)));
+        }
+        if let Some(element) = child.element_data() {
+            if REPLACED_ELEMENTS.contains(&&*element.name.local) {
+                return Err(());
+            }
+            let hidden = child
+                .primary_styles()
+                .is_some_and(|style| style.get_box().display == Display::None);
+            if hidden {
+                continue;
+            }
+            if let Some(found) = find_letter(doc, child_id)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,294 @@
+//! Font loading and glyph coverage checks.
+
+use crate::error::{Error, Result};
+use blitz_dom::BaseDocument;
+use blitz_traits::net::Bytes;
+use parley::fontique::{Blob, Collection, CollectionOptions, GenericFamily, SourceCache};
+use parley::{FontContext, PositionedLayoutItem};
+use skrifa::{string::StringId, FontRef, MetadataProvider};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// File extensions recognized as font files (matched case-insensitively).
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "woff2"];
+
+/// Recursively collect font data from every font file under `dir`.
+///
+/// Files without a recognized font extension are ignored. WOFF2 files are
+/// decompressed to plain OpenType data. Entries are visited in sorted order
+/// so registration is deterministic across platforms. Symlinked directories
+/// are followed, but each directory is read once, so links back up the tree
+/// can't loop.
+pub(crate) fn load_fonts_dir(dir: &Path) -> Result<Vec<Bytes>> {
+    let mut fonts = Vec::new();
+    collect_fonts(dir, &mut fonts, &mut HashSet::new())?;
+    Ok(fonts)
+}
+
+/// Collect the fonts under `dir` unless its canonical path is in `visited`.
+fn collect_fonts(dir: &Path, fonts: &mut Vec<Bytes>, visited: &mut HashSet<PathBuf>) -> Result<()> {
+    if !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_fonts(&path, fonts, visited)?;
+            continue;
+        }
+
+        let Some(ext) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+        else {
+            continue;
+        };
+        if !FONT_EXTENSIONS.contains(&ext.as_str()) {
+            continue;
+        }
+
+        let data = std::fs::read(&path)?;
+        let data = if ext == "woff2" {
+            wuff::decompress_woff2(&data).map_err(|e| {
+                Error::Font(format!("failed to decompress {}: {}", path.display(), e))
+            })?
+        } else {
+            data
+        };
+        fonts.push(Bytes::from(data));
+    }
+
+    Ok(())
+}
+
+/// Open Sans, bundled so text is drawn without system fonts or a fonts
+/// directory. Licensed under the Apache License 2.0, in
+/// `assets/fonts/OPEN-SANS-LICENSE.txt`.
+const DEFAULT_FONT: &[u8] = include_bytes!("../assets/fonts/OpenSans-Regular.woff2");
+
+/// The bundled default font, decompressed once.
+pub(crate) fn default_font() -> Bytes {
+    static FONT: OnceLock<Bytes> = OnceLock::new();
+    FONT.get_or_init(|| {
+        Bytes::from(wuff::decompress_woff2(DEFAULT_FONT).expect("bundled font should decompress"))
+    })
+    .clone()
+}
+
+/// Whether any of `fonts` has a glyph for `c`.
+pub(crate) fn any_maps(fonts: &[Bytes], c: char) -> bool {
+    fonts.iter().any(|data| {
+        skrifa::raw::FileRef::new(data.as_ref()).is_ok_and(|file| {
+            file.fonts()
+                .flatten()
+                .any(|font| font.charmap().map(c).is_some())
+        })
+    })
+}
+
+/// Generic families the fonts of a context without system fonts stand in
+/// for.
+const GENERIC_FAMILIES: &[GenericFamily] = &[
+    GenericFamily::Serif,
+    GenericFamily::SansSerif,
+    GenericFamily::Monospace,
+    GenericFamily::Cursive,
+    GenericFamily::Fantasy,
+    GenericFamily::SystemUi,
+    GenericFamily::UiSerif,
+    GenericFamily::UiSansSerif,
+    GenericFamily::UiMonospace,
+    GenericFamily::UiRounded,
+    GenericFamily::Emoji,
+    GenericFamily::Math,
+    GenericFamily::FangSong,
+];
+
+/// A font context holding only `fonts`, for [`Config::system_fonts`](crate::Config::system_fonts)
+/// disabled.
+///
+/// Without system fonts no family stands behind the generic families, so
+/// the given ones stand in for all of them, in order, followed by the
+/// bundled default font.
+pub(crate) fn font_context_without_system_fonts(fonts: Vec<Bytes>) -> FontContext {
+    let mut collection = Collection::new(CollectionOptions {
+        shared: false,
+        system_fonts: false,
+    });
+    let mut families = Vec::new();
+    for font in fonts.into_iter().chain([default_font()]) {
+        for (family, _) in collection.register_fonts(Blob::new(std::sync::Arc::new(font)), None) {
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+    }
+    for &generic in GENERIC_FAMILIES {
+        collection.set_generic_families(generic, families.iter().copied());
+    }
+    FontContext {
+        collection,
+        source_cache: SourceCache::default(),
+    }
+}
+
+/// Find characters that no available font could shape.
+///
+/// Scans every inline text layout for clusters mapped to the `.notdef`
+/// glyph (ID 0), which renders as "tofu". Whitespace and control characters
+/// are ignored. Characters are returned once each, in document order.
+pub(crate) fn find_missing_glyphs(doc: &BaseDocument) -> Vec<char> {
+    let mut missing = Vec::new();
+
+    for (_, node) in doc.tree().iter() {
+        let Some(text_layout) = node
+            .element_data()
+            .and_then(|data| data.inline_layout_data.as_ref())
+        else {
+            continue;
+        };
+
+        for line in text_layout.layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+                for cluster in glyph_run.run().visual_clusters() {
+                    if !cluster.glyphs().any(|glyph| glyph.id == 0) {
+                        continue;
+                    }
+                    let text = text_layout.text.get(cluster.text_range()).unwrap_or("");
+                    for c in text.chars() {
+                        if !c.is_whitespace() && !c.is_control() && !missing.contains(&c) {
+                            missing.push(c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    missing
+}
+
+/// Format missing characters for an error message, e.g. `'字' (U+5B57)`.
+pub(crate) fn describe_missing_glyphs(missing: &[char]) -> String {
+    missing
+        .iter()
+        .map(|c| format!("'{}' (U+{:04X})", c, *c as u32))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Family names of the fonts used to shape the document's text, in order of
+/// first use.
+///
+/// Fonts without a readable name table are reported as `"unknown"`.
+pub(crate) fn used_font_families(doc: &BaseDocument) -> Vec<String> {
+    let mut families: Vec<String> = Vec::new();
+
+    for (_, node) in doc.tree().iter() {
+        let Some(text_layout) = node
+            .element_data()
+            .and_then(|data| data.inline_layout_data.as_ref())
+        else {
+            continue;
+        };
+
+        for line in text_layout.layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+                let font = glyph_run.run().font();
+                let family = FontRef::from_index(font.data.as_ref(), font.index)
+                    .ok()
+                    .and_then(|font_ref| {
+                        font_ref
+                            .localized_strings(StringId::FAMILY_NAME)
+                            .english_or_first()
+                    })
+                    .map_or_else(|| "unknown".to_string(), |name| name.to_string());
+                if !families.contains(&family) {
+                    families.push(family);
+                }
+            }
+        }
+    }
+
+    families
+}
+
+/// The glyphs `font` substitutes for others under the OpenType `feature`,
+/// from the feature's single substitution lookups.
+///
+/// Other lookup types (ligatures, alternates, contextual lookups) are not
+/// applied, which covers figure styles such as `onum` in common fonts.
+#[cfg(feature = "pdf")]
+pub(crate) fn single_substitutions(
+    font: &FontRef,
+    feature: skrifa::Tag,
+) -> std::collections::HashMap<u32, u32> {
+    use skrifa::raw::tables::gsub::{ExtensionSubtable, SingleSubst, SubstitutionLookup};
+    use skrifa::raw::TableProvider;
+
+    let mut substitutions = std::collections::HashMap::new();
+    let Ok(gsub) = font.gsub() else {
+        return substitutions;
+    };
+    let (Ok(features), Ok(lookups)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return substitutions;
+    };
+    let lookup_indices = features
+        .feature_records()
+        .iter()
+        .filter(|record| record.feature_tag() == feature)
+        .filter_map(|record| record.feature(features.offset_data()).ok())
+        .flat_map(|feature| feature.lookup_list_indices().to_vec());
+    for index in lookup_indices {
+        let subtables: Vec<SingleSubst> = match lookups.lookups().get(index.get() as usize) {
+            Ok(SubstitutionLookup::Single(lookup)) => lookup.subtables().iter().flatten().collect(),
+            Ok(SubstitutionLookup::Extension(lookup)) => lookup
+                .subtables()
+                .iter()
+                .flatten()
+                .filter_map(|subtable| match subtable {
+                    ExtensionSubtable::Single(extension) => extension.extension().ok(),
+                    _ => None,
+                })
+                .collect(),
+            _ => continue,
+        };
+        for subtable in subtables {
+            match subtable {
+                SingleSubst::Format1(table) => {
+                    let Ok(coverage) = table.coverage() else {
+                        continue;
+                    };
+                    for glyph in coverage.iter() {
+                        let substitute = (glyph.to_u32() as i32 + table.delta_glyph_id() as i32)
+                            .rem_euclid(0x10000) as u32;
+                        substitutions.entry(glyph.to_u32()).or_insert(substitute);
+                    }
+                }
+                SingleSubst::Format2(table) => {
+                    let Ok(coverage) = table.coverage() else {
+                        continue;
+                    };
+                    for (glyph, substitute) in coverage.iter().zip(table.substitute_glyph_ids()) {
+                        substitutions
+                            .entry(glyph.to_u32())
+                            .or_insert(substitute.get().to_u32());
+                    }
+                }
+            }
+        }
+    }
+    substitutions
+}
@@ -0,0 +1,34 @@
+//! Form control values that Blitz leaves to the embedder.
+
+use blitz_dom::{local_name, ns, BaseDocument, QualName};
+
+/// Give each `<textarea>` without a `value` attribute one holding its text
+/// content.
+///
+/// Blitz fills a text control's editor from its `value` attribute alone, so
+/// a textarea's initial text, which HTML puts in its content, never shows.
+/// The editor is created the first time the document is resolved, so this
+/// must run before then.
+pub(crate) fn fill_textarea_values(doc: &mut BaseDocument) {
+    let values: Vec<(usize, String)> = doc
+        .tree()
+        .iter()
+        .filter(|(_, node)| {
+            node.element_data().is_some_and(|element| {
+                element.name.local == local_name!("textarea")
+                    && element.attr(local_name!("value")).is_none()
+            })
+        })
+        .map(|(node_id, node)| (node_id, node.text_content()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+
+    let mut mutator = doc.mutate();
+    for (node_id, text) in values {
+        mutator.set_attribute(
+            node_id,
+            QualName::new(None, ns!(), local_name!("value")),
+            &text,
+        );
+    }
+}
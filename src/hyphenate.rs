@@ -0,0 +1,106 @@
+//! Automatic hyphenation for `hyphens: auto`.
+
+use crate::carried;
+use blitz_dom::{local_name, BaseDocument, Node};
+use hypher::Lang;
+
+/// SOFT HYPHEN, a break opportunity that shows a hyphen when a line is
+/// broken at it.
+const SOFT_HYPHEN: &str = "\u{AD}";
+
+/// Hyphenate the text of elements with `hyphens: auto`.
+///
+/// Parley has no hyphenation of its own, so this inserts a soft hyphen at
+/// each hyphenation point of the words, found with the patterns of the
+/// element's language: the nearest `lang` attribute, which
+/// [`Config::lang`](crate::Config::lang) sets on the root. Text in a
+/// language without patterns, or without a language, is left alone, as
+/// are words that already hold a soft hyphen and the contents of
+/// `<style>`, `<script>` and `<textarea>`.
+///
+/// Styles must already be resolved; returns whether any text changed, in
+/// which case the document needs resolving again.
+pub(crate) fn hyphenate(doc: &mut BaseDocument) -> bool {
+    let mut updates = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(text) = node.text_data() else {
+            continue;
+        };
+        let Some(parent) = node.parent.and_then(|parent| doc.get_node(parent)) else {
+            continue;
+        };
+        let in_raw_text = parent.element_data().is_some_and(|parent| {
+            matches!(
+                parent.name.local,
+                local_name!("style") | local_name!("script") | local_name!("textarea")
+            )
+        });
+        if in_raw_text || !hyphens_auto(parent) {
+            continue;
+        }
+        let Some(lang) = language(doc, parent) else {
+            continue;
+        };
+        let hyphenated = hyphenate_text(&text.content, lang);
+        if hyphenated != text.content {
+            updates.push((node_id, hyphenated));
+        }
+    }
+
+    let changed = !updates.is_empty();
+    let mut mutator = doc.mutate();
+    for (node_id, text) in updates {
+        mutator.set_node_text(node_id, &text);
+    }
+    changed
+}
+
+/// Whether `node`'s text is set with `hyphens: auto`.
+fn hyphens_auto(node: &Node) -> bool {
+    node.primary_styles()
+        .and_then(|style| carried::value(&style, carried::HYPHENS))
+        .is_some_and(|value| value.eq_ignore_ascii_case("auto"))
+}
+
+/// The hyphenation patterns for the language of `node`, from the primary
+/// subtag of the nearest `lang` attribute.
+fn language(doc: &BaseDocument, node: &Node) -> Option<Lang> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if let Some(tag) = node
+            .element_data()
+            .and_then(|element| element.attr(local_name!("lang")))
+        {
+            let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+            let code: [u8; 2] = primary.as_bytes().try_into().ok()?;
+            return Lang::from_iso(code);
+        }
+        current = node.parent.and_then(|parent| doc.get_node(parent));
+    }
+    None
+}
+
+/// `text` with a soft hyphen at each hyphenation point of its words in
+/// `lang`.
+fn hyphenate_text(text: &str, lang: Lang) -> String {
+    let mut hyphenated = String::with_capacity(text.len());
+    for word in text.split_inclusive(char::is_whitespace) {
+        // Manual soft hyphens are the only break opportunities in their word
+        if word.contains(SOFT_HYPHEN) {
+            hyphenated.push_str(word);
+            continue;
+        }
+        let mut rest = word;
+        while let Some(start) = rest.find(char::is_alphabetic) {
+            hyphenated.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+            hyphenated.push_str(&hypher::hyphenate(&rest[..end], lang).join(SOFT_HYPHEN));
+            rest = &rest[end..];
+        }
+        hyphenated.push_str(rest);
+    }
+    hyphenated
+}
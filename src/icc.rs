@@ -0,0 +1,122 @@
+//! ICC profiles for [`Config::icc_profile`](crate::Config::icc_profile).
+
+#[cfg(feature = "pdf")]
+use crate::error::{Error, Result};
+#[cfg(feature = "pdf")]
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+#[cfg(feature = "pdf")]
+use std::collections::BTreeSet;
+
+/// Size of an ICC profile header in bytes.
+const HEADER_LEN: usize = 128;
+
+/// Number of color components of the ICC profile in `data`, or `None` if its
+/// header isn't that of a complete RGB, CMYK or grayscale profile.
+pub(crate) fn components(data: &[u8]) -> Option<u8> {
+    if data.len() < HEADER_LEN || &data[36..40] != b"acsp" {
+        return None;
+    }
+    let declared_len = u32::from_be_bytes(data[0..4].try_into().ok()?);
+    if declared_len as usize != data.len() {
+        return None;
+    }
+    match &data[16..20] {
+        b"GRAY" => Some(1),
+        b"RGB " => Some(3),
+        b"CMYK" => Some(4),
+        _ => None,
+    }
+}
+
+/// Add `profile` to a finished PDF as its output intent.
+///
+/// Krilla only writes an output intent for PDF/A, and always with its own
+/// sRGB profile, so the PDF is parsed again to add the profile stream and
+/// an output intent referencing it to the catalog. An RGB profile also
+/// tags the content, which Krilla writes in device RGB: it is set as the
+/// `/DefaultRGB` color space of every resource dictionary, so device RGB
+/// colors and images are drawn in it. CMYK and grayscale profiles can't
+/// describe RGB colors, so they only set the output intent.
+#[cfg(feature = "pdf")]
+pub(crate) fn add_output_intent(pdf: Vec<u8>, profile: &[u8]) -> Result<Vec<u8>> {
+    let malformed =
+        |e: lopdf::Error| Error::PdfCreate(format!("could not add the ICC output intent: {}", e));
+    let components = components(profile).ok_or_else(|| {
+        Error::PdfCreate("icc_profile is not an RGB, CMYK or grayscale ICC profile".to_string())
+    })?;
+
+    let mut doc = Document::load_mem(&pdf).map_err(malformed)?;
+    let profile_id = doc.add_object(Stream::new(
+        dictionary! { "N" => i64::from(components) },
+        profile.to_vec(),
+    ));
+    let intent_id = doc.add_object(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFA1",
+        "OutputConditionIdentifier" => Object::string_literal("Custom"),
+        "DestOutputProfile" => profile_id,
+    });
+    doc.catalog_mut()
+        .map_err(malformed)?
+        .set("OutputIntents", vec![Object::Reference(intent_id)]);
+    if components == 3 {
+        let color_space = Object::Array(vec!["ICCBased".into(), profile_id.into()]);
+        set_default_rgb(&mut doc, &color_space);
+    }
+
+    let mut bytes = Vec::with_capacity(pdf.len() + profile.len());
+    doc.save_to(&mut bytes)
+        .map_err(|e| malformed(lopdf::Error::IO(e)))?;
+    Ok(bytes)
+}
+
+/// Set `color_space` as the `/DefaultRGB` color space of every resource
+/// dictionary in `doc`, whether held directly by its page or XObject or by
+/// reference.
+#[cfg(feature = "pdf")]
+fn set_default_rgb(doc: &mut Document, color_space: &Object) {
+    let mut resources = BTreeSet::new();
+    let mut color_spaces = BTreeSet::new();
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        match dict.get_mut(b"Resources") {
+            Ok(Object::Dictionary(dict)) => {
+                color_spaces.extend(set_color_space(dict, color_space));
+            }
+            Ok(Object::Reference(id)) => {
+                resources.insert(*id);
+            }
+            _ => {}
+        }
+    }
+    for id in resources {
+        if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(id) {
+            color_spaces.extend(set_color_space(dict, color_space));
+        }
+    }
+    for id in color_spaces {
+        if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(id) {
+            dict.set("DefaultRGB", color_space.clone());
+        }
+    }
+}
+
+/// Set `color_space` as `/DefaultRGB` in the `resources` dictionary,
+/// returning the id of its color space dictionary instead if that is held
+/// by reference.
+#[cfg(feature = "pdf")]
+fn set_color_space(resources: &mut Dictionary, color_space: &Object) -> Option<ObjectId> {
+    match resources.get_mut(b"ColorSpace") {
+        Ok(Object::Dictionary(dict)) => dict.set("DefaultRGB", color_space.clone()),
+        Ok(Object::Reference(id)) => return Some(*id),
+        _ => resources.set(
+            "ColorSpace",
+            dictionary! { "DefaultRGB" => color_space.clone() },
+        ),
+    }
+    None
+}
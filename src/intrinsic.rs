@@ -0,0 +1,188 @@
+//! Intrinsic sizing keywords (`min-content`, `max-content`, `fit-content`)
+//! for widths, which Blitz lays out like `auto`.
+
+use blitz_dom::{local_name, ns, BaseDocument, QualName};
+use style::computed_values::box_sizing::T as BoxSizing;
+use style::values::computed::{CSSPixelLength, Display, MaxSize, Size};
+use taffy::{
+    AvailableSpace, LayoutInput, LayoutPartialTree, Line, NodeId, RequestedAxis, RunMode,
+    SizingMode,
+};
+
+/// An intrinsic size keyword: `min-content`, `max-content` or `fit-content`.
+#[derive(Clone, Copy)]
+enum Intrinsic {
+    Min,
+    Max,
+    /// `fit-content`, or `fit-content()` with its limit in CSS pixels.
+    Fit(Option<f32>),
+}
+
+/// Width properties that accept intrinsic sizes.
+const PROPERTIES: [&str; 3] = ["width", "min-width", "max-width"];
+
+/// Give elements whose `width`, `min-width` or `max-width` is an intrinsic
+/// size keyword that width in pixels.
+///
+/// Stylo's conversion to Taffy styles maps these keywords to `auto`, so a
+/// block with `width: fit-content` stretches to fill its container instead
+/// of shrinking to its contents. This measures the min-content and
+/// max-content width of each such element with Taffy, and sets the width
+/// the keyword resolves to through the element's `style` attribute.
+/// `fit-content` fits the width the element was laid out at, which is the
+/// space available to it. Styles and layout must already be resolved;
+/// returns whether any element changed, in which case the document needs
+/// resolving again.
+///
+/// Heights are left alone, as intrinsic heights lay out like `auto` anyway.
+pub(crate) fn fix_intrinsic_widths(doc: &mut BaseDocument) -> bool {
+    let mut targets = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(style) = node.primary_styles() else {
+            continue;
+        };
+        let display = style.get_box().display;
+        if display == Display::Inline || display.is_none() {
+            continue;
+        }
+        let position = style.get_position();
+        let container_width = containing_width(doc, node_id);
+        let percentage_basis = CSSPixelLength::new(container_width.unwrap_or_default());
+        let keywords = [
+            size_keyword(&position.width, percentage_basis),
+            size_keyword(&position.min_width, percentage_basis),
+            max_size_keyword(&position.max_width, percentage_basis),
+        ];
+        if keywords.iter().any(Option::is_some) {
+            let content_box = position.box_sizing == BoxSizing::ContentBox;
+            targets.push((node_id, keywords, content_box, container_width));
+        }
+    }
+    if targets.is_empty() {
+        return false;
+    }
+
+    let mut updates = Vec::new();
+    for (node_id, keywords, content_box, container_width) in targets {
+        let min_content = measure(doc, node_id, AvailableSpace::MinContent, container_width);
+        let max_content = measure(doc, node_id, AvailableSpace::MaxContent, container_width);
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        let layout = &node.final_layout;
+        let available = layout.size.width;
+        let edges = match content_box {
+            true => {
+                layout.padding.left
+                    + layout.padding.right
+                    + layout.border.left
+                    + layout.border.right
+            }
+            false => 0.0,
+        };
+
+        let declarations: Vec<String> = PROPERTIES
+            .iter()
+            .zip(keywords)
+            .filter_map(|(property, keyword)| {
+                let width = match keyword? {
+                    Intrinsic::Min => min_content,
+                    Intrinsic::Max => max_content,
+                    Intrinsic::Fit(limit) => {
+                        let limit = limit.unwrap_or(available);
+                        limit.min(max_content).max(min_content)
+                    }
+                };
+                Some(format!(
+                    "{}: {}px !important",
+                    property,
+                    (width - edges).max(0.0)
+                ))
+            })
+            .collect();
+        let style = match node.attr(local_name!("style")) {
+            Some(style) => format!("{}; {}", style, declarations.join("; ")),
+            None => declarations.join("; "),
+        };
+        updates.push((node_id, style));
+    }
+
+    let mut mutator = doc.mutate();
+    for (node_id, style) in updates {
+        mutator.set_attribute(
+            node_id,
+            QualName::new(None, ns!(), local_name!("style")),
+            &style,
+        );
+    }
+    true
+}
+
+/// The intrinsic keyword of a `width` or `min-width` value, resolving a
+/// `fit-content()` percentage against `basis`.
+fn size_keyword(size: &Size, basis: CSSPixelLength) -> Option<Intrinsic> {
+    match size {
+        Size::MinContent => Some(Intrinsic::Min),
+        Size::MaxContent => Some(Intrinsic::Max),
+        Size::FitContent => Some(Intrinsic::Fit(None)),
+        Size::FitContentFunction(limit) => Some(Intrinsic::Fit(Some(limit.0.resolve(basis).px()))),
+        _ => None,
+    }
+}
+
+/// The intrinsic keyword of a `max-width` value, resolving a
+/// `fit-content()` percentage against `basis`.
+fn max_size_keyword(size: &MaxSize, basis: CSSPixelLength) -> Option<Intrinsic> {
+    match size {
+        MaxSize::MinContent => Some(Intrinsic::Min),
+        MaxSize::MaxContent => Some(Intrinsic::Max),
+        MaxSize::FitContent => Some(Intrinsic::Fit(None)),
+        MaxSize::FitContentFunction(limit) => {
+            Some(Intrinsic::Fit(Some(limit.0.resolve(basis).px())))
+        }
+        _ => None,
+    }
+}
+
+/// Width of the content box of the element's parent, which percentages
+/// inside the element resolve against.
+fn containing_width(doc: &BaseDocument, node_id: usize) -> Option<f32> {
+    let parent = doc.get_node(doc.get_node(node_id)?.parent?)?;
+    let layout = &parent.final_layout;
+    Some(
+        layout.size.width
+            - layout.padding.left
+            - layout.padding.right
+            - layout.border.left
+            - layout.border.right,
+    )
+}
+
+/// The border box width of the element's contents laid out in `space`.
+fn measure(
+    doc: &mut BaseDocument,
+    node_id: usize,
+    space: AvailableSpace,
+    container_width: Option<f32>,
+) -> f32 {
+    doc.compute_child_layout(
+        NodeId::from(node_id),
+        LayoutInput {
+            run_mode: RunMode::ComputeSize,
+            sizing_mode: SizingMode::ContentSize,
+            axis: RequestedAxis::Horizontal,
+            known_dimensions: taffy::Size::NONE,
+            parent_size: taffy::Size {
+                width: container_width,
+                height: None,
+            },
+            available_space: taffy::Size {
+                width: space,
+                height: AvailableSpace::MaxContent,
+            },
+            vertical_margins_are_collapsible: Line::FALSE,
+        },
+    )
+    .size
+    .width
+}
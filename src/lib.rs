@@ -11,6 +11,8 @@
 //!
 //! - **PNG output**: Render HTML to PNG images using CPU-based rendering
 //! - **PDF output**: Render HTML to PDF documents with vector graphics
+//! - **GIF output**: Capture CSS animations as animated GIFs (`gif` feature)
+//! - **TIFF output**: Lossless multi-page raster images (`tiff` feature)
 //! - **No browser required**: Pure Rust implementation, no Chromium/WebKit
 //! - **CSS support**: Flexbox, Grid, and common CSS properties via Stylo
 //!
@@ -52,16 +54,69 @@
 //!     .format(OutputFormat::Png);
 //! ```
 
+mod autosize;
+mod background;
+mod budget;
+mod captions;
+mod capture;
+mod carried;
+mod color;
 mod config;
+mod counter_style;
+#[cfg(any(feature = "png", feature = "pdf"))]
+mod debug_layout;
+#[cfg(feature = "png")]
+mod diff;
 mod error;
+mod first_letter;
+mod fonts;
+mod forms;
+mod hyphenate;
+mod icc;
+mod intrinsic;
+mod ligatures;
+mod output;
+mod overlay;
+mod placeholder;
+mod quotes;
 mod render;
+mod resources;
+mod sanitize;
+mod small_caps;
+mod snap;
+mod sprites;
+#[cfg(feature = "pdf")]
+mod toc;
+mod warning;
+mod whitespace;
 
-pub use config::{ColorScheme, Config, OutputFormat};
+pub use budget::RenderBudget;
+pub use config::{
+    BackgroundFit, BackgroundImage, ColorScheme, Config, ErrorPolicy, LinearGradientSpec,
+    OutputFormat, PageLabelRange, PageLabelStyle, PdfA, PdfVersion, PixelMode, PngColorType,
+    ResourcePolicy, Rotation,
+};
+#[cfg(feature = "png")]
+pub use diff::{DiffBounds, DiffResult};
 pub use error::{Error, Result};
+pub use output::{PdfPage, RenderManifest, RenderOutput};
+pub use overlay::{Overlay, OverlayTarget};
+pub use warning::Warning;
 
-use blitz_dom::DocumentConfig;
+/// Re-export of the scene API used by [`paint_into_scene`].
+#[cfg(feature = "png")]
+pub use anyrender;
+
+/// Re-export of the PDF library whose surface [`OverlayTarget::Pdf`] exposes.
+#[cfg(feature = "pdf")]
+pub use krilla;
+
+use blitz_dom::net::Resource;
+use blitz_dom::{local_name, ns, DocumentConfig, QualName};
 use blitz_html::HtmlDocument;
 use blitz_traits::shell::Viewport;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Render HTML content to the specified output format.
 ///
@@ -75,16 +130,20 @@ use blitz_traits::shell::Viewport;
 ///
 /// # Returns
 ///
-/// Returns the rendered output as bytes (PNG image data or PDF document).
+/// Returns the rendered output as bytes (PNG image data, PDF document, GIF
+/// animation, SVG markup or TIFF image).
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Configuration is invalid (zero dimensions, non-positive scale)
+/// - The configured fonts directory cannot be read
+/// - `require_full_coverage` is set and a character has no glyph
 /// - HTML parsing fails
 /// - Layout computation fails
 /// - Rendering fails
 /// - The requested output format feature is not enabled
+/// - A [`RenderBudget`] bound set with [`Config::budget`] is exceeded
 ///
 /// # Example
 ///
@@ -101,19 +160,500 @@ use blitz_traits::shell::Viewport;
 /// # Ok::<(), hyper_render::Error>(())
 /// ```
 pub fn render(html: &str, config: Config) -> Result<Vec<u8>> {
+    render_with_warnings(html, config).map(|(bytes, _)| bytes)
+}
+
+/// Render several HTML documents in parallel with the same config.
+///
+/// Each document is rendered as by [`render`], entirely on one thread of a
+/// pool, and results are returned in the order of `htmls`; one failing
+/// document doesn't affect the others. The pool has
+/// [`Config::concurrency`] threads, or one per CPU by default.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_batch, Config};
+///
+/// let cards = ["<h1>One</h1>", "<h1>Two</h1>", "<h1>Three</h1>"];
+/// for (i, png) in render_batch(&cards, Config::default()).into_iter().enumerate() {
+///     std::fs::write(format!("card-{}.png", i), png?)?;
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn render_batch(htmls: &[&str], config: Config) -> Vec<Result<Vec<u8>>> {
+    use rayon::prelude::*;
+
+    let render_all = || {
+        htmls
+            .par_iter()
+            .map(|html| render(html, config.clone()))
+            .collect()
+    };
+    let pool = config
+        .concurrency
+        .filter(|&threads| threads > 0)
+        .and_then(|threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .ok()
+        });
+    match pool {
+        Some(pool) => pool.install(render_all),
+        // Without a limit (or a pool to enforce it), use the global pool;
+        // an invalid limit fails each render's validation
+        None => render_all(),
+    }
+}
+
+/// Render HTML content and report any non-fatal warnings.
+///
+/// Behaves like [`render`], but also returns the [`Warning`]s raised while
+/// rendering (for example, clipped content when [`Config::overflow_guard`]
+/// is enabled). With [`Config::strict`], the first warning is returned as
+/// [`Error::Strict`] instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_with_warnings, Config};
+///
+/// let config = Config::new().height(100).overflow_guard(true);
+/// let (png, warnings) = render_with_warnings("<div style=\"height: 500px\"></div>", config)?;
+/// for warning in &warnings {
+///     eprintln!("warning: {}", warning);
+/// }
+/// # Ok::<(), hyper_render::Error>(())
+/// ```
+pub fn render_with_warnings(html: &str, config: Config) -> Result<(Vec<u8>, Vec<Warning>)> {
+    render_detailed(html, config).map(|output| (output.bytes, output.warnings))
+}
+
+/// Render HTML content and describe the result.
+///
+/// Behaves like [`render_with_warnings`], but returns a [`RenderOutput`]
+/// that also records the output dimensions, page count, fonts used and
+/// render time. With the `serde` feature, [`RenderOutput::manifest_json`]
+/// turns these into a sidecar manifest.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_detailed, Config};
+///
+/// let output = render_detailed("<h1>Hello</h1>", Config::default())?;
+/// println!("{}x{} using {:?}", output.width, output.height, output.fonts);
+/// # Ok::<(), hyper_render::Error>(())
+/// ```
+pub fn render_detailed(html: &str, config: Config) -> Result<RenderOutput> {
+    render_detailed_with_pages(html, config, &mut |_, _| {})
+}
+
+/// [`render_detailed`], calling `on_page` as each PDF page is finished.
+///
+/// Under [`ErrorPolicy::Placeholder`], a failed render is replaced by a
+/// placeholder showing the error, if the placeholder itself renders.
+fn render_detailed_with_pages(
+    html: &str,
+    config: Config,
+    on_page: &mut dyn FnMut(usize, &PdfPage),
+) -> Result<RenderOutput> {
+    let fallback_config = match config.on_error {
+        ErrorPolicy::Propagate => None,
+        ErrorPolicy::Placeholder => Some(placeholder::placeholder_config(&config)),
+    };
+    let error = match render_document(html, config, on_page) {
+        Ok(output) => return Ok(output),
+        Err(error) => error,
+    };
+    let Some(fallback_config) = fallback_config else {
+        return Err(error);
+    };
+    match render_document(
+        &placeholder::placeholder_html(&error),
+        fallback_config,
+        on_page,
+    ) {
+        Ok(mut output) => {
+            output.warnings = vec![Warning::RenderFailed {
+                message: error.to_string(),
+            }];
+            Ok(output)
+        }
+        Err(_) => Err(error),
+    }
+}
+
+/// Render a document, calling `on_page` as each PDF page is finished.
+fn render_document(
+    html: &str,
+    config: Config,
+    on_page: &mut dyn FnMut(usize, &PdfPage),
+) -> Result<RenderOutput> {
+    let started = Instant::now();
+
+    // Resolve a CSS background color into the RGBA value the renderers use,
+    // and the height an aspect ratio gives; a forced page size leaves
+    // `auto_height` only measuring the content
+    let mut config = config;
+    config.background = config.background_rgba()?;
+    config.height = config.aspect_height();
+    config.auto_height &= !config.force_page_size;
+
+    let (mut document, warnings) = prepare_document(html, &config, started)?;
+    let height = output_height(&document, &config);
+    let content_height = render::content_height(document.as_ref()).ceil() as u32;
+
+    // Render to the specified format
+    let bytes = match config.format {
+        OutputFormat::Png => render::png::render_to_png(&mut document, &config, started),
+        OutputFormat::Pdf => {
+            let toc = toc_document(&document, &config, height, started)?;
+            render::pdf::render_to_pdf(&mut document, toc.as_ref(), &config, started, on_page)
+        }
+        OutputFormat::Gif => render::gif::render_to_gif(&mut document, &config),
+        OutputFormat::Svg => render::svg::render_to_svg(&document, &config).map(String::into_bytes),
+        OutputFormat::Tiff => render::tiff::render_to_tiff(&document, &config),
+    }?;
+
+    if let Some(budget) = &config.budget {
+        budget.check_deadline(started)?;
+    }
+
+    let page_count = match config.format {
+        OutputFormat::Gif => config.frames,
+        OutputFormat::Pdf if config.toc => 2,
+        _ => 1,
+    };
+    let capture = capture::capture_rect(&document, &config)?;
+    let overhang = capture::overhang_rect(&document, &config, height);
+    let (width, height) = match (capture, overhang) {
+        (Some(rect), _) if config.format == OutputFormat::Png => {
+            let (_, _, width, height) = rect.device_pixels(config.device_scale());
+            (
+                (width as f32 / config.device_scale()).round() as u32,
+                (height as f32 / config.device_scale()).round() as u32,
+            )
+        }
+        (None, Some(rect)) if config.format == OutputFormat::Png => {
+            (rect.width as u32, rect.height as u32)
+        }
+        _ => (config.width, height),
+    };
+    let (width, height) = config.rotation.rotated_size(width, height);
+    Ok(RenderOutput {
+        bytes,
+        format: config.format,
+        width,
+        height,
+        content_height,
+        scale: config.device_scale(),
+        page_count,
+        fonts: fonts::used_font_families(&document),
+        warnings,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Lay out HTML and paint it into a caller-provided Anyrender scene.
+///
+/// For composing documents into a larger scene (e.g. several documents or
+/// overlays) instead of getting encoded bytes. The document is laid out as
+/// for [`render`] and painted exactly as PNG output would be, then:
+///
+/// - Coordinates are device pixels: CSS pixels multiplied by [`Config::scale`].
+/// - The document's top-left corner is placed at `(x, y)` in the scene.
+/// - Painting is clipped to the output size, which is returned as
+///   `(width, height)` in device pixels.
+/// - Existing scene content is kept; nothing is painted outside the clip.
+///
+/// [`Config::format`] and the output-only options (such as [`Config::crop_margins`])
+/// are ignored. `config.background` is not painted, as with PNG output, and
+/// neither is [`Config::background_image`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::anyrender::{render_to_buffer, PaintScene};
+/// use hyper_render::{paint_into_scene, Config};
+/// use anyrender_vello_cpu::VelloCpuImageRenderer;
+///
+/// let config = Config::new().size(200, 100);
+/// let pixels = render_to_buffer::<VelloCpuImageRenderer, _>(
+///     |scene| {
+///         paint_into_scene("<h1>Left</h1>", config.clone(), scene, 0.0, 0.0).unwrap();
+///         paint_into_scene("<h1>Right</h1>", config.clone(), scene, 200.0, 0.0).unwrap();
+///     },
+///     400,
+///     100,
+/// );
+/// ```
+#[cfg(feature = "png")]
+pub fn paint_into_scene(
+    html: &str,
+    config: Config,
+    scene: &mut impl anyrender::PaintScene,
+    x: f64,
+    y: f64,
+) -> Result<(u32, u32)> {
+    let config = Config {
+        height: config.aspect_height(),
+        auto_height: config.auto_height && !config.force_page_size,
+        ..config
+    };
+    let (document, _) = prepare_document(html, &config, Instant::now())?;
+    Ok(render::scene::paint_document(
+        scene, &document, &config, x, y,
+    ))
+}
+
+/// Render two HTML documents and compare them pixel by pixel.
+///
+/// A building block for visual-regression and snapshot testing. Both
+/// documents are rendered with the same config as PNG output would be,
+/// before encoding (including backdrop blurs, [`Config::overlay`],
+/// [`Config::capture`], [`Config::crop_margins`] and [`Config::rotate_output`];
+/// [`Config::format`] and [`Config::png_color_type`] are ignored), and the
+/// result reports how many pixels changed, where, and an image highlighting
+/// the changes.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_diff, Config};
+///
+/// let diff = render_diff("<h1>Hello</h1>", "<h1>Hallo</h1>", Config::default())?;
+/// if let Some(image) = &diff.diff_image {
+///     std::fs::write("diff.png", image)?;
+/// }
+/// println!("{} pixels changed in {:?}", diff.changed_pixels, diff.bounds);
+/// # Ok::<(), hyper_render::Error>(())
+/// ```
+#[cfg(feature = "png")]
+pub fn render_diff(html_a: &str, html_b: &str, config: Config) -> Result<DiffResult> {
+    let started = Instant::now();
+    let config = Config {
+        format: OutputFormat::Png,
+        height: config.aspect_height(),
+        auto_height: config.auto_height && !config.force_page_size,
+        ..config
+    };
+    let (mut document_a, _) = prepare_document(html_a, &config, started)?;
+    let (buffer_a, width_a, height_a) =
+        render::png::render_png_buffer(&mut document_a, &config, started)?;
+    let (mut document_b, _) = prepare_document(html_b, &config, started)?;
+    let (buffer_b, width_b, height_b) =
+        render::png::render_png_buffer(&mut document_b, &config, started)?;
+    diff::compare(
+        (&buffer_a, width_a, height_a),
+        (&buffer_b, width_b, height_b),
+    )
+}
+
+/// Validate the config, then parse, style and lay out the document.
+///
+/// Applies the resource, budget and coverage checks that precede painting,
+/// returning the warnings raised along the way.
+fn prepare_document(
+    html: &str,
+    config: &Config,
+    started: Instant,
+) -> Result<(HtmlDocument, Vec<Warning>)> {
     // Validate configuration
     config.validate()?;
 
     // Parse HTML and create document
-    let mut document = create_document(html, &config)?;
+    let (mut document, resources, fonts_dir_fonts) = create_document(html, config)?;
+    if config.sanitize {
+        sanitize::strip_scripts(&mut document);
+    }
+
+    let mut warnings = Vec::new();
+    if let Some(limit) = config.max_resource_bytes {
+        warnings.extend(resources::strip_oversized_data_uris(&mut document, limit));
+    }
+    if let Some(budget) = &config.budget {
+        budget.check_document(&document)?;
+        budget.check_deadline(started)?;
+    }
 
-    // Resolve styles and compute layout
-    document.resolve(0.0);
+    forms::fill_textarea_values(&mut document);
+    if let Some(css) = carried::carry_properties(&mut document) {
+        inject_stylesheet(&mut document, &css);
+    }
+    if let Some(css) = sprites::color_attributes_css(&document) {
+        inject_stylesheet(&mut document, &css);
+    }
 
-    // Render to the specified format
-    match config.format {
-        OutputFormat::Png => render::png::render_to_png(&document, &config),
-        OutputFormat::Pdf => render::pdf::render_to_pdf(&document, &config),
+    // Resolve styles and compute layout. Animations start the first time
+    // the document is resolved, so it is resolved at time zero before
+    // sampling them at `resolve_timestamp`
+    if config.resolve_timestamp > 0.0 {
+        document.resolve(0.0);
+    }
+    resolve_document(&mut document, &resources, config);
+    let quotes_changed = quotes::generate_quotes(&mut document);
+    let first_letter_css = first_letter::wrap_first_letters(&mut document);
+    if let Some(css) = &first_letter_css {
+        inject_stylesheet(&mut document, css);
+    }
+    let marker_css = counter_style::insert_markers(&mut document);
+    if let Some(css) = &marker_css {
+        inject_stylesheet(&mut document, css);
+    }
+    let small_caps_changed = small_caps::synthesize_small_caps(&mut document);
+    if small_caps_changed {
+        inject_stylesheet(&mut document, small_caps::SMALL_CAPS_CSS);
+    }
+    let captions_changed = captions::place_captions(&mut document);
+    let sprites_changed = sprites::resolve_svg_sprites(&mut document);
+    let widths_changed = intrinsic::fix_intrinsic_widths(&mut document);
+    let hyphenated = hyphenate::hyphenate(&mut document);
+    let ligatures_broken = !config.ligatures && {
+        // Without system fonts, only the fonts directory's fonts and the
+        // bundled default font are fallen back to
+        let fallback_has_non_joiner = config.system_fonts
+            || fonts::any_maps(&[fonts::default_font()], ligatures::ZWNJ)
+            || fonts::any_maps(&fonts_dir_fonts, ligatures::ZWNJ);
+        ligatures::break_ligatures(&mut document, fallback_has_non_joiner)
+    };
+    if whitespace::collapse_pre_line(&mut document)
+        || widths_changed
+        || quotes_changed
+        || first_letter_css.is_some()
+        || marker_css.is_some()
+        || small_caps_changed
+        || captions_changed
+        || sprites_changed
+        || hyphenated
+        || ligatures_broken
+    {
+        resolve_document(&mut document, &resources, config);
+    }
+    if let Some(selector) = &config.autosize_text {
+        autosize::autosize_text(&mut document, selector, |document| {
+            resolve_document(document, &resources, config)
+        })?;
+    }
+    snap::snap_layout(&mut document, config);
+
+    // Images and stylesheets are requested while parsing, background images
+    // while resolving styles. PDF/A output must embed everything it shows,
+    // so it can't go without any of them
+    let pdf_conformance = config
+        .pdf_conformance
+        .filter(|_| config.format == OutputFormat::Pdf);
+    for url in resources.urls() {
+        let reason = resources::NOT_LOADED_REASON.to_string();
+        if let Some(level) = pdf_conformance {
+            let reason = format!("{} output must embed every resource, and {}", level, reason);
+            return Err(Error::Network { url, reason });
+        }
+        match config.resource_policy {
+            ResourcePolicy::BestEffort => warnings.push(Warning::ResourceNotLoaded { url, reason }),
+            ResourcePolicy::Strict => return Err(Error::Network { url, reason }),
+        }
+    }
+    warnings.extend(resources.oversized_images());
+
+    if let Some(budget) = &config.budget {
+        budget.check_output(output_height(&document, config), config)?;
+        budget.check_deadline(started)?;
+    }
+
+    if let Some(limit) = config.max_element_px {
+        check_element_sizes(&document, limit)?;
+    }
+
+    if config.require_full_coverage {
+        let missing = fonts::find_missing_glyphs(&document);
+        if !missing.is_empty() {
+            return Err(Error::Font(format!(
+                "no font covers {}",
+                fonts::describe_missing_glyphs(&missing)
+            )));
+        }
+    }
+
+    if config.overflow_guard {
+        warnings.extend(check_overflow(&document, config));
+    }
+    if config.strict {
+        if let Some(warning) = warnings.first() {
+            return Err(Error::Strict(warning.clone()));
+        }
+    }
+
+    Ok((document, warnings))
+}
+
+/// The [`Config::toc`] page for a PDF of `document`, laid out at the
+/// document's page `height`, or `None` if none is configured.
+#[cfg(feature = "pdf")]
+fn toc_document(
+    document: &HtmlDocument,
+    config: &Config,
+    height: u32,
+    started: Instant,
+) -> Result<Option<HtmlDocument>> {
+    if !config.toc {
+        return Ok(None);
+    }
+    let headings = render::pdf::heading_titles(document.as_ref());
+    let html = toc::toc_html(&headings, 2);
+    let (toc, _) = prepare_document(&html, &toc::toc_config(config, height), started)?;
+    Ok(Some(toc))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn toc_document(
+    _document: &HtmlDocument,
+    _config: &Config,
+    _height: u32,
+    _started: Instant,
+) -> Result<Option<HtmlDocument>> {
+    Ok(None)
+}
+
+/// Most style and layout passes [`resolve_document`] runs.
+const MAX_RESOLVE_PASSES: usize = 4;
+
+/// Resolve styles and layout, loading embedded resources as they are requested.
+///
+/// Images and stylesheets are requested while parsing and background images
+/// while resolving, and each one loaded can change layout (an image takes
+/// its intrinsic size, a web font changes text metrics), so the document is
+/// resolved again until no more resources arrive, up to
+/// [`MAX_RESOLVE_PASSES`] times. Web fonts are left out unless
+/// [`Config::retry_layout_on_font_load`] is set.
+fn resolve_document(
+    document: &mut HtmlDocument,
+    resources: &resources::OfflineResources,
+    config: &Config,
+) {
+    document.resolve(config.resolve_timestamp);
+    for _ in 1..MAX_RESOLVE_PASSES {
+        let mut loaded = resources.load_pending();
+        if !config.retry_layout_on_font_load {
+            loaded.retain(|resource| !matches!(resource, Resource::Font(_)));
+        }
+        if loaded.is_empty() {
+            break;
+        }
+        for resource in loaded {
+            document.load_resource(resource);
+        }
+        document.resolve(config.resolve_timestamp);
+    }
+}
+
+/// Output height in CSS pixels, following the content with `auto_height`.
+fn output_height(document: &HtmlDocument, config: &Config) -> u32 {
+    if config.auto_height {
+        render::content_height(document.as_ref()).ceil() as u32
+    } else {
+        config.height
     }
 }
 
@@ -155,21 +695,221 @@ pub fn render_to_pdf(html: &str, config: Config) -> Result<Vec<u8>> {
     render(html, config.format(OutputFormat::Pdf))
 }
 
+/// Render HTML content to PDF, reporting each page as it is finished.
+///
+/// Behaves like [`render_to_pdf`] and returns the same bytes, but calls
+/// `on_page` with the zero-based page index and the [`PdfPage`] once each
+/// page's content has been written, so callers can observe progress on
+/// large documents. Output is a single page, preceded by a table of
+/// contents page with [`Config::toc`].
+///
+/// Pages are reported as they are written, before the render is known to
+/// succeed. Under [`ErrorPolicy::Placeholder`], a render failing after
+/// reporting pages is replaced by the placeholder, which reports its own
+/// pages from index 0 again; the pages reported before should then be
+/// discarded.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_pdf_streaming, Config};
+///
+/// let pdf_bytes = render_pdf_streaming("<h1>Hello</h1>", Config::default(), |index, page| {
+///     eprintln!("page {} done ({}x{}pt)", index + 1, page.width, page.height);
+/// })?;
+/// std::fs::write("output.pdf", pdf_bytes)?;
+/// # Ok::<(), hyper_render::Error>(())
+/// ```
+#[cfg(feature = "pdf")]
+pub fn render_pdf_streaming(
+    html: &str,
+    config: Config,
+    mut on_page: impl FnMut(usize, &PdfPage),
+) -> Result<Vec<u8>> {
+    render_detailed_with_pages(html, config.format(OutputFormat::Pdf), &mut on_page)
+        .map(|output| output.bytes)
+}
+
+/// Render HTML content to an SVG string.
+///
+/// Convenience function for embedding the output inline (e.g. in an HTML
+/// response) without converting bytes. The markup is a standalone `<svg>`
+/// element whose `viewBox` matches the output size in device pixels.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hyper_render::{render_to_svg_string, Config};
+///
+/// let svg = render_to_svg_string("<h1>Hello</h1>", Config::default())?;
+/// println!("<figure>{}</figure>", svg);
+/// # Ok::<(), hyper_render::Error>(())
+/// ```
+#[cfg(feature = "svg")]
+pub fn render_to_svg_string(html: &str, config: Config) -> Result<String> {
+    let bytes = render(html, config.format(OutputFormat::Svg))?;
+    Ok(String::from_utf8(bytes).expect("SVG output is always UTF-8"))
+}
+
+/// User agent rules from the HTML spec mapping the `dir` attribute to
+/// `direction`, which Blitz's default stylesheet leaves out.
+const DIR_CSS: &str = "[dir=rtl i] { direction: rtl; } [dir=ltr i] { direction: ltr; }";
+
+/// User agent colors for `<mark>`. Blitz's default stylesheet uses the
+/// `Mark` and `MarkText` system colors, which Stylo's servo build doesn't
+/// parse; these are the values browsers give them.
+const MARK_CSS: &str = "mark { background-color: yellow; color: black; }";
+
+/// User agent rule making `<wbr>` a line break opportunity, as a zero width
+/// space; Blitz otherwise ignores the element.
+const WBR_CSS: &str = r#"wbr::before { content: "\200B"; }"#;
+
+/// User agent styles for [`Config::fragment_mode`], removing the default
+/// body margin.
+const FRAGMENT_CSS: &str = "html, body { margin: 0; background-color: transparent; }";
+
 /// Create and configure a Blitz document from HTML.
-fn create_document(html: &str, config: &Config) -> Result<HtmlDocument> {
+///
+/// Also returns the tracker of the resources the document requests by URL,
+/// and the fonts loaded from [`Config::fonts_dir`].
+fn create_document(
+    html: &str,
+    config: &Config,
+) -> Result<(
+    HtmlDocument,
+    Arc<resources::OfflineResources>,
+    Vec<blitz_traits::net::Bytes>,
+)> {
+    // Blitz expects the viewport in physical pixels and divides by the scale
+    // factor to get CSS pixels, so `100vw`/`100vh` must be scaled up here to
+    // cover the configured width/height at any scale.
+    let physical_width = (config.width as f64 * config.device_scale() as f64).round() as u32;
+    let physical_height = (config.height as f64 * config.device_scale() as f64).round() as u32;
     let viewport = Viewport::new(
-        config.width,
-        config.height,
-        config.scale,
+        physical_width,
+        physical_height,
+        config.device_scale(),
         config.color_scheme.into(),
     );
 
+    // Bundled fonts are registered before the first style resolution, in
+    // the font context itself when there are no system fonts to fall back on
+    let bundled_fonts = match &config.fonts_dir {
+        Some(dir) => fonts::load_fonts_dir(dir)?,
+        None => Vec::new(),
+    };
+    let fonts_dir_fonts = bundled_fonts.clone();
+    let (font_ctx, bundled_fonts) = match config.system_fonts {
+        true => (None, bundled_fonts),
+        false => (
+            Some(fonts::font_context_without_system_fonts(bundled_fonts)),
+            Vec::new(),
+        ),
+    };
+
+    let resources = Arc::new(resources::OfflineResources::new(
+        config.max_resource_bytes,
+        config.max_decoded_image_dimension,
+    ));
     let doc_config = DocumentConfig {
         viewport: Some(viewport),
+        net_provider: Some(resources.clone()),
+        font_ctx,
         ..Default::default()
     };
 
-    Ok(HtmlDocument::from_html(html, doc_config))
+    let mut document = HtmlDocument::from_html(html, doc_config);
+    document.add_user_agent_stylesheet(DIR_CSS);
+    document.add_user_agent_stylesheet(MARK_CSS);
+    document.add_user_agent_stylesheet(WBR_CSS);
+    if config.fragment_mode {
+        document.add_user_agent_stylesheet(FRAGMENT_CSS);
+    }
+
+    for font in bundled_fonts {
+        document.load_resource(Resource::Font(font));
+    }
+
+    // Set the document language before styles are resolved. The attribute is
+    // written directly: the mutator would invalidate styles that don't exist yet
+    if let Some(lang) = &config.lang {
+        let root_id = document.root_element().id;
+        if let Some(element) = document
+            .get_node_mut(root_id)
+            .and_then(|node| node.element_data_mut())
+        {
+            element
+                .attrs
+                .set(QualName::new(None, ns!(), local_name!("lang")), lang);
+        }
+    }
+
+    // Apply the stylesheet matching the configured color scheme
+    if let Some((light, dark)) = &config.theme_css {
+        let css = match config.color_scheme {
+            ColorScheme::Light => light,
+            ColorScheme::Dark => dark,
+        };
+        inject_stylesheet(&mut document, css);
+    }
+
+    Ok((document, resources, fonts_dir_fonts))
+}
+
+/// Append a `<style>` element with the given CSS to the root element.
+///
+/// The new element follows every stylesheet in the parsed document, so its
+/// rules win ties against author rules of equal specificity.
+fn inject_stylesheet(document: &mut HtmlDocument, css: &str) {
+    let root_id = document.root_element().id;
+
+    let mut mutator = document.mutate();
+    let style_id =
+        mutator.create_element(QualName::new(None, ns!(html), local_name!("style")), vec![]);
+    let text_id = mutator.create_text_node(css);
+    mutator.append_children(style_id, &[text_id]);
+    mutator.append_children(root_id, &[style_id]);
+}
+
+/// Fail if any element's border box is wider or taller than `limit` pixels.
+fn check_element_sizes(document: &HtmlDocument, limit: u32) -> Result<()> {
+    for (_, node) in document.as_ref().tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        let size = node.final_layout.size;
+        if size.width > limit as f32 || size.height > limit as f32 {
+            return Err(Error::ElementTooLarge {
+                element: element.name.local.to_string(),
+                width: size.width as u32,
+                height: size.height as u32,
+                limit,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Detect laid-out content that extends past the output bounds.
+///
+/// Height is only checked when `auto_height` is disabled, since the output
+/// grows to fit the content otherwise.
+fn check_overflow(document: &HtmlDocument, config: &Config) -> Option<Warning> {
+    let layout = &document.as_ref().root_element().final_layout;
+    let content_width = layout.size.width.max(layout.content_size.width);
+    let content_height = layout.size.height.max(layout.content_size.height);
+
+    let overflow_x = (content_width - config.width as f32).max(0.0).ceil() as u32;
+    let overflow_y = if config.auto_height {
+        0
+    } else {
+        (content_height - config.height as f32).max(0.0).ceil() as u32
+    };
+
+    (overflow_x > 0 || overflow_y > 0).then_some(Warning::ContentClipped {
+        overflow_x,
+        overflow_y,
+    })
 }
 
 #[cfg(test)]
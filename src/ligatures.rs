@@ -0,0 +1,200 @@
+//! Turning ligatures off for [`Config::ligatures`](crate::Config::ligatures).
+
+use blitz_dom::{local_name, BaseDocument};
+use parley::PositionedLayoutItem;
+use skrifa::{FontRef, MetadataProvider};
+use std::collections::{HashMap, HashSet};
+
+/// ZERO WIDTH NON-JOINER, which stops the characters either side of it
+/// forming a ligature without changing their spacing.
+pub(crate) const ZWNJ: char = '\u{200C}';
+
+/// Keep the document's text from forming ligatures.
+///
+/// Blitz shapes text with no font features of its own and offers no way to
+/// pass any, so fonts form their default ligatures (`ffi`, `fl`, a code
+/// font's `->`). This finds the character sequences each element's text
+/// was shaped into ligatures from, and inserts a zero width non-joiner
+/// between the characters of those sequences wherever they appear in the
+/// element's text, which the shaper then draws as separate glyphs at their
+/// usual advances. All other text is left as it is. Scripts whose letters
+/// join, such as Arabic, and combining marks are never separated, nor are
+/// the contents of `<style>`, `<script>` and `<textarea>`.
+///
+/// A character and the non-joiner after it shape as one cluster, which
+/// Parley moves to a fallback font if the font it was using has no glyph
+/// for the non-joiner, so when `fallback_has_non_joiner` says a font to
+/// fall back to has one, text is only changed if every font it was shaped
+/// with has one too. Otherwise the cluster stays in its font, which draws
+/// nothing for the non-joiner. Styles and layout must already be resolved;
+/// returns whether any text changed, in which case the document needs
+/// resolving again.
+pub(crate) fn break_ligatures(doc: &mut BaseDocument, fallback_has_non_joiner: bool) -> bool {
+    let ligatures = shaped_ligatures(doc, fallback_has_non_joiner);
+    let mut updates = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(text) = node.text_data() else {
+            continue;
+        };
+        let Some(sequences) = node.parent.and_then(|parent| ligatures.get(&parent)) else {
+            continue;
+        };
+        let in_raw_text = node
+            .parent
+            .and_then(|parent| doc.get_node(parent))
+            .and_then(|parent| parent.element_data())
+            .is_some_and(|parent| {
+                matches!(
+                    parent.name.local,
+                    local_name!("style") | local_name!("script") | local_name!("textarea")
+                )
+            });
+        if in_raw_text {
+            continue;
+        }
+        let broken = insert_non_joiners(&text.content, sequences);
+        if broken != text.content {
+            updates.push((node_id, broken));
+        }
+    }
+
+    let changed = !updates.is_empty();
+    let mut mutator = doc.mutate();
+    for (node_id, text) in updates {
+        mutator.set_node_text(node_id, &text);
+    }
+    changed
+}
+
+/// The character sequences shaped into ligatures, by the element whose
+/// text they are in.
+///
+/// When `fallback_has_non_joiner`, elements whose text was shaped with a
+/// font without a glyph for the zero width non-joiner are left out.
+fn shaped_ligatures(
+    doc: &BaseDocument,
+    fallback_has_non_joiner: bool,
+) -> HashMap<usize, HashSet<String>> {
+    let mut ligatures: HashMap<usize, HashSet<String>> = HashMap::new();
+    let mut uncovered = HashSet::new();
+    for (_, node) in doc.tree().iter() {
+        let Some(text_layout) = node
+            .element_data()
+            .and_then(|data| data.inline_layout_data.as_ref())
+        else {
+            continue;
+        };
+        for line in text_layout.layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+                let span = glyph_run.style().brush.id;
+                let font = glyph_run.run().font();
+                let has_non_joiner = FontRef::from_index(font.data.as_ref(), font.index)
+                    .is_ok_and(|font_ref| font_ref.charmap().map(ZWNJ).is_some());
+                if !has_non_joiner {
+                    uncovered.insert(span);
+                }
+
+                // A ligature is a starting cluster followed by the clusters
+                // of the rest of its characters
+                let mut clusters = glyph_run.run().clusters().peekable();
+                while let Some(cluster) = clusters.next() {
+                    if !cluster.is_ligature_start() {
+                        continue;
+                    }
+                    let mut range = cluster.text_range();
+                    while let Some(component) =
+                        clusters.next_if(|cluster| cluster.is_ligature_continuation())
+                    {
+                        range.end = range.end.max(component.text_range().end);
+                    }
+                    if let Some(sequence) = text_layout.text.get(range) {
+                        if sequence.chars().nth(1).is_some() {
+                            ligatures
+                                .entry(span)
+                                .or_default()
+                                .insert(sequence.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if fallback_has_non_joiner {
+        ligatures.retain(|span, _| !uncovered.contains(span));
+    }
+    ligatures
+}
+
+/// `text` with a zero width non-joiner between each pair of adjacent
+/// characters that are part of one of the `ligatures` sequences.
+fn insert_non_joiners(text: &str, ligatures: &HashSet<String>) -> String {
+    let mut breaks = HashSet::new();
+    for (start, _) in text.char_indices() {
+        for ligature in ligatures {
+            if !text[start..].starts_with(ligature.as_str()) {
+                continue;
+            }
+            let mut previous = None;
+            for (offset, c) in ligature.char_indices() {
+                if previous.is_some_and(can_ligate) && can_ligate(c) {
+                    breaks.insert(start + offset);
+                }
+                previous = Some(c);
+            }
+        }
+    }
+
+    let mut broken = String::with_capacity(text.len() + breaks.len() * ZWNJ.len_utf8());
+    for (offset, c) in text.char_indices() {
+        if breaks.contains(&offset) {
+            broken.push(ZWNJ);
+        }
+        broken.push(c);
+    }
+    broken
+}
+
+/// `text` without the non-joiners [`break_ligatures`] could have inserted,
+/// for the text of the PDF's text layer, along with where each byte offset
+/// into `text` lands in it. Non-joiners between characters that are never
+/// separated, such as Persian letters, were written by the author and stay.
+///
+/// Returns `None` when there are none to remove.
+#[cfg(feature = "pdf")]
+pub(crate) fn strip_non_joiners(text: &str) -> Option<(String, Vec<usize>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let inserted = |index: usize| {
+        chars[index].1 == ZWNJ
+            && index > 0
+            && can_ligate(chars[index - 1].1)
+            && chars
+                .get(index + 1)
+                .is_some_and(|&(_, next)| can_ligate(next))
+    };
+    if !(0..chars.len()).any(inserted) {
+        return None;
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    for (index, &(_, c)) in chars.iter().enumerate() {
+        offsets.extend(std::iter::repeat_n(stripped.len(), c.len_utf8()));
+        if !inserted(index) {
+            stripped.push(c);
+        }
+    }
+    offsets.push(stripped.len());
+    Some((stripped, offsets))
+}
+
+/// Whether `c` is a visible Latin, Greek or Cyrillic character other than a
+/// combining mark.
+fn can_ligate(c: char) -> bool {
+    !c.is_whitespace()
+        && !c.is_control()
+        && c < '\u{0530}'
+        && !('\u{0300}'..='\u{036F}').contains(&c)
+}
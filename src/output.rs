@@ -0,0 +1,117 @@
+//! Detailed render results and their manifests.
+
+use crate::config::OutputFormat;
+use crate::warning::Warning;
+use std::time::Duration;
+
+/// Rendered output along with details about how it was produced.
+///
+/// Returned by [`render_detailed`](crate::render_detailed).
+#[derive(Debug, Clone)]
+pub struct RenderOutput {
+    /// The rendered bytes (PNG image data, PDF document, GIF animation, SVG markup or TIFF image).
+    pub bytes: Vec<u8>,
+
+    /// Format of `bytes`.
+    pub format: OutputFormat,
+
+    /// Output width in CSS pixels, after [`Config::rotate_output`](crate::Config::rotate_output).
+    pub width: u32,
+
+    /// Output height in CSS pixels, after [`Config::auto_height`](crate::Config::auto_height)
+    /// and [`Config::rotate_output`](crate::Config::rotate_output).
+    pub height: u32,
+
+    /// Height of the laid-out content in CSS pixels, whatever the output
+    /// height; what [`Config::auto_height`](crate::Config::auto_height) sizes
+    /// the output to.
+    pub content_height: u32,
+
+    /// Scale factor from CSS pixels to device pixels.
+    pub scale: f32,
+
+    /// Number of pages (PDF, TIFF) or frames (GIF); `1` for still images.
+    pub page_count: u32,
+
+    /// Family names of the fonts used to shape text, in order of first use.
+    pub fonts: Vec<String>,
+
+    /// Non-fatal warnings raised while rendering.
+    pub warnings: Vec<Warning>,
+
+    /// Wall-clock time spent rendering.
+    pub elapsed: Duration,
+}
+
+impl RenderOutput {
+    /// Summarize the render as a [`RenderManifest`], without the output bytes.
+    pub fn manifest(&self) -> RenderManifest {
+        RenderManifest {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            scale: self.scale,
+            byte_len: self.bytes.len(),
+            page_count: self.page_count,
+            fonts: self.fonts.clone(),
+            warnings: self.warnings.iter().map(ToString::to_string).collect(),
+            render_time_ms: self.elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+
+    /// Serialize the [`manifest`](RenderOutput::manifest) as pretty-printed JSON.
+    ///
+    /// Requires the `serde` feature. The JSON is meant to be written alongside
+    /// the output as a sidecar file for asset pipelines.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use hyper_render::{render_detailed, Config};
+    ///
+    /// let output = render_detailed("<h1>Hello</h1>", Config::default())?;
+    /// std::fs::write("output.png", &output.bytes)?;
+    /// std::fs::write("output.json", output.manifest_json())?;
+    /// # Ok::<(), hyper_render::Error>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn manifest_json(&self) -> String {
+        serde_json::to_string_pretty(&self.manifest()).expect("manifests always serialize")
+    }
+}
+
+/// A finished PDF page, reported by [`render_pdf_streaming`](crate::render_pdf_streaming).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfPage {
+    /// Page width in points (one point per CSS pixel).
+    pub width: f32,
+    /// Page height in points.
+    pub height: f32,
+}
+
+/// A machine-readable description of a render.
+///
+/// Serializable with the `serde` feature; warnings are recorded as their
+/// display messages.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderManifest {
+    /// Output format.
+    pub format: OutputFormat,
+    /// Output width in CSS pixels.
+    pub width: u32,
+    /// Output height in CSS pixels.
+    pub height: u32,
+    /// Scale factor from CSS pixels to device pixels.
+    pub scale: f32,
+    /// Size of the output in bytes.
+    pub byte_len: usize,
+    /// Number of pages (PDF, TIFF) or frames (GIF); `1` for still images.
+    pub page_count: u32,
+    /// Family names of the fonts used to shape text.
+    pub fonts: Vec<String>,
+    /// Warning messages raised while rendering.
+    pub warnings: Vec<String>,
+    /// Wall-clock render time in milliseconds.
+    pub render_time_ms: f64,
+}
@@ -0,0 +1,56 @@
+//! Post-render overlays drawn on top of the painted document.
+
+use std::sync::Arc;
+
+/// The painted output handed to a [`Config::overlay`](crate::Config::overlay) hook.
+#[non_exhaustive]
+pub enum OverlayTarget<'a, 'b> {
+    /// Raster output (PNG, GIF frames and TIFF pages) before encoding.
+    Raster {
+        /// Premultiplied RGBA8 pixels, row by row.
+        pixels: &'a mut [u8],
+        /// Width in device pixels.
+        width: u32,
+        /// Height in device pixels.
+        height: u32,
+    },
+
+    /// The PDF page's drawing surface, before the page is finished.
+    ///
+    /// Coordinates are in points with a top-left origin, matching CSS pixels.
+    #[cfg(feature = "pdf")]
+    Pdf {
+        /// Krilla surface to draw on; see the re-exported [`krilla`](crate::krilla) crate.
+        surface: &'a mut krilla::surface::Surface<'b>,
+        /// Page width in points.
+        width: f32,
+        /// Page height in points.
+        height: f32,
+    },
+
+    #[doc(hidden)]
+    _Unused(std::marker::PhantomData<&'b ()>),
+}
+
+/// A shared overlay hook, set with [`Config::overlay`](crate::Config::overlay).
+#[derive(Clone)]
+pub struct Overlay(Arc<dyn Fn(OverlayTarget<'_, '_>) + Send + Sync>);
+
+impl Overlay {
+    /// Wrap a closure as an overlay hook.
+    pub fn new(draw: impl Fn(OverlayTarget<'_, '_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(draw))
+    }
+
+    /// Invoke the hook on `target`.
+    #[cfg_attr(not(any(feature = "png", feature = "pdf")), allow(dead_code))]
+    pub(crate) fn draw(&self, target: OverlayTarget<'_, '_>) {
+        (self.0)(target)
+    }
+}
+
+impl std::fmt::Debug for Overlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Overlay(..)")
+    }
+}
@@ -0,0 +1,58 @@
+//! The output returned in place of a failed render under
+//! [`ErrorPolicy::Placeholder`](crate::ErrorPolicy::Placeholder).
+
+use crate::config::Config;
+use crate::error::Error;
+
+/// Configuration for rendering the placeholder.
+///
+/// The placeholder keeps the failed render's output size, resolution,
+/// rotation and format, so it can stand in for the expected output, but
+/// none of its other options: those may be what the render failed on.
+pub(crate) fn placeholder_config(config: &Config) -> Config {
+    Config {
+        width: config.width,
+        height: config.height,
+        aspect_ratio: config.aspect_ratio,
+        scale: config.scale,
+        max_scale: config.max_scale,
+        dpi: config.dpi,
+        pixel_mode: config.pixel_mode,
+        format: config.format,
+        rotation: config.rotation,
+        ..Config::default()
+    }
+}
+
+/// A page saying "Render failed" with the error message, centered in the
+/// viewport.
+pub(crate) fn placeholder_html(error: &Error) -> String {
+    format!(
+        r#"<html><body style="margin: 0;">
+        <div style="box-sizing: border-box; width: 100vw; height: 100vh; padding: 16px;
+                    display: flex; flex-direction: column; justify-content: center;
+                    align-items: center; text-align: center; overflow: hidden;
+                    background: #f3f3f3; color: #555; font-family: sans-serif;
+                    border: 2px dashed #c33;">
+            <div style="font-size: 20px; font-weight: bold; color: #c33;">Render failed</div>
+            <div style="font-size: 13px; margin-top: 8px; overflow-wrap: anywhere;">{}</div>
+        </div>
+        </body></html>"#,
+        escape_html(&error.to_string())
+    )
+}
+
+/// `text` with the characters HTML gives meaning to escaped.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
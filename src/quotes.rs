@@ -0,0 +1,201 @@
+//! Quotation marks for generated content, which Blitz leaves to the embedder.
+
+use blitz_dom::{local_name, BaseDocument};
+use style::properties::ComputedValues;
+use style::selector_parser::PseudoElement;
+use style::values::computed::list::Quotes;
+use style::values::computed::{Content, ContentItem};
+
+/// Fill in `open-quote` and `close-quote` in `::before` and `::after` content.
+///
+/// Blitz only renders string `content`, so the UA rules for `<q>` produce
+/// empty pseudo-elements. This walks the document in order, tracking the
+/// quote nesting depth, and writes the marks chosen by the `quotes` property
+/// (or by the nearest `lang` attribute for `quotes: auto`) into the
+/// pseudo-elements. Styles must already be resolved; returns whether any text
+/// changed, in which case the document needs resolving again.
+pub(crate) fn generate_quotes(doc: &mut BaseDocument) -> bool {
+    let mut updates = Vec::new();
+    let mut depth = 0usize;
+    let mut stack = vec![Visit::Enter(doc.root_node().id)];
+    while let Some(visit) = stack.pop() {
+        let (node_id, pseudo) = match visit {
+            Visit::Enter(node_id) => (node_id, PseudoElement::Before),
+            Visit::Leave(node_id) => (node_id, PseudoElement::After),
+        };
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if matches!(visit, Visit::Enter(_)) {
+            stack.push(Visit::Leave(node_id));
+            stack.extend(node.children.iter().rev().map(|&child| Visit::Enter(child)));
+        }
+
+        let style = node
+            .stylo_element_data
+            .borrow()
+            .as_ref()
+            .and_then(|data| data.styles.pseudos.get(&pseudo).cloned());
+        let Some(style) = style else {
+            continue;
+        };
+        let Some(text) = quote_text(doc, node_id, &style, &mut depth) else {
+            continue;
+        };
+
+        let pseudo_id = match pseudo {
+            PseudoElement::Before => node.before,
+            _ => node.after,
+        };
+        let target = match pseudo_id {
+            Some(pseudo_id) => {
+                let existing = doc
+                    .get_node(pseudo_id)
+                    .and_then(|pseudo| pseudo.children.first().copied())
+                    .filter(|&child| doc.get_node(child).is_some_and(|c| c.is_text_node()));
+                match existing {
+                    Some(text_id) => Target::Replace(text_id),
+                    None => Target::Append(pseudo_id),
+                }
+            }
+            // Blitz doesn't create pseudo-elements for inlines nested inside
+            // inlines, so put the marks in the element itself
+            None => match (pseudo, node.children.first()) {
+                (PseudoElement::Before, Some(&first)) => Target::InsertBefore(first),
+                _ => Target::Append(node_id),
+            },
+        };
+        updates.push((target, text));
+    }
+
+    let changed = !updates.is_empty();
+    let mut mutator = doc.mutate();
+    for (target, text) in updates {
+        match target {
+            Target::Replace(text_id) => mutator.set_node_text(text_id, &text),
+            Target::InsertBefore(anchor_id) => {
+                let text_id = mutator.create_text_node(&text);
+                mutator.insert_nodes_before(anchor_id, &[text_id]);
+            }
+            Target::Append(parent_id) => {
+                let text_id = mutator.create_text_node(&text);
+                mutator.append_children(parent_id, &[text_id]);
+            }
+        }
+    }
+    changed
+}
+
+/// A step of the in-order document walk.
+#[derive(Clone, Copy)]
+enum Visit {
+    /// Before the node's children, where its `::before` content goes.
+    Enter(usize),
+    /// After the node's children, where its `::after` content goes.
+    Leave(usize),
+}
+
+/// Where generated quote text is written.
+enum Target {
+    /// Overwrite an existing text node.
+    Replace(usize),
+    /// Insert a new text node before this node.
+    InsertBefore(usize),
+    /// Append a new text node to this node.
+    Append(usize),
+}
+
+/// The text for pseudo-element `style` on `element_id` if its `content`
+/// contains quotes, advancing the nesting `depth`; `None` if there are no
+/// quotes to fill in.
+fn quote_text(
+    doc: &BaseDocument,
+    element_id: usize,
+    style: &ComputedValues,
+    depth: &mut usize,
+) -> Option<String> {
+    let Content::Items(content) = &style.get_counters().content else {
+        return None;
+    };
+    let items = &content.items[0..content.alt_start];
+    if !items.iter().any(|item| {
+        matches!(
+            item,
+            ContentItem::OpenQuote
+                | ContentItem::CloseQuote
+                | ContentItem::NoOpenQuote
+                | ContentItem::NoCloseQuote
+        )
+    }) {
+        return None;
+    }
+
+    let pairs: Vec<(String, String)> = match &style.get_list().quotes {
+        Quotes::QuoteList(list) => list
+            .0
+            .iter()
+            .map(|pair| (pair.opening.to_string(), pair.closing.to_string()))
+            .collect(),
+        Quotes::Auto => {
+            let lang = element_lang(doc, element_id);
+            lang_quotes(lang.as_deref().unwrap_or(""))
+                .iter()
+                .map(|&(open, close)| (open.to_string(), close.to_string()))
+                .collect()
+        }
+    };
+    let pair_at = |depth: usize| pairs.get(depth.min(pairs.len().saturating_sub(1)));
+
+    let mut text = String::new();
+    for item in items {
+        match item {
+            ContentItem::String(value) => text.push_str(value),
+            ContentItem::OpenQuote => {
+                if let Some((open, _)) = pair_at(*depth) {
+                    text.push_str(open);
+                }
+                *depth += 1;
+            }
+            // An unmatched close-quote produces nothing
+            ContentItem::CloseQuote if *depth > 0 => {
+                *depth -= 1;
+                if let Some((_, close)) = pair_at(*depth) {
+                    text.push_str(close);
+                }
+            }
+            ContentItem::NoOpenQuote => *depth += 1,
+            ContentItem::NoCloseQuote => *depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Some(text)
+}
+
+/// The `lang` attribute of the element or its nearest ancestor that has one.
+fn element_lang(doc: &BaseDocument, mut node_id: usize) -> Option<String> {
+    loop {
+        let node = doc.get_node(node_id)?;
+        if let Some(lang) = node.attr(local_name!("lang")) {
+            return Some(lang.to_ascii_lowercase());
+        }
+        node_id = node.parent?;
+    }
+}
+
+/// Quotation marks for `quotes: auto`, outer pair first.
+fn lang_quotes(lang: &str) -> &'static [(&'static str, &'static str)] {
+    let primary = lang.split(['-', '_']).next().unwrap_or("");
+    match primary {
+        "de" | "cs" | "sk" | "lt" | "is" => &[("\u{201E}", "\u{201C}"), ("\u{201A}", "\u{2018}")],
+        "fr" => &[
+            ("\u{AB}\u{A0}", "\u{A0}\u{BB}"),
+            ("\u{2039}\u{A0}", "\u{A0}\u{203A}"),
+        ],
+        "es" | "it" | "pt" | "ca" => &[("\u{AB}", "\u{BB}"), ("\u{201C}", "\u{201D}")],
+        "ru" | "uk" | "be" => &[("\u{AB}", "\u{BB}"), ("\u{201E}", "\u{201C}")],
+        "pl" | "hu" | "ro" => &[("\u{201E}", "\u{201D}"), ("\u{AB}", "\u{BB}")],
+        "sv" | "fi" => &[("\u{201D}", "\u{201D}"), ("\u{2019}", "\u{2019}")],
+        "ja" => &[("\u{300C}", "\u{300D}"), ("\u{300E}", "\u{300F}")],
+        _ => &[("\u{201C}", "\u{201D}"), ("\u{2018}", "\u{2019}")],
+    }
+}
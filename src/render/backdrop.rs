@@ -0,0 +1,343 @@
+//! `backdrop-filter: blur()` for raster output.
+//!
+//! Blitz parses `backdrop-filter` but paints such elements as plain
+//! translucency. This module composites the effect onto a painted buffer
+//! using two extra paint passes: one with the filtered elements ("panels")
+//! hidden, giving the backdrop, and one with only the panels visible on a
+//! transparent canvas. Inside each panel's border box the output becomes the
+//! panel painted over the blurred backdrop.
+//!
+//! Content outside a panel's subtree that is painted on top of the panel is
+//! covered by the composite, and only `blur()` is applied.
+
+use crate::background::DecodedBackground;
+use crate::config::Config;
+use crate::error::Result;
+
+use super::png::paint_to_rgba;
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, Node, QualName};
+use blitz_html::HtmlDocument;
+use std::time::Instant;
+use style::values::computed::{BorderCornerRadius, CSSPixelLength, Display, Filter};
+
+/// Attribute marking the root element with the current paint pass.
+const PASS_ATTR: &str = "data-hyper-render-backdrop-pass";
+
+/// Largest blur standard deviation, as a fraction of the canvas's longer
+/// side. The kernel, three deviations either way, then spans the canvas;
+/// with edge pixels extended past it, wider blurs look all but the same.
+const MAX_SIGMA_FRACTION: f32 = 1.0 / 6.0;
+
+/// Attribute marking elements with a backdrop blur.
+const PANEL_ATTR: &str = "data-hyper-render-backdrop-panel";
+
+/// Rules hiding the panels for the backdrop pass, and everything but the
+/// panels (including the canvas background) for the panel pass.
+const PASS_CSS: &str = r#"
+html[data-hyper-render-backdrop-pass="backdrop"] [data-hyper-render-backdrop-panel] {
+    visibility: hidden !important;
+}
+html[data-hyper-render-backdrop-pass="panels"],
+html[data-hyper-render-backdrop-pass="panels"] body {
+    visibility: hidden !important;
+    background-color: transparent !important;
+}
+html[data-hyper-render-backdrop-pass="panels"] [data-hyper-render-backdrop-panel] {
+    visibility: visible !important;
+}
+"#;
+
+/// An element with a backdrop blur, in device pixels.
+struct Panel {
+    node_id: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    /// Corner radii as `(x, y)` pairs: top-left, top-right, bottom-right, bottom-left.
+    radii: [(f32, f32); 4],
+    /// Blur standard deviation.
+    sigma: f32,
+}
+
+impl Panel {
+    /// Whether the center of device pixel `(px, py)` is inside the border box.
+    fn contains(&self, px: u32, py: u32) -> bool {
+        let x = px as f32 + 0.5 - self.x;
+        let y = py as f32 + 0.5 - self.y;
+        if x < 0.0 || y < 0.0 || x >= self.width || y >= self.height {
+            return false;
+        }
+
+        // Distance into each corner's ellipse, if the point is in a corner
+        let (w, h) = (self.width, self.height);
+        let [tl, tr, br, bl] = self.radii;
+        let corner = if x < tl.0 && y < tl.1 {
+            Some((tl, tl.0 - x, tl.1 - y))
+        } else if x > w - tr.0 && y < tr.1 {
+            Some((tr, x - (w - tr.0), tr.1 - y))
+        } else if x > w - br.0 && y > h - br.1 {
+            Some((br, x - (w - br.0), y - (h - br.1)))
+        } else if x < bl.0 && y > h - bl.1 {
+            Some((bl, bl.0 - x, y - (h - bl.1)))
+        } else {
+            None
+        };
+        corner.is_none_or(|((rx, ry), dx, dy)| (dx / rx).powi(2) + (dy / ry).powi(2) <= 1.0)
+    }
+}
+
+/// Composite `backdrop-filter: blur()` panels onto `buffer`, the painted
+/// premultiplied RGBA8 output of `document` painted over `background`.
+///
+/// Does nothing if no element has a backdrop blur. The document is repainted
+/// twice and left styled as before. Blurs are capped at
+/// [`MAX_SIGMA_FRACTION`] of the canvas, and the [`Config::budget`]
+/// deadline, counted from `started`, is checked as each one is computed.
+pub(crate) fn blur_backdrops(
+    document: &mut HtmlDocument,
+    config: &Config,
+    background: Option<&DecodedBackground>,
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    started: Instant,
+) -> Result<()> {
+    let panels = find_panels(document, config.device_scale());
+    if panels.is_empty() {
+        return Ok(());
+    }
+
+    let style_id = start_passes(document, &panels);
+    set_pass(document, Some("backdrop"), config);
+    let (backdrop, _, _) = paint_to_rgba(document, config, background);
+    // The background image is part of the backdrop, not the panels
+    set_pass(document, Some("panels"), config);
+    let (painted_panels, _, _) = paint_to_rgba(document, config, None);
+    end_passes(document, &panels, style_id, config);
+
+    for panel in &panels {
+        let blurred = gaussian_blur(&backdrop, width, height, panel, || match &config.budget {
+            Some(budget) => budget.check_deadline(started),
+            None => Ok(()),
+        })?;
+        let (x0, y0, x1, y1) = panel_bounds(panel, width, height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if !panel.contains(x, y) {
+                    continue;
+                }
+                let i = ((y * width + x) * 4) as usize;
+                let j = (((y - y0) * (x1 - x0) + (x - x0)) * 4) as usize;
+                let transmittance = 1.0 - painted_panels[i + 3] as f32 / 255.0;
+                for c in 0..4 {
+                    let value = painted_panels[i + c] as f32 + transmittance * blurred[j + c];
+                    buffer[i + c] = value.round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Elements with a positive `backdrop-filter: blur()`, in paint order.
+fn find_panels(document: &HtmlDocument, scale: f32) -> Vec<Panel> {
+    let doc: &BaseDocument = document.as_ref();
+    let mut panels = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(style) = node.primary_styles() else {
+            continue;
+        };
+        let blur: f32 = style
+            .get_effects()
+            .backdrop_filter
+            .0
+            .iter()
+            .map(|filter| match filter {
+                Filter::Blur(radius) => radius.0.px(),
+                _ => 0.0,
+            })
+            .sum();
+        if blur <= 0.0 || !is_rendered(doc, node) {
+            continue;
+        }
+
+        let size = node.final_layout.size;
+        let scroll = doc.viewport_scroll();
+        let position = node.absolute_position(-scroll.x as f32, -scroll.y as f32);
+        let border = style.get_border();
+        let resolve = |radius: &BorderCornerRadius| {
+            (
+                radius
+                    .0
+                    .width
+                    .0
+                    .resolve(CSSPixelLength::new(size.width))
+                    .px()
+                    * scale,
+                radius
+                    .0
+                    .height
+                    .0
+                    .resolve(CSSPixelLength::new(size.height))
+                    .px()
+                    * scale,
+            )
+        };
+        panels.push(Panel {
+            node_id,
+            x: position.x * scale,
+            y: position.y * scale,
+            width: size.width * scale,
+            height: size.height * scale,
+            radii: [
+                resolve(&border.border_top_left_radius),
+                resolve(&border.border_top_right_radius),
+                resolve(&border.border_bottom_right_radius),
+                resolve(&border.border_bottom_left_radius),
+            ],
+            sigma: blur * scale,
+        });
+    }
+    panels
+}
+
+/// Whether `node` and its ancestors are laid out, so it has a box to blur behind.
+pub(super) fn is_rendered(doc: &BaseDocument, node: &Node) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        let hidden = node
+            .primary_styles()
+            .is_some_and(|style| style.get_box().display == Display::None);
+        if hidden {
+            return false;
+        }
+        current = node.parent.and_then(|id| doc.get_node(id));
+    }
+    true
+}
+
+/// Mark the panels and add the pass stylesheet, returning its element id.
+fn start_passes(document: &mut HtmlDocument, panels: &[Panel]) -> usize {
+    let root_id = document.root_element().id;
+    let mut mutator = document.mutate();
+    for panel in panels {
+        mutator.set_attribute(panel.node_id, attr_name(PANEL_ATTR), "");
+    }
+    let style_id =
+        mutator.create_element(QualName::new(None, ns!(html), local_name!("style")), vec![]);
+    let text_id = mutator.create_text_node(PASS_CSS);
+    mutator.append_children(style_id, &[text_id]);
+    mutator.append_children(root_id, &[style_id]);
+    style_id
+}
+
+/// Remove the pass stylesheet and markers, restoring the document's styles.
+fn end_passes(document: &mut HtmlDocument, panels: &[Panel], style_id: usize, config: &Config) {
+    {
+        let mut mutator = document.mutate();
+        mutator.remove_and_drop_node(style_id);
+        for panel in panels {
+            mutator.clear_attribute(panel.node_id, attr_name(PANEL_ATTR));
+        }
+    }
+    set_pass(document, None, config);
+}
+
+/// Switch the root element to paint pass `pass` and restyle the document at
+/// the configured animation time.
+fn set_pass(document: &mut HtmlDocument, pass: Option<&str>, config: &Config) {
+    let root_id = document.root_element().id;
+    {
+        let mut mutator = document.mutate();
+        match pass {
+            Some(pass) => mutator.set_attribute(root_id, attr_name(PASS_ATTR), pass),
+            None => mutator.clear_attribute(root_id, attr_name(PASS_ATTR)),
+        }
+    }
+    super::resolve_at(document, config, config.resolve_timestamp);
+}
+
+/// Qualified name of an attribute without a namespace.
+fn attr_name(name: &str) -> QualName {
+    QualName::new(None, ns!(), LocalName::from(name))
+}
+
+/// Device pixel bounds `(x0, y0, x1, y1)` of `panel`, clamped to the buffer.
+fn panel_bounds(panel: &Panel, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let clamp_x = |v: f32| (v.max(0.0) as u32).min(width);
+    let clamp_y = |v: f32| (v.max(0.0) as u32).min(height);
+    (
+        clamp_x(panel.x.floor()),
+        clamp_y(panel.y.floor()),
+        clamp_x((panel.x + panel.width).ceil()),
+        clamp_y((panel.y + panel.height).ceil()),
+    )
+}
+
+/// Gaussian blur of `buffer` with the panel's sigma, at most
+/// [`MAX_SIGMA_FRACTION`] of the canvas, sampled over the panel's bounds
+/// with edge pixels extended. Returns premultiplied RGBA as `f32`s for the
+/// bounds only. `check_deadline` is called before each row, and its error
+/// returned.
+fn gaussian_blur(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    panel: &Panel,
+    check_deadline: impl Fn() -> Result<()>,
+) -> Result<Vec<f32>> {
+    let (x0, y0, x1, y1) = panel_bounds(panel, width, height);
+    let sigma = panel
+        .sigma
+        .min(width.max(height) as f32 * MAX_SIGMA_FRACTION);
+    let radius = (sigma * 3.0).ceil() as i64;
+    let kernel: Vec<f32> = {
+        let weights: Vec<f32> = (-radius..=radius)
+            .map(|d| (-((d * d) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let total: f32 = weights.iter().sum();
+        weights.into_iter().map(|w| w / total).collect()
+    };
+    let sample = |x: i64, y: i64, c: usize| {
+        let x = x.clamp(0, width as i64 - 1) as usize;
+        let y = y.clamp(0, height as i64 - 1) as usize;
+        buffer[(y * width as usize + x) * 4 + c] as f32
+    };
+
+    // Blur horizontally over the rows the vertical pass reads, then vertically
+    let row_start = y0 as i64 - radius;
+    let rows = (y1 - y0) as usize + 2 * radius as usize;
+    let cols = (x1 - x0) as usize;
+    let mut horizontal = vec![0.0; rows * cols * 4];
+    for row in 0..rows {
+        check_deadline()?;
+        let y = row_start + row as i64;
+        for col in 0..cols {
+            let x = x0 as i64 + col as i64;
+            for c in 0..4 {
+                horizontal[(row * cols + col) * 4 + c] = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, w)| w * sample(x + k as i64 - radius, y, c))
+                    .sum();
+            }
+        }
+    }
+
+    let out_rows = (y1 - y0) as usize;
+    let mut blurred = vec![0.0; out_rows * cols * 4];
+    for row in 0..out_rows {
+        check_deadline()?;
+        for col in 0..cols {
+            for c in 0..4 {
+                blurred[(row * cols + col) * 4 + c] = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, w)| w * horizontal[((row + k) * cols + col) * 4 + c])
+                    .sum();
+            }
+        }
+    }
+    Ok(blurred)
+}
@@ -0,0 +1,107 @@
+//! Animated GIF rendering implementation.
+//!
+//! Frames are rasterized through the PNG pipeline at evenly spaced animation
+//! timestamps and encoded with the `gif` crate. GIF has no partial alpha, so
+//! each frame is flattened against the configured background color.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+
+#[cfg(feature = "gif")]
+use super::png::{get_content_height, render_to_rgba};
+#[cfg(feature = "gif")]
+use crate::background;
+#[cfg(feature = "gif")]
+use blitz_html::HtmlDocument;
+
+/// Quantization speed passed to the GIF encoder (1 = best, 30 = fastest).
+#[cfg(feature = "gif")]
+const QUANTIZE_SPEED: i32 = 10;
+
+/// Render a Blitz document to animated GIF bytes.
+///
+/// The document is re-resolved at `i * frame_duration` for each frame `i`,
+/// advancing CSS animations and transitions between frames.
+#[cfg(feature = "gif")]
+pub fn render_to_gif(document: &mut HtmlDocument, config: &Config) -> Result<Vec<u8>> {
+    // Fix the height from the first frame so every frame has the same size
+    let mut frame_config = config.clone();
+    if config.auto_height {
+        frame_config.height = get_content_height(document).unwrap_or(config.height);
+        frame_config.auto_height = false;
+    }
+
+    let (render_width, render_height) = config.rotation.rotated_size(
+        (frame_config.width as f64 * frame_config.device_scale() as f64) as u32,
+        (frame_config.height as f64 * frame_config.device_scale() as f64) as u32,
+    );
+    let (width, height) = match (u16::try_from(render_width), u16::try_from(render_height)) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => {
+            return Err(Error::GifEncode(format!(
+                "{}x{} exceeds the maximum GIF dimensions of 65535x65535",
+                render_width, render_height
+            )))
+        }
+    };
+
+    // GIF delays are expressed in hundredths of a second
+    let delay = (config.frame_duration.as_millis() / 10).min(u16::MAX as u128) as u16;
+
+    let background = background::decode(config)?;
+    let mut output = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut output, width, height, &[])
+            .map_err(|e| Error::GifEncode(e.to_string()))?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| Error::GifEncode(e.to_string()))?;
+
+        for i in 0..config.frames {
+            // The caller has already resolved the document for the first frame
+            if i > 0 {
+                super::resolve_at(
+                    document,
+                    config,
+                    config.resolve_timestamp + i as f64 * config.frame_duration.as_secs_f64(),
+                );
+            }
+
+            let (buffer, _, _) = render_to_rgba(document, &frame_config, background.as_ref());
+            let rgb = flatten_to_rgb(&buffer, config.background);
+
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &rgb, QUANTIZE_SPEED);
+            frame.delay = delay;
+            encoder
+                .write_frame(&frame)
+                .map_err(|e| Error::GifEncode(e.to_string()))?;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Composite a premultiplied RGBA buffer over an opaque background color.
+#[cfg(feature = "gif")]
+fn flatten_to_rgb(buffer: &[u8], background: [u8; 4]) -> Vec<u8> {
+    let [bg_r, bg_g, bg_b, _] = background;
+    let mut rgb = Vec::with_capacity(buffer.len() / 4 * 3);
+
+    for pixel in buffer.chunks_exact(4) {
+        let inverse_alpha = 255 - pixel[3] as u16;
+        for (channel, bg) in pixel[..3].iter().zip([bg_r, bg_g, bg_b]) {
+            let value = *channel as u16 + (bg as u16 * inverse_alpha + 127) / 255;
+            rgb.push(value.min(255) as u8);
+        }
+    }
+
+    rgb
+}
+
+#[cfg(not(feature = "gif"))]
+pub fn render_to_gif(
+    _document: &mut blitz_html::HtmlDocument,
+    _config: &Config,
+) -> Result<Vec<u8>> {
+    Err(Error::FormatNotEnabled("gif"))
+}
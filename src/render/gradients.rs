@@ -0,0 +1,249 @@
+//! Rasterized gradient backgrounds for [`Config::rasterize_gradients`](crate::Config::rasterize_gradients).
+//!
+//! PDF output converts gradients to vector shadings itself, which only
+//! approximates some of them. Instead, this paints the document once more
+//! with the raster renderer, with everything transparent but the background
+//! images of elements with a gradient, and crops each such element's border
+//! box out of the result for PDF output to draw as an image.
+//!
+//! The crop holds all of the element's background images. Elements whose
+//! border boxes overlap, such as an element and its descendants, are painted
+//! in separate passes, so no crop holds another element's gradients.
+
+use crate::config::Config;
+use crate::error::Result;
+
+use blitz_html::HtmlDocument;
+use std::collections::HashMap;
+
+#[cfg(feature = "png")]
+use super::backdrop::is_rendered;
+#[cfg(feature = "png")]
+use super::png::{paint_to_rgba, unpremultiply};
+#[cfg(feature = "png")]
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, QualName};
+#[cfg(feature = "png")]
+use style::values::generics::image::GenericImage;
+
+/// Part of the page painted by the raster renderer, as straight-alpha RGBA8
+/// pixels.
+pub(crate) struct RasterTile {
+    pub pixels: Vec<u8>,
+    /// Width in device pixels.
+    pub width: u32,
+    /// Height in device pixels.
+    pub height: u32,
+    /// Offset of the tile from the element's border box, in CSS pixels.
+    pub x: f32,
+    pub y: f32,
+    /// Width in CSS pixels.
+    pub css_width: f32,
+    /// Height in CSS pixels.
+    pub css_height: f32,
+}
+
+/// Rasterized gradient backgrounds, by node id.
+pub(crate) type GradientTiles = HashMap<usize, RasterTile>;
+
+/// Attribute marking the root element during the gradient pass.
+#[cfg(feature = "png")]
+const PASS_ATTR: &str = "data-hyper-render-gradient-pass";
+
+/// Attribute marking elements with a gradient background.
+#[cfg(feature = "png")]
+const GRADIENT_ATTR: &str = "data-hyper-render-gradient";
+
+/// Rules making everything transparent but the background images of the
+/// marked elements. Blitz skips the contents of hidden elements, so only
+/// replaced content is hidden. Opacity is reset, as PDF output doesn't
+/// apply it.
+#[cfg(feature = "png")]
+const PASS_CSS: &str = r#"
+html[data-hyper-render-gradient-pass],
+html[data-hyper-render-gradient-pass] * {
+    color: transparent !important;
+    background-color: transparent !important;
+    border-color: transparent !important;
+    outline-style: none !important;
+    box-shadow: none !important;
+    text-shadow: none !important;
+    opacity: 1 !important;
+}
+html[data-hyper-render-gradient-pass]:not([data-hyper-render-gradient]),
+html[data-hyper-render-gradient-pass] :not([data-hyper-render-gradient]) {
+    background-image: none !important;
+}
+html[data-hyper-render-gradient-pass] :is(img, svg, video, canvas, iframe, object, embed) {
+    visibility: hidden !important;
+}
+"#;
+
+/// Paint the gradient backgrounds in `document` and crop out a tile for
+/// each element that has one.
+///
+/// Does nothing if no element has a gradient background. The document
+/// is restyled for each pass and left styled as before.
+#[cfg(feature = "png")]
+pub(crate) fn rasterize_gradients(
+    document: &mut HtmlDocument,
+    config: &Config,
+) -> Result<GradientTiles> {
+    let node_ids = find_gradients(document);
+    let scale = config.device_scale();
+
+    let mut tiles = GradientTiles::new();
+    for pass in separate_overlapping(document.as_ref(), node_ids) {
+        let style_id = start_pass(document, &pass, config);
+        let (buffer, width, height) = paint_to_rgba(document, config, None);
+        end_pass(document, &pass, style_id, config);
+
+        let doc: &BaseDocument = document.as_ref();
+        for node_id in pass {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            let size = node.final_layout.size;
+            let position = node.absolute_position(0.0, 0.0);
+            let clamp_x = |v: f32| ((v * scale).round().max(0.0) as u32).min(width);
+            let clamp_y = |v: f32| ((v * scale).round().max(0.0) as u32).min(height);
+            let (x0, x1) = (clamp_x(position.x), clamp_x(position.x + size.width));
+            let (y0, y1) = (clamp_y(position.y), clamp_y(position.y + size.height));
+            if x1 <= x0 || y1 <= y0 {
+                continue;
+            }
+
+            let stride = width as usize * 4;
+            let pixels = (y0..y1)
+                .flat_map(|y| {
+                    let start = y as usize * stride + x0 as usize * 4;
+                    buffer[start..start + (x1 - x0) as usize * 4].chunks_exact(4)
+                })
+                .flat_map(unpremultiply)
+                .collect();
+            tiles.insert(
+                node_id,
+                RasterTile {
+                    pixels,
+                    width: x1 - x0,
+                    height: y1 - y0,
+                    x: x0 as f32 / scale - position.x,
+                    y: y0 as f32 / scale - position.y,
+                    css_width: (x1 - x0) as f32 / scale,
+                    css_height: (y1 - y0) as f32 / scale,
+                },
+            );
+        }
+    }
+    Ok(tiles)
+}
+
+/// Split `node_ids` into passes of elements whose border boxes don't
+/// overlap, each element going in the first pass it fits.
+#[cfg(feature = "png")]
+fn separate_overlapping(doc: &BaseDocument, node_ids: Vec<usize>) -> Vec<Vec<usize>> {
+    let mut passes: Vec<Vec<(usize, [f32; 4])>> = Vec::new();
+    for node_id in node_ids {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        let position = node.absolute_position(0.0, 0.0);
+        let size = node.final_layout.size;
+        let bounds = [
+            position.x,
+            position.y,
+            position.x + size.width,
+            position.y + size.height,
+        ];
+        let overlaps = |other: &[f32; 4]| {
+            bounds[0] < other[2]
+                && other[0] < bounds[2]
+                && bounds[1] < other[3]
+                && other[1] < bounds[3]
+        };
+        let entry = (node_id, bounds);
+        match passes
+            .iter_mut()
+            .find(|pass| !pass.iter().any(|(_, other)| overlaps(other)))
+        {
+            Some(pass) => pass.push(entry),
+            None => passes.push(vec![entry]),
+        }
+    }
+    passes
+        .into_iter()
+        .map(|pass| pass.into_iter().map(|(node_id, _)| node_id).collect())
+        .collect()
+}
+
+/// Rendered elements with a gradient among their background images.
+#[cfg(feature = "png")]
+fn find_gradients(document: &HtmlDocument) -> Vec<usize> {
+    let doc: &BaseDocument = document.as_ref();
+    doc.tree()
+        .iter()
+        .filter(|(_, node)| {
+            node.primary_styles().is_some_and(|style| {
+                style
+                    .get_background()
+                    .background_image
+                    .0
+                    .iter()
+                    .any(|image| matches!(image, GenericImage::Gradient(_)))
+            }) && is_rendered(doc, node)
+        })
+        .map(|(node_id, _)| node_id)
+        .collect()
+}
+
+/// Mark the elements, add the pass stylesheet and restyle the document,
+/// returning the stylesheet's element id.
+#[cfg(feature = "png")]
+fn start_pass(document: &mut HtmlDocument, node_ids: &[usize], config: &Config) -> usize {
+    let root_id = document.root_element().id;
+    let style_id = {
+        let mut mutator = document.mutate();
+        for &node_id in node_ids {
+            mutator.set_attribute(node_id, attr_name(GRADIENT_ATTR), "");
+        }
+        let style_id =
+            mutator.create_element(QualName::new(None, ns!(html), local_name!("style")), vec![]);
+        let text_id = mutator.create_text_node(PASS_CSS);
+        mutator.append_children(style_id, &[text_id]);
+        mutator.append_children(root_id, &[style_id]);
+        mutator.set_attribute(root_id, attr_name(PASS_ATTR), "");
+        style_id
+    };
+    super::resolve_at(document, config, config.resolve_timestamp);
+    style_id
+}
+
+/// Remove the pass stylesheet and markers, restoring the document's styles.
+#[cfg(feature = "png")]
+fn end_pass(document: &mut HtmlDocument, node_ids: &[usize], style_id: usize, config: &Config) {
+    let root_id = document.root_element().id;
+    {
+        let mut mutator = document.mutate();
+        mutator.remove_and_drop_node(style_id);
+        mutator.clear_attribute(root_id, attr_name(PASS_ATTR));
+        for &node_id in node_ids {
+            mutator.clear_attribute(node_id, attr_name(GRADIENT_ATTR));
+        }
+    }
+    super::resolve_at(document, config, config.resolve_timestamp);
+}
+
+/// Qualified name of an attribute without a namespace.
+#[cfg(feature = "png")]
+pub(super) fn attr_name(name: &str) -> QualName {
+    QualName::new(None, ns!(), LocalName::from(name))
+}
+
+/// Gradients are painted by the raster renderer, so rasterizing them needs
+/// the `png` feature.
+#[cfg(not(feature = "png"))]
+pub(crate) fn rasterize_gradients(
+    _document: &mut HtmlDocument,
+    _config: &Config,
+) -> Result<GradientTiles> {
+    Err(crate::error::Error::FormatNotEnabled("png"))
+}
@@ -1,4 +1,151 @@
 //! Rendering implementations for different output formats.
 
+#[cfg(feature = "png")]
+mod backdrop;
+pub mod gif;
+#[cfg(feature = "pdf")]
+mod gradients;
 pub mod pdf;
 pub mod png;
+pub mod scene;
+pub mod svg;
+pub mod tiff;
+#[cfg(feature = "pdf")]
+mod transforms;
+
+#[cfg(feature = "png")]
+use crate::config::Config;
+#[cfg(any(feature = "png", feature = "svg"))]
+use anyrender::PaintScene;
+use blitz_dom::{BaseDocument, Node};
+#[cfg(feature = "png")]
+use blitz_html::HtmlDocument;
+#[cfg(any(feature = "png", feature = "svg"))]
+use std::sync::Mutex;
+use style::values::computed::{Contain, Overflow};
+
+/// Held while Blitz paints a document.
+#[cfg(any(feature = "png", feature = "svg"))]
+static PAINT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Paint `doc` into `scene` with Blitz, one document at a time.
+///
+/// Blitz counts the layers it pushes in process-wide statics, which every
+/// paint resets when it starts, so documents painted at once on different
+/// threads (as [`render_batch`](crate::render_batch) does) would corrupt
+/// each other's counts.
+#[cfg(any(feature = "png", feature = "svg"))]
+pub(crate) fn paint_scene(
+    scene: &mut impl PaintScene,
+    doc: &BaseDocument,
+    scale: f64,
+    width: u32,
+    height: u32,
+) {
+    let _guard = PAINT_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    blitz_paint::paint_scene(scene, doc, scale, width, height);
+}
+
+/// Resolve the document's styles and layout at animation time `timestamp`
+/// while rendering, keeping its layout snapped to device pixels under
+/// [`Config::pixel_snap`].
+#[cfg(feature = "png")]
+pub(crate) fn resolve_at(document: &mut HtmlDocument, config: &Config, timestamp: f64) {
+    document.resolve(timestamp);
+    crate::snap::snap_layout(document, config);
+}
+
+/// Height in CSS pixels needed to show all of the document's content.
+///
+/// This is the lowest bottom margin edge of any box, so content overflowing
+/// a fixed-height `html` or `body` is included. Content clipped by an
+/// `overflow` other than `visible` or by `contain: paint` only counts up to
+/// the clipping box.
+pub(crate) fn content_height(doc: &BaseDocument) -> f32 {
+    let root = doc.root_element();
+    root.final_layout
+        .size
+        .height
+        .max(max_bottom(doc, root, 0.0))
+}
+
+/// Lowest bottom margin edge within `node`, whose parent's border box starts
+/// at `parent_y`.
+///
+/// The tree is walked with an explicit stack, so deeply nested documents
+/// can't overflow the call stack.
+fn max_bottom(doc: &BaseDocument, node: &Node, parent_y: f32) -> f32 {
+    let mut bottom = f32::NEG_INFINITY;
+    let mut stack = vec![(node, parent_y)];
+    while let Some((node, parent_y)) = stack.pop() {
+        let layout = &node.final_layout;
+        let y = parent_y + layout.location.y;
+        bottom = bottom.max(y + layout.size.height + layout.margin.bottom);
+
+        if !clips_contents(node) {
+            if let Some(children) = &*node.paint_children.borrow() {
+                let children = children.iter().filter_map(|id| doc.get_node(*id));
+                stack.extend(children.map(|child| (child, y)));
+            }
+        }
+    }
+    bottom
+}
+
+/// The smallest rect holding the border box of every box in the document
+/// with any area, in CSS pixels from the page's top-left corner, as
+/// `[left, top, right, bottom]`, or `None` if no box has any area.
+///
+/// Like [`content_height`], content clipped by an `overflow` other than
+/// `visible` or by `contain: paint` only counts up to the clipping box.
+pub(crate) fn content_bounds(doc: &BaseDocument) -> Option<[f32; 4]> {
+    let mut bounds = None;
+    extend_bounds(doc, doc.root_element(), 0.0, 0.0, &mut bounds);
+    bounds
+}
+
+/// Grow `bounds` to hold the border boxes within `node`, whose parent's
+/// border box starts at `(parent_x, parent_y)`.
+///
+/// The tree is walked with an explicit stack, so deeply nested documents
+/// can't overflow the call stack.
+fn extend_bounds(
+    doc: &BaseDocument,
+    node: &Node,
+    parent_x: f32,
+    parent_y: f32,
+    bounds: &mut Option<[f32; 4]>,
+) {
+    let mut stack = vec![(node, parent_x, parent_y)];
+    while let Some((node, parent_x, parent_y)) = stack.pop() {
+        let layout = &node.final_layout;
+        let x = parent_x + layout.location.x;
+        let y = parent_y + layout.location.y;
+        if layout.size.width > 0.0 && layout.size.height > 0.0 {
+            let [left, top, right, bottom] = bounds.get_or_insert([x, y, x, y]);
+            *left = left.min(x);
+            *top = top.min(y);
+            *right = right.max(x + layout.size.width);
+            *bottom = bottom.max(y + layout.size.height);
+        }
+
+        if !clips_contents(node) {
+            if let Some(children) = &*node.paint_children.borrow() {
+                let children = children.iter().filter_map(|id| doc.get_node(*id));
+                stack.extend(children.map(|child| (child, x, y)));
+            }
+        }
+    }
+}
+
+/// Whether `node` clips its contents to its own box.
+fn clips_contents(node: &Node) -> bool {
+    node.primary_styles().is_some_and(|style| {
+        let box_style = style.get_box();
+        box_style.overflow_x != Overflow::Visible
+            || box_style.overflow_y != Overflow::Visible
+            || box_style.contain.contains(Contain::PAINT)
+    })
+}
@@ -4,33 +4,75 @@
 //! It uses the Krilla library which provides a high-level API for PDF generation.
 //!
 //! Supports:
-//! - Background colors on all elements
-//! - Linear gradient backgrounds
+//! - Background colors on all elements, including `currentColor` and `var()` values
+//! - Linear gradient backgrounds (elements and the page itself)
 //! - Border-radius (rounded corners via clip paths)
 //! - Box shadows (outset and inset with blur approximation)
+//! - Alpha masks from `mask-image` linear gradients
 //! - Borders (solid style with per-edge colors)
+//! - Overflow clipping to the padding box (`overflow: hidden` / `clip`, `contain: paint`)
+//! - Skipping `display: none` (including `[hidden]`) and `content-visibility: hidden` content
 //! - Text rendering with font embedding
+//! - Underlines and line-throughs in `text-decoration-color`, which defaults to the text color
+//! - `text-align: start`/`end` resolved against the element's `direction`
+//! - `text-align-last` for final lines and lines before forced breaks
+//! - `line-clamp` / `-webkit-line-clamp` truncation with an ellipsis
+//! - Hyphens at lines broken at a soft hyphen (`&shy;`)
+//! - `vertical-align: top`/`middle`/`bottom` for inline boxes
 //! - Nested layout positioning
 
+#[cfg(feature = "pdf")]
+use crate::background::{self, DecodedBackground};
+#[cfg(feature = "pdf")]
+use crate::carried;
 use crate::config::Config;
+#[cfg(feature = "pdf")]
+use crate::config::{LinearGradientSpec, PageLabelRange};
+#[cfg(feature = "pdf")]
+use crate::debug_layout::layout_boxes;
 use crate::error::{Error, Result};
+#[cfg(feature = "pdf")]
+use crate::icc;
+use crate::output::PdfPage;
+#[cfg(feature = "pdf")]
+use crate::overlay::OverlayTarget;
+#[cfg(feature = "pdf")]
+use crate::render::gradients::{rasterize_gradients, GradientTiles, RasterTile};
+#[cfg(feature = "pdf")]
+use crate::render::transforms::{element_transform, rasterize_transforms, TransformTiles};
+#[cfg(feature = "pdf")]
+use crate::toc;
 
 #[cfg(feature = "pdf")]
-use blitz_dom::{BaseDocument, Node};
+use blitz_dom::{BaseDocument, LocalName, Node};
 #[cfg(feature = "pdf")]
 use blitz_html::HtmlDocument;
 #[cfg(feature = "pdf")]
+use krilla::annotation::{Annotation, LinkAnnotation, Target};
+#[cfg(feature = "pdf")]
 use krilla::color::rgb;
 #[cfg(feature = "pdf")]
+use krilla::configure::Configuration;
+#[cfg(feature = "pdf")]
+use krilla::destination::XyzDestination;
+#[cfg(feature = "pdf")]
+use krilla::error::KrillaError;
+#[cfg(feature = "pdf")]
 use krilla::geom::Transform;
 #[cfg(feature = "pdf")]
-use krilla::geom::{Path, PathBuilder, Point, Size};
+use krilla::geom::{Path, PathBuilder, Point, Rect, Size};
+#[cfg(feature = "pdf")]
+use krilla::image::Image;
+#[cfg(feature = "pdf")]
+use krilla::mask::{Mask, MaskType};
 #[cfg(feature = "pdf")]
 use krilla::num::NormalizedF32;
 #[cfg(feature = "pdf")]
-use krilla::page::PageSettings;
+use krilla::outline::{Outline, OutlineNode};
+#[cfg(feature = "pdf")]
+use krilla::page::{PageLabel, PageSettings};
 #[cfg(feature = "pdf")]
-use krilla::paint::{Fill, FillRule};
+use krilla::paint::{Fill, FillRule, Pattern, Stroke};
 #[cfg(feature = "pdf")]
 use krilla::paint::{LinearGradient, SpreadMethod, Stop};
 #[cfg(feature = "pdf")]
@@ -40,13 +82,21 @@ use krilla::text::{Font, GlyphId, KrillaGlyph};
 #[cfg(feature = "pdf")]
 use krilla::Document;
 #[cfg(feature = "pdf")]
+use krilla::SerializeSettings;
+#[cfg(feature = "pdf")]
 use parley::PositionedLayoutItem;
 #[cfg(feature = "pdf")]
+use std::cell::RefCell;
+#[cfg(feature = "pdf")]
 use std::collections::HashMap;
 #[cfg(feature = "pdf")]
+use std::num::NonZeroU32;
+#[cfg(feature = "pdf")]
+use std::time::Instant;
+#[cfg(feature = "pdf")]
 use style::color::AbsoluteColor;
 #[cfg(feature = "pdf")]
-use style::values::computed::{BorderCornerRadius, CSSPixelLength};
+use style::values::computed::{BorderCornerRadius, CSSPixelLength, Contain, Overflow};
 #[cfg(feature = "pdf")]
 use style::values::generics::image::{GenericGradient, GenericGradientItem, GradientFlags};
 #[cfg(feature = "pdf")]
@@ -92,7 +142,12 @@ impl BorderRadii {
 
 /// Font cache to avoid re-creating fonts for the same font data.
 #[cfg(feature = "pdf")]
-type FontCache = HashMap<u64, Font>;
+#[derive(Default)]
+struct FontCache {
+    fonts: HashMap<u64, Font>,
+    /// Glyphs substituted for `font-variant-numeric: oldstyle-nums`, by font.
+    oldstyle_figures: HashMap<(u64, u32), HashMap<u32, u32>>,
+}
 
 /// Render a Blitz document to PDF bytes.
 ///
@@ -102,8 +157,19 @@ type FontCache = HashMap<u64, Font>;
 /// - Background colors on all elements
 /// - Text rendering with embedded fonts
 /// - Nested layout positioning
+///
+/// `toc` is the laid-out [`Config::toc`] page, drawn as the first page when
+/// given. `on_page` is called with the index and size of each page once it
+/// is finished. Rasterizing 3D transforms checks the [`Config::budget`]
+/// deadline, counted from `started`, as it goes.
 #[cfg(feature = "pdf")]
-pub fn render_to_pdf(document: &HtmlDocument, config: &Config) -> Result<Vec<u8>> {
+pub fn render_to_pdf(
+    document: &mut HtmlDocument,
+    toc: Option<&HtmlDocument>,
+    config: &Config,
+    started: Instant,
+    on_page: &mut dyn FnMut(usize, &PdfPage),
+) -> Result<Vec<u8>> {
     let width = config.width as f32;
     let height = if config.auto_height {
         get_content_height(document).unwrap_or(config.height as f32)
@@ -111,41 +177,358 @@ pub fn render_to_pdf(document: &HtmlDocument, config: &Config) -> Result<Vec<u8>
         config.height as f32
     };
 
-    // Create PDF document
-    let mut pdf_doc = Document::new();
+    // Create PDF document with the requested version and conformance
+    let configuration = match config.pdf_conformance {
+        Some(level) => Configuration::new_with(level.into(), config.pdf_version.into())
+            .ok_or_else(|| {
+                Error::InvalidConfig(format!(
+                    "{} is not compatible with PDF {}",
+                    level, config.pdf_version
+                ))
+            })?,
+        None => Configuration::new_with_version(config.pdf_version.into()),
+    };
+    let mut pdf_doc = Document::new_with(SerializeSettings {
+        configuration,
+        ..Default::default()
+    });
 
-    // Create a page with the specified dimensions
-    let size = Size::from_wh(width, height)
+    // Font cache to reuse fonts across the document
+    let mut font_cache = FontCache::default();
+
+    // The table of contents comes first, moving the document to the next page
+    let (page_width, page_height) = config.rotation.rotated_size(width, height);
+    let size = Size::from_wh(page_width, page_height)
         .ok_or_else(|| Error::PdfCreate("Invalid page dimensions".to_string()))?;
-    let page_settings = PageSettings::new(size);
+    let page_transform = config.rotation.transform(width, height);
+    let page_index = usize::from(toc.is_some());
+    let mut headings = Vec::new();
+    collect_headings(
+        document.as_ref(),
+        document.as_ref().root_element(),
+        page_transform,
+        page_index,
+        &mut headings,
+    );
+    if let Some(toc) = toc {
+        render_toc_page(
+            &mut pdf_doc,
+            toc,
+            config,
+            (width, height, size),
+            &headings,
+            &mut font_cache,
+        )?;
+        on_page(
+            0,
+            &PdfPage {
+                width: page_width,
+                height: page_height,
+            },
+        );
+    }
+
+    // Everything that can fail before drawing starts, as a surface must
+    // not be dropped with anything still pushed on it
+    let background_image = background::decode(config)?;
+    let resources = PageResources {
+        interpolate_images: config.pdf_conformance.is_none(),
+        gradients: match config.rasterize_gradients {
+            true => rasterize_gradients(document, config)?,
+            false => GradientTiles::new(),
+        },
+        transforms: match config.flatten_transforms_for_pdf {
+            true => rasterize_transforms(document, config, started)?,
+            false => TransformTiles::new(),
+        },
+        background_images: RefCell::new(HashMap::new()),
+    };
+
+    // Create a page with the specified dimensions
+    let page_settings =
+        PageSettings::new(size).with_page_label(page_label(&config.page_labels, page_index));
     let mut page = pdf_doc.start_page_with(page_settings);
 
     // Get the drawing surface
     let mut surface = page.surface();
 
     // Krilla uses a top-left origin coordinate system (like web graphics),
-    // so no transform is needed - coordinates map directly.
+    // so coordinates map directly apart from the configured rotation.
+    let [a, b, c, d, e, f] = page_transform;
+    surface.push_transform(&Transform::from_row(a, b, c, d, e, f));
 
     // Draw page background
     let [r, g, b, _a] = config.background;
     draw_rect(&mut surface, 0.0, 0.0, width, height, Rgb::new(r, g, b));
 
-    // Font cache to reuse fonts across the document
-    let mut font_cache = FontCache::new();
+    // Draw page gradient on top of the solid background
+    if let Some(spec) = &config.page_gradient {
+        if let Some(gradient) = page_gradient(spec, width, height) {
+            draw_gradient_rect(&mut surface, 0.0, 0.0, width, height, gradient);
+        }
+    }
+
+    // Draw the background image over the page background
+    if let Some(image) = &background_image {
+        let interpolate = resources.interpolate_images
+            && !background::is_pixelated(document.as_ref().root_element());
+        draw_background_image(&mut surface, image, width, height, interpolate);
+    }
 
-    // Render the document tree (backgrounds and text)
+    // Render the document tree (backgrounds and text), popping the page
+    // clip even if rendering fails
     let doc = document.as_ref();
     let root = doc.root_element();
-    render_node(&mut surface, doc, root, 0.0, 0.0, &mut font_cache)?;
+    let clipped = push_page_clip(&mut surface, config, width, height);
+    let rendered = render_node(
+        &mut surface,
+        doc,
+        root,
+        0.0,
+        0.0,
+        &resources,
+        &mut font_cache,
+    );
+    if rendered.is_ok() && config.debug_layout {
+        draw_layout_boxes(&mut surface, doc);
+    }
+    if clipped {
+        surface.pop();
+    }
+
+    if let Some(overlay) = &config.overlay {
+        overlay.draw(OverlayTarget::Pdf {
+            surface: &mut surface,
+            width,
+            height,
+        });
+    }
 
     // Finish the surface and page
+    surface.pop();
     surface.finish();
     page.finish();
+    rendered?;
+    on_page(
+        page_index,
+        &PdfPage {
+            width: page_width,
+            height: page_height,
+        },
+    );
+
+    if config.generate_outline {
+        let mut outline = Outline::new();
+        for node in outline_nodes(&headings) {
+            outline.push_child(node);
+        }
+        pdf_doc.set_outline(outline);
+    }
 
     // Generate the PDF bytes
-    pdf_doc
+    let pdf = pdf_doc
         .finish()
-        .map_err(|e| Error::PdfCreate(format!("{:?}", e)))
+        .map_err(|e| match (e, config.pdf_conformance) {
+            (KrillaError::Validation(errors), Some(level)) => Error::PdfCreate(format!(
+                "content does not conform to {}: {:?}",
+                level, errors
+            )),
+            (e, _) => Error::PdfCreate(format!("{:?}", e)),
+        })?;
+    match &config.icc_profile {
+        Some(profile) => icc::add_output_intent(pdf, profile),
+        None => Ok(pdf),
+    }
+}
+
+/// Clip drawing to the `width` x `height` page with [`Config::clip_to_page`],
+/// returning whether a clip was pushed.
+///
+/// Content past the page would otherwise be drawn outside the MediaBox,
+/// where viewers differ in how much of it they show.
+#[cfg(feature = "pdf")]
+fn push_page_clip(surface: &mut Surface, config: &Config, width: f32, height: f32) -> bool {
+    if !config.clip_to_page {
+        return false;
+    }
+    match build_rounded_rect_path(0.0, 0.0, width, height, &BorderRadii::default()) {
+        Some(clip_path) => {
+            surface.push_clip_path(&clip_path, &FillRule::NonZero);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The label of the page at `index` from the [`Config::page_labels`] range
+/// holding it, or an empty label if no range does.
+#[cfg(feature = "pdf")]
+fn page_label(ranges: &[PageLabelRange], index: usize) -> PageLabel {
+    let Some(range) = ranges.iter().rev().find(|range| range.start <= index) else {
+        return PageLabel::default();
+    };
+    let number = u32::try_from(index - range.start)
+        .ok()
+        .and_then(|offset| range.first_number.checked_add(offset))
+        .and_then(NonZeroU32::new);
+    PageLabel::new(Some(range.style.into()), range.prefix.clone(), number)
+}
+
+/// What drawing a page's nodes takes beyond the nodes themselves: the parts
+/// painted by the raster renderer, by node id, how images are scaled, and
+/// the images made so far.
+#[cfg(feature = "pdf")]
+struct PageResources {
+    /// Whether images not set to `image-rendering: pixelated` are
+    /// interpolated when scaled, which PDF/A forbids.
+    interpolate_images: bool,
+    /// Gradient backgrounds, for `Config::rasterize_gradients`.
+    gradients: GradientTiles,
+    /// Elements with 3D transforms, for `Config::flatten_transforms_for_pdf`.
+    transforms: TransformTiles,
+    /// CSS background images already made into Krilla images, by URL and
+    /// whether they are interpolated.
+    background_images: RefCell<HashMap<(String, bool), Image>>,
+}
+
+#[cfg(feature = "pdf")]
+impl PageResources {
+    /// The Krilla image of the CSS background image at `url`, made from
+    /// its decoded `raster` the first time it is drawn.
+    ///
+    /// Images are expensive to make, as their pixels are copied and split
+    /// into color and alpha, but cheap to clone, so elements sharing an
+    /// image share one.
+    fn background_image(
+        &self,
+        url: &str,
+        raster: &blitz_dom::node::RasterImageData,
+        interpolate: bool,
+    ) -> Image {
+        self.background_images
+            .borrow_mut()
+            .entry((url.to_string(), interpolate))
+            .or_insert_with(|| {
+                rgba_image(
+                    raster.data.to_vec(),
+                    raster.width,
+                    raster.height,
+                    interpolate,
+                )
+            })
+            .clone()
+    }
+}
+
+/// An image of `width` x `height` straight-alpha RGBA `pixels`, marked to
+/// be interpolated when scaled if `interpolate`.
+///
+/// Without `/Interpolate`, viewers scale images by sampling the nearest
+/// pixel, as `image-rendering: pixelated` asks for.
+#[cfg(feature = "pdf")]
+fn rgba_image(pixels: Vec<u8>, width: u32, height: u32, interpolate: bool) -> Image {
+    use krilla::image::{BitsPerComponent, CustomImage, ImageColorspace};
+    use std::sync::Arc;
+
+    /// RGBA pixels split into the color and alpha channels Krilla takes.
+    #[derive(Clone, Hash)]
+    struct SplitRgba {
+        color: Arc<[u8]>,
+        alpha: Option<Arc<[u8]>>,
+        size: (u32, u32),
+    }
+
+    impl CustomImage for SplitRgba {
+        fn color_channel(&self) -> &[u8] {
+            &self.color
+        }
+        fn alpha_channel(&self) -> Option<&[u8]> {
+            self.alpha.as_deref()
+        }
+        fn bits_per_component(&self) -> BitsPerComponent {
+            BitsPerComponent::Eight
+        }
+        fn size(&self) -> (u32, u32) {
+            self.size
+        }
+        fn icc_profile(&self) -> Option<&[u8]> {
+            None
+        }
+        fn color_space(&self) -> ImageColorspace {
+            ImageColorspace::Rgb
+        }
+    }
+
+    if !interpolate {
+        return Image::from_rgba8(pixels, width, height);
+    }
+    let color: Vec<u8> = pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+    let alpha: Vec<u8> = pixels.chunks_exact(4).map(|pixel| pixel[3]).collect();
+    let image = SplitRgba {
+        color: color.into(),
+        alpha: alpha.iter().any(|&a| a < 255).then(|| alpha.into()),
+        size: (width, height),
+    };
+    // Krilla only fails to take images of formats it decodes itself
+    Image::from_custom(image, true).unwrap_or_else(|_| Image::from_rgba8(pixels, width, height))
+}
+
+/// Draw a raster tile for the element whose border box starts at `(x, y)`.
+#[cfg(feature = "pdf")]
+fn draw_raster_tile(surface: &mut Surface, x: f32, y: f32, tile: &RasterTile) {
+    let Some(size) = Size::from_wh(tile.width as f32, tile.height as f32) else {
+        return;
+    };
+    let image = Image::from_rgba8(tile.pixels.clone(), tile.width, tile.height);
+    surface.push_transform(&Transform::from_row(
+        tile.css_width / tile.width as f32,
+        0.0,
+        0.0,
+        tile.css_height / tile.height as f32,
+        x + tile.x,
+        y + tile.y,
+    ));
+    surface.draw_image(image, size);
+    surface.pop();
+}
+
+/// Outline the document's layout boxes for [`Config::debug_layout`].
+#[cfg(feature = "pdf")]
+fn draw_layout_boxes(surface: &mut Surface, doc: &BaseDocument) {
+    /// Line width in points, inset by half so it stays inside each box.
+    const LINE_WIDTH: f32 = 0.5;
+
+    surface.set_fill(None);
+    for layout_box in layout_boxes(doc) {
+        let inset = LINE_WIDTH / 2.0;
+        let (x, y) = (layout_box.x + inset, layout_box.y + inset);
+        let (w, h) = (
+            layout_box.width - LINE_WIDTH,
+            layout_box.height - LINE_WIDTH,
+        );
+        if w <= 0.0 || h <= 0.0 {
+            continue;
+        }
+        let mut builder = PathBuilder::new();
+        builder.move_to(x, y);
+        builder.line_to(x + w, y);
+        builder.line_to(x + w, y + h);
+        builder.line_to(x, y + h);
+        builder.close();
+        if let Some(path) = builder.finish() {
+            let [r, g, b] = layout_box.color;
+            surface.set_stroke(Some(Stroke {
+                paint: rgb::Color::new(r, g, b).into(),
+                width: LINE_WIDTH,
+                ..Stroke::default()
+            }));
+            surface.draw_path(&path);
+        }
+    }
+    surface.set_stroke(None);
 }
 
 /// Draw a filled rectangle at the given position with the given color.
@@ -309,20 +692,7 @@ fn convert_linear_gradient(
     // CSS gradients go from start to end in the direction specified
     let (x1, y1, x2, y2) = match direction {
         LineDirection::Angle(angle) => {
-            // CSS angle: 0deg = to top, 90deg = to right, etc.
-            // We need to convert to standard math angle (counter-clockwise from right)
-            let radians = -angle.radians() + std::f32::consts::PI;
-            let center_x = rect_width / 2.0;
-            let center_y = rect_height / 2.0;
-            // Calculate offset to reach corners
-            let offset_len =
-                rect_width / 2.0 * radians.sin().abs() + rect_height / 2.0 * radians.cos().abs();
-            (
-                center_x - offset_len * radians.sin(),
-                center_y - offset_len * radians.cos(),
-                center_x + offset_len * radians.sin(),
-                center_y + offset_len * radians.cos(),
-            )
+            gradient_line_for_angle(angle.radians(), rect_width, rect_height)
         }
         LineDirection::Horizontal(horizontal) => {
             let mid_y = rect_height / 2.0;
@@ -379,6 +749,309 @@ fn convert_linear_gradient(
     })
 }
 
+/// Compute the start and end points of a gradient line for a CSS angle.
+///
+/// CSS angles start at "to top" and run clockwise; the line passes through
+/// the rectangle's center and is long enough to reach the corners.
+#[cfg(feature = "pdf")]
+fn gradient_line_for_angle(
+    css_radians: f32,
+    rect_width: f32,
+    rect_height: f32,
+) -> (f32, f32, f32, f32) {
+    // Convert to standard math angle (counter-clockwise from right)
+    let radians = -css_radians + std::f32::consts::PI;
+    let center_x = rect_width / 2.0;
+    let center_y = rect_height / 2.0;
+    // Calculate offset to reach corners
+    let offset_len =
+        rect_width / 2.0 * radians.sin().abs() + rect_height / 2.0 * radians.cos().abs();
+    (
+        center_x - offset_len * radians.sin(),
+        center_y - offset_len * radians.cos(),
+        center_x + offset_len * radians.sin(),
+        center_y + offset_len * radians.cos(),
+    )
+}
+
+/// Build a Krilla gradient covering the page from a [`LinearGradientSpec`].
+/// Draw the configured background image over a `width` x `height` page.
+#[cfg(feature = "pdf")]
+fn draw_background_image(
+    surface: &mut Surface,
+    background: &DecodedBackground,
+    width: f32,
+    height: f32,
+    interpolate: bool,
+) {
+    // Draw at the natural size, whatever size the image was decoded at
+    let (image_width, image_height) = (
+        background.natural_width as f32,
+        background.natural_height as f32,
+    );
+    let (Some(image_size), Some(page)) = (
+        Size::from_wh(image_width, image_height),
+        build_rounded_rect_path(0.0, 0.0, width, height, &BorderRadii::default()),
+    ) else {
+        return;
+    };
+    let image = rgba_image(
+        background.pixels.to_vec(),
+        background.width,
+        background.height,
+        interpolate,
+    );
+
+    match background.placement(width, height) {
+        Some(placement) => {
+            surface.push_clip_path(&page, &FillRule::NonZero);
+            surface.push_transform(&Transform::from_row(
+                placement.width / image_width,
+                0.0,
+                0.0,
+                placement.height / image_height,
+                placement.x,
+                placement.y,
+            ));
+            surface.draw_image(image, image_size);
+            surface.pop();
+            surface.pop();
+        }
+        None => {
+            // Tile with a pattern holding a single copy of the image
+            let mut builder = surface.stream_builder();
+            let mut tile_surface = builder.surface();
+            tile_surface.draw_image(image, image_size);
+            tile_surface.finish();
+            let pattern = Pattern {
+                stream: builder.finish(),
+                transform: Transform::identity(),
+                width: image_width,
+                height: image_height,
+            };
+            surface.set_fill(Some(Fill {
+                paint: pattern.into(),
+                opacity: NormalizedF32::ONE,
+                rule: FillRule::NonZero,
+            }));
+            surface.draw_path(&page);
+        }
+    }
+}
+
+/// Draw the raster image of background layer `index` of `node`, whose
+/// border box is `width` x `height` at `(x, y)`.
+///
+/// The image is sized and positioned in the layer's `background-origin` box
+/// by its `background-size` and `background-position`, tiled along the axes
+/// `background-repeat` repeats it on, and clipped to its `background-clip`
+/// box. `space` and `round` repeat as `repeat` does.
+#[cfg(feature = "pdf")]
+fn draw_element_background_image(
+    surface: &mut Surface,
+    node: &Node,
+    background: &style::properties::style_structs::Background,
+    index: usize,
+    (x, y, width, height): (f32, f32, f32, f32),
+    resources: &PageResources,
+) {
+    use blitz_dom::node::ImageData;
+    use style::properties::generated::longhands::background_clip::single_value::computed_value::T as BackgroundClip;
+    use style::properties::generated::longhands::background_origin::single_value::computed_value::T as BackgroundOrigin;
+    use style::values::computed::{BackgroundRepeat, BackgroundSize, Length};
+    use style::values::generics::length::GenericLengthPercentageOrAuto as LengthOrAuto;
+    use style::values::specified::background::BackgroundRepeatKeyword;
+
+    let Some(Some(layer)) = node
+        .element_data()
+        .and_then(|element| element.background_images.get(index))
+    else {
+        return;
+    };
+    let ImageData::Raster(raster) = &layer.image else {
+        return;
+    };
+    let (image_width, image_height) = (raster.width as f32, raster.height as f32);
+    let Some(image_size) = Size::from_wh(image_width, image_height) else {
+        return;
+    };
+
+    // Lists shorter than the list of images repeat
+    fn layer_value<T>(values: &[T], index: usize) -> &T {
+        &values[index % values.len()]
+    }
+
+    // The border box inset by the given edges, as (x, y, width, height)
+    let layout = &node.final_layout;
+    let inset = |edges: taffy::Rect<f32>| {
+        (
+            x + edges.left,
+            y + edges.top,
+            width - edges.left - edges.right,
+            height - edges.top - edges.bottom,
+        )
+    };
+    let (origin_x, origin_y, origin_width, origin_height) =
+        match layer_value(&background.background_origin.0, index) {
+            BackgroundOrigin::BorderBox => (x, y, width, height),
+            BackgroundOrigin::PaddingBox => inset(layout.border),
+            BackgroundOrigin::ContentBox => inset(layout.border + layout.padding),
+        };
+    let (clip_x, clip_y, clip_width, clip_height) =
+        match layer_value(&background.background_clip.0, index) {
+            BackgroundClip::BorderBox => (x, y, width, height),
+            BackgroundClip::PaddingBox => inset(layout.border),
+            BackgroundClip::ContentBox => inset(layout.border + layout.padding),
+        };
+
+    let (size_width, size_height) = match layer_value(&background.background_size.0, index) {
+        BackgroundSize::ExplicitSize {
+            width: explicit_width,
+            height: explicit_height,
+        } => {
+            let explicit_width = match explicit_width {
+                LengthOrAuto::LengthPercentage(w) => {
+                    Some(w.0.resolve(Length::new(origin_width)).px())
+                }
+                LengthOrAuto::Auto => None,
+            };
+            let explicit_height = match explicit_height {
+                LengthOrAuto::LengthPercentage(h) => {
+                    Some(h.0.resolve(Length::new(origin_height)).px())
+                }
+                LengthOrAuto::Auto => None,
+            };
+            // An auto side keeps the image's aspect ratio
+            match (explicit_width, explicit_height) {
+                (Some(w), Some(h)) => (w, h),
+                (Some(w), None) => (w, w * image_height / image_width),
+                (None, Some(h)) => (h * image_width / image_height, h),
+                (None, None) => (image_width, image_height),
+            }
+        }
+        BackgroundSize::Cover => {
+            let ratio = (origin_width / image_width).max(origin_height / image_height);
+            (image_width * ratio, image_height * ratio)
+        }
+        BackgroundSize::Contain => {
+            let ratio = (origin_width / image_width).min(origin_height / image_height);
+            (image_width * ratio, image_height * ratio)
+        }
+    };
+    if size_width <= 0.0 || size_height <= 0.0 {
+        return;
+    }
+
+    // Percentages and keywords place the image's matching point on the
+    // origin box's, so they resolve against the space left over
+    let image_x = origin_x
+        + layer_value(&background.background_position_x.0, index)
+            .resolve(Length::new(origin_width - size_width))
+            .px();
+    let image_y = origin_y
+        + layer_value(&background.background_position_y.0, index)
+            .resolve(Length::new(origin_height - size_height))
+            .px();
+
+    // Along an axis the image doesn't repeat on, only its own span is painted
+    let BackgroundRepeat(repeat_x, repeat_y) = layer_value(&background.background_repeat.0, index);
+    let (mut left, mut top) = (clip_x, clip_y);
+    let (mut right, mut bottom) = (clip_x + clip_width, clip_y + clip_height);
+    let no_repeat_x = matches!(repeat_x, BackgroundRepeatKeyword::NoRepeat);
+    let no_repeat_y = matches!(repeat_y, BackgroundRepeatKeyword::NoRepeat);
+    if no_repeat_x {
+        left = left.max(image_x);
+        right = right.min(image_x + size_width);
+    }
+    if no_repeat_y {
+        top = top.max(image_y);
+        bottom = bottom.min(image_y + size_height);
+    }
+    if right <= left || bottom <= top {
+        return;
+    }
+    let Some(area) = build_rounded_rect_path(
+        left,
+        top,
+        right - left,
+        bottom - top,
+        &BorderRadii::default(),
+    ) else {
+        return;
+    };
+
+    let image = resources.background_image(
+        layer.url.as_str(),
+        raster,
+        resources.interpolate_images && !background::is_pixelated(node),
+    );
+    let placement = Transform::from_row(
+        size_width / image_width,
+        0.0,
+        0.0,
+        size_height / image_height,
+        image_x,
+        image_y,
+    );
+    if no_repeat_x && no_repeat_y {
+        surface.push_clip_path(&area, &FillRule::NonZero);
+        surface.push_transform(&placement);
+        surface.draw_image(image, image_size);
+        surface.pop();
+        surface.pop();
+        return;
+    }
+
+    // Tile with a pattern holding a single copy of the image
+    let mut builder = surface.stream_builder();
+    let mut tile_surface = builder.surface();
+    tile_surface.draw_image(image, image_size);
+    tile_surface.finish();
+    let pattern = Pattern {
+        stream: builder.finish(),
+        transform: placement,
+        width: image_width,
+        height: image_height,
+    };
+    surface.set_fill(Some(Fill {
+        paint: pattern.into(),
+        opacity: NormalizedF32::ONE,
+        rule: FillRule::NonZero,
+    }));
+    surface.draw_path(&area);
+}
+
+#[cfg(feature = "pdf")]
+fn page_gradient(spec: &LinearGradientSpec, width: f32, height: f32) -> Option<LinearGradient> {
+    let stops: Vec<Stop> = spec
+        .stops
+        .iter()
+        .filter_map(|&(offset, [r, g, b, a])| {
+            Some(Stop {
+                offset: NormalizedF32::new(offset.clamp(0.0, 1.0))?,
+                color: rgb::Color::new(r, g, b).into(),
+                opacity: NormalizedF32::new(a as f32 / 255.0).unwrap_or(NormalizedF32::ONE),
+            })
+        })
+        .collect();
+    if stops.is_empty() {
+        return None;
+    }
+
+    let (x1, y1, x2, y2) = gradient_line_for_angle(spec.angle.to_radians(), width, height);
+
+    Some(LinearGradient {
+        x1,
+        y1,
+        x2,
+        y2,
+        transform: Transform::identity(),
+        spread_method: SpreadMethod::Pad,
+        stops,
+        anti_alias: true,
+    })
+}
+
 /// Convert Stylo gradient color stops to Krilla stops.
 #[cfg(feature = "pdf")]
 fn convert_gradient_stops(
@@ -716,6 +1389,9 @@ fn draw_border_edge(
 }
 
 /// Extract box-shadow data from Stylo computed styles.
+///
+/// A shadow without a color computes to `currentColor`, so it is resolved
+/// against the element's own (possibly inherited) `color`.
 #[cfg(feature = "pdf")]
 fn extract_box_shadows(
     style: &style::properties::ComputedValues,
@@ -998,7 +1674,69 @@ fn draw_rect_simple(surface: &mut Surface, x: f32, y: f32, w: f32, h: f32, color
     }
 }
 
-/// Recursively render a node and its children.
+/// Build an alpha mask from the first linear gradient in `mask-image`.
+///
+/// Only the unprefixed property is honored: Stylo's servo engine doesn't
+/// register vendor-prefixed aliases such as `-webkit-mask-image`.
+///
+/// The mask covers the element's border box; anything painted outside it
+/// (such as outset shadows) is masked out, matching the default
+/// `mask-clip: border-box`. Returns `None` when no supported mask is set:
+/// image masks and radial and conic gradient masks aren't supported, so
+/// elements masked by them are drawn unmasked.
+#[cfg(feature = "pdf")]
+fn build_gradient_mask(
+    surface: &mut Surface,
+    style: &style::properties::ComputedValues,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    current_color: &AbsoluteColor,
+) -> Option<Mask> {
+    let gradient = style
+        .get_svg()
+        .mask_image
+        .0
+        .iter()
+        .find_map(|image| match image {
+            style::values::generics::image::GenericImage::Gradient(gradient) => {
+                match gradient.as_ref() {
+                    GenericGradient::Linear {
+                        direction,
+                        items,
+                        flags,
+                        ..
+                    } => convert_linear_gradient(
+                        direction,
+                        items,
+                        *flags,
+                        width,
+                        height,
+                        current_color,
+                    ),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })?;
+
+    // The mask's alpha channel is taken from the gradient stop opacities
+    let mut builder = surface.stream_builder();
+    let mut mask_surface = builder.surface();
+    draw_gradient_rect(&mut mask_surface, x, y, width, height, gradient);
+    mask_surface.finish();
+
+    Some(Mask::new(builder.finish(), MaskType::Alpha))
+}
+
+/// Recursively render a node and its children, under the node's CSS
+/// transform.
+///
+/// 2D transforms are applied to the surface. A 3D transform is drawn as its
+/// 2D part, unless the node was rasterized for
+/// `Config::flatten_transforms_for_pdf`, in which case the projected image
+/// replaces the node and its contents.
 #[cfg(feature = "pdf")]
 fn render_node(
     surface: &mut Surface,
@@ -1006,6 +1744,51 @@ fn render_node(
     node: &Node,
     offset_x: f32,
     offset_y: f32,
+    resources: &PageResources,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    let x = offset_x + node.final_layout.location.x;
+    let y = offset_y + node.final_layout.location.y;
+    if let Some(tile) = resources.transforms.get(&node.id) {
+        draw_raster_tile(surface, x, y, tile);
+        return Ok(());
+    }
+    let Some(transform) = element_transform(doc, node) else {
+        return render_box(
+            surface, doc, node, offset_x, offset_y, resources, font_cache,
+        );
+    };
+
+    // The transform is relative to the border box, so move it to the page
+    let [a, b, c, d, e, f] = transform.affine();
+    surface.push_transform(&Transform::from_row(
+        a,
+        b,
+        c,
+        d,
+        x + e - (a * x + c * y),
+        y + f - (b * x + d * y),
+    ));
+    let result = render_box(
+        surface, doc, node, offset_x, offset_y, resources, font_cache,
+    );
+    surface.pop();
+    result
+}
+
+/// Render a node's box and its children.
+///
+/// Background gradients are drawn as shadings only when linear; radial and
+/// conic ones are skipped unless [`Config::rasterize_gradients`] painted
+/// them with the raster renderer.
+#[cfg(feature = "pdf")]
+fn render_box(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node: &Node,
+    offset_x: f32,
+    offset_y: f32,
+    resources: &PageResources,
     font_cache: &mut FontCache,
 ) -> Result<()> {
     // Get layout information
@@ -1015,13 +1798,36 @@ fn render_node(
     let width = layout.size.width;
     let height = layout.size.height;
 
-    // Skip nodes with no size
+    // Hidden elements (including `[hidden]`, which Blitz maps to
+    // `display: none`) paint nothing, even if stale layout remains
+    if node
+        .primary_styles()
+        .is_some_and(|style| style.get_box().display.is_none())
+    {
+        return Ok(());
+    }
+    let skips_contents = has_hidden_content_visibility(node);
+
+    // Paint containment clips like `overflow: clip`
+    let clips_overflow = node.primary_styles().is_some_and(|style| {
+        let box_style = style.get_box();
+        box_style.overflow_x != Overflow::Visible
+            || box_style.overflow_y != Overflow::Visible
+            || box_style.contain.contains(Contain::PAINT)
+    });
+
+    // Skip painting boxes with no size
     if width <= 0.0 || height <= 0.0 {
-        // Still process paint children as they might have their own layout
+        if clips_overflow || skips_contents {
+            return Ok(());
+        }
+        // Text still overflows a zero-sized box (e.g. a collapsed table cell),
+        // and paint children might have their own layout
+        render_inline_text(surface, doc, node, x, y, font_cache)?;
         if let Some(paint_children) = &*node.paint_children.borrow() {
             for child_id in paint_children.iter() {
                 if let Some(child) = doc.get_node(*child_id) {
-                    render_node(surface, doc, child, x, y, font_cache)?;
+                    render_node(surface, doc, child, x, y, resources, font_cache)?;
                 }
             }
         }
@@ -1055,15 +1861,35 @@ fn render_node(
     };
     let has_radius = radii.has_any_radius();
 
+    // 0. Apply opacity and mask-image (affect the element and all of its
+    // descendants); inline elements have no box here, so their text takes
+    // their opacity instead, in `text_opacity`
+    let opacity = node
+        .primary_styles()
+        .map_or(1.0, |style| style.get_effects().opacity);
+    let has_opacity = opacity < 1.0;
+    if has_opacity {
+        surface.push_opacity(NormalizedF32::new(opacity.max(0.0)).unwrap_or(NormalizedF32::ZERO));
+    }
+    let mask = node.primary_styles().and_then(|style| {
+        build_gradient_mask(surface, &style, x, y, width, height, &current_color)
+    });
+    let has_mask = mask.is_some();
+    if let Some(mask) = mask {
+        surface.push_mask(mask);
+    }
+
     // 1. Draw OUTSET box shadows (before clipping, behind everything)
     for shadow in shadows.iter().filter(|s| !s.inset) {
         draw_outset_box_shadow(surface, x, y, width, height, shadow, &radii);
     }
 
     // 2. Apply clip path for rounded corners
+    let mut pushed_radius_clip = false;
     if has_radius {
         if let Some(clip_path) = build_rounded_rect_path(x, y, width, height, &radii) {
             surface.push_clip_path(&clip_path, &FillRule::NonZero);
+            pushed_radius_clip = true;
         }
     }
 
@@ -1071,34 +1897,51 @@ fn render_node(
     if let Some(style) = node.primary_styles() {
         // Draw background color
         let bg_color = style.clone_background_color();
-        if let Some((r, g, b, a)) = extract_color(&bg_color) {
-            if a > 0.0 {
-                let color = Rgb::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
-                draw_rect(surface, x, y, width, height, color);
-            }
+        let (r, g, b, a) = extract_color(&bg_color, &current_color);
+        if a > 0.0 {
+            let color = Rgb::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
+            draw_rect(surface, x, y, width, height, color);
         }
 
-        // Draw background gradients (on top of color)
+        // Draw background images and gradients (on top of color), with
+        // gradients as painted by the raster renderer if they were rasterized.
+        // The first layer is on top, so layers are drawn last to first
         let bg = style.get_background();
-        for bg_image in bg.background_image.0.iter() {
-            if let style::values::generics::image::GenericImage::Gradient(gradient) = bg_image {
-                // TODO: Support radial and conic gradients
-                if let GenericGradient::Linear {
-                    direction,
-                    items,
-                    flags,
-                    ..
-                } = gradient.as_ref()
-                {
-                    if let Some(linear_grad) = convert_linear_gradient(
-                        direction,
-                        items,
-                        *flags,
-                        width,
-                        height,
-                        &current_color,
-                    ) {
-                        draw_gradient_rect(surface, x, y, width, height, linear_grad);
+        match resources.gradients.get(&node.id) {
+            Some(tile) => draw_raster_tile(surface, x, y, tile),
+            None => {
+                for (index, bg_image) in bg.background_image.0.iter().enumerate().rev() {
+                    if let style::values::generics::image::GenericImage::Url(_) = bg_image {
+                        draw_element_background_image(
+                            surface,
+                            node,
+                            bg,
+                            index,
+                            (x, y, width, height),
+                            resources,
+                        );
+                    }
+                    if let style::values::generics::image::GenericImage::Gradient(gradient) =
+                        bg_image
+                    {
+                        if let GenericGradient::Linear {
+                            direction,
+                            items,
+                            flags,
+                            ..
+                        } = gradient.as_ref()
+                        {
+                            if let Some(linear_grad) = convert_linear_gradient(
+                                direction,
+                                items,
+                                *flags,
+                                width,
+                                height,
+                                &current_color,
+                            ) {
+                                draw_gradient_rect(surface, x, y, width, height, linear_grad);
+                            }
+                        }
                     }
                 }
             }
@@ -1113,185 +1956,1360 @@ fn render_node(
     // 5. Draw borders (after background and shadows, before content)
     draw_borders(surface, x, y, width, height, &borders);
 
-    // Check for inline text layout data
-    // Text is rendered at the content box position (inside padding and border)
-    if let Some(element_data) = node.element_data() {
-        if let Some(text_layout) = &element_data.inline_layout_data {
-            let content_x = x + layout.padding.left + layout.border.left;
-            let content_y = y + layout.padding.top + layout.border.top;
-            render_text(surface, doc, text_layout, content_x, content_y, font_cache)?;
+    // 6. Clip content to the padding box when overflow is not visible
+    let mut content_hidden = skips_contents;
+    if clips_overflow && !content_hidden {
+        let clip_x = x + layout.border.left;
+        let clip_y = y + layout.border.top;
+        let clip_w = width - layout.border.left - layout.border.right;
+        let clip_h = height - layout.border.top - layout.border.bottom;
+        match build_rounded_rect_path(clip_x, clip_y, clip_w, clip_h, &BorderRadii::default()) {
+            Some(clip_path) if clip_w > 0.0 && clip_h > 0.0 => {
+                surface.push_clip_path(&clip_path, &FillRule::NonZero);
+            }
+            // An empty padding box clips away all of the content
+            _ => content_hidden = true,
         }
     }
+    let pushed_overflow_clip = clips_overflow && !content_hidden;
 
-    // Render children using paint_children (computed layout order, handles anonymous blocks)
-    // This is important because inline content (like inline-block elements) gets wrapped
-    // in anonymous blocks which are part of paint_children but not regular children.
-    if let Some(paint_children) = &*node.paint_children.borrow() {
-        for child_id in paint_children.iter() {
-            if let Some(child) = doc.get_node(*child_id) {
-                render_node(surface, doc, child, x, y, font_cache)?;
-            }
-        }
+    // Everything pushed is popped even if the content fails to render, as a
+    // surface must not be dropped with pushes left on it
+    let rendered = match content_hidden {
+        true => Ok(()),
+        false => render_contents(surface, doc, node, (x, y, width), resources, font_cache),
+    };
+
+    // Pop overflow clip if we applied one
+    if pushed_overflow_clip {
+        surface.pop();
     }
 
     // Pop clip path if we applied one
-    if has_radius {
+    if pushed_radius_clip {
+        surface.pop();
+    }
+
+    // Pop mask if we applied one
+    if has_mask {
+        surface.pop();
+    }
+
+    if has_opacity {
         surface.pop();
     }
 
+    rendered
+}
+
+/// Render the text laid out in a node and its children, for the node whose
+/// `width`-wide border box starts at `(x, y)`.
+#[cfg(feature = "pdf")]
+fn render_contents(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node: &Node,
+    (x, y, width): (f32, f32, f32),
+    resources: &PageResources,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    render_inline_text(surface, doc, node, x, y, font_cache)?;
+
+    // Render children using paint_children (computed layout order, handles anonymous blocks)
+    // This is important because inline content (like inline-block elements) gets wrapped
+    // in anonymous blocks which are part of paint_children but not regular children.
+    // `display: contents` elements have no box and are replaced by their children here.
+    if let Some(paint_children) = &*node.paint_children.borrow() {
+        let box_offsets = inline_box_offsets(doc, node);
+        let mirrors_columns = is_rtl_table(node);
+        for child_id in paint_children.iter() {
+            if let Some(child) = doc.get_node(*child_id) {
+                let dy = box_offsets
+                    .iter()
+                    .find(|(id, _)| id == child_id)
+                    .map_or(0.0, |(_, dy)| *dy);
+                // Offset the child so it lands mirrored across the table
+                let dx = if mirrors_columns {
+                    width - 2.0 * child.final_layout.location.x - child.final_layout.size.width
+                } else {
+                    0.0
+                };
+                render_node(surface, doc, child, x + dx, y + dy, resources, font_cache)?;
+            }
+        }
+    }
     Ok(())
 }
 
+/// Whether the node is a table with `direction: rtl`.
+///
+/// Blitz places table columns left to right whatever the direction, so the
+/// rows and cells of such a table are mirrored horizontally when painted,
+/// putting the first column on the right.
+#[cfg(feature = "pdf")]
+fn is_rtl_table(node: &Node) -> bool {
+    use style::computed_values::direction::T as Direction;
+    use style::values::specified::box_::DisplayInside;
+
+    node.primary_styles().is_some_and(|style| {
+        style.get_box().display.inside() == DisplayInside::Table
+            && style.get_inherited_box().direction == Direction::Rtl
+    })
+}
+
+/// Whether the node has `content-visibility: hidden`.
+///
+/// Such an element still paints its own box but skips its contents. Stylo's
+/// servo build doesn't implement the property, so it is carried through the
+/// cascade in a custom property.
+#[cfg(feature = "pdf")]
+fn has_hidden_content_visibility(node: &Node) -> bool {
+    node.primary_styles()
+        .and_then(|style| carried::value(&style, carried::CONTENT_VISIBILITY))
+        .is_some_and(|value| value.eq_ignore_ascii_case("hidden"))
+}
+
+/// The number of lines the node clamps its text to with `line-clamp` or
+/// `-webkit-line-clamp`.
+///
+/// Like `content-visibility`, the property is only implemented in Stylo's
+/// gecko build, so it is carried in a custom property. The clamp applies
+/// to the text laid out in the node itself, without needing the
+/// `display: -webkit-box` that browsers also require.
+#[cfg(feature = "pdf")]
+fn line_clamp(node: &Node) -> Option<usize> {
+    carried::value(&*node.primary_styles()?, carried::LINE_CLAMP)?
+        .parse()
+        .ok()
+        .filter(|&lines| lines > 0)
+}
+
+/// Width of a `tab-size`, in spaces or in CSS pixels.
+#[cfg(feature = "pdf")]
+#[derive(Clone, Copy)]
+enum TabSize {
+    Spaces(f32),
+    Pixels(f32),
+}
+
+/// The `tab-size` of the node, defaulting to 8 spaces.
+///
+/// Like `content-visibility`, the property is only implemented in Stylo's
+/// gecko build, so it is carried in a custom property, which inherits as
+/// `tab-size` does.
+#[cfg(feature = "pdf")]
+fn tab_size(doc: &BaseDocument, node_id: usize) -> TabSize {
+    let value = doc
+        .get_node(node_id)
+        .and_then(|node| node.primary_styles())
+        .and_then(|style| carried::value(&style, carried::TAB_SIZE))
+        .map(|value| value.to_ascii_lowercase());
+    let size = value.and_then(|value| match value.strip_suffix("px") {
+        Some(px) => px.trim().parse().ok().map(TabSize::Pixels),
+        None => value.parse().ok().map(TabSize::Spaces),
+    });
+    size.filter(|size| match size {
+        TabSize::Spaces(n) | TabSize::Pixels(n) => *n >= 0.0,
+    })
+    .unwrap_or(TabSize::Spaces(8.0))
+}
+
+/// Glyph ID and advance (at `font_size`) of `ch` in `font`.
+#[cfg(feature = "pdf")]
+fn char_glyph(
+    font: &linebender_resource_handle::FontData,
+    ch: char,
+    font_size: f32,
+) -> Option<(u32, f32)> {
+    use skrifa::instance::{LocationRef, Size};
+    use skrifa::{FontRef, MetadataProvider};
+
+    let font = FontRef::from_index(font.data.data(), font.index).ok()?;
+    let glyph = font.charmap().map(ch)?;
+    let advance = font
+        .glyph_metrics(Size::new(font_size), LocationRef::default())
+        .advance_width(glyph)?;
+    Some((glyph.to_u32(), advance))
+}
+
+/// Render a node's inline text layout, or the value of a text `<input>` or
+/// `<textarea>`, if it has one, along with its list marker.
+///
+/// Text is positioned at the content box (inside padding and border) of the
+/// node whose border box starts at `(x, y)`. Taffy's `final_layout` always
+/// describes the border box, so this holds for either `box-sizing` value.
+#[cfg(feature = "pdf")]
+fn render_inline_text(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node: &Node,
+    x: f32,
+    y: f32,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    let layout = &node.final_layout;
+    let content_x = x + layout.padding.left + layout.border.left;
+    let content_y = y + layout.padding.top + layout.border.top;
+    render_list_marker(surface, doc, node, content_x, content_y, font_cache)?;
+
+    let Some(text_layout) = node
+        .element_data()
+        .and_then(|data| data.inline_layout_data.as_ref())
+    else {
+        return render_text_input(surface, doc, node, content_x, content_y, font_cache);
+    };
+
+    let content_width = layout.size.width
+        - layout.padding.left
+        - layout.padding.right
+        - layout.border.left
+        - layout.border.right;
+    let placement = LinePlacement {
+        line_clamp: line_clamp(node),
+        ..node
+            .primary_styles()
+            .map(|style| LinePlacement::new(&style, text_layout.layout.is_rtl(), content_width))
+            .unwrap_or_default()
+    };
+    render_text(
+        surface,
+        doc,
+        &text_layout.text,
+        &text_layout.layout,
+        content_x,
+        content_y,
+        &placement,
+        font_cache,
+    )
+}
+
+/// Render the value of a text `<input>` or `<textarea>` at its content box,
+/// which starts at `(content_x, content_y)`.
+///
+/// Blitz keeps a control's value in its own text editor rather than an
+/// inline layout.
+#[cfg(feature = "pdf")]
+fn render_text_input(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node: &Node,
+    content_x: f32,
+    content_y: f32,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    let Some(input) = node.element_data().and_then(|data| data.text_input_data()) else {
+        return Ok(());
+    };
+    let Some(layout) = input.editor.try_layout() else {
+        return Ok(());
+    };
+    render_device_text(
+        surface,
+        doc,
+        input.editor.raw_text(),
+        layout,
+        content_x,
+        content_y,
+        font_cache,
+    )
+}
+
+/// Render the marker of a list item with `list-style-position: outside`,
+/// right-aligned before its content box, which starts at
+/// `(content_x, content_y)`.
+///
+/// Markers placed inside are part of the item's inline layout, and items
+/// with `list-style-type: none` have no marker at all. Like Blitz's own
+/// painter, a bullet is padded away from the content and the marker sits
+/// on the baseline of the item's first line of text.
+#[cfg(feature = "pdf")]
+fn render_list_marker(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node: &Node,
+    content_x: f32,
+    content_y: f32,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    use blitz_dom::node::{ListItemLayout, ListItemLayoutPosition, Marker};
+
+    let Some(element) = node.element_data() else {
+        return Ok(());
+    };
+    let Some(ListItemLayout {
+        marker,
+        position: ListItemLayoutPosition::Outside(layout),
+    }) = element.list_item_data.as_deref()
+    else {
+        return Ok(());
+    };
+
+    let mut buffer = [0u8; 4];
+    let (text, padding) = match marker {
+        Marker::Char(char) => (&*char.encode_utf8(&mut buffer), 8.0),
+        Marker::String(string) => (string.as_str(), 0.0),
+    };
+    let scale = layout.scale();
+    let x_offset = -(layout.full_width() / scale + padding);
+    let y_offset = match (
+        element
+            .inline_layout_data
+            .as_ref()
+            .and_then(|text_layout| text_layout.layout.lines().next()),
+        layout.lines().next(),
+    ) {
+        (Some(text_line), Some(marker_line)) => {
+            (text_line.metrics().baseline - marker_line.metrics().baseline) / scale
+        }
+        _ => 0.0,
+    };
+    render_device_text(
+        surface,
+        doc,
+        text,
+        layout,
+        content_x + x_offset,
+        content_y + y_offset,
+        font_cache,
+    )
+}
+
+/// Render a Parley layout built in device pixels, such as a text editor's
+/// or a list marker's, with its origin at `(x, y)` in CSS pixels.
+#[cfg(feature = "pdf")]
+fn render_device_text(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    text: &str,
+    layout: &parley::Layout<blitz_dom::node::TextBrush>,
+    x: f32,
+    y: f32,
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    let scale = doc.viewport().scale();
+    surface.push_transform(&Transform::from_scale(1.0 / scale, 1.0 / scale));
+    let result = render_text(
+        surface,
+        doc,
+        text,
+        layout,
+        x * scale,
+        y * scale,
+        &LinePlacement::default(),
+        font_cache,
+    );
+    surface.pop();
+    result
+}
+
+/// Vertical shifts for the inline boxes in a node's inline layout that use
+/// `vertical-align: top`, `middle` or `bottom`, keyed by node ID.
+///
+/// Parley sits every inline box on the baseline, so boxes with other
+/// alignments are moved within their line when painted. `middle` uses the
+/// usual 0.5em approximation of the x-height. The line height itself is not
+/// recomputed.
+#[cfg(feature = "pdf")]
+fn inline_box_offsets(doc: &BaseDocument, node: &Node) -> Vec<(usize, f32)> {
+    use style::values::generics::box_::{VerticalAlign, VerticalAlignKeyword};
+
+    let Some(text_layout) = node
+        .element_data()
+        .and_then(|data| data.inline_layout_data.as_ref())
+    else {
+        return Vec::new();
+    };
+    let em = node.primary_styles().map_or(16.0, |style| {
+        style.get_font().font_size.computed_size().px()
+    });
+
+    let mut offsets = Vec::new();
+    for line in text_layout.layout.lines() {
+        let metrics = line.metrics();
+        for item in line.items() {
+            let PositionedLayoutItem::InlineBox(inline_box) = item else {
+                continue;
+            };
+            let id = inline_box.id as usize;
+            let Some(style) = doc.get_node(id).and_then(|child| child.primary_styles()) else {
+                continue;
+            };
+            let VerticalAlign::Keyword(keyword) = style.get_box().vertical_align else {
+                continue;
+            };
+            let top = match keyword {
+                VerticalAlignKeyword::Top => metrics.min_coord,
+                VerticalAlignKeyword::Bottom => metrics.max_coord - inline_box.height,
+                VerticalAlignKeyword::Middle => {
+                    metrics.baseline - em * 0.25 - inline_box.height / 2.0
+                }
+                _ => continue,
+            };
+            offsets.push((id, top - inline_box.y));
+        }
+    }
+    offsets
+}
+
+/// Whether Parley placed `text-align: start`/`end` lines on the wrong side.
+///
+/// Parley resolves `start` and `end` against the base direction it detects
+/// from the text itself, while CSS resolves them against the element's
+/// `direction`. They disagree for e.g. Latin text in a `direction: rtl`
+/// element, in which case the renderer mirrors the line offsets.
+#[cfg(feature = "pdf")]
+fn flips_start_end(style: &style::properties::ComputedValues, layout_is_rtl: bool) -> bool {
+    use style::computed_values::direction::T as Direction;
+    use style::values::specified::TextAlignKeyword;
+
+    let css_is_rtl = style.get_inherited_box().direction == Direction::Rtl;
+    let is_start_end = matches!(
+        style.clone_text_align(),
+        TextAlignKeyword::Start | TextAlignKeyword::End
+    );
+    is_start_end && css_is_rtl != layout_is_rtl
+}
+
+/// Horizontal shift that moves a start/end-aligned line to the opposite side
+/// of a content box `width` wide.
+///
+/// Lines holding inline boxes are left alone, since those boxes are
+/// positioned by layout and would no longer line up with the text.
+#[cfg(feature = "pdf")]
+fn mirrored_line_shift(
+    line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+    layout_is_rtl: bool,
+    width: f32,
+) -> f32 {
+    let has_inline_box = line
+        .items()
+        .any(|item| matches!(item, PositionedLayoutItem::InlineBox(_)));
+    let metrics = line.metrics();
+    let free_space = width - metrics.advance + metrics.trailing_whitespace;
+    if has_inline_box || free_space <= 0.0 {
+        return 0.0;
+    }
+
+    // Parley hangs trailing whitespace to the left of RTL lines
+    let base_offset = if layout_is_rtl {
+        -metrics.trailing_whitespace
+    } else {
+        0.0
+    };
+    let aligned_offset = metrics.offset - base_offset;
+    free_space - 2.0 * aligned_offset
+}
+
+/// Corrections to the line placement Parley computed for a node's text.
+#[cfg(feature = "pdf")]
+#[derive(Default)]
+struct LinePlacement {
+    /// Width of the content box the lines are aligned in.
+    width: f32,
+    /// Whether `start`/`end` lines are mirrored (see [`flips_start_end`]).
+    mirror: bool,
+    /// `text-align-last`, unless `auto`.
+    align_last: Option<style::values::specified::TextAlignLast>,
+    /// Whether the element's `direction` is `rtl`.
+    css_is_rtl: bool,
+    /// Most lines drawn, from `line-clamp` (see [`line_clamp`]).
+    line_clamp: Option<usize>,
+}
+
+#[cfg(feature = "pdf")]
+impl LinePlacement {
+    fn new(style: &style::properties::ComputedValues, layout_is_rtl: bool, width: f32) -> Self {
+        use style::computed_values::direction::T as Direction;
+        use style::values::specified::TextAlignLast;
+
+        let align_last = style.clone_text_align_last();
+        LinePlacement {
+            width,
+            mirror: flips_start_end(style, layout_is_rtl),
+            align_last: (align_last != TextAlignLast::Auto).then_some(align_last),
+            css_is_rtl: style.get_inherited_box().direction == Direction::Rtl,
+            line_clamp: None,
+        }
+    }
+
+    /// Horizontal shift for `line`, and the extra advance for each of its
+    /// justifiable spaces.
+    fn line_adjustment(
+        &self,
+        line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+        text: &str,
+        layout_is_rtl: bool,
+    ) -> (f32, f32) {
+        use parley::layout::BreakReason;
+        use style::values::specified::TextAlignLast;
+
+        let is_last = matches!(
+            line.break_reason(),
+            BreakReason::None | BreakReason::Explicit
+        );
+        let Some(align_last) = self.align_last.filter(|_| is_last) else {
+            let shift = match self.mirror {
+                true => mirrored_line_shift(line, layout_is_rtl, self.width),
+                false => 0.0,
+            };
+            return (shift, 0.0);
+        };
+
+        // Like `mirrored_line_shift`, leave lines holding inline boxes alone
+        let has_inline_box = line
+            .items()
+            .any(|item| matches!(item, PositionedLayoutItem::InlineBox(_)));
+        let metrics = line.metrics();
+        let free_space = self.width - metrics.advance + metrics.trailing_whitespace;
+        if has_inline_box || free_space <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        // Parley hangs trailing whitespace to the left of RTL lines
+        let base_offset = if layout_is_rtl {
+            -metrics.trailing_whitespace
+        } else {
+            0.0
+        };
+        let (offset, space_adjustment) = match align_last {
+            TextAlignLast::Left => (0.0, 0.0),
+            TextAlignLast::Right => (free_space, 0.0),
+            TextAlignLast::Center => (free_space / 2.0, 0.0),
+            TextAlignLast::Start if self.css_is_rtl => (free_space, 0.0),
+            TextAlignLast::End if !self.css_is_rtl => (free_space, 0.0),
+            TextAlignLast::Start | TextAlignLast::End | TextAlignLast::Auto => (0.0, 0.0),
+            TextAlignLast::Justify => match justifiable_spaces(line, text) {
+                0 => (0.0, 0.0),
+                spaces => (0.0, free_space / spaces as f32),
+            },
+        };
+        (base_offset + offset - metrics.offset, space_adjustment)
+    }
+}
+
+/// Number of spaces in `line` that justification stretches: those before
+/// its trailing whitespace.
+#[cfg(feature = "pdf")]
+fn justifiable_spaces(
+    line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+    text: &str,
+) -> usize {
+    let content_end = justifiable_end(line, text);
+    line.runs()
+        .map(|run| {
+            run.clusters()
+                .filter(|cluster| {
+                    cluster.is_space_or_nbsp() && cluster.text_range().start < content_end
+                })
+                .count()
+        })
+        .sum()
+}
+
+/// Position of the first tab stop after `pen`, with stops every `interval`
+/// from the line start.
+///
+/// A stop closer than half a space (taken as a quarter of `font_size`) is
+/// skipped, as CSS requires. A zero interval leaves tabs with no width.
+#[cfg(feature = "pdf")]
+fn next_tab_stop(pen: f32, interval: f32, font_size: f32) -> f32 {
+    if interval <= 0.0 {
+        return pen;
+    }
+    let mut stop = ((pen / interval).floor() + 1.0) * interval;
+    if stop - pen < font_size / 8.0 {
+        stop += interval;
+    }
+    stop
+}
+
+/// Byte index in `text` where `line`'s trailing whitespace starts.
+#[cfg(feature = "pdf")]
+fn justifiable_end(
+    line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+    text: &str,
+) -> usize {
+    // Empty lines can report a range outside the text
+    let range = line.text_range();
+    text.get(range.clone())
+        .map_or(0, |line_text| range.start + line_text.trim_end().len())
+}
+
 /// Render text from a Parley layout to the PDF surface.
+///
+/// With a [`LinePlacement::line_clamp`], only that many lines are drawn,
+/// and if any are left out, the last one drawn ends in an ellipsis, with
+/// as many of its glyphs dropped as it takes to fit the content box. Lines
+/// broken at a soft hyphen end in a hyphen, which Parley leaves out.
 #[cfg(feature = "pdf")]
+#[allow(clippy::too_many_arguments)]
 fn render_text(
     surface: &mut Surface,
     doc: &BaseDocument,
-    text_layout: &blitz_dom::node::TextLayout,
+    text: &str,
+    layout: &parley::Layout<blitz_dom::node::TextBrush>,
     pos_x: f32,
     pos_y: f32,
+    placement: &LinePlacement,
     font_cache: &mut FontCache,
 ) -> Result<()> {
-    use linebender_resource_handle::FontData;
-
-    let text = &text_layout.text;
-    let layout = &text_layout.layout;
+    let shown_lines = placement.line_clamp.unwrap_or(usize::MAX);
+    let clamped = layout.len() > shown_lines;
+    // The text layer reads synthesized small capitals as they were written
+    // and leaves out the non-joiners that turned ligatures off
+    let restored = crate::small_caps::original_text(doc, text, layout);
+    let layer_text = restored.as_ref().map_or(text, |(text, _)| text);
+    let stripped = crate::ligatures::strip_non_joiners(layer_text);
+    let layer_range = |range: std::ops::Range<usize>| {
+        let range = match &restored {
+            Some((_, offsets)) => offsets[range.start]..offsets[range.end],
+            None => range,
+        };
+        match &stripped {
+            Some((_, offsets)) => offsets[range.start]..offsets[range.end],
+            None => range,
+        }
+    };
 
-    for line in layout.lines() {
+    for (line_index, line) in layout.lines().take(shown_lines).enumerate() {
+        let (line_shift, space_adjustment) =
+            placement.line_adjustment(&line, text, layout.is_rtl());
+        let content_end = justifiable_end(&line, text);
+        // Space added by `space_adjustment` before the current glyph run
+        let mut justified_shift = 0.0;
+        // Parley splits a shaped run into one glyph run per style, and
+        // `run.visual_clusters()` covers the whole run, so track which of the
+        // run's glyphs belong to the current glyph run
+        let mut run_glyphs: Option<(std::ops::Range<usize>, usize)> = None;
+        // The ellipsis ending the last line shown, and where glyphs stop
+        // fitting before it
+        let ellipsis = match clamped && line_index + 1 == shown_lines && !layout.is_rtl() {
+            true => LineEndMark::ellipsis(&line),
+            false => None,
+        };
+        let hyphen = match ellipsis.is_none()
+            && line_index + 1 < layout.len()
+            && !layout.is_rtl()
+            && ends_with_soft_hyphen(&line, text)
+        {
+            true => LineEndMark::hyphen(&line),
+            false => None,
+        };
+        let limit = ellipsis.as_ref().map_or(f32::INFINITY, |ellipsis| {
+            placement.width - line_shift - ellipsis.width
+        });
+        // End of the last glyph drawn that isn't a space, where the ellipsis
+        // or hyphen goes
+        let mut text_end = 0.0;
+        let mut truncated = false;
         for item in line.items() {
             // Only handle GlyphRun items for text rendering.
             // InlineBox items (inline-block elements) are rendered through paint_children,
             // not through the inline layout system. The inline box in Parley just reserves
             // space in the text flow.
+            if truncated {
+                break;
+            }
             if let PositionedLayoutItem::GlyphRun(glyph_run) = item {
                 let run = glyph_run.run();
-                let font_data: FontData = run.font().clone();
+                let glyph_start = match &run_glyphs {
+                    Some((range, next)) if *range == run.text_range() => *next,
+                    _ => 0,
+                };
+                let glyph_end = glyph_start + glyph_run.glyphs().count();
+                run_glyphs = Some((run.text_range(), glyph_end));
+
                 let font_size = run.font_size();
+                let space = char_glyph(run.font(), ' ', font_size);
                 let style = glyph_run.style();
-
-                // Get or create Krilla font from the Parley font data
-                let (raw_data, font_id) = font_data.data.into_raw_parts();
-                let krilla_font = if let Some(font) = font_cache.get(&font_id) {
-                    font.clone()
-                } else {
-                    let data: krilla::Data = raw_data.into();
-                    let font = Font::new(data, font_data.index)
-                        .ok_or_else(|| Error::Font("failed to load font from data".to_string()))?;
-                    font_cache.insert(font_id, font.clone());
-                    font
+                let krilla_font = krilla_font(font_cache, run.font())?;
+                let figures = match oldstyle_nums(doc, style.brush.id) {
+                    true => Some(oldstyle_figures(font_cache, run.font())),
+                    false => None,
                 };
-
-                // Get text color from computed styles
-                // Note: Alpha is extracted but not used - PDF text opacity would require
-                // additional graphics state handling which is not yet implemented.
-                let text_color = doc
-                    .get_node(style.brush.id)
-                    .and_then(|n| n.primary_styles())
-                    .map(|styles| {
-                        let inherited = styles.get_inherited_text();
-                        // inherited.color is an AbsoluteColor, convert to sRGB
-                        let srgb = inherited
-                            .color
-                            .to_color_space(style::color::ColorSpace::Srgb);
-                        (
-                            srgb.components.0,
-                            srgb.components.1,
-                            srgb.components.2,
-                            srgb.alpha,
-                        )
-                    })
-                    .unwrap_or((0.0, 0.0, 0.0, 1.0)); // Default to opaque black
-
-                // Set fill color for text
-                let (r, g, b, _a) = text_color;
-                surface.set_fill(Some(Fill {
-                    paint: rgb::Color::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
-                        .into(),
-                    opacity: NormalizedF32::ONE,
-                    rule: FillRule::NonZero,
-                }));
+                let text_fill = text_fill(doc, style.brush.id);
 
                 // Build glyphs for this run using clusters for proper text ranges
                 let mut glyphs: Vec<KrillaGlyph> = Vec::new();
                 let baseline = glyph_run.baseline();
 
-                for cluster in run.visual_clusters() {
+                // Tabs advance to the next tab stop, measured from the line
+                // start, instead of by the advance of the glyph they shape to
+                let tab_interval = match tab_size(doc, style.brush.id) {
+                    TabSize::Spaces(spaces) => {
+                        spaces * space.map_or(font_size / 4.0, |(_, advance)| advance)
+                    }
+                    TabSize::Pixels(px) => px * doc.viewport().scale(),
+                };
+                let run_start = glyph_run.offset() + justified_shift;
+                let mut pen = run_start;
+
+                let mut glyph_index = 0;
+                let mut run_extra = 0.0;
+                'clusters: for cluster in run.visual_clusters() {
+                    if glyph_index >= glyph_end {
+                        break;
+                    }
                     if cluster.is_ligature_continuation() {
                         // Ligature continuations have no glyphs of their own
                         if let Some(glyph) = glyphs.last_mut() {
-                            glyph.text_range.end = cluster.text_range().end;
+                            glyph.text_range.end = layer_range(cluster.text_range()).end;
                         }
                         continue;
                     }
 
                     let text_range = cluster.text_range();
+                    let extra_advance =
+                        match cluster.is_space_or_nbsp() && text_range.start < content_end {
+                            true => space_adjustment,
+                            false => 0.0,
+                        };
+                    let is_tab = text.get(text_range.clone()) == Some("\t");
                     for glyph in cluster.glyphs() {
+                        glyph_index += 1;
+                        if glyph_index <= glyph_start {
+                            continue;
+                        }
+                        let (glyph_id, extra_advance) = match is_tab {
+                            true => {
+                                let stop = next_tab_stop(pen, tab_interval, font_size);
+                                let id = space.map_or(glyph.id, |(id, _)| id);
+                                (id, stop - pen - glyph.advance)
+                            }
+                            false => (glyph.id, extra_advance),
+                        };
+                        // Substituted figures keep the advances of the
+                        // ones they replace
+                        let glyph_id = figures
+                            .and_then(|figures| figures.get(&glyph_id).copied())
+                            .unwrap_or(glyph_id);
+                        if pen + glyph.advance + extra_advance > limit {
+                            truncated = true;
+                            break 'clusters;
+                        }
+                        pen += glyph.advance + extra_advance;
+                        run_extra += extra_advance;
                         glyphs.push(KrillaGlyph::new(
-                            GlyphId::new(glyph.id),
-                            glyph.advance / font_size,
+                            GlyphId::new(glyph_id),
+                            (glyph.advance + extra_advance) / font_size,
                             glyph.x / font_size,
                             glyph.y / font_size,
                             0.0,
-                            text_range.clone(),
+                            layer_range(text_range.clone()),
                             None,
                         ));
                     }
+                    if !cluster.is_space_or_nbsp() && !is_tab {
+                        text_end = pen;
+                    }
                 }
 
-                if !glyphs.is_empty() {
-                    // Position: add node position + glyph run offset
-                    let draw_x = pos_x + glyph_run.offset();
-                    let draw_y = pos_y + baseline;
+                // Position: add node position + glyph run offset
+                let draw_x = pos_x + line_shift + run_start;
+                let draw_y = pos_y + baseline;
+
+                // Backgrounds of inline elements go behind their text
+                let metrics = run.metrics();
+                draw_inline_backgrounds(
+                    surface,
+                    doc,
+                    style.brush.id,
+                    draw_x,
+                    draw_y - metrics.ascent,
+                    pen - run_start,
+                    metrics.ascent + metrics.descent,
+                );
 
+                if !glyphs.is_empty() {
+                    surface.set_fill(Some(text_fill));
                     surface.draw_glyphs(
                         Point::from_xy(draw_x, draw_y),
                         &glyphs,
                         krilla_font,
-                        text,
+                        stripped.as_ref().map_or(layer_text, |(text, _)| text),
                         font_size,
                         false, // outlined
                     );
                 }
+                draw_text_decorations(
+                    surface,
+                    doc,
+                    style.brush.id,
+                    draw_x,
+                    draw_y,
+                    pen - run_start,
+                    metrics,
+                );
+                justified_shift += run_extra;
             }
         }
+
+        if let Some(mark) = ellipsis.or(hyphen) {
+            mark.draw(
+                surface,
+                doc,
+                pos_x + line_shift + text_end,
+                pos_y + line.metrics().baseline,
+                font_cache,
+            )?;
+        }
     }
 
     Ok(())
 }
 
-/// Extract RGBA color components from a Stylo color value.
+/// Whether `line` ends in a soft hyphen, so it was broken there.
+#[cfg(feature = "pdf")]
+fn ends_with_soft_hyphen(
+    line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+    text: &str,
+) -> bool {
+    text.get(line.text_range())
+        .is_some_and(|line_text| line_text.trim_end().ends_with('\u{AD}'))
+}
+
+/// A mark added at the end of a line, set in the font of the line it ends:
+/// an ellipsis marking text cut off by `line-clamp`, or the hyphen of a
+/// soft hyphen the line was broken at.
+#[cfg(feature = "pdf")]
+struct LineEndMark {
+    font: linebender_resource_handle::FontData,
+    font_size: f32,
+    /// Node whose text color the mark takes.
+    node_id: usize,
+    /// The text the mark stands for, one character per glyph.
+    text: &'static str,
+    /// Glyph IDs and advances.
+    glyphs: Vec<(u32, f32)>,
+    width: f32,
+}
+
+#[cfg(feature = "pdf")]
+impl LineEndMark {
+    /// The ellipsis for the end of `line`: `…`, or three periods in fonts
+    /// without it.
+    fn ellipsis(line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>) -> Option<Self> {
+        Self::for_line(line, |font, font_size| {
+            match char_glyph(font, '\u{2026}', font_size) {
+                Some(glyph) => Some(("\u{2026}", vec![glyph])),
+                None => Some(("...", vec![char_glyph(font, '.', font_size)?; 3])),
+            }
+        })
+    }
+
+    /// The hyphen for the end of `line`: `‐`, or `-` in fonts without it.
+    fn hyphen(line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>) -> Option<Self> {
+        Self::for_line(line, |font, font_size| {
+            match char_glyph(font, '\u{2010}', font_size) {
+                Some(glyph) => Some(("\u{2010}", vec![glyph])),
+                None => Some(("-", vec![char_glyph(font, '-', font_size)?])),
+            }
+        })
+    }
+
+    /// The mark `glyphs` gives in the style of the last glyph run of `line`.
+    fn for_line(
+        line: &parley::layout::Line<'_, blitz_dom::node::TextBrush>,
+        glyphs: impl FnOnce(
+            &linebender_resource_handle::FontData,
+            f32,
+        ) -> Option<(&'static str, Vec<(u32, f32)>)>,
+    ) -> Option<Self> {
+        let glyph_run = line
+            .items()
+            .filter_map(|item| match item {
+                PositionedLayoutItem::GlyphRun(glyph_run) => Some(glyph_run),
+                PositionedLayoutItem::InlineBox(_) => None,
+            })
+            .last()?;
+        let run = glyph_run.run();
+        let (font, font_size) = (run.font().clone(), run.font_size());
+        let (text, glyphs) = glyphs(&font, font_size)?;
+        Some(LineEndMark {
+            width: glyphs.iter().map(|(_, advance)| advance).sum(),
+            font,
+            font_size,
+            node_id: glyph_run.style().brush.id,
+            text,
+            glyphs,
+        })
+    }
+
+    /// Draw the mark with its baseline starting at `(x, y)`.
+    fn draw(
+        &self,
+        surface: &mut Surface,
+        doc: &BaseDocument,
+        x: f32,
+        y: f32,
+        font_cache: &mut FontCache,
+    ) -> Result<()> {
+        let per_glyph = self.text.len() / self.glyphs.len();
+        let glyphs: Vec<KrillaGlyph> = self
+            .glyphs
+            .iter()
+            .enumerate()
+            .map(|(i, &(id, advance))| {
+                KrillaGlyph::new(
+                    GlyphId::new(id),
+                    advance / self.font_size,
+                    0.0,
+                    0.0,
+                    0.0,
+                    i * per_glyph..(i + 1) * per_glyph,
+                    None,
+                )
+            })
+            .collect();
+        surface.set_fill(Some(text_fill(doc, self.node_id)));
+        surface.draw_glyphs(
+            Point::from_xy(x, y),
+            &glyphs,
+            krilla_font(font_cache, &self.font)?,
+            self.text,
+            self.font_size,
+            false, // outlined
+        );
+        Ok(())
+    }
+}
+
+/// The Krilla font for a Parley font, loaded once per font file.
+#[cfg(feature = "pdf")]
+fn krilla_font(
+    font_cache: &mut FontCache,
+    font_data: &linebender_resource_handle::FontData,
+) -> Result<Font> {
+    let (raw_data, font_id) = font_data.data.clone().into_raw_parts();
+    if let Some(font) = font_cache.fonts.get(&font_id) {
+        return Ok(font.clone());
+    }
+    let data: krilla::Data = raw_data.into();
+    let font = Font::new(data, font_data.index)
+        .ok_or_else(|| Error::Font("failed to load font from data".to_string()))?;
+    font_cache.fonts.insert(font_id, font.clone());
+    Ok(font)
+}
+
+/// The old-style figures a Parley font substitutes for its lining ones,
+/// from its `onum` feature, read once per font.
+#[cfg(feature = "pdf")]
+fn oldstyle_figures<'a>(
+    font_cache: &'a mut FontCache,
+    font_data: &linebender_resource_handle::FontData,
+) -> &'a HashMap<u32, u32> {
+    let key = (font_data.data.id(), font_data.index);
+    font_cache.oldstyle_figures.entry(key).or_insert_with(|| {
+        skrifa::FontRef::from_index(font_data.data.as_ref(), font_data.index)
+            .map(|font| crate::fonts::single_substitutions(&font, skrifa::Tag::new(b"onum")))
+            .unwrap_or_default()
+    })
+}
+
+/// Whether the text of a node is set in `font-variant-numeric:
+/// oldstyle-nums`, which Stylo's servo build drops (see [`carried`]).
+#[cfg(feature = "pdf")]
+fn oldstyle_nums(doc: &BaseDocument, node_id: usize) -> bool {
+    doc.get_node(node_id)
+        .and_then(|node| node.primary_styles())
+        .and_then(|style| carried::value(&style, carried::FONT_VARIANT_NUMERIC))
+        .is_some_and(|value| {
+            value
+                .split_whitespace()
+                .any(|keyword| keyword.eq_ignore_ascii_case("oldstyle-nums"))
+        })
+}
+
+/// Fill for the text of a node, in its computed `color` and faded by
+/// [`text_opacity`].
+///
+/// The alpha of `color` itself is not applied.
+#[cfg(feature = "pdf")]
+fn text_fill(doc: &BaseDocument, node_id: usize) -> Fill {
+    let (r, g, b) = doc
+        .get_node(node_id)
+        .and_then(|n| n.primary_styles())
+        .map(|styles| {
+            // inherited.color is an AbsoluteColor, convert to sRGB
+            let srgb = styles
+                .get_inherited_text()
+                .color
+                .to_color_space(style::color::ColorSpace::Srgb);
+            (srgb.components.0, srgb.components.1, srgb.components.2)
+        })
+        .unwrap_or((0.0, 0.0, 0.0)); // Default to black
+    Fill {
+        paint: rgb::Color::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8).into(),
+        opacity: NormalizedF32::new(text_opacity(doc, node_id)).unwrap_or(NormalizedF32::ONE),
+        rule: FillRule::NonZero,
+    }
+}
+
+/// The `opacity` of the inline elements that the text of a node is in,
+/// composed from the innermost outwards.
+///
+/// Boxes are faded as a group in [`render_box`], which covers the text laid
+/// out in them, but inline elements have no box of their own there, so
+/// their opacity is applied to each run of their text instead.
+#[cfg(feature = "pdf")]
+fn text_opacity(doc: &BaseDocument, node_id: usize) -> f32 {
+    use style::values::computed::Display;
+
+    let mut opacity = 1.0;
+    let mut current = doc.get_node(node_id);
+    while let Some(node) = current {
+        if let Some(style) = node.primary_styles() {
+            // Text nodes take the display of their parent
+            if node.is_element() && style.get_box().display != Display::Inline {
+                break;
+            }
+            opacity *= style.get_effects().opacity.clamp(0.0, 1.0);
+        }
+        current = node.parent.and_then(|parent| doc.get_node(parent));
+    }
+    opacity
+}
+
+/// Draw the background colors of the inline elements that a glyph run is
+/// in, outermost first, over the run's `width` x `height` content area at
+/// `(x, y)`.
+///
+/// Blocks paint their own backgrounds in [`render_node`], but inline
+/// elements (such as `<mark>`) have no box of their own there, so theirs
+/// are painted run by run behind the text. An element wrapping across lines
+/// thus gets a background on each line, as `box-decoration-break: clone`
+/// gives.
+#[cfg(feature = "pdf")]
+#[allow(clippy::too_many_arguments)]
+fn draw_inline_backgrounds(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node_id: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) {
+    use style::values::computed::Display;
+
+    let mut backgrounds = Vec::new();
+    let mut current = doc.get_node(node_id);
+    while let Some(node) = current {
+        if let Some(style) = node.primary_styles() {
+            if style.get_box().display != Display::Inline {
+                break;
+            }
+            let current_color = style.clone_color();
+            let (r, g, b, a) = extract_color(&style.clone_background_color(), &current_color);
+            backgrounds.push((r, g, b, a * text_opacity(doc, node.id)));
+        }
+        current = node.parent.and_then(|parent| doc.get_node(parent));
+    }
+
+    for (r, g, b, a) in backgrounds.into_iter().rev() {
+        let color = Rgb::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
+        draw_rect_simple(surface, x, y, width, height, color, a);
+    }
+}
+
+/// Draw the underline and line-through of a glyph run `width` wide whose
+/// baseline starts at `(x, baseline)`, positioned by the font's metrics.
+///
+/// Decorations propagate to the text of descendants, so these are those of
+/// the run's element and each ancestor up to the block or atomic inline it
+/// sits in, each drawn in the `text-decoration-color` of the element that
+/// set it, which defaults to `currentColor`: that element's text color.
 #[cfg(feature = "pdf")]
-fn extract_color(color: &style::values::computed::color::Color) -> Option<(f32, f32, f32, f32)> {
-    use style::values::generics::color::Color as GenericColor;
-
-    match color {
-        GenericColor::Absolute(abs) => {
-            // AbsoluteColor has to_color_space method to convert to sRGB
-            let srgb = abs.to_color_space(style::color::ColorSpace::Srgb);
-            Some((
-                srgb.components.0,
-                srgb.components.1,
-                srgb.components.2,
-                srgb.alpha,
-            ))
+fn draw_text_decorations(
+    surface: &mut Surface,
+    doc: &BaseDocument,
+    node_id: usize,
+    x: f32,
+    baseline: f32,
+    width: f32,
+    metrics: &parley::RunMetrics,
+) {
+    use style::values::computed::Display;
+    use style::values::specified::TextDecorationLine;
+
+    let lines = [
+        (
+            TextDecorationLine::UNDERLINE,
+            metrics.underline_offset,
+            metrics.underline_size,
+        ),
+        (
+            TextDecorationLine::LINE_THROUGH,
+            metrics.strikethrough_offset,
+            metrics.strikethrough_size,
+        ),
+    ];
+    let opacity = text_opacity(doc, node_id);
+    let mut current = doc.get_node(node_id);
+    while let Some(node) = current {
+        current = node.parent.and_then(|parent| doc.get_node(parent));
+        // Text nodes take the decorations of their parent
+        let Some(style) = node.primary_styles().filter(|_| node.is_element()) else {
+            continue;
+        };
+        let text = style.get_text();
+        let (r, g, b, a) = extract_color(&text.text_decoration_color, &style.clone_color());
+        let color = Rgb::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
+        for (line, offset, size) in lines {
+            if text.text_decoration_line.contains(line) {
+                draw_rect_simple(
+                    surface,
+                    x,
+                    baseline - offset,
+                    width,
+                    size,
+                    color,
+                    a * opacity,
+                );
+            }
         }
-        GenericColor::CurrentColor => {
-            // CurrentColor inherits from parent - default to black for now
-            Some((0.0, 0.0, 0.0, 1.0))
+        if style.get_box().display != Display::Inline {
+            break;
         }
+    }
+}
+
+/// Extract RGBA color components from a Stylo color value.
+///
+/// `currentColor` and colors mixed with it resolve against `current_color`,
+/// the element's computed `color`.
+#[cfg(feature = "pdf")]
+fn extract_color(
+    color: &style::values::computed::color::Color,
+    current_color: &AbsoluteColor,
+) -> (f32, f32, f32, f32) {
+    let srgb = color
+        .resolve_to_absolute(current_color)
+        .to_color_space(style::color::ColorSpace::Srgb);
+    (
+        srgb.components.0,
+        srgb.components.1,
+        srgb.components.2,
+        srgb.alpha,
+    )
+}
+
+/// A heading collected for the document outline.
+#[cfg(feature = "pdf")]
+struct Heading {
+    /// Heading level, 1 for `<h1>` through 6 for `<h6>`.
+    level: u8,
+    title: String,
+    destination: XyzDestination,
+}
+
+/// Collect the headings under `node` in document order, skipping content
+/// with `display: none`, for the outline and table of contents.
+///
+/// Destinations point to the page at `page_index`, where `page_transform`
+/// maps CSS pixels to page coordinates, as applied for
+/// [`Config::rotation`](crate::Config::rotation).
+#[cfg(feature = "pdf")]
+fn collect_headings(
+    doc: &BaseDocument,
+    node: &Node,
+    page_transform: [f32; 6],
+    page_index: usize,
+    headings: &mut Vec<Heading>,
+) {
+    use style::values::computed::Display;
+
+    let Some(element) = node.element_data() else {
+        return;
+    };
+    if node
+        .primary_styles()
+        .is_some_and(|style| style.get_box().display == Display::None)
+    {
+        return;
+    }
+
+    let level = match element.name.local.as_ref() {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
         _ => None,
+    };
+    if let Some(level) = level {
+        let title = node
+            .text_content()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !title.is_empty() {
+            let position = node.absolute_position(0.0, 0.0);
+            let [a, b, c, d, e, f] = page_transform;
+            let point = Point::from_xy(
+                a * position.x + c * position.y + e,
+                b * position.x + d * position.y + f,
+            );
+            headings.push(Heading {
+                level,
+                title,
+                destination: XyzDestination::new(page_index, point),
+            });
+        }
+        return;
+    }
+
+    for &child_id in &node.children {
+        if let Some(child) = doc.get_node(child_id) {
+            collect_headings(doc, child, page_transform, page_index, headings);
+        }
     }
 }
 
+/// Level and title of each of the document's visible headings, in document
+/// order, for the [`Config::toc`] page to list.
+#[cfg(feature = "pdf")]
+pub(crate) fn heading_titles(doc: &BaseDocument) -> Vec<(u8, String)> {
+    let mut headings = Vec::new();
+    collect_headings(
+        doc,
+        doc.root_element(),
+        [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        0,
+        &mut headings,
+    );
+    headings
+        .into_iter()
+        .map(|heading| (heading.level, heading.title))
+        .collect()
+}
+
+/// Draw the [`Config::toc`] page `toc` as a `width` x `height` page, which
+/// is `size` once rotated, with each of its entries linking to the
+/// destination of the heading it lists.
+#[cfg(feature = "pdf")]
+fn render_toc_page(
+    pdf_doc: &mut Document,
+    toc: &HtmlDocument,
+    config: &Config,
+    (width, height, size): (f32, f32, Size),
+    headings: &[Heading],
+    font_cache: &mut FontCache,
+) -> Result<()> {
+    let page_settings = PageSettings::new(size).with_page_label(page_label(&config.page_labels, 0));
+    let mut page = pdf_doc.start_page_with(page_settings);
+    let doc = toc.as_ref();
+    let page_transform = config.rotation.transform(width, height);
+
+    let mut surface = page.surface();
+    let [a, b, c, d, e, f] = page_transform;
+    surface.push_transform(&Transform::from_row(a, b, c, d, e, f));
+    let [red, green, blue, _] = config.background;
+    draw_rect(
+        &mut surface,
+        0.0,
+        0.0,
+        width,
+        height,
+        Rgb::new(red, green, blue),
+    );
+    let resources = PageResources {
+        interpolate_images: config.pdf_conformance.is_none(),
+        gradients: GradientTiles::new(),
+        transforms: TransformTiles::new(),
+        background_images: RefCell::new(HashMap::new()),
+    };
+    let clipped = push_page_clip(&mut surface, config, width, height);
+    let rendered = render_node(
+        &mut surface,
+        doc,
+        doc.root_element(),
+        0.0,
+        0.0,
+        &resources,
+        font_cache,
+    );
+    if clipped {
+        surface.pop();
+    }
+    surface.pop();
+    surface.finish();
+    rendered?;
+
+    for (_, node) in doc.tree().iter() {
+        let Some(heading) = node
+            .attr(LocalName::from(toc::ENTRY_ATTR))
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| headings.get(index))
+        else {
+            continue;
+        };
+        // The link covers the entry's border box, rotated with the page
+        let position = node.absolute_position(0.0, 0.0);
+        let entry_size = node.final_layout.size;
+        let corners = [
+            (position.x, position.y),
+            (
+                position.x + entry_size.width,
+                position.y + entry_size.height,
+            ),
+        ]
+        .map(|(x, y)| (a * x + c * y + e, b * x + d * y + f));
+        let Some(rect) = Rect::from_ltrb(
+            corners[0].0.min(corners[1].0),
+            corners[0].1.min(corners[1].1),
+            corners[0].0.max(corners[1].0),
+            corners[0].1.max(corners[1].1),
+        ) else {
+            continue;
+        };
+        page.add_annotation(Annotation::new_link(
+            LinkAnnotation::new(
+                rect,
+                Target::Destination(heading.destination.clone().into()),
+            ),
+            Some(heading.title.clone()),
+        ));
+    }
+    page.finish();
+    Ok(())
+}
+
+/// Nest headings into outline entries, each holding the headings of a
+/// deeper level that follow it.
+#[cfg(feature = "pdf")]
+fn outline_nodes(headings: &[Heading]) -> Vec<OutlineNode> {
+    let mut nodes = Vec::new();
+    let mut start = 0;
+    while let Some(heading) = headings.get(start) {
+        let end = headings[start + 1..]
+            .iter()
+            .position(|next| next.level <= heading.level)
+            .map_or(headings.len(), |offset| start + 1 + offset);
+        let mut node = OutlineNode::new(heading.title.clone(), heading.destination.clone());
+        for child in outline_nodes(&headings[start + 1..end]) {
+            node.push_child(child);
+        }
+        nodes.push(node);
+        start = end;
+    }
+    nodes
+}
+
 /// Get the actual content height from the document layout.
 #[cfg(feature = "pdf")]
 fn get_content_height(document: &HtmlDocument) -> Option<f32> {
-    let doc = document.as_ref();
-    let root = doc.root_element();
-    Some(root.final_layout.size.height)
+    Some(super::content_height(document.as_ref()))
 }
 
 #[cfg(not(feature = "pdf"))]
-pub fn render_to_pdf(_document: &blitz_html::HtmlDocument, _config: &Config) -> Result<Vec<u8>> {
+pub fn render_to_pdf(
+    _document: &mut blitz_html::HtmlDocument,
+    _toc: Option<&blitz_html::HtmlDocument>,
+    _config: &Config,
+    _started: std::time::Instant,
+    _on_page: &mut dyn FnMut(usize, &PdfPage),
+) -> Result<Vec<u8>> {
     Err(Error::FormatNotEnabled("pdf"))
 }
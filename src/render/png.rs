@@ -1,50 +1,413 @@
 //! PNG rendering implementation using Blitz and Vello.
 
 use crate::config::Config;
+#[cfg(feature = "png")]
+use crate::config::{PngColorType, Rotation};
 use crate::error::{Error, Result};
 
 #[cfg(feature = "png")]
-use anyrender::render_to_buffer;
+use super::paint_scene;
+#[cfg(feature = "png")]
+use crate::background::{self, DecodedBackground};
+#[cfg(feature = "png")]
+use crate::capture::{capture_rect, overhang_rect, CaptureRect};
+#[cfg(feature = "png")]
+use crate::debug_layout::layout_boxes;
+#[cfg(feature = "png")]
+use crate::overlay::OverlayTarget;
+#[cfg(feature = "png")]
+use crate::render::scene::paint_document;
+#[cfg(feature = "png")]
+use anyrender::{render_to_buffer, PaintScene};
 #[cfg(feature = "png")]
 use anyrender_vello_cpu::VelloCpuImageRenderer;
 #[cfg(feature = "png")]
 use blitz_html::HtmlDocument;
 #[cfg(feature = "png")]
-use blitz_paint::paint_scene;
+use kurbo::{Affine, Rect, Stroke};
+#[cfg(feature = "png")]
+use peniko::{
+    Blob, Color, Extend, Fill, ImageAlphaType, ImageBrush, ImageData, ImageFormat, ImageQuality,
+};
+#[cfg(feature = "png")]
+use std::time::Instant;
 
 /// Render a Blitz document to PNG bytes.
+///
+/// Elements with `backdrop-filter: blur()` are composited over a blurred
+/// copy of what they cover; the document is restyled for the extra paint
+/// passes this takes, which is why it is borrowed mutably. The blur checks
+/// the [`Config::budget`] deadline, counted from `started`, as it goes.
 #[cfg(feature = "png")]
-pub fn render_to_png(document: &HtmlDocument, config: &Config) -> Result<Vec<u8>> {
-    let scale = config.scale as f64;
-    let width = config.width;
-    let height = if config.auto_height {
-        get_content_height(document).unwrap_or(config.height)
-    } else {
-        config.height
+pub fn render_to_png(
+    document: &mut HtmlDocument,
+    config: &Config,
+    started: Instant,
+) -> Result<Vec<u8>> {
+    let (buffer, width, height) = render_png_buffer(document, config, started)?;
+
+    // Vello paints premultiplied colors, but PNG stores straight alpha; the
+    // colors themselves are already sRGB-encoded, as PNG assumes
+    let buffer: Vec<u8> = buffer.chunks_exact(4).flat_map(unpremultiply).collect();
+
+    // Encode to PNG
+    match config.png_color_type {
+        PngColorType::Rgba8 => encode_png(&buffer, width, height, config.dpi),
+        PngColorType::Indexed { dither } => {
+            encode_indexed_png(&buffer, width, height, dither, config.dpi)
+        }
+    }
+}
+
+/// Rasterize a Blitz document as PNG output holds it, before encoding:
+/// painted over the configured background image, with blurred backdrops
+/// and the configured overlay, cropped to the captured element and by the
+/// crop margins, and rotated.
+///
+/// Returns the premultiplied RGBA8 buffer along with its width and height
+/// in device pixels.
+#[cfg(feature = "png")]
+pub(crate) fn render_png_buffer(
+    document: &mut HtmlDocument,
+    config: &Config,
+    started: Instant,
+) -> Result<(Vec<u8>, u32, u32)> {
+    let capture = capture_rect(document, config)?;
+    let overhang = overhang_rect(document, config, page_height(document, config));
+    if let Some(rect) = overhang {
+        // Scroll content overhanging the left and top edges into view
+        document.set_viewport_scroll(blitz_dom::Point {
+            x: rect.x as f64,
+            y: rect.y as f64,
+        });
+    }
+    let paint_config = match (capture, overhang) {
+        (Some(rect), _) => covering(document, config, rect),
+        (None, Some(rect)) => Config {
+            width: rect.width as u32,
+            height: rect.height as u32,
+            auto_height: false,
+            ..config.clone()
+        },
+        (None, None) => config.clone(),
     };
+    // The raster can grow past the output size the budget was checked against
+    if let Some(budget) = &config.budget {
+        budget.check_output(page_height(document, &paint_config), &paint_config)?;
+    }
+    let background = background::decode(config)?;
+    let (mut buffer, mut render_width, mut render_height) =
+        paint_to_rgba(document, &paint_config, background.as_ref());
+    super::backdrop::blur_backdrops(
+        document,
+        &paint_config,
+        background.as_ref(),
+        &mut buffer,
+        render_width,
+        render_height,
+        started,
+    )?;
+    draw_overlay(&mut buffer, render_width, render_height, config);
+
+    if let Some(rect) = capture {
+        let (x, y, width, height) = rect.device_pixels(config.device_scale());
+        let margins = [
+            y,
+            render_width.saturating_sub(x + width),
+            render_height.saturating_sub(y + height),
+            x,
+        ];
+        (buffer, render_width, render_height) =
+            crop_buffer(&buffer, render_width, render_height, margins)?;
+    }
+    if config.crop_margins != [0; 4] {
+        (buffer, render_width, render_height) =
+            crop_buffer(&buffer, render_width, render_height, config.crop_margins)?;
+    }
+    Ok(rotate_buffer(
+        buffer,
+        render_width,
+        render_height,
+        config.rotation,
+    ))
+}
+
+/// `config` with its raster grown, if need be, to hold all of `rect`.
+///
+/// Only the painted area grows; the document keeps its layout.
+#[cfg(feature = "png")]
+fn covering(document: &HtmlDocument, config: &Config, rect: CaptureRect) -> Config {
+    Config {
+        width: config.width.max((rect.x + rect.width).ceil() as u32),
+        height: page_height(document, config).max((rect.y + rect.height).ceil() as u32),
+        auto_height: false,
+        ..config.clone()
+    }
+}
+
+/// Height of the page in CSS pixels, following the content with `auto_height`.
+#[cfg(feature = "png")]
+fn page_height(document: &HtmlDocument, config: &Config) -> u32 {
+    match config.auto_height {
+        true => get_content_height(document).unwrap_or(config.height),
+        false => config.height,
+    }
+}
 
-    // Calculate scaled dimensions for the output buffer
-    let render_width = (width as f64 * scale) as u32;
-    let render_height = (height as f64 * scale) as u32;
+/// Crop `[top, right, bottom, left]` pixels off the edges of an RGBA8 buffer.
+#[cfg(feature = "png")]
+fn crop_buffer(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    [top, right, bottom, left]: [u32; 4],
+) -> Result<(Vec<u8>, u32, u32)> {
+    let cropped_width = width.saturating_sub(left.saturating_add(right));
+    let cropped_height = height.saturating_sub(top.saturating_add(bottom));
+    if cropped_width == 0 || cropped_height == 0 {
+        return Err(Error::InvalidConfig(format!(
+            "crop margins of {}/{}/{}/{} pixels leave nothing of a {}x{} image",
+            top, right, bottom, left, width, height
+        )));
+    }
+
+    let stride = width as usize * 4;
+    let row_start = left as usize * 4;
+    let row_len = cropped_width as usize * 4;
+    let cropped = buffer
+        .chunks_exact(stride)
+        .skip(top as usize)
+        .take(cropped_height as usize)
+        .flat_map(|row| &row[row_start..row_start + row_len])
+        .copied()
+        .collect();
+
+    Ok((cropped, cropped_width, cropped_height))
+}
+
+/// Rotate an RGBA8 buffer clockwise, returning it with its new width and height.
+#[cfg(feature = "png")]
+pub(crate) fn rotate_buffer(
+    buffer: Vec<u8>,
+    width: u32,
+    height: u32,
+    rotation: Rotation,
+) -> (Vec<u8>, u32, u32) {
+    if rotation == Rotation::None {
+        return (buffer, width, height);
+    }
+
+    let (rotated_width, rotated_height) = rotation.rotated_size(width, height);
+    let mut rotated = vec![0; buffer.len()];
+    for (i, pixel) in buffer.chunks_exact(4).enumerate() {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        let (rx, ry) = match rotation {
+            Rotation::None => (x, y),
+            Rotation::Deg90 => (height - 1 - y, x),
+            Rotation::Deg180 => (width - 1 - x, height - 1 - y),
+            Rotation::Deg270 => (y, width - 1 - x),
+        };
+        let j = ((ry * rotated_width + rx) * 4) as usize;
+        rotated[j..j + 4].copy_from_slice(pixel);
+    }
+
+    (rotated, rotated_width, rotated_height)
+}
+
+/// Rasterize a Blitz document over `background`, the decoded configured
+/// background image, to a premultiplied RGBA8 pixel buffer.
+///
+/// Returns the buffer along with its width and height in device pixels. The
+/// configured overlay has been drawn on top and the configured rotation
+/// applied.
+#[cfg(any(feature = "gif", feature = "tiff"))]
+pub(crate) fn render_to_rgba(
+    document: &HtmlDocument,
+    config: &Config,
+    background: Option<&DecodedBackground>,
+) -> (Vec<u8>, u32, u32) {
+    let (mut buffer, render_width, render_height) = paint_to_rgba(document, config, background);
+    draw_overlay(&mut buffer, render_width, render_height, config);
+    rotate_buffer(buffer, render_width, render_height, config.rotation)
+}
+
+/// Rasterize a Blitz document over `background`, the decoded configured
+/// background image if it is to be drawn, without the configured overlay.
+#[cfg(feature = "png")]
+pub(crate) fn paint_to_rgba(
+    document: &HtmlDocument,
+    config: &Config,
+    background: Option<&DecodedBackground>,
+) -> (Vec<u8>, u32, u32) {
+    let scale = config.device_scale() as f64;
+    let (render_width, render_height) = output_size(document, config);
 
     // Render to pixel buffer
     // Note: Background is rendered by the HTML body element's background style
     let buffer = render_to_buffer::<VelloCpuImageRenderer, _>(
         |scene| {
-            // Render the document
-            paint_scene(scene, document.as_ref(), scale, render_width, render_height);
+            // Render the document, over the background image if there is one
+            // (`paint_document` keeps Blitz from resetting the scene first)
+            match background {
+                Some(background) => {
+                    let pixelated = background::is_pixelated(document.root_element());
+                    paint_background_image(
+                        scene,
+                        background,
+                        pixelated,
+                        scale,
+                        render_width,
+                        render_height,
+                    );
+                    paint_document(scene, document, config, 0.0, 0.0);
+                }
+                None => paint_scene(scene, document.as_ref(), scale, render_width, render_height),
+            }
+            if config.debug_layout {
+                paint_layout_boxes(scene, document, scale);
+            }
         },
         render_width,
         render_height,
     );
 
-    // Encode to PNG
-    encode_png(&buffer, render_width, render_height)
+    (buffer, render_width, render_height)
 }
 
-/// Encode RGBA buffer to PNG bytes.
+/// Rasterize the `width` x `height` device pixel region of a Blitz document
+/// with its top left corner at `(x, y)`, without the configured background
+/// image, overlay or layout boxes.
+#[cfg(all(feature = "png", feature = "pdf"))]
+pub(crate) fn paint_region_to_rgba(
+    document: &HtmlDocument,
+    config: &Config,
+    (x, y): (u32, u32),
+    (width, height): (u32, u32),
+) -> Vec<u8> {
+    render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| {
+            paint_document(scene, document, config, -(x as f64), -(y as f64));
+        },
+        width,
+        height,
+    )
+}
+
+/// Outline the document's layout boxes for [`Config::debug_layout`], one
+/// device pixel wide.
 #[cfg(feature = "png")]
-fn encode_png(buffer: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+fn paint_layout_boxes(scene: &mut impl PaintScene, document: &HtmlDocument, scale: f64) {
+    let scroll = document.as_ref().viewport_scroll();
+    for layout_box in layout_boxes(document.as_ref()) {
+        // Snap the edges to device pixels, and inset the line by half its
+        // width so it covers whole pixels inside the box
+        let edge = |position: f32, scroll: f64| ((position as f64 - scroll) * scale).round();
+        let left = edge(layout_box.x, scroll.x) + 0.5;
+        let top = edge(layout_box.y, scroll.y) + 0.5;
+        let right = edge(layout_box.x + layout_box.width, scroll.x) - 0.5;
+        let bottom = edge(layout_box.y + layout_box.height, scroll.y) - 0.5;
+        if right < left || bottom < top {
+            continue;
+        }
+        let [r, g, b] = layout_box.color;
+        scene.stroke(
+            &Stroke::new(1.0),
+            Affine::IDENTITY,
+            Color::from_rgb8(r, g, b),
+            None,
+            &Rect::new(left, top, right, bottom),
+        );
+    }
+}
+
+/// Paint a background image onto a `width` x `height` device pixel scene,
+/// scaled with nearest-neighbor sampling if `pixelated`.
+#[cfg(feature = "png")]
+fn paint_background_image(
+    scene: &mut impl PaintScene,
+    background: &DecodedBackground,
+    pixelated: bool,
+    scale: f64,
+    width: u32,
+    height: u32,
+) {
+    let page_width = (width as f64 / scale) as f32;
+    let page_height = (height as f64 / scale) as f32;
+    let placement = background.placement(page_width, page_height);
+    let (image_width, image_height) = (background.width as f64, background.height as f64);
+    let brush = ImageBrush::new(ImageData {
+        data: Blob::new(background.pixels.clone()),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: background.width,
+        height: background.height,
+    });
+    // Bicubic filtering keeps flat colors exact
+    let quality = match pixelated {
+        true => ImageQuality::Low,
+        false => ImageQuality::High,
+    };
+
+    match placement {
+        Some(placement) => {
+            let transform =
+                Affine::translate((placement.x as f64 * scale, placement.y as f64 * scale))
+                    * Affine::scale_non_uniform(
+                        placement.width as f64 * scale / image_width,
+                        placement.height as f64 * scale / image_height,
+                    );
+            // Pad so filtering doesn't fade the edges into transparency
+            let brush = brush.with_extend(Extend::Pad).with_quality(quality);
+            scene.draw_image(brush.as_ref(), transform);
+        }
+        None => {
+            // Tiles are the natural size, even if the image was downscaled
+            let brush = brush.with_extend(Extend::Repeat).with_quality(quality);
+            let tile_scale = Affine::scale_non_uniform(
+                background.natural_width as f64 * scale / image_width,
+                background.natural_height as f64 * scale / image_height,
+            );
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                brush.as_ref(),
+                Some(tile_scale),
+                &Rect::new(0.0, 0.0, width as f64, height as f64),
+            );
+        }
+    }
+}
+
+/// Draw the configured overlay, if any, on top of a rendered buffer.
+#[cfg(feature = "png")]
+fn draw_overlay(buffer: &mut [u8], width: u32, height: u32, config: &Config) {
+    if let Some(overlay) = &config.overlay {
+        overlay.draw(OverlayTarget::Raster {
+            pixels: buffer,
+            width,
+            height,
+        });
+    }
+}
+
+/// Size of the rendered raster in device pixels.
+#[cfg(feature = "png")]
+pub(crate) fn output_size(document: &HtmlDocument, config: &Config) -> (u32, u32) {
+    let scale = config.device_scale() as f64;
+    (
+        (config.width as f64 * scale) as u32,
+        (page_height(document, config) as f64 * scale) as u32,
+    )
+}
+
+/// Encode RGBA buffer to PNG bytes, recording `dpi` if given.
+#[cfg(feature = "png")]
+pub(crate) fn encode_png(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    dpi: Option<f32>,
+) -> Result<Vec<u8>> {
     let mut output = Vec::new();
 
     {
@@ -52,6 +415,7 @@ fn encode_png(buffer: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
         encoder.set_color(png::ColorType::Rgba);
         encoder.set_depth(png::BitDepth::Eight);
         encoder.set_compression(png::Compression::Fast);
+        encoder.set_pixel_dims(dpi.map(pixel_dims));
 
         let mut writer = encoder
             .write_header()
@@ -65,15 +429,168 @@ fn encode_png(buffer: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
     Ok(output)
 }
 
+/// Convert a premultiplied RGBA8 pixel to straight alpha.
+#[cfg(feature = "png")]
+pub(crate) fn unpremultiply(pixel: &[u8]) -> [u8; 4] {
+    let alpha = pixel[3];
+    if alpha == 0 {
+        return [0; 4];
+    }
+    let channel = |c: u8| ((c as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8;
+    [
+        channel(pixel[0]),
+        channel(pixel[1]),
+        channel(pixel[2]),
+        alpha,
+    ]
+}
+
+/// The `pHYs` pixel density for `dpi`, which PNG stores per meter.
+#[cfg(feature = "png")]
+fn pixel_dims(dpi: f32) -> png::PixelDimensions {
+    let per_meter = (dpi / 0.0254).round() as u32;
+    png::PixelDimensions {
+        xppu: per_meter,
+        yppu: per_meter,
+        unit: png::Unit::Meter,
+    }
+}
+
+/// Encode an RGBA buffer as an 8-bit indexed PNG.
+///
+/// Buffers with at most 256 distinct colors keep them exactly; others are
+/// quantized to a 256-color palette with NeuQuant, dithered if `dither` is set.
+/// `dpi` is recorded if given.
+#[cfg(feature = "png")]
+fn encode_indexed_png(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    dither: bool,
+    dpi: Option<f32>,
+) -> Result<Vec<u8>> {
+    let (palette, indices) = match exact_palette(buffer) {
+        Some(exact) => exact,
+        None => quantize(buffer, width, dither),
+    };
+
+    let mut output = Vec::new();
+
+    {
+        let mut encoder = png::Encoder::new(&mut output, width, height);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Fast);
+        encoder.set_pixel_dims(dpi.map(pixel_dims));
+        encoder.set_palette(
+            palette
+                .iter()
+                .flat_map(|color| [color[0], color[1], color[2]])
+                .collect::<Vec<_>>(),
+        );
+        if palette.iter().any(|color| color[3] != 255) {
+            encoder.set_trns(palette.iter().map(|color| color[3]).collect::<Vec<_>>());
+        }
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| Error::PngEncode(e.to_string()))?;
+
+        writer
+            .write_image_data(&indices)
+            .map_err(|e| Error::PngEncode(e.to_string()))?;
+    }
+
+    Ok(output)
+}
+
+/// The palette and per-pixel indices of a buffer with at most 256 colors.
+#[cfg(feature = "png")]
+fn exact_palette(buffer: &[u8]) -> Option<(Vec<[u8; 4]>, Vec<u8>)> {
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut lookup = std::collections::HashMap::new();
+    let mut indices = Vec::with_capacity(buffer.len() / 4);
+    for pixel in buffer.chunks_exact(4) {
+        let color = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        let index = match lookup.get(&color) {
+            Some(&index) => index,
+            None => {
+                let index = u8::try_from(palette.len()).ok()?;
+                palette.push(color);
+                lookup.insert(color, index);
+                index
+            }
+        };
+        indices.push(index);
+    }
+    Some((palette, indices))
+}
+
+/// Quantize a buffer to a 256-color palette, returning it with per-pixel
+/// indices.
+#[cfg(feature = "png")]
+fn quantize(buffer: &[u8], width: u32, dither: bool) -> (Vec<[u8; 4]>, Vec<u8>) {
+    /// NeuQuant sampling factor (1 = best, 30 = fastest).
+    const SAMPLE_FACTOR: i32 = 10;
+
+    let quantizer = color_quant::NeuQuant::new(SAMPLE_FACTOR, 256, buffer);
+    let palette: Vec<[u8; 4]> = quantizer
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|color| [color[0], color[1], color[2], color[3]])
+        .collect();
+
+    if !dither {
+        let indices = buffer
+            .chunks_exact(4)
+            .map(|pixel| quantizer.index_of(pixel) as u8)
+            .collect();
+        return (palette, indices);
+    }
+
+    // Floyd-Steinberg: push each pixel's error onto its unvisited neighbors
+    let width = width as usize;
+    let mut pixels: Vec<f32> = buffer.iter().map(|&channel| channel as f32).collect();
+    let mut indices = Vec::with_capacity(buffer.len() / 4);
+    for i in 0..buffer.len() / 4 {
+        let (x, y) = (i % width, i / width);
+        let pixel: [u8; 4] =
+            std::array::from_fn(|c| pixels[i * 4 + c].round().clamp(0.0, 255.0) as u8);
+        let index = quantizer.index_of(&pixel);
+        indices.push(index as u8);
+
+        let chosen = palette[index];
+        for c in 0..4 {
+            let error = pixels[i * 4 + c] - chosen[c] as f32;
+            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                let Some(nx) = x.checked_add_signed(dx).filter(|&nx| nx < width) else {
+                    return;
+                };
+                let j = ((y + dy) * width + nx) * 4 + c;
+                if j < pixels.len() {
+                    pixels[j] += error * weight;
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+    (palette, indices)
+}
+
 /// Get the actual content height from the document layout.
 #[cfg(feature = "png")]
-fn get_content_height(document: &HtmlDocument) -> Option<u32> {
-    let doc = document.as_ref();
-    let root = doc.root_element();
-    Some(root.final_layout.size.height as u32)
+pub(crate) fn get_content_height(document: &HtmlDocument) -> Option<u32> {
+    Some(super::content_height(document.as_ref()).ceil() as u32)
 }
 
 #[cfg(not(feature = "png"))]
-pub fn render_to_png(_document: &blitz_html::HtmlDocument, _config: &Config) -> Result<Vec<u8>> {
+pub fn render_to_png(
+    _document: &mut blitz_html::HtmlDocument,
+    _config: &Config,
+    _started: std::time::Instant,
+) -> Result<Vec<u8>> {
     Err(Error::FormatNotEnabled("png"))
 }
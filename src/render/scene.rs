@@ -0,0 +1,157 @@
+//! Painting documents into caller-provided Anyrender scenes.
+
+#[cfg(feature = "png")]
+use super::paint_scene;
+#[cfg(feature = "png")]
+use anyrender::{Glyph, NormalizedCoord, PaintRef, PaintScene};
+#[cfg(feature = "png")]
+use blitz_html::HtmlDocument;
+#[cfg(feature = "png")]
+use kurbo::{Affine, Rect, Shape, Stroke};
+#[cfg(feature = "png")]
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+
+#[cfg(feature = "png")]
+use crate::config::Config;
+#[cfg(feature = "png")]
+use crate::render::png::output_size;
+
+/// Paint a resolved document into `scene` with its top-left corner at
+/// `(x, y)` in device pixels, clipped to the output size.
+///
+/// Returns the painted width and height in device pixels.
+#[cfg(feature = "png")]
+pub(crate) fn paint_document(
+    scene: &mut impl PaintScene,
+    document: &HtmlDocument,
+    config: &Config,
+    x: f64,
+    y: f64,
+) -> (u32, u32) {
+    let (width, height) = output_size(document, config);
+    let offset = Affine::translate((x, y));
+
+    scene.push_clip_layer(offset, &Rect::new(0.0, 0.0, width as f64, height as f64));
+    let mut offset_scene = OffsetScene {
+        inner: &mut *scene,
+        offset,
+    };
+    paint_scene(
+        &mut offset_scene,
+        document.as_ref(),
+        config.device_scale() as f64,
+        width,
+        height,
+    );
+    scene.pop_layer();
+
+    (width, height)
+}
+
+/// A scene adapter that translates everything painted into it.
+///
+/// Blitz resets the scene before painting; the adapter ignores that so the
+/// caller's existing content survives.
+#[cfg(feature = "png")]
+struct OffsetScene<'s, S> {
+    inner: &'s mut S,
+    offset: Affine,
+}
+
+#[cfg(feature = "png")]
+impl<S: PaintScene> PaintScene for OffsetScene<'_, S> {
+    fn reset(&mut self) {}
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        #[allow(deprecated, reason = "forwarding the trait method as-is")]
+        self.inner
+            .push_layer(blend, alpha, self.offset * transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        self.inner.push_clip_layer(self.offset * transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.inner.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.inner.stroke(
+            style,
+            self.offset * transform,
+            brush,
+            brush_transform,
+            shape,
+        );
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.inner.fill(
+            style,
+            self.offset * transform,
+            brush,
+            brush_transform,
+            shape,
+        );
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        self.inner.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            self.offset * transform,
+            glyph_transform,
+            glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.inner
+            .draw_box_shadow(self.offset * transform, rect, brush, radius, std_dev);
+    }
+}
@@ -0,0 +1,694 @@
+//! SVG rendering implementation.
+//!
+//! This module implements Anyrender's `PaintScene` on top of an SVG string
+//! writer, so Blitz paints the document exactly as it does for PNG output but
+//! the result stays vector.
+//!
+//! Supports:
+//! - Filled and stroked shapes with solid colors
+//! - Linear and radial gradients (sweep gradients fall back to a solid color)
+//! - Clip layers, layer opacity and blend modes
+//! - Text as glyph outlines, deduplicated through `<defs>`
+//! - Blurred box shadows via `feGaussianBlur`
+//!
+//! Raster images are not embedded.
+
+use crate::config::Config;
+#[cfg(feature = "svg")]
+use crate::config::Rotation;
+#[cfg(not(feature = "svg"))]
+use crate::error::Error;
+use crate::error::Result;
+
+#[cfg(feature = "svg")]
+use super::paint_scene;
+#[cfg(feature = "svg")]
+use anyrender::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene};
+#[cfg(feature = "svg")]
+use blitz_html::HtmlDocument;
+#[cfg(feature = "svg")]
+use kurbo::{Affine, BezPath, Cap, Join, PathEl, Rect, Shape, Stroke};
+#[cfg(feature = "svg")]
+use peniko::color::{AlphaColor, Srgb};
+#[cfg(feature = "svg")]
+use peniko::{BlendMode, Color, Extend, Fill, FontData, GradientKind, Mix, StyleRef};
+#[cfg(feature = "svg")]
+use skrifa::instance::{LocationRef, Size};
+#[cfg(feature = "svg")]
+use skrifa::outline::{DrawSettings, OutlinePen};
+#[cfg(feature = "svg")]
+use skrifa::raw::types::F2Dot14;
+#[cfg(feature = "svg")]
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+#[cfg(feature = "svg")]
+use std::collections::HashMap;
+#[cfg(feature = "svg")]
+use std::fmt::Write;
+
+/// Tolerance used when flattening shapes (circles, rounded rects) to paths.
+#[cfg(feature = "svg")]
+const PATH_TOLERANCE: f64 = 0.1;
+
+/// Render a Blitz document to an SVG string.
+///
+/// The SVG is sized in device pixels (CSS pixels multiplied by the scale
+/// factor), with a matching `viewBox`.
+#[cfg(feature = "svg")]
+pub fn render_to_svg(document: &HtmlDocument, config: &Config) -> Result<String> {
+    let scale = config.device_scale() as f64;
+    let height = if config.auto_height {
+        get_content_height(document).unwrap_or(config.height)
+    } else {
+        config.height
+    };
+
+    let render_width = (config.width as f64 * scale) as u32;
+    let render_height = (height as f64 * scale) as u32;
+
+    let mut scene = SvgScene::default();
+    paint_scene(
+        &mut scene,
+        document.as_ref(),
+        scale,
+        render_width,
+        render_height,
+    );
+
+    Ok(scene.finish(render_width, render_height, config.rotation))
+}
+
+/// Get the actual content height from the document layout.
+#[cfg(feature = "svg")]
+fn get_content_height(document: &HtmlDocument) -> Option<u32> {
+    Some(super::content_height(document.as_ref()).ceil() as u32)
+}
+
+/// Key identifying a glyph outline at a given size and variation.
+#[cfg(feature = "svg")]
+#[derive(Hash, PartialEq, Eq)]
+struct GlyphKey {
+    font_id: u64,
+    font_index: u32,
+    glyph_id: u32,
+    size_bits: u32,
+    coords: Vec<NormalizedCoord>,
+}
+
+/// Buffer lengths recorded when a layer group was opened.
+#[cfg(feature = "svg")]
+struct OpenLayer {
+    /// Length of `defs` before the layer's clip path was added.
+    defs_start: usize,
+    /// Length of `body` before the opening `<g>` tag.
+    body_start: usize,
+    /// Length of `body` after the opening `<g>` tag.
+    content_start: usize,
+}
+
+/// A `PaintScene` that records drawing commands as SVG markup.
+#[cfg(feature = "svg")]
+#[derive(Default)]
+struct SvgScene {
+    /// Shared definitions: gradients, clip paths, filters and glyphs.
+    defs: String,
+    /// Drawing content.
+    body: String,
+    /// Open layer groups (see [`OpenLayer`]).
+    layers: Vec<OpenLayer>,
+    /// Counter for generating unique element ids.
+    next_id: usize,
+    /// Glyph outline ids by font, glyph and size (`None` for empty glyphs).
+    glyphs: HashMap<GlyphKey, Option<String>>,
+}
+
+#[cfg(feature = "svg")]
+impl SvgScene {
+    /// Wrap the recorded content in an `<svg>` root element, rotating a
+    /// `width` x `height` drawing by `rotation`.
+    fn finish(mut self, width: u32, height: u32, rotation: Rotation) -> String {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+
+        let (svg_width, svg_height) = rotation.rotated_size(width, height);
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = svg_width,
+            h = svg_height
+        );
+        if !self.defs.is_empty() {
+            svg.push_str("<defs>");
+            svg.push_str(&self.defs);
+            svg.push_str("</defs>");
+        }
+        if rotation == Rotation::None {
+            svg.push_str(&self.body);
+        } else {
+            let [a, b, c, d, e, f] = rotation.transform(width as f32, height as f32);
+            let _ = write!(
+                svg,
+                r#"<g transform="matrix({} {} {} {} {} {})">"#,
+                a, b, c, d, e, f
+            );
+            svg.push_str(&self.body);
+            svg.push_str("</g>");
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    /// Write a paint as `fill`/`stroke` attributes (`attr` is either name).
+    ///
+    /// Returns `false` if the paint can't be represented and nothing should
+    /// be drawn.
+    fn paint_attrs(
+        &mut self,
+        out: &mut String,
+        attr: &str,
+        paint: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        alpha: f32,
+    ) -> bool {
+        match paint {
+            Paint::Solid(color) => {
+                if color.components[3] * alpha <= 0.0 {
+                    return false;
+                }
+                write_color(out, attr, color, alpha);
+                true
+            }
+            Paint::Gradient(gradient) => {
+                let stops: Vec<(f32, AlphaColor<Srgb>)> = gradient
+                    .stops
+                    .iter()
+                    .map(|stop| (stop.offset, stop.color.to_alpha_color::<Srgb>()))
+                    .collect();
+                let Some(&(_, first)) = stops.first() else {
+                    return false;
+                };
+
+                let id = self.new_id("g");
+                let mut def = String::new();
+                match gradient.kind {
+                    GradientKind::Linear(line) => {
+                        let _ = write!(
+                            def,
+                            r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}""#,
+                            id,
+                            num(line.start.x),
+                            num(line.start.y),
+                            num(line.end.x),
+                            num(line.end.y)
+                        );
+                    }
+                    GradientKind::Radial(radial) => {
+                        let _ = write!(
+                            def,
+                            r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}" fx="{}" fy="{}" fr="{}""#,
+                            id,
+                            num(radial.end_center.x),
+                            num(radial.end_center.y),
+                            num(radial.end_radius as f64),
+                            num(radial.start_center.x),
+                            num(radial.start_center.y),
+                            num(radial.start_radius as f64)
+                        );
+                    }
+                    GradientKind::Sweep(_) => {
+                        // SVG has no conic gradients
+                        write_color(out, attr, first, alpha);
+                        return true;
+                    }
+                }
+
+                let spread = match gradient.extend {
+                    Extend::Pad => "pad",
+                    Extend::Repeat => "repeat",
+                    Extend::Reflect => "reflect",
+                };
+                let _ = write!(def, r#" spreadMethod="{}""#, spread);
+                if let Some(transform) = brush_transform {
+                    let _ = write!(def, r#" gradientTransform="{}""#, matrix(transform));
+                }
+                def.push('>');
+                for (offset, color) in &stops {
+                    let [r, g, b, a] = color.to_rgba8().to_u8_array();
+                    let _ = write!(
+                        def,
+                        r##"<stop offset="{}" stop-color="#{:02x}{:02x}{:02x}""##,
+                        num(*offset as f64),
+                        r,
+                        g,
+                        b
+                    );
+                    if a < 255 {
+                        let _ = write!(def, r#" stop-opacity="{}""#, num(a as f64 / 255.0));
+                    }
+                    def.push_str("/>");
+                }
+                def.push_str(match gradient.kind {
+                    GradientKind::Linear(_) => "</linearGradient>",
+                    _ => "</radialGradient>",
+                });
+                self.defs.push_str(&def);
+
+                let _ = write!(out, r#" {}="url(#{})""#, attr, id);
+                if alpha < 1.0 {
+                    let _ = write!(out, r#" {}-opacity="{}""#, attr, num(alpha as f64));
+                }
+                true
+            }
+            // Images are not embedded and custom paints are backend-specific
+            Paint::Image(_) | Paint::Custom(_) => false,
+        }
+    }
+
+    /// Get (or create) the `<defs>` id of a glyph outline.
+    fn glyph_id(
+        &mut self,
+        font: &FontData,
+        font_size: f32,
+        normalized_coords: &[NormalizedCoord],
+        glyph_id: u32,
+    ) -> Option<String> {
+        let key = GlyphKey {
+            font_id: font.data.id(),
+            font_index: font.index,
+            glyph_id,
+            size_bits: font_size.to_bits(),
+            coords: normalized_coords.to_vec(),
+        };
+        if let Some(id) = self.glyphs.get(&key) {
+            return id.clone();
+        }
+
+        let outline = glyph_outline(font, font_size, normalized_coords, glyph_id);
+        let id = outline.map(|path| {
+            let id = self.new_id("t");
+            let _ = write!(self.defs, r#"<path id="{}" d="{}"/>"#, id, path_data(&path));
+            id
+        });
+        self.glyphs.insert(key, id.clone());
+        id
+    }
+}
+
+#[cfg(feature = "svg")]
+impl PaintScene for SvgScene {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let defs_start = self.defs.len();
+        let body_start = self.body.len();
+        let clip_id = self.new_id("c");
+        let _ = write!(
+            self.defs,
+            r#"<clipPath id="{}"><path d="{}"{}/></clipPath>"#,
+            clip_id,
+            path_data(&clip.to_path(PATH_TOLERANCE)),
+            transform_attr(transform)
+        );
+
+        let _ = write!(self.body, r#"<g clip-path="url(#{})""#, clip_id);
+        if alpha < 1.0 {
+            let _ = write!(self.body, r#" opacity="{}""#, num(alpha as f64));
+        }
+        if let Some(mode) = blend_mode_name(blend.into().mix) {
+            let _ = write!(self.body, r#" style="mix-blend-mode:{}""#, mode);
+        }
+        self.body.push('>');
+        self.layers.push(OpenLayer {
+            defs_start,
+            body_start,
+            content_start: self.body.len(),
+        });
+    }
+
+    fn pop_layer(&mut self) {
+        let Some(layer) = self.layers.pop() else {
+            return;
+        };
+        if self.body.len() == layer.content_start {
+            // Nothing was drawn in the layer: drop the group and its clip path
+            self.defs.truncate(layer.defs_start);
+            self.body.truncate(layer.body_start);
+        } else {
+            self.body.push_str("</g>");
+        }
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let mut attrs = String::from(r#" fill="none""#);
+        if !self.paint_attrs(&mut attrs, "stroke", brush.into(), brush_transform, 1.0) {
+            return;
+        }
+        write_stroke_style(&mut attrs, style);
+
+        let _ = write!(
+            self.body,
+            r#"<path d="{}"{}{}/>"#,
+            path_data(&shape.to_path(PATH_TOLERANCE)),
+            transform_attr(transform),
+            attrs
+        );
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        // Zero-area shapes (such as unset border edges) can't paint anything
+        let bounds = shape.bounding_box();
+        if bounds.width() <= 0.0 || bounds.height() <= 0.0 {
+            return;
+        }
+
+        let mut attrs = String::new();
+        if !self.paint_attrs(&mut attrs, "fill", brush.into(), brush_transform, 1.0) {
+            return;
+        }
+        if style == Fill::EvenOdd {
+            attrs.push_str(r#" fill-rule="evenodd""#);
+        }
+
+        let _ = write!(
+            self.body,
+            r#"<path d="{}"{}{}/>"#,
+            path_data(&shape.to_path(PATH_TOLERANCE)),
+            transform_attr(transform),
+            attrs
+        );
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        _hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let mut attrs = String::new();
+        let paint_attr = match style.into() {
+            StyleRef::Fill(fill) => {
+                if fill == Fill::EvenOdd {
+                    attrs.push_str(r#" fill-rule="evenodd""#);
+                }
+                "fill"
+            }
+            StyleRef::Stroke(stroke) => {
+                attrs.push_str(r#" fill="none""#);
+                write_stroke_style(&mut attrs, stroke);
+                "stroke"
+            }
+        };
+        // Glyph outlines live in their own coordinate space, so gradients
+        // are resolved against the run rather than each glyph
+        if !self.paint_attrs(&mut attrs, paint_attr, brush.into(), None, brush_alpha) {
+            return;
+        }
+
+        let glyph_transform = glyph_transform.unwrap_or(Affine::IDENTITY);
+        let mut uses = String::new();
+        for glyph in glyphs {
+            let Some(id) = self.glyph_id(font, font_size, normalized_coords, glyph.id) else {
+                continue;
+            };
+            // Font outlines are y-up; flip them into the y-down page space
+            let placement = transform
+                * Affine::translate((glyph.x as f64, glyph.y as f64))
+                * glyph_transform
+                * Affine::FLIP_Y;
+            let _ = write!(
+                uses,
+                r##"<use xlink:href="#{}"{}/>"##,
+                id,
+                transform_attr(placement)
+            );
+        }
+
+        if !uses.is_empty() {
+            let _ = write!(self.body, "<g{}>{}</g>", attrs, uses);
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        // Extend the filter region so the blur isn't cut off at the box edge
+        let region = rect.inflate(3.0 * std_dev, 3.0 * std_dev);
+        let filter_id = self.new_id("f");
+        let _ = write!(
+            self.defs,
+            r#"<filter id="{}" filterUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}"><feGaussianBlur stdDeviation="{}"/></filter>"#,
+            filter_id,
+            num(region.x0),
+            num(region.y0),
+            num(region.width()),
+            num(region.height()),
+            num(std_dev)
+        );
+
+        let mut attrs = String::new();
+        write_color(&mut attrs, "fill", brush, 1.0);
+        let _ = write!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" filter="url(#{})"{}{}/>"#,
+            num(rect.x0),
+            num(rect.y0),
+            num(rect.width()),
+            num(rect.height()),
+            num(radius),
+            filter_id,
+            transform_attr(transform),
+            attrs
+        );
+    }
+}
+
+/// Collects a skrifa glyph outline into a kurbo path.
+#[cfg(feature = "svg")]
+struct PathPen(BezPath);
+
+#[cfg(feature = "svg")]
+impl OutlinePen for PathPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to((x as f64, y as f64));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to((x as f64, y as f64));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.0
+            .quad_to((cx0 as f64, cy0 as f64), (x as f64, y as f64));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.0.curve_to(
+            (cx0 as f64, cy0 as f64),
+            (cx1 as f64, cy1 as f64),
+            (x as f64, y as f64),
+        );
+    }
+
+    fn close(&mut self) {
+        self.0.close_path();
+    }
+}
+
+/// Outline a glyph at `font_size` pixels, in y-up font space.
+///
+/// Returns `None` for glyphs without an outline (such as spaces).
+#[cfg(feature = "svg")]
+fn glyph_outline(
+    font: &FontData,
+    font_size: f32,
+    normalized_coords: &[NormalizedCoord],
+    glyph_id: u32,
+) -> Option<BezPath> {
+    let font_ref = FontRef::from_index(font.data.as_ref(), font.index).ok()?;
+    let outline = font_ref.outline_glyphs().get(GlyphId::new(glyph_id))?;
+
+    let coords: Vec<F2Dot14> = normalized_coords
+        .iter()
+        .map(|&coord| F2Dot14::from_bits(coord))
+        .collect();
+    let settings = DrawSettings::unhinted(Size::new(font_size), LocationRef::new(&coords));
+
+    let mut pen = PathPen(BezPath::new());
+    outline.draw(settings, &mut pen).ok()?;
+    (!pen.0.elements().is_empty()).then_some(pen.0)
+}
+
+/// Write a color as a paint attribute plus its opacity, if not opaque.
+#[cfg(feature = "svg")]
+fn write_color(out: &mut String, attr: &str, color: AlphaColor<Srgb>, alpha: f32) {
+    let [r, g, b, a] = color.to_rgba8().to_u8_array();
+    let _ = write!(out, r##" {}="#{:02x}{:02x}{:02x}""##, attr, r, g, b);
+    let opacity = a as f64 / 255.0 * alpha as f64;
+    if opacity < 1.0 {
+        let _ = write!(out, r#" {}-opacity="{}""#, attr, num(opacity));
+    }
+}
+
+/// Write stroke geometry attributes.
+#[cfg(feature = "svg")]
+fn write_stroke_style(out: &mut String, style: &Stroke) {
+    let _ = write!(out, r#" stroke-width="{}""#, num(style.width));
+    match style.join {
+        Join::Bevel => out.push_str(r#" stroke-linejoin="bevel""#),
+        Join::Round => out.push_str(r#" stroke-linejoin="round""#),
+        Join::Miter => {
+            let _ = write!(out, r#" stroke-miterlimit="{}""#, num(style.miter_limit));
+        }
+    }
+    match style.start_cap {
+        Cap::Butt => {}
+        Cap::Square => out.push_str(r#" stroke-linecap="square""#),
+        Cap::Round => out.push_str(r#" stroke-linecap="round""#),
+    }
+    if !style.dash_pattern.is_empty() {
+        let dashes: Vec<String> = style.dash_pattern.iter().map(|d| num(*d)).collect();
+        let _ = write!(
+            out,
+            r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+            dashes.join(" "),
+            num(style.dash_offset)
+        );
+    }
+}
+
+/// Map a blend mode to its CSS `mix-blend-mode` name (`None` for normal).
+#[cfg(feature = "svg")]
+fn blend_mode_name(mix: Mix) -> Option<&'static str> {
+    #[allow(deprecated)]
+    let name = match mix {
+        Mix::Normal | Mix::Clip => return None,
+        Mix::Multiply => "multiply",
+        Mix::Screen => "screen",
+        Mix::Overlay => "overlay",
+        Mix::Darken => "darken",
+        Mix::Lighten => "lighten",
+        Mix::ColorDodge => "color-dodge",
+        Mix::ColorBurn => "color-burn",
+        Mix::HardLight => "hard-light",
+        Mix::SoftLight => "soft-light",
+        Mix::Difference => "difference",
+        Mix::Exclusion => "exclusion",
+        Mix::Hue => "hue",
+        Mix::Saturation => "saturation",
+        Mix::Color => "color",
+        Mix::Luminosity => "luminosity",
+    };
+    Some(name)
+}
+
+/// Format a `transform` attribute, or nothing for the identity.
+#[cfg(feature = "svg")]
+fn transform_attr(transform: Affine) -> String {
+    if transform == Affine::IDENTITY {
+        String::new()
+    } else {
+        format!(r#" transform="{}""#, matrix(transform))
+    }
+}
+
+/// Format an affine transform as an SVG `matrix(...)`.
+#[cfg(feature = "svg")]
+fn matrix(transform: Affine) -> String {
+    let [a, b, c, d, e, f] = transform.as_coeffs();
+    format!(
+        "matrix({} {} {} {} {} {})",
+        num(a),
+        num(b),
+        num(c),
+        num(d),
+        num(e),
+        num(f)
+    )
+}
+
+/// Format a path as SVG path data.
+#[cfg(feature = "svg")]
+fn path_data(path: &BezPath) -> String {
+    let mut d = String::new();
+    for el in path.elements() {
+        if !d.is_empty() {
+            d.push(' ');
+        }
+        let _ = match el {
+            PathEl::MoveTo(p) => write!(d, "M{} {}", num(p.x), num(p.y)),
+            PathEl::LineTo(p) => write!(d, "L{} {}", num(p.x), num(p.y)),
+            PathEl::QuadTo(c, p) => {
+                write!(d, "Q{} {} {} {}", num(c.x), num(c.y), num(p.x), num(p.y))
+            }
+            PathEl::CurveTo(c0, c1, p) => write!(
+                d,
+                "C{} {} {} {} {} {}",
+                num(c0.x),
+                num(c0.y),
+                num(c1.x),
+                num(c1.y),
+                num(p.x),
+                num(p.y)
+            ),
+            PathEl::ClosePath => write!(d, "Z"),
+        };
+    }
+    d
+}
+
+/// Format a number with at most three decimals and no trailing zeros.
+#[cfg(feature = "svg")]
+fn num(value: f64) -> String {
+    let mut s = format!("{:.3}", value);
+    if s.contains('.') {
+        let trimmed = s.trim_end_matches('0').trim_end_matches('.').len();
+        s.truncate(trimmed);
+    }
+    if s == "-0" {
+        s = "0".to_string();
+    }
+    s
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn render_to_svg(_document: &blitz_html::HtmlDocument, _config: &Config) -> Result<String> {
+    Err(Error::FormatNotEnabled("svg"))
+}
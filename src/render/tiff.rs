@@ -0,0 +1,75 @@
+//! TIFF rendering implementation.
+//!
+//! Pages are rasterized through the PNG pipeline and written as the image
+//! directories of a single LZW-compressed TIFF file, keeping the alpha
+//! channel. Output is currently a single page, giving a one-directory file.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+
+#[cfg(feature = "tiff")]
+use super::png::{render_to_rgba, unpremultiply};
+#[cfg(feature = "tiff")]
+use crate::background;
+#[cfg(feature = "tiff")]
+use blitz_html::HtmlDocument;
+#[cfg(feature = "tiff")]
+use std::io::Cursor;
+#[cfg(feature = "tiff")]
+use tiff::encoder::{colortype, Compression, TiffEncoder};
+
+/// Render a Blitz document to TIFF bytes.
+#[cfg(feature = "tiff")]
+pub fn render_to_tiff(document: &HtmlDocument, config: &Config) -> Result<Vec<u8>> {
+    let background = background::decode(config)?;
+    let (buffer, width, height) = render_to_rgba(document, config, background.as_ref());
+    // TIFF's RGBA8 alpha is unassociated, so undo Vello's premultiplication
+    let buffer = buffer.chunks_exact(4).flat_map(unpremultiply).collect();
+    encode_tiff(&[(buffer, width, height)])
+}
+
+/// Encode RGBA pages, given as `(buffer, width, height)`, as one TIFF
+/// directory each.
+#[cfg(feature = "tiff")]
+fn encode_tiff(pages: &[(Vec<u8>, u32, u32)]) -> Result<Vec<u8>> {
+    let mut output = Cursor::new(Vec::new());
+
+    {
+        let mut encoder = TiffEncoder::new(&mut output)
+            .map_err(|e| Error::TiffEncode(e.to_string()))?
+            .with_compression(Compression::Lzw);
+
+        for (buffer, width, height) in pages {
+            encoder
+                .write_image::<colortype::RGBA8>(*width, *height, buffer)
+                .map_err(|e| Error::TiffEncode(e.to_string()))?;
+        }
+    }
+
+    Ok(output.into_inner())
+}
+
+#[cfg(not(feature = "tiff"))]
+pub fn render_to_tiff(_document: &blitz_html::HtmlDocument, _config: &Config) -> Result<Vec<u8>> {
+    Err(Error::FormatNotEnabled("tiff"))
+}
+
+#[cfg(all(test, feature = "tiff"))]
+mod tests {
+    use super::*;
+    use tiff::decoder::Decoder;
+
+    #[test]
+    fn test_encode_tiff_writes_a_directory_per_page() {
+        let pages = [(vec![255; 4 * 4 * 2], 4, 2), (vec![0; 3 * 5 * 4], 3, 5)];
+        let bytes = encode_tiff(&pages).expect("should encode");
+
+        let mut decoder = Decoder::new(Cursor::new(bytes)).expect("should decode");
+        let mut dimensions = vec![decoder.dimensions().expect("first page")];
+        while decoder.more_images() {
+            decoder.next_image().expect("next page");
+            dimensions.push(decoder.dimensions().expect("next page"));
+        }
+        assert_eq!(dimensions, [(4, 2), (3, 5)]);
+    }
+}
@@ -0,0 +1,494 @@
+//! CSS transforms for PDF output, and rasterized 3D transforms for
+//! [`Config::flatten_transforms_for_pdf`](crate::Config::flatten_transforms_for_pdf).
+//!
+//! PDF content can only be transformed by 2D affine matrices. Elements with
+//! a 3D transform, or a parent with `perspective`, are instead painted once
+//! more with the raster renderer, with everything else transparent and the
+//! element untransformed, as Blitz paints 3D transforms. Its border box is
+//! cropped out and projected through the transform into an image for PDF
+//! output to draw.
+
+use crate::config::Config;
+use crate::error::Result;
+
+use blitz_dom::{BaseDocument, Node};
+use blitz_html::HtmlDocument;
+use std::collections::HashMap;
+use style::values::computed::{CSSPixelLength, Display, Length};
+use style::values::generics::box_::Perspective;
+
+use super::gradients::RasterTile;
+
+#[cfg(feature = "png")]
+use super::backdrop::is_rendered;
+#[cfg(feature = "png")]
+use super::gradients::attr_name;
+#[cfg(feature = "png")]
+use super::png::{output_size, paint_region_to_rgba, unpremultiply};
+#[cfg(feature = "png")]
+use blitz_dom::{local_name, ns, QualName};
+#[cfg(feature = "png")]
+use std::time::Instant;
+#[cfg(feature = "png")]
+use style_traits::ToCss;
+
+/// Elements with 3D transforms painted by the raster renderer, by node id.
+pub(crate) type TransformTiles = HashMap<usize, RasterTile>;
+
+/// A 4x4 matrix applied to row vectors, as in Stylo.
+type Matrix = [[f64; 4]; 4];
+
+/// Smallest homogeneous coordinate of a projected corner. Elements bent
+/// further, past the viewer, are drawn as their 2D part.
+#[cfg(feature = "png")]
+const MIN_W: f64 = 1e-3;
+
+/// Most elements rasterized in one render, each taking a paint pass of its
+/// own.
+#[cfg(feature = "png")]
+const MAX_RASTERIZED: usize = 32;
+
+/// An element's transform, in CSS pixels relative to its border box.
+pub(crate) struct ElementTransform {
+    matrix: Matrix,
+    /// Whether the transform has depth or perspective, which PDF can't draw.
+    #[cfg_attr(not(feature = "png"), allow(dead_code))]
+    pub is_3d: bool,
+}
+
+impl ElementTransform {
+    /// The 2D part `[a, b, c, d, e, f]`, mapping `(x, y)` to
+    /// `(a x + c y + e, b x + d y + f)` and dropping depth and perspective.
+    pub fn affine(&self) -> [f32; 6] {
+        let m = &self.matrix;
+        [m[0][0], m[0][1], m[1][0], m[1][1], m[3][0], m[3][1]].map(|v| v as f32)
+    }
+
+    /// The homography taking points `(x, y, 1)` of the border box plane to
+    /// projected ones.
+    #[cfg(feature = "png")]
+    fn homography(&self) -> [[f64; 3]; 3] {
+        let m = &self.matrix;
+        [
+            [m[0][0], m[0][1], m[0][3]],
+            [m[1][0], m[1][1], m[1][3]],
+            [m[3][0], m[3][1], m[3][3]],
+        ]
+    }
+}
+
+/// The transform of `node`, combining its `transform` about its
+/// `transform-origin` with its parent's `perspective`; `None` if it has
+/// no transform.
+///
+/// Inline boxes aren't transformable, and only the parent's `perspective`
+/// applies: `transform-style: preserve-3d` isn't supported.
+pub(crate) fn element_transform(doc: &BaseDocument, node: &Node) -> Option<ElementTransform> {
+    let style = node.primary_styles()?;
+    let box_style = style.get_box();
+    if box_style.transform.0.is_empty() || box_style.display == Display::Inline {
+        return None;
+    }
+
+    let size = node.final_layout.size;
+    let reference_box = euclid::default::Rect::new(
+        euclid::default::Point2D::new(Length::new(0.0), Length::new(0.0)),
+        euclid::default::Size2D::new(Length::new(size.width), Length::new(size.height)),
+    );
+    let (transform, mut is_3d) = box_style
+        .transform
+        .to_transform_3d_matrix_f64(Some(&reference_box))
+        .ok()?;
+    let origin = &box_style.transform_origin;
+    let (ox, oy, oz) = (
+        origin
+            .horizontal
+            .resolve(CSSPixelLength::new(size.width))
+            .px() as f64,
+        origin
+            .vertical
+            .resolve(CSSPixelLength::new(size.height))
+            .px() as f64,
+        origin.depth.px() as f64,
+    );
+    let mut matrix = multiply(
+        &multiply(&translation(-ox, -oy, -oz), &transform.to_arrays()),
+        &translation(ox, oy, oz),
+    );
+
+    // The parent's perspective, about its perspective origin
+    let parent = node.parent.and_then(|parent_id| doc.get_node(parent_id));
+    if let Some((parent, parent_style)) =
+        parent.and_then(|parent| Some((parent, parent.primary_styles()?)))
+    {
+        if let Perspective::Length(distance) = parent_style.get_box().perspective {
+            let distance = distance.0.px() as f64;
+            let parent_size = parent.final_layout.size;
+            let parent_origin = &parent_style.get_box().perspective_origin;
+            let position = node.absolute_position(0.0, 0.0);
+            let parent_position = parent.absolute_position(0.0, 0.0);
+            let px = parent_origin
+                .horizontal
+                .resolve(CSSPixelLength::new(parent_size.width))
+                .px()
+                - (position.x - parent_position.x);
+            let py = parent_origin
+                .vertical
+                .resolve(CSSPixelLength::new(parent_size.height))
+                .px()
+                - (position.y - parent_position.y);
+            let mut perspective = translation(0.0, 0.0, 0.0);
+            perspective[2][3] = -1.0 / distance.max(1.0);
+            matrix = multiply(
+                &multiply(&matrix, &translation(-px as f64, -py as f64, 0.0)),
+                &multiply(&perspective, &translation(px as f64, py as f64, 0.0)),
+            );
+            is_3d = true;
+        }
+    }
+
+    Some(ElementTransform { matrix, is_3d })
+}
+
+/// The product `a b`, applying `a` first.
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 4]; 4];
+    for (row, a_row) in product.iter_mut().zip(a) {
+        for (column, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a_row[k] * b[k][column]).sum();
+        }
+    }
+    product
+}
+
+/// A translation by `(x, y, z)`.
+fn translation(x: f64, y: f64, z: f64) -> Matrix {
+    [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [x, y, z, 1.0],
+    ]
+}
+
+/// Paint each element with a 3D transform in `document` on its own,
+/// projected through its transform.
+///
+/// Elements inside another such element are painted untransformed as part
+/// of it, and elements bent past the viewer are left out. Each element is
+/// painted in a pass of its own, restyling the document, which is left
+/// styled as before. Only the element's border box is painted, so content
+/// overflowing it is clipped. Past the first [`MAX_RASTERIZED`] elements,
+/// the rest are drawn as their 2D part. The [`Config::budget`] deadline,
+/// counted from `started`, is checked before each element.
+#[cfg(feature = "png")]
+pub(crate) fn rasterize_transforms(
+    document: &mut HtmlDocument,
+    config: &Config,
+    started: Instant,
+) -> Result<TransformTiles> {
+    let node_ids = find_3d_transforms(document);
+    let scale = config.device_scale() as f64;
+    let (page_width, page_height) = output_size(document, config);
+
+    let mut tiles = TransformTiles::new();
+    for node_id in node_ids.into_iter().take(MAX_RASTERIZED) {
+        if let Some(budget) = &config.budget {
+            budget.check_deadline(started)?;
+        }
+        let Some(node) = document.get_node(node_id) else {
+            continue;
+        };
+        let position = node.absolute_position(0.0, 0.0);
+        let size = node.final_layout.size;
+        let (x, y) = (position.x as f64 * scale, position.y as f64 * scale);
+        let clamp = |v: f64, max: u32| (v.max(0.0) as u32).min(max);
+        let (x0, x1) = (
+            clamp(x.floor(), page_width),
+            clamp((x + size.width as f64 * scale).ceil(), page_width),
+        );
+        let (y0, y1) = (
+            clamp(y.floor(), page_height),
+            clamp((y + size.height as f64 * scale).ceil(), page_height),
+        );
+        if x1 <= x0 || y1 <= y0 {
+            continue;
+        }
+
+        let style_id = start_pass(document, node_id, config);
+        let buffer = paint_region_to_rgba(document, config, (x0, y0), (x1 - x0, y1 - y0));
+        end_pass(document, node_id, style_id, config);
+
+        let doc: &BaseDocument = document.as_ref();
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        let Some(transform) = element_transform(doc, node) else {
+            continue;
+        };
+        let source = Source {
+            buffer: &buffer,
+            width: x1 - x0,
+            height: y1 - y0,
+            x: x - x0 as f64,
+            y: y - y0 as f64,
+            css_width: size.width as f64,
+            css_height: size.height as f64,
+            scale,
+        };
+        let page = (page_width, page_height);
+        if let Some(tile) = project(&source, &transform, (position.x, position.y), page) {
+            tiles.insert(node_id, tile);
+        }
+    }
+    Ok(tiles)
+}
+
+/// Rendered elements with 3D transforms, outside any other such element.
+#[cfg(feature = "png")]
+fn find_3d_transforms(document: &HtmlDocument) -> Vec<usize> {
+    let doc: &BaseDocument = document.as_ref();
+    let is_3d = |node: &Node| element_transform(doc, node).is_some_and(|t| t.is_3d);
+    doc.tree()
+        .iter()
+        .filter(|(_, node)| is_3d(node) && is_rendered(doc, node))
+        .filter(|(_, node)| {
+            let mut ancestor = node.parent.and_then(|id| doc.get_node(id));
+            while let Some(current) = ancestor {
+                if is_3d(current) {
+                    return false;
+                }
+                ancestor = current.parent.and_then(|id| doc.get_node(id));
+            }
+            true
+        })
+        .map(|(node_id, _)| node_id)
+        .collect()
+}
+
+/// Attribute marking the root element during a transform pass.
+#[cfg(feature = "png")]
+const PASS_ATTR: &str = "data-hyper-render-transform-pass";
+
+/// Attribute marking the element painted in a transform pass.
+#[cfg(feature = "png")]
+const TARGET_ATTR: &str = "data-hyper-render-transform";
+
+/// Rules making everything but the marked element and its contents
+/// transparent. Blitz skips the contents of hidden elements, so only
+/// replaced content is hidden, and the element's inherited color is
+/// restored by a rule added for it. Opacity and transforms of the
+/// ancestors are reset, as they apply to the element in PDF output.
+#[cfg(feature = "png")]
+const PASS_CSS: &str = r#"
+html[data-hyper-render-transform-pass],
+html[data-hyper-render-transform-pass] :not([data-hyper-render-transform], [data-hyper-render-transform] *) {
+    color: transparent !important;
+    background-color: transparent !important;
+    background-image: none !important;
+    border-color: transparent !important;
+    outline-style: none !important;
+    box-shadow: none !important;
+    opacity: 1 !important;
+    transform: none !important;
+}
+html[data-hyper-render-transform-pass] :is(img, svg, video, canvas, iframe, object, embed):not([data-hyper-render-transform], [data-hyper-render-transform] *) {
+    visibility: hidden !important;
+}
+"#;
+
+/// Mark the element, add the pass stylesheet and restyle the document,
+/// returning the stylesheet's element id.
+#[cfg(feature = "png")]
+fn start_pass(document: &mut HtmlDocument, node_id: usize, config: &Config) -> usize {
+    let root_id = document.root_element().id;
+    let color = document
+        .get_node(node_id)
+        .and_then(|node| node.primary_styles())
+        .map(|style| style.clone_color().to_css_string())
+        .unwrap_or_else(|| "black".to_string());
+    let css = format!(
+        "{}[{}] {{ color: {} !important; }}\n",
+        PASS_CSS, TARGET_ATTR, color
+    );
+    let style_id = {
+        let mut mutator = document.mutate();
+        mutator.set_attribute(node_id, attr_name(TARGET_ATTR), "");
+        let style_id =
+            mutator.create_element(QualName::new(None, ns!(html), local_name!("style")), vec![]);
+        let text_id = mutator.create_text_node(&css);
+        mutator.append_children(style_id, &[text_id]);
+        mutator.append_children(root_id, &[style_id]);
+        mutator.set_attribute(root_id, attr_name(PASS_ATTR), "");
+        style_id
+    };
+    super::resolve_at(document, config, config.resolve_timestamp);
+    style_id
+}
+
+/// Remove the pass stylesheet and markers, restoring the document's styles.
+#[cfg(feature = "png")]
+fn end_pass(document: &mut HtmlDocument, node_id: usize, style_id: usize, config: &Config) {
+    let root_id = document.root_element().id;
+    {
+        let mut mutator = document.mutate();
+        mutator.remove_and_drop_node(style_id);
+        mutator.clear_attribute(root_id, attr_name(PASS_ATTR));
+        mutator.clear_attribute(node_id, attr_name(TARGET_ATTR));
+    }
+    super::resolve_at(document, config, config.resolve_timestamp);
+}
+
+/// A painted element's border box within premultiplied RGBA8 pixels.
+#[cfg(feature = "png")]
+struct Source<'a> {
+    buffer: &'a [u8],
+    width: u32,
+    height: u32,
+    /// Top left corner of the border box in device pixels of the buffer.
+    x: f64,
+    y: f64,
+    /// Border box size in CSS pixels.
+    css_width: f64,
+    css_height: f64,
+    /// Device pixels per CSS pixel.
+    scale: f64,
+}
+
+#[cfg(feature = "png")]
+impl Source<'_> {
+    /// Bilinearly interpolated premultiplied color at `(u, v)` in CSS
+    /// pixels of the border box, or `None` outside it.
+    fn sample(&self, u: f64, v: f64) -> Option<[f64; 4]> {
+        if u < 0.0 || v < 0.0 || u >= self.css_width || v >= self.css_height {
+            return None;
+        }
+        let sx = self.x + u * self.scale - 0.5;
+        let sy = self.y + v * self.scale - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let pixel = |x: f64, y: f64| -> [f64; 4] {
+            let x = (x.max(0.0) as u32).min(self.width - 1) as usize;
+            let y = (y.max(0.0) as u32).min(self.height - 1) as usize;
+            let start = (y * self.width as usize + x) * 4;
+            let p = &self.buffer[start..start + 4];
+            [p[0], p[1], p[2], p[3]].map(f64::from)
+        };
+        let (p00, p10) = (pixel(x0, y0), pixel(x0 + 1.0, y0));
+        let (p01, p11) = (pixel(x0, y0 + 1.0), pixel(x0 + 1.0, y0 + 1.0));
+        Some(std::array::from_fn(|i| {
+            let top = p00[i] + (p10[i] - p00[i]) * fx;
+            let bottom = p01[i] + (p11[i] - p01[i]) * fx;
+            top + (bottom - top) * fy
+        }))
+    }
+}
+
+/// Project `source` through `transform` into a tile, clipped to the `page`
+/// size in device pixels. `position` is the border box's top left corner on
+/// the page, in CSS pixels.
+#[cfg(feature = "png")]
+fn project(
+    source: &Source,
+    transform: &ElementTransform,
+    position: (f32, f32),
+    page: (u32, u32),
+) -> Option<RasterTile> {
+    let h = transform.homography();
+    let (w, hgt) = (source.css_width, source.css_height);
+    let mut corners = Vec::new();
+    for (u, v) in [(0.0, 0.0), (w, 0.0), (0.0, hgt), (w, hgt)] {
+        let x = u * h[0][0] + v * h[1][0] + h[2][0];
+        let y = u * h[0][1] + v * h[1][1] + h[2][1];
+        let z = u * h[0][2] + v * h[1][2] + h[2][2];
+        if z < MIN_W {
+            return None;
+        }
+        corners.push((x / z, y / z));
+    }
+    let inverse = invert(&h)?;
+
+    // Bounds of the projected border box in device pixels, within the page
+    let scale = source.scale;
+    let (px, py) = (position.0 as f64, position.1 as f64);
+    let bound = |values: &mut dyn Iterator<Item = f64>, page_offset: f64, page_size: u32| {
+        let (min, max) = values.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let lo = ((min + page_offset) * scale).floor().max(0.0);
+        let hi = ((max + page_offset) * scale).ceil().min(page_size as f64);
+        (lo, hi)
+    };
+    let (x0, x1) = bound(&mut corners.iter().map(|c| c.0), px, page.0);
+    let (y0, y1) = bound(&mut corners.iter().map(|c| c.1), py, page.1);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    let (width, height) = ((x1 - x0) as u32, (y1 - y0) as u32);
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in 0..height {
+        let y = (y0 + row as f64 + 0.5) / scale - py;
+        for column in 0..width {
+            let x = (x0 + column as f64 + 0.5) / scale - px;
+            let u = x * inverse[0][0] + y * inverse[1][0] + inverse[2][0];
+            let v = x * inverse[0][1] + y * inverse[1][1] + inverse[2][1];
+            let z = x * inverse[0][2] + y * inverse[1][2] + inverse[2][2];
+            let color = (z.abs() > f64::EPSILON)
+                .then(|| source.sample(u / z, v / z))
+                .flatten()
+                .unwrap_or_default();
+            let premultiplied = color.map(|c| c.round().clamp(0.0, 255.0) as u8);
+            pixels.extend(unpremultiply(&premultiplied));
+        }
+    }
+
+    Some(RasterTile {
+        pixels,
+        width,
+        height,
+        x: (x0 / scale - px) as f32,
+        y: (y0 / scale - py) as f32,
+        css_width: (width as f64 / scale) as f32,
+        css_height: (height as f64 / scale) as f32,
+    })
+}
+
+/// The inverse of a 3x3 matrix, if it is invertible.
+#[cfg(feature = "png")]
+fn invert(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let determinant = m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2)
+        + m[0][2] * cofactor(1, 2, 0, 1);
+    if determinant.abs() < f64::EPSILON {
+        return None;
+    }
+    let adjugate = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    Some(adjugate.map(|row| row.map(|value| value / determinant)))
+}
+
+/// 3D transforms are painted by the raster renderer, so rasterizing them
+/// needs the `png` feature.
+#[cfg(not(feature = "png"))]
+pub(crate) fn rasterize_transforms(
+    _document: &mut HtmlDocument,
+    _config: &Config,
+    _started: std::time::Instant,
+) -> Result<TransformTiles> {
+    Err(crate::error::Error::FormatNotEnabled("png"))
+}
@@ -0,0 +1,213 @@
+//! Limits on resources embedded in the HTML, loading of the ones embedded
+//! as `data:` URIs, and tracking of the ones it references by URL.
+
+use crate::warning::Warning;
+use blitz_dom::net::Resource;
+use blitz_dom::{BaseDocument, QualName};
+use blitz_traits::net::{BoxedHandler, Bytes, NetProvider, Request, Url};
+use data_url::DataUrl;
+use image::ImageReader;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+/// Why resources referenced by URL are never loaded.
+pub(crate) const NOT_LOADED_REASON: &str = "network loading is not supported";
+
+/// A `data:` URI request waiting to be decoded.
+type PendingRequest = (usize, Url, BoxedHandler<Resource>);
+
+/// A [`NetProvider`] that loads the resources embedded in a document as
+/// `data:` URIs, and records the ones it requests by URL without loading
+/// them.
+///
+/// hyper-render renders without network access, so every image, stylesheet
+/// and font requested by URL is left unloaded. `data:` URIs are decoded by
+/// [`load_pending`](Self::load_pending) rather than as they are requested,
+/// so ones removed by [`Config::max_resource_bytes`](crate::Config::max_resource_bytes)
+/// while parsing are never decoded. Images whose header gives a size over
+/// [`Config::max_decoded_image_dimension`](crate::Config::max_decoded_image_dimension)
+/// are skipped before their pixels are decoded.
+pub(crate) struct OfflineResources {
+    urls: Mutex<Vec<String>>,
+    pending: Mutex<Vec<PendingRequest>>,
+    oversized: Mutex<Vec<(u32, u32)>>,
+    max_data_uri_bytes: Option<usize>,
+    max_image_dimension: Option<u32>,
+}
+
+impl OfflineResources {
+    /// A provider skipping `data:` URIs longer than `max_data_uri_bytes`,
+    /// and images wider or taller than `max_image_dimension` pixels.
+    pub(crate) fn new(max_data_uri_bytes: Option<usize>, max_image_dimension: Option<u32>) -> Self {
+        OfflineResources {
+            urls: Mutex::new(Vec::new()),
+            pending: Mutex::new(Vec::new()),
+            oversized: Mutex::new(Vec::new()),
+            max_data_uri_bytes,
+            max_image_dimension,
+        }
+    }
+
+    /// URLs requested so far, in request order and without duplicates.
+    pub(crate) fn urls(&self) -> Vec<String> {
+        let urls = self.urls.lock().unwrap_or_else(|e| e.into_inner());
+        let mut unique: Vec<String> = Vec::with_capacity(urls.len());
+        for url in urls.iter() {
+            if !unique.contains(url) {
+                unique.push(url.clone());
+            }
+        }
+        unique
+    }
+
+    /// Warnings for the images skipped so far for being over the size
+    /// limit, in the order they were loaded.
+    pub(crate) fn oversized_images(&self) -> Vec<Warning> {
+        let Some(limit) = self.max_image_dimension else {
+            return Vec::new();
+        };
+        let oversized = self.oversized.lock().unwrap_or_else(|e| e.into_inner());
+        oversized
+            .iter()
+            .map(|&(width, height)| Warning::ImageTooLarge {
+                width,
+                height,
+                limit,
+            })
+            .collect()
+    }
+
+    /// Decode the `data:` URIs requested since the last call, returning the
+    /// resources to load into the document. URIs that fail to decode are
+    /// skipped, like a failed network request, as are images over the size
+    /// limit.
+    ///
+    /// Decoding some resources requests others without producing anything
+    /// to load (an `@import`ed stylesheet requests its fonts), so requests
+    /// made while decoding are decoded too until there is something to load.
+    pub(crate) fn load_pending(&self) -> Vec<Resource> {
+        loop {
+            let pending =
+                std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+            if pending.is_empty() {
+                return Vec::new();
+            }
+            let loaded = Arc::new(Mutex::new(Vec::new()));
+            for (doc_id, url, handler) in pending {
+                let Some(body) = DataUrl::process(url.as_str())
+                    .ok()
+                    .and_then(|data_url| data_url.decode_to_vec().ok())
+                else {
+                    continue;
+                };
+                if let Some(size) = self.oversized_image(&body.0) {
+                    self.oversized
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(size);
+                    continue;
+                }
+                let loaded = loaded.clone();
+                let callback = move |_, result: Result<Resource, Option<String>>| {
+                    if let Ok(resource) = result {
+                        loaded
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push(resource);
+                    }
+                };
+                handler.bytes(doc_id, Bytes::from(body.0), Arc::new(callback));
+            }
+            let loaded = std::mem::take(&mut *loaded.lock().unwrap_or_else(|e| e.into_inner()));
+            let loaded: Vec<Resource> = loaded
+                .into_iter()
+                .filter(|resource| !matches!(resource, Resource::None))
+                .collect();
+            if !loaded.is_empty() {
+                return loaded;
+            }
+        }
+    }
+
+    /// The size of `body` if it is a raster image wider or taller than the
+    /// limit, read from its header without decoding its pixels.
+    fn oversized_image(&self, body: &[u8]) -> Option<(u32, u32)> {
+        let limit = self.max_image_dimension?;
+        let (width, height) = ImageReader::new(Cursor::new(body))
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()?;
+        (width > limit || height > limit).then_some((width, height))
+    }
+}
+
+impl NetProvider<Resource> for OfflineResources {
+    fn fetch(&self, doc_id: usize, request: Request, handler: BoxedHandler<Resource>) {
+        if request.url.scheme() != "data" {
+            self.urls
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(request.url.to_string());
+        } else if self
+            .max_data_uri_bytes
+            .is_none_or(|limit| request.url.as_str().len() <= limit)
+        {
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((doc_id, request.url, handler));
+        }
+    }
+}
+
+/// Remove every element attribute holding a `data:` URI longer than `limit`
+/// bytes, returning a warning for each one removed.
+pub(crate) fn strip_oversized_data_uris(doc: &mut BaseDocument, limit: usize) -> Vec<Warning> {
+    let mut oversized: Vec<(usize, QualName, Warning)> = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        for attr in element.attrs() {
+            let value = attr.value.trim_start();
+            if value.len() > limit && is_data_uri(value) {
+                let warning = Warning::ResourceTooLarge {
+                    element: element.name.local.to_string(),
+                    attribute: attr.name.local.to_string(),
+                    bytes: value.len(),
+                    limit,
+                };
+                oversized.push((node_id, attr.name.clone(), warning));
+            }
+        }
+    }
+
+    let mut mutator = doc.mutate();
+    oversized
+        .into_iter()
+        .map(|(node_id, name, warning)| {
+            mutator.clear_attribute(node_id, name);
+            warning
+        })
+        .collect()
+}
+
+/// Total size in bytes of the `data:` URIs held in element attributes.
+pub(crate) fn data_uri_bytes(doc: &BaseDocument) -> usize {
+    doc.tree()
+        .iter()
+        .filter_map(|(_, node)| node.element_data())
+        .flat_map(|element| element.attrs())
+        .map(|attr| attr.value.trim_start())
+        .filter(|value| is_data_uri(value))
+        .map(str::len)
+        .sum()
+}
+
+/// Whether `value` starts with the `data:` scheme (case-insensitive).
+fn is_data_uri(value: &str) -> bool {
+    value
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+}
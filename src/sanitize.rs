@@ -0,0 +1,40 @@
+//! Removal of scripting from untrusted HTML for [`Config::sanitize`](crate::Config::sanitize).
+
+use blitz_dom::{local_name, BaseDocument, QualName};
+
+/// Remove `<script>` elements (HTML and SVG) and inline event handler
+/// attributes such as `onload` and `onclick` from the document.
+///
+/// Scripts never run, but a `<script>` still holds its source as text,
+/// which author CSS (`script { display: block }`) could lay out and paint.
+pub(crate) fn strip_scripts(doc: &mut BaseDocument) {
+    let mut scripts = Vec::new();
+    let mut handlers: Vec<(usize, QualName)> = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        if element.name.local == local_name!("script") {
+            scripts.push(node_id);
+            continue;
+        }
+        for attr in element.attrs() {
+            if attr
+                .name
+                .local
+                .get(..2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("on"))
+            {
+                handlers.push((node_id, attr.name.clone()));
+            }
+        }
+    }
+
+    let mut mutator = doc.mutate();
+    for (node_id, name) in handlers {
+        mutator.clear_attribute(node_id, name);
+    }
+    for node_id in scripts {
+        mutator.remove_and_drop_node(node_id);
+    }
+}
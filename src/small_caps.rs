@@ -0,0 +1,201 @@
+//! Synthesized small capitals for `font-variant: small-caps`.
+
+use blitz_dom::node::Attribute;
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, QualName};
+use style::computed_values::font_variant_caps::T as FontVariantCaps;
+
+/// Attribute on the generated spans holding uppercased lowercase letters,
+/// with the letters as they were for its value.
+const SMALL_CAPS_ATTR: &str = "data-hyper-render-small-caps";
+
+/// Styles for the generated spans, which set their capitals at the size
+/// browsers use for synthesized small capitals.
+pub(crate) const SMALL_CAPS_CSS: &str = r#"
+[data-hyper-render-small-caps] {
+    font-size: 0.7em !important;
+}
+"#;
+
+/// Synthesize small capitals for text set in `font-variant-caps: small-caps`.
+///
+/// Blitz shapes text with no font features of its own and offers no way to
+/// pass any, so a font's `smcp` glyphs are never used and small-caps text
+/// comes out in ordinary lowercase. As browsers do for fonts without small
+/// capitals, this wraps each run of lowercase letters in such text in a
+/// generated `<span>` holding the run in uppercase, which [`SMALL_CAPS_CSS`]
+/// scales down, and keeps the run as it was for the PDF's text layer (see
+/// [`original_text`]). The contents of `<style>`, `<script>` and
+/// `<textarea>` are left alone.
+///
+/// Styles must already be resolved; returns whether any text was wrapped, in
+/// which case [`SMALL_CAPS_CSS`] must be injected and the document resolved
+/// again.
+pub(crate) fn synthesize_small_caps(doc: &mut BaseDocument) -> bool {
+    let mut updates = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(text) = node.text_data() else {
+            continue;
+        };
+        // Text generated since styles were resolved sits in unstyled
+        // elements, so the style comes from the nearest styled one
+        let mut styled = node.parent.and_then(|parent| doc.get_node(parent));
+        while let Some(element) = styled.filter(|element| element.primary_styles().is_none()) {
+            styled = element.parent.and_then(|parent| doc.get_node(parent));
+        }
+        let Some(element) = styled else {
+            continue;
+        };
+        let in_raw_text = element.element_data().is_some_and(|element| {
+            matches!(
+                element.name.local,
+                local_name!("style") | local_name!("script") | local_name!("textarea")
+            )
+        });
+        let small_caps = element
+            .primary_styles()
+            .is_some_and(|style| style.get_font().font_variant_caps == FontVariantCaps::SmallCaps);
+        if !small_caps || in_raw_text {
+            continue;
+        }
+        let runs = case_runs(&text.content);
+        if runs.iter().any(|(_, lowercase)| *lowercase) {
+            updates.push((node_id, runs));
+        }
+    }
+
+    let changed = !updates.is_empty();
+    let mut mutator = doc.mutate();
+    for (node_id, runs) in updates {
+        let replacements: Vec<usize> = runs
+            .into_iter()
+            .map(|(run, lowercase)| match lowercase {
+                true => {
+                    let span_id = mutator.create_element(
+                        QualName::new(None, ns!(html), local_name!("span")),
+                        vec![Attribute {
+                            name: QualName::new(None, ns!(), LocalName::from(SMALL_CAPS_ATTR)),
+                            value: run.clone(),
+                        }],
+                    );
+                    let text_id = mutator.create_text_node(&run.to_uppercase());
+                    mutator.append_children(span_id, &[text_id]);
+                    span_id
+                }
+                false => mutator.create_text_node(&run),
+            })
+            .collect();
+        mutator.replace_node_with(node_id, &replacements);
+    }
+    changed
+}
+
+/// `text` split into runs of lowercase letters and runs of everything else,
+/// each with whether it is lowercase.
+fn case_runs(text: &str) -> Vec<(String, bool)> {
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for c in text.chars() {
+        let lowercase = c.is_lowercase() && !c.to_uppercase().eq([c]);
+        match runs.last_mut() {
+            Some((run, run_lowercase)) if *run_lowercase == lowercase => run.push(c),
+            _ => runs.push((c.to_string(), lowercase)),
+        }
+    }
+    runs
+}
+
+/// `text`, the text of the inline `layout`, with the runs synthesized as
+/// small capitals put back as they were, and the offset in it of each byte
+/// offset of `text`.
+///
+/// This keeps the PDF's text layer reading "Hello" rather than "HELLO". The
+/// first character a letter was uppercased to stands for the letter and any
+/// others for nothing, and the soft hyphens and zero width non-joiners
+/// inserted since are kept. Returns `None` when the layout holds no small
+/// capitals.
+#[cfg(feature = "pdf")]
+pub(crate) fn original_text(
+    doc: &BaseDocument,
+    text: &str,
+    layout: &parley::Layout<blitz_dom::node::TextBrush>,
+) -> Option<(String, Vec<usize>)> {
+    use parley::PositionedLayoutItem;
+    use std::collections::BTreeMap;
+
+    // The text each generated span covers, by where it starts
+    let mut spans: BTreeMap<usize, (std::ops::Range<usize>, &str)> = BTreeMap::new();
+    let mut ranges: std::collections::HashMap<usize, std::ops::Range<usize>> =
+        std::collections::HashMap::new();
+    for line in layout.lines() {
+        for item in line.items() {
+            let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                continue;
+            };
+            for cluster in glyph_run.run().clusters() {
+                let range = cluster.text_range();
+                ranges
+                    .entry(cluster.first_style().brush.id)
+                    .and_modify(|span| {
+                        span.start = span.start.min(range.start);
+                        span.end = span.end.max(range.end);
+                    })
+                    .or_insert(range);
+            }
+        }
+    }
+    for (node_id, range) in ranges {
+        let original = doc
+            .get_node(node_id)
+            .and_then(|node| node.element_data())
+            .and_then(|element| element.attr(LocalName::from(SMALL_CAPS_ATTR)));
+        if let Some(original) = original {
+            spans.insert(range.start, (range, original));
+        }
+    }
+    if spans.is_empty() {
+        return None;
+    }
+
+    let inserted = |c: char| c == '\u{AD}' || c == crate::ligatures::ZWNJ;
+    let mut restored = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut copied = 0;
+    for (range, original) in spans.into_values() {
+        let Some(span) = text.get(range.clone()) else {
+            continue;
+        };
+        if range.start < copied
+            || !span
+                .chars()
+                .filter(|&c| !inserted(c))
+                .eq(original.chars().flat_map(char::to_uppercase))
+        {
+            continue;
+        }
+        offsets.extend((copied..range.start).map(|offset| restored.len() + offset - copied));
+        restored.push_str(&text[copied..range.start]);
+
+        let mut letters = original.chars();
+        // Capitals still to come for the current letter
+        let mut pending = 0;
+        for c in span.chars() {
+            if inserted(c) {
+                offsets.extend(std::iter::repeat_n(restored.len(), c.len_utf8()));
+                restored.push(c);
+                continue;
+            }
+            if pending == 0 {
+                let letter = letters.next()?;
+                pending = letter.to_uppercase().count();
+                offsets.extend(std::iter::repeat_n(restored.len(), c.len_utf8()));
+                restored.push(letter);
+            } else {
+                offsets.extend(std::iter::repeat_n(restored.len(), c.len_utf8()));
+            }
+            pending -= 1;
+        }
+        copied = range.end;
+    }
+    offsets.extend((copied..=text.len()).map(|offset| restored.len() + offset - copied));
+    restored.push_str(&text[copied..]);
+    Some((restored, offsets))
+}
@@ -0,0 +1,96 @@
+//! Snapping layout to device pixels for [`Config::pixel_snap`](crate::Config::pixel_snap).
+
+use crate::config::Config;
+use blitz_dom::BaseDocument;
+use taffy::{Layout, Point, Rect};
+
+/// Snap the document's layout to device pixels if [`Config::pixel_snap`]
+/// is enabled. Call after every time the document is resolved.
+pub(crate) fn snap_layout(doc: &mut BaseDocument, config: &Config) {
+    if config.pixel_snap {
+        snap_to_device_pixels(doc, config.device_scale());
+    }
+}
+
+/// Move every box edge in the document's layout onto a device pixel
+/// boundary, `scale` device pixels to the CSS pixel.
+///
+/// Taffy rounds layout to whole CSS pixels, but Blitz paints from the
+/// unrounded layout, so a box after a line of text 18.4px tall starts
+/// part way into a pixel and its 1px border is drawn as two half-covered
+/// rows; at fractional scales whole CSS pixels aren't whole device pixels
+/// either. Each edge is rounded to the nearest device pixel from its
+/// unrounded position in the page, so rounding errors don't add up down the
+/// tree, and border and padding widths are rounded too, keeping a nonzero
+/// border at least one device pixel wide. Both the rounded and unrounded
+/// layouts are replaced, so PNG and PDF output agree. Layout must already be
+/// resolved, and resolving again undoes the snapping.
+fn snap_to_device_pixels(doc: &mut BaseDocument, scale: f32) {
+    // The tree is walked with an explicit stack, so deeply nested
+    // documents can't overflow the call stack
+    let mut stack = vec![(doc.root_element().id, Point::ZERO, Point::ZERO)];
+    while let Some((node_id, parent, snapped_parent)) = stack.pop() {
+        stack.extend(snap_node(doc, node_id, parent, snapped_parent, scale));
+    }
+}
+
+/// Snap `node_id`, where its layout parent's border box starts at `parent`
+/// before snapping and `snapped_parent` after.
+///
+/// Returns its layout children, each with where the node's border box
+/// starts before and after snapping, to be snapped in turn.
+fn snap_node(
+    doc: &mut BaseDocument,
+    node_id: usize,
+    parent: Point<f32>,
+    snapped_parent: Point<f32>,
+    scale: f32,
+) -> Vec<(usize, Point<f32>, Point<f32>)> {
+    let Some(node) = doc.get_node(node_id) else {
+        return Vec::new();
+    };
+    let layout = node.unrounded_layout;
+    let children = node.layout_children.borrow().clone().unwrap_or_default();
+
+    let snap = |value: f32| (value * scale).round() / scale;
+    let position = Point {
+        x: parent.x + layout.location.x,
+        y: parent.y + layout.location.y,
+    };
+    let left = snap(position.x);
+    let top = snap(position.y);
+    let right = snap(position.x + layout.size.width);
+    let bottom = snap(position.y + layout.size.height);
+    let snapped = Layout {
+        location: Point {
+            x: left - snapped_parent.x,
+            y: top - snapped_parent.y,
+        },
+        size: taffy::Size {
+            width: right - left,
+            height: bottom - top,
+        },
+        border: snap_widths(layout.border, scale),
+        padding: snap_widths(layout.padding, scale),
+        ..layout
+    };
+
+    if let Some(node) = doc.get_node_mut(node_id) {
+        node.unrounded_layout = snapped;
+        node.final_layout = snapped;
+    }
+    let snapped_position = Point { x: left, y: top };
+    children
+        .into_iter()
+        .map(|child_id| (child_id, position, snapped_position))
+        .collect()
+}
+
+/// Round each width to whole device pixels, keeping nonzero widths at
+/// least one device pixel wide.
+fn snap_widths(widths: Rect<f32>, scale: f32) -> Rect<f32> {
+    widths.map(|width| match width > 0.0 {
+        true => (width * scale).round().max(1.0) / scale,
+        false => 0.0,
+    })
+}
@@ -0,0 +1,273 @@
+//! SVG sprite references between inline `<svg>` elements.
+
+use blitz_dom::node::ElementData;
+use blitz_dom::{local_name, ns, BaseDocument, LocalName, QualName};
+use std::collections::{HashMap, HashSet};
+use style::color::ColorSpace;
+
+/// Make `<use>` references to other inline SVGs resolvable, and pass the
+/// CSS `color` to SVGs that use `currentColor`.
+///
+/// Blitz rasterizes each inline `<svg>` on its own, so a `<use href="#icon">`
+/// (or `xlink:href`) pointing at a `<symbol>` in a separate (typically
+/// hidden) sprite sheet finds nothing, and `currentColor` only sees colors
+/// set inside the SVG. This copies each referenced element into a `<defs>`
+/// of the referencing SVG, renaming the `id`s in the copy so the document
+/// keeps one element per `id`, and sets a `color` attribute on SVGs using
+/// `currentColor` from the color they inherit. Styles must already be
+/// resolved; returns whether the document changed, in which case it needs
+/// resolving again.
+pub(crate) fn resolve_svg_sprites(doc: &mut BaseDocument) -> bool {
+    let ids = element_ids(doc);
+    // (outermost svg, referenced element) pairs to copy
+    let mut copies: Vec<(usize, usize)> = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        if element.name.ns != ns!(svg) || element.name.local != local_name!("use") {
+            continue;
+        }
+        let Some(&target_id) = href(element)
+            .and_then(|href| href.strip_prefix('#'))
+            .and_then(|id| ids.get(id))
+        else {
+            continue;
+        };
+        let Some(svg_id) = outermost_svg(doc, node_id) else {
+            continue;
+        };
+        if outermost_svg(doc, target_id) != Some(svg_id) && !copies.contains(&(svg_id, target_id)) {
+            copies.push((svg_id, target_id));
+        }
+    }
+
+    let mut changed = !copies.is_empty();
+    let mut copied: Vec<(usize, usize)> = Vec::new();
+    {
+        let mut mutator = doc.mutate();
+        for &(svg_id, target_id) in &copies {
+            let copy_id = mutator.deep_clone_node(target_id);
+            let defs_id = mutator.create_element(
+                QualName::new(None, ns!(svg), LocalName::from("defs")),
+                vec![],
+            );
+            mutator.append_children(defs_id, &[copy_id]);
+            mutator.append_children(svg_id, &[defs_id]);
+            copied.push((svg_id, copy_id));
+        }
+    }
+    rename_copied_ids(doc, &copied);
+
+    let mut colors = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        if element.name.local != local_name!("svg")
+            || element.attr(local_name!("color")).is_some()
+            || outermost_svg(doc, node_id) != Some(node_id)
+            || !uses_current_color(doc, node_id)
+        {
+            continue;
+        }
+        let Some(style) = node.primary_styles() else {
+            continue;
+        };
+        let color = style.clone_color().to_color_space(ColorSpace::Srgb);
+        let [r, g, b] = [color.components.0, color.components.1, color.components.2]
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+        colors.push((
+            node_id,
+            format!("rgba({}, {}, {}, {})", r, g, b, color.alpha),
+        ));
+    }
+
+    changed |= !colors.is_empty();
+    let mut mutator = doc.mutate();
+    for (svg_id, color) in colors {
+        mutator.set_attribute(
+            svg_id,
+            QualName::new(None, ns!(), local_name!("color")),
+            &color,
+        );
+    }
+    changed
+}
+
+/// Give the elements of each `(svg, copy)` copy a new `id`, unique to the
+/// copy, and point the references in `svg` to the old ones at the new.
+///
+/// References are `href` and `xlink:href` values of `#id`, and `url(#id)`
+/// in any attribute, such as `fill` or `style`.
+fn rename_copied_ids(doc: &mut BaseDocument, copied: &[(usize, usize)]) {
+    let mut updates: Vec<(usize, QualName, String)> = Vec::new();
+    for &(svg_id, copy_id) in copied {
+        let copy = descendants(doc, copy_id);
+        let renamed: Vec<(String, String)> = copy
+            .iter()
+            .filter_map(|&node_id| doc.get_node(node_id)?.attr(local_name!("id")))
+            .map(|id| (id.to_string(), format!("{}-sprite-{}", id, copy_id)))
+            .collect();
+        if renamed.is_empty() {
+            continue;
+        }
+        let in_copy: HashSet<usize> = copy.into_iter().collect();
+        for node_id in descendants(doc, svg_id) {
+            let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+                continue;
+            };
+            for attr in element.attrs() {
+                let mut value = attr.value.to_string();
+                for (old, new) in &renamed {
+                    if attr.name.local == local_name!("id") {
+                        if in_copy.contains(&node_id) && value == *old {
+                            value = new.clone();
+                        }
+                    } else if is_href(&attr.name) {
+                        if value.strip_prefix('#') == Some(old.as_str()) {
+                            value = format!("#{}", new);
+                        }
+                    } else {
+                        value = value.replace(&format!("url(#{})", old), &format!("url(#{})", new));
+                    }
+                }
+                if value != *attr.value {
+                    updates.push((node_id, attr.name.clone(), value));
+                }
+            }
+        }
+    }
+
+    let mut mutator = doc.mutate();
+    for (node_id, name, value) in updates {
+        mutator.set_attribute(node_id, name, &value);
+    }
+}
+
+/// Whether the SVG element `svg_id` or anything in it uses `currentColor`,
+/// in an attribute (including `style`) or a `<style>` element.
+fn uses_current_color(doc: &BaseDocument, svg_id: usize) -> bool {
+    let has_current_color = |value: &str| value.to_ascii_lowercase().contains("currentcolor");
+    descendants(doc, svg_id).into_iter().any(|node_id| {
+        let Some(node) = doc.get_node(node_id) else {
+            return false;
+        };
+        if let Some(element) = node.element_data() {
+            return element
+                .attrs()
+                .iter()
+                .any(|attr| has_current_color(&attr.value));
+        }
+        let in_style = node
+            .parent
+            .and_then(|parent| doc.get_node(parent))
+            .and_then(|parent| parent.element_data())
+            .is_some_and(|parent| parent.name.local == local_name!("style"));
+        in_style
+            && node
+                .text_data()
+                .is_some_and(|text| has_current_color(&text.content))
+    })
+}
+
+/// The `href` of a `<use>` element, from its `href` or `xlink:href`
+/// attribute.
+fn href(element: &ElementData) -> Option<&str> {
+    element
+        .attrs()
+        .iter()
+        .find(|attr| is_href(&attr.name))
+        .map(|attr| &*attr.value)
+}
+
+/// Whether `name` is `href` or `xlink:href`.
+fn is_href(name: &QualName) -> bool {
+    name.local == local_name!("href") && (name.ns == ns!() || name.ns == ns!(xlink))
+}
+
+/// A stylesheet giving SVG elements the CSS `color` of their `color`
+/// attribute, or `None` if no element in an SVG has one.
+///
+/// Blitz hands each inline SVG to its rasterizer with every `currentColor`
+/// replaced by the element's computed CSS color, but Stylo doesn't treat
+/// SVG's `color` presentation attribute as a style, so a path under
+/// `<g color="red">` takes the color inherited from outside the SVG. Each
+/// rule matches the elements with one `color` value through `:where()`, so
+/// like a presentation attribute it loses to any author rule. Values that
+/// couldn't be written into a rule safely are skipped. The stylesheet must
+/// be injected before styles are resolved.
+pub(crate) fn color_attributes_css(doc: &BaseDocument) -> Option<String> {
+    let mut values: Vec<&str> = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        let Some(value) = element.attr(local_name!("color")) else {
+            continue;
+        };
+        let value = value.trim();
+        if outermost_svg(doc, node_id).is_none()
+            || value.is_empty()
+            || value.contains(['"', '\\', '{', '}', ';', '<', '\n'])
+            || values.contains(&value)
+        {
+            continue;
+        }
+        values.push(value);
+    }
+    if values.is_empty() {
+        return None;
+    }
+    Some(
+        values
+            .iter()
+            .map(|value| {
+                format!(
+                    ":where(svg [color=\"{0}\"], svg[color=\"{0}\"]) {{ color: {0}; }}\n",
+                    value
+                )
+            })
+            .collect(),
+    )
+}
+
+/// The outermost `<svg>` element containing `node_id` (or `node_id` itself).
+fn outermost_svg(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    let mut outermost = None;
+    let mut current = doc.get_node(node_id);
+    while let Some(node) = current {
+        if node
+            .element_data()
+            .is_some_and(|element| element.name.local == local_name!("svg"))
+        {
+            outermost = Some(node.id);
+        }
+        current = node.parent.and_then(|parent| doc.get_node(parent));
+    }
+    outermost
+}
+
+/// The first element in the document with each `id`.
+fn element_ids(doc: &BaseDocument) -> HashMap<String, usize> {
+    let mut ids = HashMap::new();
+    for (node_id, node) in doc.tree().iter() {
+        if let Some(id) = node.attr(local_name!("id")) {
+            ids.entry(id.to_string()).or_insert(node_id);
+        }
+    }
+    ids
+}
+
+/// `node_id` and every node under it.
+fn descendants(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![node_id];
+    while let Some(node_id) = stack.pop() {
+        nodes.push(node_id);
+        if let Some(node) = doc.get_node(node_id) {
+            stack.extend(node.children.iter().rev());
+        }
+    }
+    nodes
+}
@@ -0,0 +1,50 @@
+//! The table of contents page added by [`Config::toc`](crate::Config::toc).
+
+use crate::config::Config;
+use crate::placeholder::escape_html;
+
+/// Attribute on each entry of the table of contents, holding the index of
+/// the heading it lists.
+pub(crate) const ENTRY_ATTR: &str = "data-hyper-render-toc-entry";
+
+/// Configuration for rendering the table of contents page.
+///
+/// The page keeps the document's page size and the options affecting how
+/// text is set, but none of those adding to or changing the page.
+pub(crate) fn toc_config(config: &Config, height: u32) -> Config {
+    Config {
+        width: config.width,
+        height,
+        color_scheme: config.color_scheme,
+        background: config.background,
+        fonts_dir: config.fonts_dir.clone(),
+        system_fonts: config.system_fonts,
+        lang: config.lang.clone(),
+        format: config.format,
+        ..Config::default()
+    }
+}
+
+/// A page headed "Contents" listing `headings`, given as their level and
+/// title, each indented by its level and followed by `page_number`.
+pub(crate) fn toc_html(headings: &[(u8, String)], page_number: usize) -> String {
+    let entries: String = headings
+        .iter()
+        .enumerate()
+        .map(|(index, (level, title))| {
+            format!(
+                r#"<div {ENTRY_ATTR}="{index}" style="padding-left: {}px;"><span>{}</span><span>{page_number}</span></div>"#,
+                (level - 1) as u32 * 20,
+                escape_html(title)
+            )
+        })
+        .collect();
+    format!(
+        r#"<html><head><style>
+            body {{ margin: 48px; font-family: sans-serif; font-size: 14px; }}
+            h1 {{ font-size: 24px; margin: 0 0 16px; }}
+            [{ENTRY_ATTR}] {{ display: flex; gap: 16px; margin: 6px 0; }}
+            [{ENTRY_ATTR}] > span:first-child {{ flex: 1; }}
+        </style></head><body><h1>Contents</h1>{entries}</body></html>"#
+    )
+}
@@ -0,0 +1,102 @@
+//! Non-fatal diagnostics reported during rendering.
+
+/// A non-fatal issue detected while rendering.
+///
+/// Warnings are returned by [`render_with_warnings`](crate::render_with_warnings)
+/// alongside the output. With [`Config::strict`](crate::Config::strict)
+/// enabled, the first warning is returned as [`Error::Strict`](crate::Error::Strict)
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// Laid-out content extends past the output bounds and was clipped.
+    ///
+    /// Overflow amounts are in CSS pixels; an axis that fits reports `0`.
+    ContentClipped {
+        /// Horizontal overflow past the configured width.
+        overflow_x: u32,
+        /// Vertical overflow past the configured height.
+        overflow_y: u32,
+    },
+
+    /// A data URI exceeded [`Config::max_resource_bytes`](crate::Config::max_resource_bytes)
+    /// and was removed from the document before rendering.
+    ResourceTooLarge {
+        /// Tag name of the element carrying the URI (e.g. `img`).
+        element: String,
+        /// Attribute that held the URI (e.g. `src`).
+        attribute: String,
+        /// Length of the data URI in bytes.
+        bytes: usize,
+        /// The configured limit in bytes.
+        limit: usize,
+    },
+
+    /// A resource referenced by URL could not be loaded and was skipped,
+    /// under [`ResourcePolicy::BestEffort`](crate::ResourcePolicy::BestEffort).
+    ResourceNotLoaded {
+        /// The resolved URL of the image, stylesheet or font.
+        url: String,
+        /// Why it was not loaded.
+        reason: String,
+    },
+
+    /// An embedded image was larger than
+    /// [`Config::max_decoded_image_dimension`](crate::Config::max_decoded_image_dimension)
+    /// in either direction and was skipped without being decoded.
+    ImageTooLarge {
+        /// Width of the image in pixels, from its header.
+        width: u32,
+        /// Height of the image in pixels, from its header.
+        height: u32,
+        /// The configured limit in pixels.
+        limit: u32,
+    },
+
+    /// The render failed and a placeholder was returned in its place, under
+    /// [`ErrorPolicy::Placeholder`](crate::ErrorPolicy::Placeholder).
+    RenderFailed {
+        /// The error the render failed with.
+        message: String,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::ContentClipped {
+                overflow_x,
+                overflow_y,
+            } => write!(
+                f,
+                "content clipped: overflows by {}px horizontally and {}px vertically",
+                overflow_x, overflow_y
+            ),
+            Warning::ResourceTooLarge {
+                element,
+                attribute,
+                bytes,
+                limit,
+            } => write!(
+                f,
+                "resource too large: skipped {}-byte data URI in <{} {}> (limit {} bytes)",
+                bytes, element, attribute, limit
+            ),
+            Warning::ResourceNotLoaded { url, reason } => {
+                write!(f, "resource not loaded: skipped {} ({})", url, reason)
+            }
+            Warning::ImageTooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "image too large: skipped {}x{} image (limit {} pixels)",
+                width, height, limit
+            ),
+            Warning::RenderFailed { message } => {
+                write!(f, "render failed: returned a placeholder ({})", message)
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! White-space processing that Blitz leaves to the embedder.
+
+use blitz_dom::BaseDocument;
+use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
+
+/// Apply `white-space: pre-line` collapsing to the document's text nodes.
+///
+/// Blitz lays out `pre-line` text as if it were `pre`, preserving every
+/// space. This rewrites the affected text so runs of spaces and tabs collapse
+/// to a single space and spaces next to a newline are removed, leaving only
+/// the explicit line breaks. Styles must already be resolved; returns whether
+/// any text changed, in which case the document needs resolving again.
+pub(crate) fn collapse_pre_line(doc: &mut BaseDocument) -> bool {
+    let mut updates = Vec::new();
+    for (node_id, node) in doc.tree().iter() {
+        let Some(text) = node.text_data() else {
+            continue;
+        };
+        // Text nodes take the style of the element holding them, and text
+        // generated since styles were resolved sits in unstyled elements, so
+        // the style comes from the nearest styled one
+        let mut styled = node.parent.and_then(|parent| doc.get_node(parent));
+        while let Some(element) = styled.filter(|element| element.primary_styles().is_none()) {
+            styled = element.parent.and_then(|parent| doc.get_node(parent));
+        }
+        let is_pre_line = styled
+            .and_then(|element| element.primary_styles())
+            .is_some_and(|style| {
+                style.get_inherited_text().white_space_collapse
+                    == WhiteSpaceCollapse::PreserveBreaks
+            });
+        if is_pre_line {
+            let collapsed = collapse_spaces_keeping_breaks(&text.content);
+            if collapsed != text.content {
+                updates.push((node_id, collapsed));
+            }
+        }
+    }
+
+    let changed = !updates.is_empty();
+    let mut mutator = doc.mutate();
+    for (node_id, text) in updates {
+        mutator.set_node_text(node_id, &text);
+    }
+    changed
+}
+
+/// Collapse spaces and tabs while keeping newlines.
+///
+/// Leading and trailing spaces are only trimmed next to a newline, since the
+/// text node's ends may border other inline content.
+fn collapse_spaces_keeping_breaks(text: &str) -> String {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let last = lines.len() - 1;
+    let mut collapsed = String::with_capacity(text.len());
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            collapsed.push('\n');
+        }
+        let mut line = *line;
+        if i > 0 {
+            line = line.trim_start_matches([' ', '\t']);
+        }
+        if i < last {
+            line = line.trim_end_matches([' ', '\t']);
+        }
+        let mut in_space = false;
+        for c in line.chars() {
+            if c == ' ' || c == '\t' {
+                if !in_space {
+                    collapsed.push(' ');
+                }
+                in_space = true;
+            } else {
+                collapsed.push(c);
+                in_space = false;
+            }
+        }
+    }
+    collapsed
+}
@@ -0,0 +1,119 @@
+//! Integration tests for `Config::budget` resource limits.
+
+#![cfg(feature = "png")]
+
+use hyper_render::{render, Config, Error, RenderBudget};
+use std::time::Duration;
+
+fn render_with_budget(html: &str, config: Config, budget: RenderBudget) -> Result<Vec<u8>, Error> {
+    render(html, config.budget(budget))
+}
+
+#[test]
+fn test_default_budget_allows_ordinary_page() {
+    let html = "<html><body><h1>Hello</h1><p>World</p></body></html>";
+    let result = render_with_budget(html, Config::new(), RenderBudget::default());
+    assert!(result.is_ok(), "default budget should allow a small page");
+}
+
+#[test]
+fn test_budget_max_pixels() {
+    let config = Config::new().size(200, 100).scale(2.0);
+    let budget = RenderBudget::unlimited().max_pixels(40_000);
+
+    let result = render_with_budget("<p>Hi</p>", config, budget);
+    match result {
+        Err(Error::PixelLimitExceeded { pixels, limit }) => {
+            assert_eq!(pixels, 400 * 200);
+            assert_eq!(limit, 40_000);
+        }
+        other => panic!("expected PixelLimitExceeded, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_budget_max_pixels_covers_grown_raster() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="position: absolute; left: -3000px; top: -3000px; width: 10px; height: 10px;"></div>
+    </body></html>"#;
+    let budget = RenderBudget::unlimited().max_pixels(1_000_000);
+
+    // The page itself fits, but holding the overhang would not
+    let config = Config::new().size(100, 100);
+    assert!(render_with_budget(html, config.clone(), budget).is_ok());
+    let result = render_with_budget(html, config.capture_overhang(true), budget);
+    match result {
+        Err(Error::PixelLimitExceeded { pixels, limit }) => {
+            assert_eq!(pixels, 3100 * 3100);
+            assert_eq!(limit, 1_000_000);
+        }
+        other => panic!("expected PixelLimitExceeded, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_budget_max_height() {
+    let html =
+        r#"<html><body style="margin: 0;"><div style="height: 3000px;"></div></body></html>"#;
+    let config = Config::new().auto_height(true);
+    let budget = RenderBudget::unlimited().max_height(1000);
+
+    let result = render_with_budget(html, config, budget);
+    match result {
+        Err(Error::HeightLimitExceeded { height, limit }) => {
+            assert_eq!(height, 3000);
+            assert_eq!(limit, 1000);
+        }
+        other => panic!("expected HeightLimitExceeded, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_budget_max_resource_bytes() {
+    // Two data URIs that fit individually but not together
+    let uri = format!("data:image/png;base64,{}", "A".repeat(600));
+    let html = format!(r#"<img src="{0}"><img src="{0}">"#, uri);
+    let budget = RenderBudget::unlimited().max_resource_bytes(1000);
+
+    let result = render_with_budget(&html, Config::new(), budget);
+    match result {
+        Err(Error::ResourceLimitExceeded { bytes, limit }) => {
+            assert_eq!(bytes, 2 * uri.len());
+            assert_eq!(limit, 1000);
+        }
+        other => panic!(
+            "expected ResourceLimitExceeded, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn test_budget_max_depth() {
+    let html = format!("{}x{}", "<div>".repeat(50), "</div>".repeat(50));
+    let budget = RenderBudget::unlimited().max_depth(20);
+
+    let result = render_with_budget(&html, Config::new(), budget);
+    match result {
+        Err(Error::DepthLimitExceeded { depth, limit }) => {
+            assert!(
+                depth > 50,
+                "depth should include every nested div, got {}",
+                depth
+            );
+            assert_eq!(limit, 20);
+        }
+        other => panic!("expected DepthLimitExceeded, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_budget_deadline() {
+    let budget = RenderBudget::unlimited().deadline(Duration::ZERO);
+
+    let result = render_with_budget("<p>Hi</p>", Config::new(), budget);
+    assert!(
+        matches!(result, Err(Error::DeadlineExceeded(deadline)) if deadline == Duration::ZERO),
+        "expected DeadlineExceeded"
+    );
+}
@@ -1,6 +1,9 @@
 //! Integration tests for configuration options.
 
-use hyper_render::{render, ColorScheme, Config, OutputFormat};
+use hyper_render::{
+    render, ColorScheme, Config, Error, OutputFormat, PageLabelRange, PageLabelStyle, PdfA,
+    PdfVersion, PixelMode, Rotation,
+};
 
 #[test]
 fn test_config_default_values() {
@@ -86,8 +89,31 @@ fn test_config_validate_edge_cases() {
     // Large dimensions
     assert!(Config::new().width(10000).height(10000).validate().is_ok());
 
-    // Large scale
-    assert!(Config::new().scale(100.0).validate().is_ok());
+    // Scale up to the default maximum
+    assert!(Config::new()
+        .scale(Config::DEFAULT_MAX_SCALE)
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn test_config_validate_max_scale() {
+    // A typo'd scale would allocate an 80000x60000 buffer
+    let result = Config::new().scale(100.0).validate();
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("scale")),
+        "scale above the maximum should be rejected, got {:?}",
+        result
+    );
+    assert!(Config::new().scale(8.0).validate().is_ok());
+
+    // The limit is configurable
+    assert!(Config::new()
+        .max_scale(128.0)
+        .scale(100.0)
+        .validate()
+        .is_ok());
+    assert!(Config::new().max_scale(2.0).scale(3.0).validate().is_err());
 }
 
 #[test]
@@ -114,6 +140,8 @@ fn test_output_format_pdf_rendering() {
 fn test_output_format_display() {
     assert_eq!(format!("{}", OutputFormat::Png), "png");
     assert_eq!(format!("{}", OutputFormat::Pdf), "pdf");
+    assert_eq!(format!("{}", OutputFormat::Gif), "gif");
+    assert_eq!(format!("{}", OutputFormat::Svg), "svg");
 }
 
 #[test]
@@ -238,3 +266,338 @@ fn test_color_scheme_equality() {
     assert_eq!(ColorScheme::Dark, ColorScheme::Dark);
     assert_ne!(ColorScheme::Light, ColorScheme::Dark);
 }
+
+#[test]
+fn test_config_background_css_named() {
+    let config = Config::new().background_css("navy");
+    assert_eq!(config.background_rgba().unwrap(), [0, 0, 128, 255]);
+}
+
+#[test]
+fn test_config_background_css_hex() {
+    let config = Config::new().background_css("#ff8800");
+    assert_eq!(config.background_rgba().unwrap(), [255, 136, 0, 255]);
+
+    let short = Config::new().background_css("#f80");
+    assert_eq!(short.background_rgba().unwrap(), [255, 136, 0, 255]);
+}
+
+#[test]
+fn test_config_background_css_rgba() {
+    let config = Config::new().background_css("rgba(0, 0, 0, 0.5)");
+    assert_eq!(config.background_rgba().unwrap(), [0, 0, 0, 128]);
+}
+
+#[test]
+fn test_config_background_css_invalid() {
+    for css in ["not-a-color", "rgb(1, 2)", "currentcolor", ""] {
+        let config = Config::new().background_css(css);
+        assert!(config.validate().is_err(), "{:?} should be rejected", css);
+
+        let result = render("<html><body></body></html>", config);
+        assert!(
+            matches!(result, Err(hyper_render::Error::InvalidConfig(_))),
+            "render should fail for {:?}",
+            css
+        );
+    }
+}
+
+#[test]
+fn test_config_background_css_trailing_input() {
+    for css in ["red foo", "#fff;garbage", "navy navy"] {
+        let config = Config::new().background_css(css);
+        let result = config.background_rgba();
+        assert!(
+            matches!(result, Err(hyper_render::Error::InvalidConfig(_))),
+            "{:?} should be rejected, got {:?}",
+            css,
+            result
+        );
+    }
+
+    // Surrounding whitespace isn't part of the color
+    let config = Config::new().background_css(" navy ");
+    assert_eq!(config.background_rgba().unwrap(), [0, 0, 128, 255]);
+}
+
+#[test]
+fn test_config_background_overrides_background_css() {
+    let config = Config::new()
+        .background_css("navy")
+        .background([1, 2, 3, 4]);
+    assert_eq!(config.background_css, None);
+    assert_eq!(config.background_rgba().unwrap(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_config_pdf_version_and_conformance() {
+    let config = Config::default();
+    assert_eq!(config.pdf_version, PdfVersion::Pdf17);
+    assert_eq!(config.pdf_conformance, None);
+
+    let config = Config::new()
+        .pdf_version(PdfVersion::Pdf14)
+        .pdf_conformance(Some(PdfA::A1b));
+    assert!(config.validate().is_ok());
+
+    let config = config.pdf_conformance(Some(PdfA::A2b));
+    assert!(config.validate().is_ok(), "PDF/A-2 accepts PDF 1.4");
+
+    let config = config
+        .pdf_version(PdfVersion::Pdf17)
+        .pdf_conformance(Some(PdfA::A1b));
+    assert!(config.validate().is_err(), "PDF/A-1 requires PDF 1.4");
+}
+
+#[test]
+fn test_config_rotate_output() {
+    assert_eq!(Config::default().rotation, Rotation::None);
+    assert!(Rotation::Deg90.swaps_dimensions());
+    assert!(Rotation::Deg270.swaps_dimensions());
+    assert!(!Rotation::Deg180.swaps_dimensions());
+
+    // The default PNG output reports the rotated size
+    #[cfg(feature = "png")]
+    {
+        let config = Config::new().size(100, 50).rotate_output(Rotation::Deg270);
+        let output =
+            hyper_render::render_detailed("<p>Label</p>", config).expect("render should succeed");
+        assert_eq!((output.width, output.height), (50, 100));
+    }
+}
+
+#[test]
+fn test_config_max_decoded_image_dimension() {
+    let config = Config::new().max_decoded_image_dimension(1024);
+    assert_eq!(config.max_decoded_image_dimension, Some(1024));
+    assert!(config.validate().is_ok());
+    assert_eq!(Config::new().max_decoded_image_dimension, None);
+
+    let result = Config::new().max_decoded_image_dimension(0).validate();
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("max_decoded_image_dimension")),
+        "got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_config_resolve_timestamp() {
+    assert_eq!(Config::new().resolve_timestamp, 0.0);
+    let config = Config::new().resolve_timestamp(1.5);
+    assert_eq!(config.resolve_timestamp, 1.5);
+    assert!(config.validate().is_ok());
+
+    for seconds in [-1.0, f64::NAN, f64::INFINITY] {
+        let result = Config::new().resolve_timestamp(seconds).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("resolve_timestamp")),
+            "{} should be rejected, got {:?}",
+            seconds,
+            result
+        );
+    }
+}
+
+#[test]
+fn test_config_autosize_text() {
+    assert_eq!(Config::new().autosize_text, None);
+    let config = Config::new().autosize_text(".title");
+    assert_eq!(config.autosize_text.as_deref(), Some(".title"));
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_config_generate_outline() {
+    assert!(!Config::new().generate_outline);
+    assert!(Config::new().generate_outline(true).generate_outline);
+}
+
+#[test]
+fn test_config_rasterize_gradients() {
+    assert!(!Config::new().rasterize_gradients);
+    assert!(Config::new().rasterize_gradients(true).rasterize_gradients);
+}
+
+#[test]
+fn test_config_flatten_transforms_for_pdf() {
+    assert!(!Config::new().flatten_transforms_for_pdf);
+    assert!(
+        Config::new()
+            .flatten_transforms_for_pdf(true)
+            .flatten_transforms_for_pdf
+    );
+}
+
+#[test]
+fn test_config_page_labels_validation() {
+    assert!(Config::new().page_labels.is_empty());
+
+    let valid = vec![
+        PageLabelRange::new(0, PageLabelStyle::RomanLower),
+        PageLabelRange::new(2, PageLabelStyle::Decimal).prefix("p. "),
+    ];
+    assert!(Config::new().page_labels(valid).validate().is_ok());
+
+    let invalid = [
+        vec![
+            PageLabelRange::new(2, PageLabelStyle::Decimal),
+            PageLabelRange::new(0, PageLabelStyle::RomanLower),
+        ],
+        vec![
+            PageLabelRange::new(1, PageLabelStyle::Decimal),
+            PageLabelRange::new(1, PageLabelStyle::RomanLower),
+        ],
+        vec![PageLabelRange::new(0, PageLabelStyle::Decimal).first_number(0)],
+    ];
+    for ranges in invalid {
+        let result = Config::new().page_labels(ranges).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("page_labels")),
+            "got {:?}",
+            result
+        );
+    }
+}
+
+#[test]
+fn test_config_icc_profile_validation() {
+    let profile = |color_space: &[u8; 4], len: u32| {
+        let mut profile = vec![0; 132];
+        profile[0..4].copy_from_slice(&len.to_be_bytes());
+        profile[16..20].copy_from_slice(color_space);
+        profile[36..40].copy_from_slice(b"acsp");
+        profile
+    };
+
+    for color_space in [b"RGB ", b"CMYK", b"GRAY"] {
+        assert!(Config::new()
+            .icc_profile(profile(color_space, 132))
+            .validate()
+            .is_ok());
+    }
+
+    let invalid = [
+        b"not a profile".to_vec(),
+        profile(b"RGB ", 200),
+        profile(b"XYZ ", 132),
+    ];
+    for data in invalid {
+        let result = Config::new().icc_profile(data).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("icc_profile")),
+            "got {:?}",
+            result
+        );
+    }
+
+    let result = Config::new()
+        .icc_profile(profile(b"RGB ", 132))
+        .pdf_conformance(Some(PdfA::A2b))
+        .validate();
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("pdf_conformance"))
+    );
+}
+
+#[test]
+fn test_config_retry_layout_on_font_load() {
+    assert!(Config::new().retry_layout_on_font_load);
+    assert!(
+        !Config::new()
+            .retry_layout_on_font_load(false)
+            .retry_layout_on_font_load
+    );
+}
+
+#[test]
+fn test_config_sanitize() {
+    assert!(!Config::new().sanitize);
+    assert!(Config::new().sanitize(true).sanitize);
+}
+
+#[test]
+fn test_config_fragment_mode() {
+    assert!(!Config::new().fragment_mode);
+    assert!(Config::new().fragment_mode(true).fragment_mode);
+}
+
+#[test]
+fn test_config_toc() {
+    assert!(!Config::new().toc);
+    assert!(Config::new().toc(true).toc);
+}
+
+#[test]
+fn test_config_clip_to_page() {
+    assert!(Config::new().clip_to_page);
+    assert!(!Config::new().clip_to_page(false).clip_to_page);
+}
+
+#[test]
+fn test_config_force_page_size() {
+    assert!(!Config::new().force_page_size);
+    assert!(Config::new().force_page_size(true).force_page_size);
+}
+
+#[test]
+fn test_config_snapshot_mode() {
+    assert!(Config::new().system_fonts);
+    assert!(!Config::new().system_fonts(false).system_fonts);
+
+    let config = Config::new().resolve_timestamp(2.0).snapshot_mode();
+    assert!(!config.system_fonts);
+    assert_eq!(config.resolve_timestamp, 0.0);
+}
+
+#[test]
+fn test_config_aspect_ratio() {
+    let config = Config::new().size(1200, 900);
+    assert_eq!(config.aspect_height(), 900);
+    assert_eq!(config.clone().aspect_ratio(2.0).aspect_height(), 600);
+    assert!(config.clone().aspect_ratio(2.0).validate().is_ok());
+
+    for ratio in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let result = config.clone().aspect_ratio(ratio).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("aspect_ratio")),
+            "{ratio}: {result:?}"
+        );
+    }
+    // Too wide a ratio leaves no height
+    assert!(config.clone().aspect_ratio(100.0).validate().is_err());
+    assert!(config
+        .aspect_ratio(2.0)
+        .auto_height(true)
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_config_dpi_validation() {
+    assert_eq!(Config::new().dpi, None);
+    assert_eq!(Config::new().pixel_mode, PixelMode::CssPx);
+    assert_eq!(
+        Config::new().pixel_mode(PixelMode::Physical).device_scale(),
+        1.0
+    );
+    assert_eq!(Config::new().scale(2.0).dpi(300.0).device_scale(), 2.0);
+
+    for dpi in [0.0, -72.0, f32::NAN, f32::INFINITY] {
+        let result = Config::new().dpi(dpi).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("dpi")),
+            "{dpi}: {result:?}"
+        );
+    }
+
+    // The device scale is bounded like the scale
+    let physical = Config::new().dpi(1200.0).pixel_mode(PixelMode::Physical);
+    assert!(Config::new().dpi(1200.0).validate().is_ok());
+    assert!(matches!(
+        physical.clone().validate(),
+        Err(Error::InvalidConfig(ref msg)) if msg.contains("max_scale")
+    ));
+    assert!(physical.max_scale(16.0).validate().is_ok());
+}
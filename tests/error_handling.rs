@@ -2,7 +2,7 @@
 
 #![cfg(feature = "png")]
 
-use hyper_render::{render, Config, Error};
+use hyper_render::{render, render_with_warnings, Config, Error, ErrorPolicy, Warning};
 
 #[test]
 fn test_empty_html() {
@@ -185,3 +185,94 @@ fn test_html_with_doctype() {
     let result = render(html, config);
     assert!(result.is_ok(), "HTML with DOCTYPE should render");
 }
+
+#[test]
+fn test_assert_dimensions_rejects_runaway_layout() {
+    let html = r#"<div style="width: 10000000px; height: 10px;"></div>"#;
+    let config = Config::new().assert_dimensions(100_000);
+
+    match render(html, config) {
+        Err(Error::ElementTooLarge {
+            element,
+            width,
+            limit,
+            ..
+        }) => {
+            assert_eq!(element, "div");
+            assert_eq!(width, 10_000_000);
+            assert_eq!(limit, 100_000);
+        }
+        other => panic!("expected ElementTooLarge, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_assert_dimensions_allows_normal_layout() {
+    let html = r#"<div style="width: 500px; height: 300px;">Content</div>"#;
+    let config = Config::new().assert_dimensions(100_000);
+
+    assert!(render(html, config).is_ok());
+}
+
+/// HTML that fails to render under [`runaway_config`].
+const RUNAWAY_HTML: &str = r#"<div style="width: 10000000px; height: 10px;"></div>"#;
+
+fn runaway_config() -> Config {
+    Config::new().size(320, 200).assert_dimensions(100_000)
+}
+
+#[test]
+fn test_on_error_placeholder_png() {
+    assert!(render(RUNAWAY_HTML, runaway_config()).is_err());
+
+    let config = runaway_config().on_error(ErrorPolicy::Placeholder);
+    let (png, warnings) =
+        render_with_warnings(RUNAWAY_HTML, config).expect("should return a placeholder");
+
+    let decoder = png::Decoder::new(png.as_slice());
+    let mut reader = decoder
+        .read_info()
+        .expect("placeholder should be a valid PNG");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("should decode PNG");
+    assert_eq!((info.width, info.height), (320, 200));
+    // The error is flagged in red
+    assert!(
+        pixels
+            .chunks(4)
+            .any(|pixel| pixel[0] > 150 && pixel[1] < 100 && pixel[2] < 100),
+        "placeholder should show the failure in red"
+    );
+
+    match warnings.as_slice() {
+        [Warning::RenderFailed { message }] => assert!(
+            message.contains("100000"),
+            "warning should carry the error message, got {:?}",
+            message
+        ),
+        other => panic!("expected a RenderFailed warning, got {:?}", other),
+    }
+}
+
+#[test]
+#[cfg(feature = "pdf")]
+fn test_on_error_placeholder_pdf() {
+    let config = runaway_config()
+        .format(hyper_render::OutputFormat::Pdf)
+        .on_error(ErrorPolicy::Placeholder);
+    let pdf = render(RUNAWAY_HTML, config).expect("should return a placeholder");
+
+    assert!(pdf.starts_with(b"%PDF-"));
+    let text = pdf_extract::extract_text_from_mem(&pdf).expect("should extract text");
+    assert!(text.contains("Render failed"), "got {:?}", text);
+}
+
+#[test]
+fn test_on_error_placeholder_needs_valid_size() {
+    let config = Config::new().width(0).on_error(ErrorPolicy::Placeholder);
+
+    assert!(matches!(
+        render("<p>x</p>", config),
+        Err(Error::InvalidConfig(_))
+    ));
+}
@@ -0,0 +1,272 @@
+//! Integration tests for font loading.
+
+#![cfg(feature = "pdf")]
+
+mod common;
+
+use common::base64;
+use hyper_render::{render, render_detailed, Config, OutputFormat};
+use std::path::{Path, PathBuf};
+
+/// Path to the fonts bundled with the crate.
+fn fonts_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("fonts")
+}
+
+/// Create an empty scratch directory unique to this test process.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hyper-render-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("should create scratch dir");
+    dir
+}
+
+/// Search for a string pattern in the PDF bytes.
+fn pdf_contains(data: &[u8], pattern: &[u8]) -> bool {
+    data.windows(pattern.len()).any(|w| w == pattern)
+}
+
+#[test]
+fn test_fonts_dir_registers_fonts() {
+    // Nest the font in a subdirectory next to a non-font file
+    let dir = scratch_dir("fonts-dir");
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::copy(
+        fonts_dir().join("OpenSans-Regular.woff2"),
+        dir.join("nested").join("OpenSans-Regular.woff2"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("README.txt"), "not a font").unwrap();
+
+    let html = r#"<html><body><p style="font-family: 'Open Sans';">Hello</p></body></html>"#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let without = render(html, config.clone()).expect("should render");
+    let with = render(html, config.fonts_dir(&dir)).expect("should render with fonts dir");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(
+        !pdf_contains(&without, b"OpenSans"),
+        "Open Sans should not be available without the fonts dir"
+    );
+    assert!(
+        pdf_contains(&with, b"OpenSans"),
+        "Open Sans from the fonts dir should be embedded"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_fonts_dir_symlink_loop_is_read_once() {
+    // A link back up the tree would otherwise be followed forever
+    let dir = scratch_dir("fonts-dir-loop");
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::copy(
+        fonts_dir().join("OpenSans-Regular.woff2"),
+        dir.join("nested").join("OpenSans-Regular.woff2"),
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("nested").join("loop")).unwrap();
+
+    let html = r#"<p style="font-family: 'Open Sans';">Hello</p>"#;
+    let result = render(
+        html,
+        Config::new().format(OutputFormat::Pdf).fonts_dir(&dir),
+    );
+    std::fs::remove_dir_all(&dir).ok();
+
+    let pdf = result.expect("should render with a looping fonts dir");
+    assert!(pdf_contains(&pdf, b"OpenSans"));
+}
+
+#[test]
+fn test_fonts_dir_missing_directory() {
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .fonts_dir("/nonexistent/hyper-render/fonts");
+
+    let result = render("<p>Test</p>", config);
+    assert!(
+        matches!(result, Err(hyper_render::Error::Io(_))),
+        "missing fonts dir should be an I/O error"
+    );
+}
+
+#[test]
+fn test_require_full_coverage_reports_missing_character() {
+    // A private-use codepoint that no system font maps
+    let html = "<html><body><p>Tofu: \u{10FFFD}</p></body></html>";
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .require_full_coverage(true);
+
+    let err = render(html, config).expect_err("uncovered character should fail");
+    assert!(
+        matches!(err, hyper_render::Error::Font(_)),
+        "should be a font error"
+    );
+
+    let msg = err.to_string();
+    assert!(
+        msg.contains("U+10FFFD"),
+        "error should name the codepoint: {}",
+        msg
+    );
+    assert!(
+        !msg.contains("'T'"),
+        "covered characters should not be listed: {}",
+        msg
+    );
+}
+
+#[test]
+fn test_require_full_coverage_passes_for_covered_text() {
+    let html = "<html><body><p>Plain ASCII text</p><ul><li>Item</li></ul><ol><li>Step</li></ol></body></html>";
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .require_full_coverage(true);
+
+    assert!(render(html, config).is_ok(), "covered text should render");
+}
+
+#[test]
+fn test_missing_character_renders_without_coverage_check() {
+    let html = "<html><body><p>Tofu: \u{10FFFD}</p></body></html>";
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    assert!(render(html, config).is_ok(), "tofu is allowed by default");
+}
+
+#[test]
+fn test_web_font_retries_layout() {
+    // The font arrives two loads after the first layout: the imported
+    // stylesheet is loaded first, and it requests the font
+    let font = std::fs::read(fonts_dir().join("OpenSans-Regular.woff2")).unwrap();
+    let font_face = format!(
+        "@font-face {{ font-family: 'Open Sans'; src: url(data:font/woff2;base64,{}) format('woff2'); }}",
+        base64(&font)
+    );
+    let html = format!(
+        r#"<html><head><style>@import url("data:text/css;base64,{}");</style></head>
+        <body style="margin: 0;">
+            <p style="font-family: 'Open Sans'; font-size: 20px; line-height: 25px; width: 350px; margin: 0;">
+                The quick brown fox jumps over the lazy dog while the five boxing
+                wizards jump quickly and a wide sphinx of black quartz judges my vow
+            </p>
+        </body></html>"#,
+        base64(font_face.as_bytes())
+    );
+    let config = Config::new().auto_height(true).format(OutputFormat::Pdf);
+
+    let fallback = render_detailed(&html, config.clone().retry_layout_on_font_load(false))
+        .expect("should render without the web font");
+    let retried = render_detailed(&html, config).expect("should render with the web font");
+
+    assert!(!fallback.fonts.contains(&"Open Sans".to_string()));
+    assert_eq!(retried.fonts, vec!["Open Sans".to_string()]);
+    // Open Sans is narrower than the fallback, so the paragraph wraps to
+    // fewer lines
+    assert!(
+        retried.height < fallback.height,
+        "text should be laid out again with the web font: {}px tall with it, {}px without",
+        retried.height,
+        fallback.height
+    );
+}
+
+#[test]
+fn test_system_fonts_disabled_uses_fonts_dir_for_generic_families() {
+    let html = r#"<html><body>
+        <p>Serif</p>
+        <p style="font-family: sans-serif;">Sans</p>
+        <p style="font-family: monospace;">Mono</p>
+    </body></html>"#;
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .fonts_dir(fonts_dir());
+
+    let with_system = render_detailed(html, config.clone()).expect("should render");
+    let without_system = render_detailed(html, config.system_fonts(false)).expect("should render");
+
+    assert!(!with_system.fonts.contains(&"Open Sans".to_string()));
+    assert_eq!(without_system.fonts, vec!["Open Sans".to_string()]);
+}
+
+#[test]
+fn test_snapshot_mode_without_fonts_dir_draws_bundled_font() {
+    let html = r#"<html><body><p style="font-family: serif;">Snapshot text</p></body></html>"#;
+    let config = Config::new().format(OutputFormat::Pdf).snapshot_mode();
+
+    let result = render_detailed(html, config).expect("should render");
+    assert_eq!(result.fonts, vec!["Open Sans".to_string()]);
+    let text = pdf_extract::extract_text_from_mem(&result.bytes).expect("should extract text");
+    assert!(text.contains("Snapshot text"), "{text}");
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_snapshot_mode_output_is_identical() {
+    let html = r#"<html><body style="font-family: system-ui;">
+        <h1>Snapshot</h1>
+        <p style="animation: fade 2s infinite;">The same bytes <em>every time</em></p>
+        <style>@keyframes fade { to { opacity: 0.2; } }</style>
+    </body></html>"#;
+
+    for format in [OutputFormat::Png, OutputFormat::Pdf] {
+        let config = Config::new()
+            .format(format)
+            .fonts_dir(fonts_dir())
+            .snapshot_mode();
+        assert!(!config.system_fonts);
+
+        let first = render_detailed(html, config.clone()).expect("should render");
+        let second = render_detailed(html, config).expect("should render again");
+        assert_eq!(first.fonts, vec!["Open Sans".to_string()], "{format:?}");
+        assert!(
+            first.bytes == second.bytes,
+            "{format:?} output should match"
+        );
+    }
+}
+
+#[test]
+#[cfg(all(feature = "png", feature = "svg"))]
+fn test_snapshot_mode_output_does_not_depend_on_map_order() {
+    // Every thread seeds its hash maps differently, so renders on fresh
+    // threads differ if anything is output in map iteration order
+    let html = r#"<html><head><style>
+        @counter-style ticks { system: cyclic; symbols: "+" "-"; suffix: " "; }
+        @counter-style stars { system: fixed; symbols: "*" "**"; }
+        .tile { width: 60px; height: 20px; margin: 4px; }
+    </style></head><body>
+        <ol style="list-style: ticks;"><li>One</li><li>Two</li></ol>
+        <ul style="list-style: stars;"><li>Three</li><li>Four</li></ul>
+        <div class="tile" style="background: linear-gradient(red, blue);"></div>
+        <div class="tile" style="background: linear-gradient(green, yellow);"></div>
+        <div class="tile" style="background: radial-gradient(white, black);"></div>
+        <p>Plain, <b>bold</b> and <i>italic</i> glyphs</p>
+    </body></html>"#;
+
+    for format in [OutputFormat::Png, OutputFormat::Pdf, OutputFormat::Svg] {
+        let config = Config::new()
+            .format(format)
+            .fonts_dir(fonts_dir())
+            .rasterize_gradients(true)
+            .snapshot_mode();
+        let renders: Vec<Vec<u8>> = (0..4)
+            .map(|_| {
+                let config = config.clone();
+                std::thread::spawn(move || render(html, config).expect("should render"))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().expect("render thread should not panic"))
+            .collect();
+        assert!(
+            renders.iter().all(|bytes| *bytes == renders[0]),
+            "{format:?} output should match across threads"
+        );
+    }
+}
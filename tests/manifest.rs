@@ -0,0 +1,79 @@
+//! Integration tests for `render_detailed` and render manifests.
+
+#![cfg(feature = "pdf")]
+
+use hyper_render::{render_detailed, Config, OutputFormat};
+
+#[test]
+fn test_render_detailed_reports_output() {
+    let html =
+        r#"<html><body style="margin: 0;"><div style="height: 1234px;">Hello</div></body></html>"#;
+    let config = Config::new()
+        .width(400)
+        .auto_height(true)
+        .format(OutputFormat::Pdf);
+
+    let output = render_detailed(html, config).expect("should render");
+    assert!(output.bytes.starts_with(b"%PDF-"));
+    assert_eq!(output.format, OutputFormat::Pdf);
+    assert_eq!(output.width, 400);
+    assert_eq!(output.height, 1234);
+    assert_eq!(output.content_height, 1234);
+    assert_eq!(output.page_count, 1);
+    assert!(
+        !output.fonts.is_empty(),
+        "text should be shaped with a font"
+    );
+    assert!(output.warnings.is_empty());
+}
+
+#[test]
+fn test_force_page_size_measures_content_without_resizing() {
+    let html =
+        r#"<html><body style="margin: 0;"><div style="height: 1234px;">Hello</div></body></html>"#;
+    let config = Config::new()
+        .size(400, 300)
+        .auto_height(true)
+        .force_page_size(true)
+        .format(OutputFormat::Pdf);
+
+    let output = render_detailed(html, config).expect("should render");
+    assert_eq!((output.width, output.height), (400, 300));
+    assert_eq!(output.content_height, 1234);
+    assert!(
+        String::from_utf8_lossy(&output.bytes).contains("/MediaBox [0 0 400 300]"),
+        "the page should keep the configured size"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_manifest_json_round_trips() {
+    let html = r#"<div style="width: 2000px;">Wide</div>"#;
+    let config = Config::new()
+        .size(300, 200)
+        .scale(2.0)
+        .overflow_guard(true)
+        .format(OutputFormat::Pdf);
+
+    let output = render_detailed(html, config).expect("should render");
+    let json = output.manifest_json();
+    let value: serde_json::Value = serde_json::from_str(&json).expect("manifest should be JSON");
+
+    assert_eq!(value["format"], "pdf");
+    assert_eq!(value["width"], 300);
+    assert_eq!(value["height"], 200);
+    assert_eq!(value["scale"], 2.0);
+    assert_eq!(value["byte_len"], output.bytes.len());
+    assert_eq!(value["page_count"], 1);
+    assert_eq!(
+        value["fonts"].as_array().map(Vec::len),
+        Some(output.fonts.len())
+    );
+    assert_eq!(value["warnings"][0], output.warnings[0].to_string());
+    assert!(value["render_time_ms"].as_f64().is_some_and(|ms| ms > 0.0));
+
+    let manifest: hyper_render::RenderManifest =
+        serde_json::from_str(&json).expect("manifest should deserialize");
+    assert_eq!(manifest, output.manifest());
+}
@@ -360,3 +360,25 @@ fn test_receipt_like_document() {
     let text = render_and_extract(html);
     check_snapshot("receipt_like_document", &text);
 }
+
+#[test]
+fn test_white_space_pre_line() {
+    // Newlines are preserved while runs of spaces collapse. The extractor
+    // only starts a new line for gaps over 1.5x the font size, hence the
+    // generous line-height.
+    let html = "<html><body><p style=\"white-space: pre-line; line-height: 2;\">First    line\nSecond   line\n   Third line</p></body></html>";
+
+    let text = render_and_extract(html);
+    check_snapshot("white_space_pre_line", &text);
+}
+
+#[test]
+fn test_white_space_pre_line_inline_descendants() {
+    // Text in inline elements follows its own white-space: pre-line
+    // inherited through <b> collapses, while a nested white-space: pre span
+    // keeps its spaces
+    let html = "<html><body><p style=\"white-space: pre-line; line-height: 2;\">First <b>bold    text\n   Second</b> line\n<span style=\"white-space: pre;\">Kept    spaces</span></p></body></html>";
+
+    let text = render_and_extract(html);
+    check_snapshot("white_space_pre_line_inline_descendants", &text);
+}
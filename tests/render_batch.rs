@@ -0,0 +1,60 @@
+//! Integration tests for `render_batch`.
+
+#![cfg(all(feature = "png", feature = "pdf"))]
+
+use hyper_render::{render, render_batch, Config, Error, OutputFormat};
+
+fn inputs() -> Vec<String> {
+    (1..=10)
+        .map(|i| {
+            format!(
+                r#"<html><body style="background: hsl({} 70% 80%);">
+                    <h1>Card {i}</h1>
+                    <p style="width: {}px;">Rendered in parallel</p>
+                </body></html>"#,
+                i * 36,
+                100 + i * 20,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_render_batch_matches_serial_renders() {
+    let inputs = inputs();
+    let htmls: Vec<&str> = inputs.iter().map(String::as_str).collect();
+
+    for config in [
+        Config::new().size(320, 200),
+        Config::new().size(320, 200).concurrency(3),
+        Config::new().size(320, 200).format(OutputFormat::Pdf),
+    ] {
+        let results = render_batch(&htmls, config.clone());
+        assert_eq!(results.len(), htmls.len());
+        for (html, result) in htmls.iter().zip(results) {
+            let serial = render(html, config.clone()).expect("render should succeed");
+            assert_eq!(result.expect("batch render should succeed"), serial);
+        }
+    }
+}
+
+#[test]
+fn test_render_batch_failures_are_independent() {
+    let htmls = [
+        "<h1>Fine</h1>",
+        "<div style=\"width: 10000px\"></div>",
+        "<p>Fine</p>",
+    ];
+    let config = Config::new().assert_dimensions(5000);
+
+    let results = render_batch(&htmls, config);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(Error::ElementTooLarge { .. })));
+    assert!(results[2].is_ok());
+
+    assert!(render_batch(&[], Config::new()).is_empty());
+    let results = render_batch(&htmls, Config::new().concurrency(0));
+    assert!(results.iter().all(
+        |result| matches!(result, Err(Error::InvalidConfig(msg)) if msg.contains("concurrency"))
+    ));
+}
@@ -0,0 +1,127 @@
+//! Integration tests for pixel diffs between renders.
+
+#![cfg(feature = "png")]
+
+use hyper_render::{render_diff, Config};
+
+const PAGE: &str = r#"<html><body style="margin: 0; background: white; font-size: 20px;">
+    <p style="margin: 10px;">The quick brown fox</p>
+    <p style="margin: 10px;">jumps over the lazy dog</p>
+</body></html>"#;
+
+#[test]
+fn test_diff_identical_html_has_no_changes() {
+    let diff = render_diff(PAGE, PAGE, Config::new().size(300, 120)).expect("diff should succeed");
+
+    assert!(diff.is_identical());
+    assert_eq!(diff.changed_pixels, 0);
+    assert_eq!(diff.bounds, None);
+    assert_eq!(diff.diff_image, None);
+    assert_eq!((diff.width, diff.height), (300, 120));
+}
+
+#[test]
+fn test_diff_one_word_change_is_localized() {
+    let changed = PAGE.replace("lazy", "sleepy");
+    let diff =
+        render_diff(PAGE, &changed, Config::new().size(300, 120)).expect("diff should succeed");
+
+    assert!(!diff.is_identical());
+    let bounds = diff.bounds.expect("a changed word should have bounds");
+
+    // Only the second line changed, from the edited word onwards
+    assert!(bounds.y >= 40, "first line should be unchanged: {bounds:?}");
+    assert!(bounds.y + bounds.height <= 90, "{bounds:?}");
+    assert!(
+        bounds.x > 60,
+        "text before the word should be unchanged: {bounds:?}"
+    );
+    assert!(diff.changed_pixels <= (bounds.width * bounds.height) as u64);
+
+    let image = diff
+        .diff_image
+        .expect("changes should produce a diff image");
+    let decoder = png::Decoder::new(image.as_slice());
+    let reader = decoder.read_info().expect("diff image should be a PNG");
+    assert_eq!((reader.info().width, reader.info().height), (300, 120));
+}
+
+#[test]
+fn test_diff_counts_size_changes() {
+    let config = Config::new().size(100, 50).auto_height(true);
+    let short =
+        "<html><body style=\"margin: 0;\"><div style=\"height: 50px;\"></div></body></html>";
+    let tall = "<html><body style=\"margin: 0;\"><div style=\"height: 80px;\"></div></body></html>";
+
+    let diff = render_diff(short, tall, config).expect("diff should succeed");
+    assert_eq!((diff.width, diff.height), (100, 80));
+    assert_eq!(diff.changed_pixels, 100 * 30);
+}
+
+#[test]
+fn test_diff_image_has_straight_alpha() {
+    let page = |color: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div style="height: 10px; background: rgba(255, 255, 255, 0.5);"></div>
+                <div style="height: 10px; background: {color};"></div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().size(20, 20).transparent();
+
+    let diff = render_diff(&page("red"), &page("blue"), config).expect("diff should succeed");
+    let image = diff
+        .diff_image
+        .expect("changes should produce a diff image");
+    let mut reader = png::Decoder::new(image.as_slice())
+        .read_info()
+        .expect("diff image should be a PNG");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).expect("should decode");
+
+    // The unchanged translucent white stays white rather than darkening
+    // with its premultiplied color
+    assert_eq!(&pixels[..4], &[255, 255, 255, 128]);
+    // Changes are highlighted in red
+    let changed = (15 * 20 + 5) * 4;
+    assert_eq!(&pixels[changed..changed + 4], &[255, 0, 0, 255]);
+}
+
+#[test]
+fn test_diff_follows_png_pipeline() {
+    let page = |color: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div id="box" style="width: 40px; height: 30px; background: green;"></div>
+                <div style="height: 30px; background: {color};"></div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().size(100, 60);
+
+    // Cropped and captured away, the change isn't compared
+    let cropped = render_diff(
+        &page("red"),
+        &page("blue"),
+        config.clone().crop_margins(0, 0, 30, 0),
+    )
+    .expect("diff should succeed");
+    assert_eq!((cropped.width, cropped.height), (100, 30));
+    assert!(cropped.is_identical());
+
+    let captured = render_diff(&page("red"), &page("blue"), config.clone().capture("#box"))
+        .expect("diff should succeed");
+    assert_eq!((captured.width, captured.height), (40, 30));
+    assert!(captured.is_identical());
+
+    // Backdrop blurs are drawn as in PNG output
+    let blurred = r#"<html><body style="margin: 0;">
+        <div style="width: 50px; height: 60px; background: black;"></div>
+        <div style="position: absolute; top: 0; left: 0; width: 100px; height: 60px;
+                    backdrop-filter: blur(10px);"></div>
+    </body></html>"#;
+    let sharp = blurred.replace("backdrop-filter: blur(10px);", "");
+    let diff = render_diff(&sharp, blurred, config).expect("diff should succeed");
+    assert!(!diff.is_identical());
+}
@@ -0,0 +1,110 @@
+//! Integration tests for animated GIF rendering.
+
+#![cfg(feature = "gif")]
+
+use hyper_render::{render, Config, OutputFormat};
+use std::time::Duration;
+
+const SPINNER_HTML: &str = r#"
+    <html>
+    <head><style>
+        @keyframes pulse {
+            from { background: red; }
+            to { background: blue; }
+        }
+        body { margin: 0; }
+        .spinner { width: 40px; height: 40px; animation: pulse 1s linear infinite; }
+    </style></head>
+    <body><div class="spinner"></div></body>
+    </html>
+"#;
+
+/// Decode a GIF into its (width, height) and RGBA frames.
+fn decode_gif(data: &[u8]) -> (u16, u16, Vec<gif::Frame<'static>>) {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(data).expect("should read GIF header");
+
+    let (width, height) = (decoder.width(), decoder.height());
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().expect("should decode frame") {
+        frames.push(frame.clone());
+    }
+
+    (width, height, frames)
+}
+
+#[test]
+fn test_gif_three_frames() {
+    let config = Config::new()
+        .size(64, 48)
+        .format(OutputFormat::Gif)
+        .frames(3)
+        .frame_duration(Duration::from_millis(200));
+
+    let bytes = render(SPINNER_HTML, config).expect("GIF should render");
+    assert!(bytes.starts_with(b"GIF89a"), "output should be a GIF89a");
+
+    let (width, height, frames) = decode_gif(&bytes);
+    assert_eq!((width, height), (64, 48), "GIF size should match config");
+    assert_eq!(frames.len(), 3, "GIF should contain three frames");
+    assert!(
+        frames.iter().all(|f| f.delay == 20),
+        "200ms should be 20cs delays"
+    );
+
+    // The animated element's color should change between frames
+    let first_pixel = |f: &gif::Frame| f.buffer[..4].to_vec();
+    assert_ne!(
+        first_pixel(&frames[0]),
+        first_pixel(&frames[1]),
+        "animation should advance between frames"
+    );
+}
+
+#[test]
+fn test_gif_scale_and_single_frame() {
+    let config = Config::new()
+        .size(32, 32)
+        .scale(2.0)
+        .format(OutputFormat::Gif);
+
+    let bytes = render(SPINNER_HTML, config).expect("GIF should render");
+    let (width, height, frames) = decode_gif(&bytes);
+    assert_eq!((width, height), (64, 64), "GIF size should be scaled");
+    assert_eq!(frames.len(), 1, "default is a single frame");
+}
+
+#[test]
+fn test_gif_zero_frames_invalid() {
+    let config = Config::new().format(OutputFormat::Gif).frames(0);
+    assert!(
+        render(SPINNER_HTML, config).is_err(),
+        "zero frames should error"
+    );
+}
+
+#[test]
+fn test_gif_background_image_on_every_frame() {
+    use hyper_render::BackgroundFit;
+
+    let image = render(
+        r#"<html><body style="margin: 0; background: rgb(255, 0, 0);"></body></html>"#,
+        Config::new().size(16, 16),
+    )
+    .expect("background should render");
+    let config = Config::new()
+        .size(64, 48)
+        .format(OutputFormat::Gif)
+        .frames(3)
+        .background_image(image, BackgroundFit::Tile);
+
+    let bytes = render(SPINNER_HTML, config).expect("GIF should render");
+    let (width, _, frames) = decode_gif(&bytes);
+    assert_eq!(frames.len(), 3);
+    // Clear of the spinner, every frame shows the background image
+    let i = (44 * width as usize + 60) * 4;
+    for (n, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.buffer[i..i + 4], [255, 0, 0, 255], "frame {n}");
+    }
+}
@@ -2,7 +2,14 @@
 
 #![cfg(feature = "pdf")]
 
-use hyper_render::{render, render_to_pdf, Config, OutputFormat};
+mod common;
+
+#[cfg(feature = "png")]
+use common::base64;
+use hyper_render::{
+    render, render_detailed, render_pdf_streaming, render_to_pdf, Config, Error, ErrorPolicy,
+    LinearGradientSpec, OutputFormat, PageLabelRange, PageLabelStyle, PdfA, PdfVersion,
+};
 
 /// PDF magic bytes
 const PDF_SIGNATURE: &[u8] = b"%PDF-";
@@ -30,6 +37,20 @@ fn pdf_contains(data: &[u8], pattern: &[u8]) -> bool {
     data.windows(pattern.len()).any(|w| w == pattern)
 }
 
+/// Decompressed content stream of every page, concatenated.
+fn page_content(data: &[u8]) -> String {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    doc.get_pages()
+        .into_values()
+        .map(|page_id| {
+            let content = doc
+                .get_page_content(page_id)
+                .expect("should read page content");
+            String::from_utf8_lossy(&content).into_owned()
+        })
+        .collect()
+}
+
 #[test]
 fn test_pdf_basic_render() {
     let html = "<html><body><h1>Hello</h1></body></html>";
@@ -98,6 +119,34 @@ fn test_pdf_auto_height() {
     assert!(is_valid_pdf(&result.unwrap()), "output should be valid PDF");
 }
 
+#[test]
+fn test_pdf_clip_to_page() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div style="margin-top: 60px; height: 100px; background: red;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(300, 100).format(OutputFormat::Pdf);
+
+    // The element runs 60px past the bottom of the page, where it is clipped
+    let content = page_content(&render(html, config.clone()).expect("should render"));
+    let clip = content
+        .find("0 100 m\n300 100 l\n300 0 l\n0 0 l\n0 100 l\nh\nW\nn")
+        .expect("content should be clipped to the page");
+    let element = content
+        .find("1 0 0 rg\n0 60 m\n300 60 l\n300 160 l")
+        .expect("element should be drawn");
+    assert!(clip < element, "clip should come before the element");
+
+    let content = page_content(&render(html, config.clip_to_page(false)).expect("should render"));
+    assert!(
+        !content.contains("W\nn"),
+        "content should bleed past the page: {content}"
+    );
+}
+
 #[test]
 fn test_pdf_with_text_content() {
     let html = r#"<html><body><p>Hello World</p></body></html>"#;
@@ -140,6 +189,20 @@ fn test_pdf_custom_page_background() {
     assert!(is_valid_pdf(&result.unwrap()), "output should be valid PDF");
 }
 
+#[test]
+fn test_pdf_undecodable_background_image_is_an_error() {
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .background_image(vec![1, 2, 3, 4], hyper_render::BackgroundFit::default());
+
+    // The error is returned rather than leaving the page half drawn
+    let result = render("<html><body><p>Text</p></body></html>", config);
+    assert!(
+        result.is_err(),
+        "an undecodable image should fail the render"
+    );
+}
+
 #[test]
 fn test_pdf_unicode_content() {
     let html = r#"<html><body><p>Unicode: 日本語 中文 한국어</p></body></html>"#;
@@ -221,6 +284,73 @@ fn test_pdf_flexbox_layout() {
     assert!(is_valid_pdf(&result.unwrap()), "output should be valid PDF");
 }
 
+#[test]
+fn test_pdf_legacy_grid_gap_matches_gap() {
+    let grid = |gap: &str| {
+        let cells = r#"<div style="height: 20px; background: red;"></div>"#.repeat(4);
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div style="display: grid; grid-template-columns: 50px 50px; {gap}">{cells}</div>
+            </body></html>"#
+        )
+    };
+    let content = |gap: &str| {
+        let config = Config::new().size(400, 300).format(OutputFormat::Pdf);
+        page_content(&render(&grid(gap), config).expect("grid should render"))
+    };
+
+    let modern = content("gap: 10px");
+    assert_eq!(content("grid-gap: 10px"), modern);
+    // The second cell is a column gap to the right, the third a row gap down
+    assert!(modern.contains("60 0 m\n110 0 l"), "{modern}");
+    assert!(modern.contains("0 30 m\n50 30 l"), "{modern}");
+
+    assert_eq!(content("grid-gap: 10px 20px"), content("gap: 10px 20px"));
+    assert_eq!(
+        content("grid-row-gap: 5px; grid-column-gap: 7px"),
+        content("row-gap: 5px; column-gap: 7px")
+    );
+}
+
+#[test]
+fn test_pdf_flex_shorthands_match_longhands() {
+    let row = |flex: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div style="display: flex; width: 300px;">
+                    <div style="{flex}; height: 20px; background: red;">Text</div>
+                    <div style="flex: 2 1 0%; height: 20px; background: blue;"></div>
+                </div>
+            </body></html>"#
+        )
+    };
+    let content = |flex: &str| {
+        let config = Config::new().size(400, 300).format(OutputFormat::Pdf);
+        page_content(&render(&row(flex), config).expect("flex row should render"))
+    };
+
+    for (shorthand, longhands) in [
+        ("flex: 1", "flex-grow: 1; flex-shrink: 1; flex-basis: 0%"),
+        (
+            "flex: auto",
+            "flex-grow: 1; flex-shrink: 1; flex-basis: auto",
+        ),
+        (
+            "flex: none",
+            "flex-grow: 0; flex-shrink: 0; flex-basis: auto",
+        ),
+        (
+            "flex: 50px",
+            "flex-grow: 1; flex-shrink: 1; flex-basis: 50px",
+        ),
+        ("flex: 2 3", "flex-grow: 2; flex-shrink: 3; flex-basis: 0%"),
+    ] {
+        assert_eq!(content(shorthand), content(longhands), "{shorthand}");
+    }
+    // A third of the row goes to `flex: 1` next to `flex: 2`
+    assert!(content("flex: 1").contains("0 0 m\n100 0 l"));
+}
+
 #[test]
 fn test_pdf_minimum_dimensions() {
     let html = "<html><body></body></html>";
@@ -421,6 +551,27 @@ fn test_pdf_box_shadow_basic() {
     assert!(is_valid_pdf(&result.unwrap()), "output should be valid PDF");
 }
 
+#[test]
+fn test_pdf_box_shadow_defaults_to_current_color() {
+    // The shadow has no color of its own and the element inherits `red`
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white; color: red;">
+            <div style="margin: 20px; width: 60px; height: 60px; box-shadow: 0 2px 4px;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(100, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+    assert!(
+        content.contains("1 0 0 rg"),
+        "shadow should be filled with the text color (red):\n{}",
+        content
+    );
+}
+
 #[test]
 fn test_pdf_box_shadow_with_spread() {
     let html = r#"
@@ -581,3 +732,2903 @@ fn test_pdf_border_with_alpha() {
     assert!(result.is_ok(), "border with alpha should render");
     assert!(is_valid_pdf(&result.unwrap()), "output should be valid PDF");
 }
+
+#[test]
+fn test_pdf_page_gradient() {
+    let html = "<html><body></body></html>";
+    let gradient = LinearGradientSpec::new(90.0)
+        .stop(0.0, [255, 0, 0, 255])
+        .stop(1.0, [0, 0, 255, 255]);
+
+    let plain = render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+    let bytes = render(
+        html,
+        Config::new()
+            .format(OutputFormat::Pdf)
+            .page_gradient(Some(gradient)),
+    )
+    .expect("page gradient should render");
+
+    assert!(is_valid_pdf(&bytes), "output should be valid PDF");
+    assert!(
+        !pdf_contains(&plain, b"/Shading"),
+        "plain page should not contain a shading"
+    );
+    assert!(
+        pdf_contains(&bytes, b"/Shading"),
+        "page gradient should emit a shading pattern"
+    );
+}
+
+#[test]
+fn test_pdf_mask_image_linear_gradient() {
+    let masked = r#"
+        <html>
+        <body>
+            <div style="width: 200px; height: 100px; background: red;
+                        mask-image: linear-gradient(black, transparent);"></div>
+        </body>
+        </html>
+    "#;
+    let plain = r#"
+        <html>
+        <body>
+            <div style="width: 200px; height: 100px; background: red;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let plain_bytes = render(plain, config.clone()).expect("plain should render");
+    let bytes = render(masked, config).expect("masked element should render");
+
+    assert!(is_valid_pdf(&bytes), "output should be valid PDF");
+    assert!(
+        !pdf_contains(&plain_bytes, b"/SMask"),
+        "unmasked element should not use a soft mask"
+    );
+    assert!(
+        pdf_contains(&bytes, b"/SMask") && pdf_contains(&bytes, b"/Alpha"),
+        "mask-image should produce an alpha soft mask"
+    );
+}
+
+/// The fill and stroke opacity (`/ca` and `/CA`) of every graphics state
+/// in the PDF, rounded to hundredths.
+fn graphics_state_opacities(data: &[u8]) -> Vec<(u32, u32)> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let opacity = |dict: &pdf_extract::Dictionary, key: &[u8]| {
+        let value = dict
+            .get(key)
+            .and_then(|value| value.as_float())
+            .unwrap_or(1.0);
+        (value * 100.0).round() as u32
+    };
+    doc.objects
+        .values()
+        .filter_map(|object| object.as_dict().ok())
+        .filter(|dict| dict.has_type(b"ExtGState"))
+        .map(|dict| (opacity(dict, b"ca"), opacity(dict, b"CA")))
+        .collect()
+}
+
+#[test]
+fn test_pdf_inline_opacity_fades_text_run() {
+    let html = r#"
+        <html>
+        <body>
+            <p>Solid <span style="opacity: 0.5">faded <span style="opacity: 0.6">fainter</span></span> solid</p>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("inline opacity should render");
+    assert!(is_valid_pdf(&bytes), "output should be valid PDF");
+
+    // Each text run is drawn in its own `q ... Q` group; only the faded
+    // runs set a graphics state
+    let content = page_content(&bytes);
+    let runs: Vec<&str> = content
+        .split(
+            "
+Q",
+        )
+        .filter(|run| run.contains("TJ"))
+        .collect();
+    let faded: Vec<&str> = runs
+        .iter()
+        .copied()
+        .filter(|run| run.contains(" gs"))
+        .collect();
+    assert_eq!(
+        runs.len(),
+        4,
+        "text should be drawn in four runs: {content}"
+    );
+    assert_eq!(
+        faded.len(),
+        2,
+        "only the span text should be faded: {content}"
+    );
+    let mut opacities = graphics_state_opacities(&bytes);
+    opacities.sort();
+    assert_eq!(
+        opacities,
+        vec![(30, 100), (50, 100)],
+        "nested span opacity should compose with its parent's, fading only the text fill"
+    );
+}
+
+#[test]
+fn test_pdf_element_opacity_fades_box() {
+    let html = r#"
+        <html>
+        <body>
+            <div style="opacity: 0.4; width: 100px; height: 50px; background: red;">Faded</div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("element opacity should render");
+    assert!(is_valid_pdf(&bytes), "output should be valid PDF");
+    assert_eq!(
+        graphics_state_opacities(&bytes),
+        vec![(40, 40)],
+        "element opacity should fade the element as a group"
+    );
+}
+
+/// Collects the position of every character drawn on a PDF page.
+#[derive(Default)]
+struct CharPositions {
+    page_height: f64,
+    chars: Vec<(String, f64, f64)>,
+}
+
+impl pdf_extract::OutputDev for CharPositions {
+    fn begin_page(
+        &mut self,
+        _page_num: u32,
+        media_box: &pdf_extract::MediaBox,
+        _art_box: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), pdf_extract::OutputError> {
+        self.page_height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &pdf_extract::Transform,
+        _width: f64,
+        _spacing: f64,
+        _font_size: f64,
+        char: &str,
+    ) -> Result<(), pdf_extract::OutputError> {
+        // PDF space has a bottom-left origin; report y from the top like CSS
+        self.chars
+            .push((char.to_string(), trm.m31, self.page_height - trm.m32));
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+}
+
+/// Return the `(char, x, y)` baseline position of every character drawn in
+/// the PDF, in drawing order, with y measured from the top of the page.
+fn char_positions(data: &[u8]) -> Vec<(String, f64, f64)> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let mut positions = CharPositions::default();
+    pdf_extract::output_doc(&doc, &mut positions).expect("should walk PDF content");
+    positions.chars
+}
+
+#[test]
+fn test_pdf_fixed_table_layout_column_offsets() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <table style="table-layout: fixed; width: 600px; border-spacing: 0;">
+                <tr>
+                    <td style="width: 100px; padding: 0;">A</td>
+                    <td style="width: 200px; padding: 0;">B</td>
+                    <td style="width: 300px; padding: 0;">C</td>
+                </tr>
+            </table>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(800)
+        .height(200)
+        .format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("fixed table should render");
+    let positions = char_positions(&bytes);
+
+    for (text, column_x) in [("A", 0.0), ("B", 100.0), ("C", 300.0)] {
+        let (_, x, _) = positions
+            .iter()
+            .find(|(c, _, _)| c == text)
+            .unwrap_or_else(|| panic!("cell text {:?} missing from PDF", text));
+        assert!(
+            (x - column_x).abs() < 1.0,
+            "cell text {:?} drawn at x={}, expected column offset {}",
+            text,
+            x,
+            column_x
+        );
+    }
+}
+
+#[test]
+fn test_pdf_zero_width_table_cell_text_still_renders() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <table style="table-layout: fixed; width: 300px; border-spacing: 0;">
+                <tr>
+                    <td style="width: 0; padding: 0;">Z</td>
+                    <td style="padding: 0;">Y</td>
+                </tr>
+            </table>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("table should render");
+    let positions = char_positions(&bytes);
+
+    assert!(
+        positions.iter().any(|(c, _, _)| c == "Z"),
+        "text in a zero-width cell should overflow visibly, not be dropped"
+    );
+}
+
+#[test]
+fn test_pdf_border_box_text_inside_padding() {
+    let html = |box_sizing: &str| {
+        format!(
+            r#"
+            <html>
+            <body style="margin: 0;">
+                <div style="box-sizing: {}; width: 200px; height: 100px;
+                            padding: 20px; border: 5px solid black;">X</div>
+            </body>
+            </html>
+        "#,
+            box_sizing
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    // Taffy reports border-box sizes and resolved padding/border either way, so
+    // the content origin is at border + padding for both box-sizing modes.
+    for box_sizing in ["border-box", "content-box"] {
+        let bytes = render(&html(box_sizing), config.clone()).expect("should render");
+        let positions = char_positions(&bytes);
+        let (_, x, y) = positions
+            .iter()
+            .find(|(c, _, _)| c == "X")
+            .expect("text should be drawn");
+        assert!(
+            (x - 25.0).abs() < 1.0,
+            "with box-sizing: {} text drawn at x={}, expected 25 (border + padding)",
+            box_sizing,
+            x
+        );
+        assert!(
+            *y > 25.0 && *y < 125.0,
+            "with box-sizing: {} text baseline at y={}, expected inside the content box",
+            box_sizing,
+            y
+        );
+    }
+}
+
+#[test]
+fn test_pdf_text_align_start_follows_direction() {
+    let html = |direction: &str, align: &str| {
+        format!(
+            r#"
+            <html>
+            <body style="margin: 0;">
+                <p style="width: 400px; margin: 0; direction: {}; text-align: {};">Hi</p>
+            </body>
+            </html>
+        "#,
+            direction, align
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    let first_glyph_x = |direction: &str, align: &str| {
+        let bytes = render(&html(direction, align), config.clone()).expect("should render");
+        char_positions(&bytes)
+            .first()
+            .map(|(_, x, _)| *x)
+            .expect("text should be drawn")
+    };
+
+    // "Hi" is about 20px wide, so right-aligned text starts past x=350
+    let ltr_start = first_glyph_x("ltr", "start");
+    let rtl_start = first_glyph_x("rtl", "start");
+    let ltr_end = first_glyph_x("ltr", "end");
+    let rtl_end = first_glyph_x("rtl", "end");
+    assert!(
+        ltr_start < 1.0,
+        "LTR start should left-align, got x={}",
+        ltr_start
+    );
+    assert!(
+        rtl_start > 350.0,
+        "RTL start should right-align, got x={}",
+        rtl_start
+    );
+    assert!(
+        ltr_end > 350.0,
+        "LTR end should right-align, got x={}",
+        ltr_end
+    );
+    assert!(
+        rtl_end < 1.0,
+        "RTL end should left-align, got x={}",
+        rtl_end
+    );
+}
+
+#[test]
+fn test_pdf_text_align_last() {
+    let html = |align_last: &str| {
+        format!(
+            r#"
+            <html>
+            <body style="margin: 0;">
+                <p style="width: 300px; margin: 0; font-size: 16px;
+                    text-align: justify; text-align-last: {};">
+                    The quick brown fox jumps over the lazy dog and keeps running
+                    across the field until the sun goes down over the hills
+                </p>
+            </body>
+            </html>
+        "#,
+            align_last
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    // (first x, last x) of each line's characters, top to bottom. The
+    // extractor tracks the spacing Krilla adds between glyphs but not the
+    // glyph advances, so x grows along a line only where spaces are stretched.
+    let line_extents = |align_last: &str| {
+        let bytes = render(&html(align_last), config.clone()).expect("should render");
+        let mut lines: Vec<(f64, f64, f64)> = Vec::new();
+        for (_, x, y) in char_positions(&bytes) {
+            match lines.last_mut() {
+                Some((line_y, _, last)) if (*line_y - y).abs() < 1.0 => *last = x,
+                _ => lines.push((y, x, x)),
+            }
+        }
+        lines
+            .into_iter()
+            .map(|(_, first, last)| (first, last))
+            .collect::<Vec<_>>()
+    };
+
+    let right = line_extents("right");
+    assert!(right.len() >= 3, "text should wrap: {:?}", right);
+    let (final_line, justified) = right.split_last().unwrap();
+    for &(first, last) in justified {
+        assert!(first < 1.0, "justified line should start at 0: {:?}", right);
+        assert!(
+            last > first,
+            "justified line should stretch spaces: {:?}",
+            right
+        );
+    }
+    assert!(
+        final_line.0 > 50.0 && final_line.0 == final_line.1,
+        "final line should be right-aligned, not justified: {:?}",
+        right
+    );
+
+    let auto = line_extents("auto");
+    let final_line = auto.last().unwrap();
+    assert!(
+        final_line.0 < 1.0 && final_line.0 == final_line.1,
+        "{:?}",
+        auto
+    );
+
+    // Centering leaves half the space a right-aligned line has
+    let right_start = right.last().unwrap().0;
+    let center = line_extents("center");
+    let centered = center.last().unwrap().0;
+    assert!((centered - right_start / 2.0).abs() < 1.0, "{:?}", center);
+
+    let justify = line_extents("justify");
+    let final_line = justify.last().unwrap();
+    assert!(final_line.0 < 1.0 && final_line.1 > 1.0, "{:?}", justify);
+}
+
+#[test]
+fn test_pdf_version_header() {
+    let html = "<html><body><p>Version</p></body></html>";
+
+    for (version, header) in [
+        (PdfVersion::Pdf14, b"%PDF-1.4"),
+        (PdfVersion::Pdf17, b"%PDF-1.7"),
+        (PdfVersion::Pdf20, b"%PDF-2.0"),
+    ] {
+        let config = Config::new().format(OutputFormat::Pdf).pdf_version(version);
+        let bytes = render(html, config).expect("should render");
+        assert!(
+            bytes.starts_with(header),
+            "PDF {} should start with its version header",
+            version
+        );
+    }
+}
+
+#[test]
+fn test_pdf_a2b_embeds_output_intent() {
+    let html = "<html><body><h1>Archive</h1><p>Long-term storage</p></body></html>";
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .pdf_conformance(Some(PdfA::A2b));
+
+    let plain = render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+    let bytes = render(html, config).expect("PDF/A-2b should render");
+
+    assert!(
+        bytes.starts_with(b"%PDF-1.7"),
+        "PDF/A-2b defaults to PDF 1.7"
+    );
+    assert!(
+        !pdf_contains(&plain, b"/OutputIntents"),
+        "plain PDF should not carry an output intent"
+    );
+    assert!(
+        pdf_contains(&bytes, b"/OutputIntents"),
+        "PDF/A should embed an output intent"
+    );
+    assert!(
+        pdf_contains(&bytes, b"pdfaid:part"),
+        "PDF/A should declare its conformance in XMP metadata"
+    );
+}
+
+#[test]
+fn test_pdf_a_incompatible_version_rejected() {
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .pdf_version(PdfVersion::Pdf20)
+        .pdf_conformance(Some(PdfA::A2b));
+
+    assert!(config.validate().is_err());
+    let result = render("<html><body></body></html>", config);
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn test_pdf_a_rejects_external_resources() {
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .pdf_conformance(Some(PdfA::A2b));
+    let image = r#"<img src="https://example.com/logo.png" width="10" height="10">"#;
+    let stylesheet = r#"<link rel="stylesheet" href="https://example.com/fonts.css"><p>Text</p>"#;
+
+    for (html, url) in [
+        (image, "https://example.com/logo.png"),
+        (stylesheet, "https://example.com/fonts.css"),
+    ] {
+        let result = render(html, config.clone());
+        assert!(
+            matches!(&result, Err(Error::Network { url: failed, reason })
+                if failed == url && reason.contains("PDF/A-2b")),
+            "PDF/A can't go without {url}, got {:?}",
+            result.map(|bytes| bytes.len())
+        );
+        // Without PDF/A the resource is left out with a warning
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+    }
+
+    // Embedded resources are fine
+    let embedded = r#"<img src="data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' width='4' height='4'/%3E">"#;
+    render(embedded, config).expect("PDF/A should render data: URIs");
+}
+
+#[test]
+fn test_pdf_a1b_rejects_transparency() {
+    let html = r#"
+        <html>
+        <body>
+            <div style="width: 100px; height: 100px; background: red;
+                        mask-image: linear-gradient(black, transparent);"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .pdf_version(PdfVersion::Pdf14)
+        .pdf_conformance(Some(PdfA::A1b));
+
+    let result = render(html, config);
+    assert!(
+        matches!(&result, Err(Error::PdfCreate(msg)) if msg.contains("PDF/A-1b")),
+        "PDF/A-1b forbids transparency, got {:?}",
+        result.map(|bytes| bytes.len())
+    );
+}
+
+#[test]
+fn test_pdf_overflow_wrap_anywhere_breaks_long_url() {
+    // A 40-character URL in a 100px column
+    let html = |wrap: &str| {
+        format!(
+            r#"
+            <html>
+            <body style="margin: 0;">
+                <div style="width: 100px; overflow-wrap: {};">https://averyveryverylongdomainnamexx.io</div>
+            </body>
+            </html>
+        "#,
+            wrap
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    let line_count = |wrap: &str| {
+        let bytes = render(&html(wrap), config.clone()).expect("should render");
+        let mut baselines: Vec<i64> = char_positions(&bytes)
+            .iter()
+            .map(|(_, _, y)| y.round() as i64)
+            .collect();
+        baselines.dedup();
+        baselines.len()
+    };
+
+    // The 32-letter host is about 250px wide: without a break opportunity it
+    // overflows on a single line after "https://"
+    assert_eq!(
+        line_count("normal"),
+        2,
+        "normal wrapping only breaks after //"
+    );
+    let anywhere = line_count("anywhere");
+    assert!(
+        anywhere >= 4,
+        "overflow-wrap: anywhere should split the host across lines, got {} lines",
+        anywhere
+    );
+}
+
+#[test]
+fn test_pdf_skips_hidden_content() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div>Shown</div>
+            <div hidden>Secret</div>
+            <div style="content-visibility: hidden; height: 40px;">Skipped</div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let text: String = char_positions(&bytes)
+        .into_iter()
+        .map(|(c, _, _)| c)
+        .collect();
+    assert!(text.contains("Shown"), "visible text missing: {:?}", text);
+    assert!(
+        !text.contains("Secret"),
+        "[hidden] content should not be drawn: {:?}",
+        text
+    );
+    assert!(
+        !text.contains("Skipped"),
+        "content-visibility: hidden content should not be drawn: {:?}",
+        text
+    );
+}
+
+#[test]
+fn test_pdf_display_contents_has_no_box() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="padding: 10px;">
+                <div style="display: contents; background: red; border: 5px solid green;">
+                    <div style="width: 30px; height: 20px; background: blue;"></div>
+                </div>
+            </div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(100, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+    // Only the white page and body backgrounds and the blue child are filled
+    let fills: Vec<&str> = content.lines().filter(|l| l.ends_with(" rg")).collect();
+    assert!(
+        fills.iter().all(|f| *f == "1 1 1 rg" || *f == "0 0 1 rg"),
+        "display: contents wrapper should not paint a background or border: {:?}",
+        fills
+    );
+    // The child sits at the grandparent's content box, untouched by the wrapper's border
+    assert!(
+        content.contains("0 0 1 rg\n10 10 m\n40 10 l\n40 30 l\n10 30 l"),
+        "child should be painted at (10, 10):\n{}",
+        content
+    );
+}
+
+#[test]
+fn test_pdf_vertical_align_middle_inline_image() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="font-size: 40px; line-height: 80px;">Xx<img style="width: 10px; height: 10px; background: red; vertical-align: middle;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let baseline = char_positions(&bytes)[0].2;
+
+    // The red fill is followed by the path's top-left `x y m` move
+    let content = page_content(&bytes);
+    let mut lines = content.lines();
+    lines
+        .find(|line| *line == "1 0 0 rg")
+        .expect("image box should be filled");
+    let top: f64 = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|y| y.parse().ok())
+        .expect("fill should start with a move");
+
+    // The box's midpoint sits half an x-height (about 0.25em) above the baseline
+    let middle = top + 5.0;
+    assert!(
+        (middle - (baseline - 10.0)).abs() < 1.0,
+        "image center at {} should be 10px above the baseline at {}",
+        middle,
+        baseline
+    );
+}
+
+#[test]
+fn test_pdf_styled_spans_draw_text_once() {
+    // Parley shapes the paragraph as one run and splits it into a glyph run
+    // per style; each glyph run draws only its own glyphs
+    let html = r#"<p>Plain <b>bold</b> and <span style="color: red;">red</span> end.</p>"#;
+    let bytes = render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    assert_eq!(
+        words,
+        ["Plain", "bold", "and", "red", "end."],
+        "got {text:?}"
+    );
+}
+
+#[test]
+fn test_pdf_q_element_quotation_marks() {
+    let text_of_with = |html: &str, config: Config| -> String {
+        let bytes = render(html, config).expect("should render");
+        // Spaces may or may not be drawn as glyphs, so compare without them
+        char_positions(&bytes)
+            .into_iter()
+            .map(|(c, _, _)| c)
+            .filter(|c| !c.trim().is_empty())
+            .collect()
+    };
+    let text_of = |html: &str| text_of_with(html, Config::new().format(OutputFormat::Pdf));
+
+    let text = text_of("<p>She said <q>hello <q>there</q></q> twice</p>");
+    assert!(
+        text.contains("\u{201C}hello\u{2018}there\u{2019}\u{201D}"),
+        "nested <q> should use outer and inner quotation marks: {:?}",
+        text
+    );
+
+    let text = text_of(r#"<p lang="de">Er sagte <q>hallo</q></p>"#);
+    assert!(
+        text.contains("\u{201E}hallo\u{201C}"),
+        "lang should select localized quotation marks: {:?}",
+        text
+    );
+
+    let config = Config::new().format(OutputFormat::Pdf).lang("fr");
+    let text = text_of_with("<p>Il a dit <q>bonjour</q></p>", config);
+    assert!(
+        text.contains("\u{AB}bonjour\u{BB}"),
+        "Config::lang should select localized quotation marks: {:?}",
+        text
+    );
+
+    let text = text_of(r#"<p><q style="quotes: '<' '>'">custom</q></p>"#);
+    assert!(
+        text.contains("<custom>"),
+        "the quotes property should override the defaults: {:?}",
+        text
+    );
+}
+
+#[test]
+fn test_pdf_streaming_reports_each_page() {
+    let html = "<html><body><h1>Streaming</h1><p>One page at a time</p></body></html>";
+    let config = Config::new().size(300, 200);
+
+    let mut pages = Vec::new();
+    let bytes = render_pdf_streaming(html, config.clone(), |index, page| {
+        pages.push((index, *page));
+    })
+    .expect("should render");
+
+    let page_count = render_detailed(html, config.clone().format(OutputFormat::Pdf))
+        .expect("should render")
+        .page_count;
+    assert_eq!(pages.len(), page_count as usize);
+    assert_eq!(pages[0].0, 0);
+    assert_eq!((pages[0].1.width, pages[0].1.height), (300.0, 200.0));
+
+    let expected = render_to_pdf(html, config).expect("should render");
+    assert!(bytes == expected, "streaming should not change the output");
+}
+
+#[test]
+fn test_pdf_streaming_placeholder_reports_pages_again() {
+    // PDF/A-1b forbids the mask's transparency, which is only found once
+    // the page has been written
+    let html = r#"
+        <html>
+        <body>
+            <div style="width: 100px; height: 100px; background: red;
+                        mask-image: linear-gradient(black, transparent);"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .size(300, 200)
+        .pdf_version(PdfVersion::Pdf14)
+        .pdf_conformance(Some(PdfA::A1b))
+        .on_error(ErrorPolicy::Placeholder);
+
+    let mut pages = Vec::new();
+    let bytes = render_pdf_streaming(html, config, |index, page| {
+        pages.push((index, page.width, page.height));
+    })
+    .expect("the placeholder should render");
+
+    // The failed render's page, then the placeholder's
+    assert_eq!(pages, vec![(0, 300.0, 200.0), (0, 300.0, 200.0)]);
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("Render failed"), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_css_zoom_scales_element() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="zoom: 2; width: 30px; height: 20px; margin-left: 5px; background: blue;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+    // Width, height and margin are all doubled
+    assert!(
+        content.contains("0 0 1 rg\n10 0 m\n70 0 l\n70 40 l\n10 40 l"),
+        "zoomed box should be painted as 60x40 at (10, 0):\n{}",
+        content
+    );
+}
+
+#[test]
+fn test_pdf_custom_properties_and_current_color() {
+    let html = r#"
+        <html>
+        <head><style>
+            :root { --brand: #f08; }
+            body { margin: 0; background: white; color: var(--brand); }
+            .swatch { width: 20px; height: 20px; background: currentColor; }
+        </style></head>
+        <body><p>Brand</p><div class="swatch"></div></body>
+        </html>
+    "#;
+    let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+    // #f08 is rgb(255, 0, 136)
+    let pink = "1 0 0.53333336 rg";
+    assert!(
+        content.contains(&format!("{}\nBT", pink)),
+        "text should be filled with the custom property color:\n{}",
+        content
+    );
+    assert!(
+        content.contains(&format!("{}\n0 51 m\n20 51 l", pink)),
+        "background: currentColor should resolve to the text color:\n{}",
+        content
+    );
+}
+
+#[test]
+fn test_pdf_figure_with_caption() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white; font-size: 16px;">
+            <figure>
+                <img width="100" height="50" style="background: red;">
+                <figcaption>Figure 1. A caption</figcaption>
+            </figure>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(300, 200).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    // The UA stylesheet gives <figure> 1em vertical and 40px horizontal margins
+    let content = page_content(&bytes);
+    assert!(
+        content.contains("1 0 0 rg\n40 16 m\n140 16 l\n140 66 l\n40 66 l"),
+        "image should be painted at (40, 16):\n{}",
+        content
+    );
+
+    let chars = char_positions(&bytes);
+    let caption: String = chars
+        .iter()
+        .map(|(c, _, _)| c.as_str())
+        .filter(|c| !c.trim().is_empty())
+        .collect();
+    assert_eq!(
+        caption, "Figure1.Acaption",
+        "caption text should be drawn: {:?}",
+        caption
+    );
+    let (_, x, baseline) = chars[0];
+    assert!(
+        (x - 40.0).abs() < 0.5,
+        "caption should be indented with the figure, got x = {}",
+        x
+    );
+    assert!(
+        baseline > 66.0 + 10.0 && baseline < 66.0 + 30.0,
+        "caption baseline at {} should be on the line below the image",
+        baseline
+    );
+}
+
+#[test]
+fn test_pdf_overlay_draws_on_page_surface() {
+    use hyper_render::krilla::color::rgb;
+    use hyper_render::krilla::geom::PathBuilder;
+    use hyper_render::krilla::num::NormalizedF32;
+    use hyper_render::krilla::paint::{Fill, FillRule};
+    use hyper_render::OverlayTarget;
+
+    let html = "<html><body style=\"margin: 0; background: white;\"><p>Report</p></body></html>";
+    let config = Config::new()
+        .size(200, 100)
+        .format(OutputFormat::Pdf)
+        .overlay(|target| {
+            if let OverlayTarget::Pdf {
+                surface,
+                width,
+                height,
+            } = target
+            {
+                // A red mark in the bottom-right corner
+                let mut builder = PathBuilder::new();
+                builder.move_to(width - 10.0, height - 10.0);
+                builder.line_to(width, height - 10.0);
+                builder.line_to(width, height);
+                builder.line_to(width - 10.0, height);
+                builder.close();
+                surface.set_fill(Some(Fill {
+                    paint: rgb::Color::new(255, 0, 0).into(),
+                    opacity: NormalizedF32::ONE,
+                    rule: FillRule::NonZero,
+                }));
+                surface.draw_path(&builder.finish().expect("path should be valid"));
+            }
+        });
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+    let mark = content
+        .find("1 0 0 rg\n190 90 m\n200 90 l\n200 100 l\n190 100 l")
+        .unwrap_or_else(|| panic!("overlay mark should be drawn:\n{}", content));
+    assert!(
+        content.find("BT").is_some_and(|text| text < mark),
+        "overlay should be drawn after the document:\n{}",
+        content
+    );
+}
+
+#[test]
+fn test_pdf_rotate_output_swaps_page_size() {
+    use hyper_render::{render_pdf_streaming, PdfPage, Rotation};
+
+    let html = "<html><body style=\"margin: 0;\"><p>Label</p></body></html>";
+    let config = Config::new().size(100, 50).rotate_output(Rotation::Deg90);
+
+    let mut pages = Vec::new();
+    let bytes = render_pdf_streaming(html, config, |_, page| pages.push(*page))
+        .expect("render should succeed");
+    assert!(is_valid_pdf(&bytes));
+    assert_eq!(
+        pages,
+        [PdfPage {
+            width: 50.0,
+            height: 100.0
+        }]
+    );
+    // Krilla folds its y-axis flip into the rotation
+    assert!(page_content(&bytes).contains("0 -1 -1 0 50 100 cm"));
+}
+
+#[test]
+fn test_pdf_first_letter_drop_cap() {
+    let html = r#"<html><head><style>
+        p { font-size: 16px; color: black; }
+        p::first-letter { font-size: 3em; color: red; }
+    </style></head><body><p>“Once upon a time</p></body></html>"#;
+    let config = Config::new().size(300, 100).format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let content = page_content(&bytes);
+
+    // The drop cap is set in its own larger, red run ahead of the rest of the text
+    let drop_cap = content.find(" 48 Tf").expect("first letter should be 48px");
+    let body = content
+        .find(" 16 Tf")
+        .expect("remaining text should be 16px");
+    assert!(drop_cap < body);
+    assert!(content[..drop_cap].contains("1 0 0 rg"));
+
+    // The opening quote is part of the first letter
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("“O"), "got {text:?}");
+    assert!(text.contains("nce upon a time"), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_contain_paint_clips_overflowing_child() {
+    let page = |contain: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div style="margin: 10px; width: 100px; height: 50px; {contain}">
+                    <div style="width: 300px; height: 200px; background: red;"></div>
+                </div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().size(400, 300).format(OutputFormat::Pdf);
+
+    // Clipped to the container's padding box (in PDF coordinates, y up)
+    let clip = "10 290 m\n110 290 l\n110 240 l\n10 240 l\n10 290 l\nh\nW\nn";
+    for contain in [
+        "contain: paint;",
+        "contain: content;",
+        "contain: layout paint;",
+    ] {
+        let content = page_content(&render(&page(contain), config.clone()).unwrap());
+        assert!(content.contains(clip), "{contain} should clip: {content}");
+        // Layout containment leaves the child where it was
+        assert!(content.contains("10 10 m\n310 10 l\n310 210 l"));
+    }
+
+    for contain in ["", "contain: layout;"] {
+        let content = page_content(&render(&page(contain), config.clone()).unwrap());
+        assert!(!content.contains(clip), "{contain:?} should not clip");
+        assert!(content.contains("10 10 m\n310 10 l\n310 210 l"));
+    }
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_background_image() {
+    use hyper_render::BackgroundFit;
+
+    // Render the input image with the PNG backend
+    let image = render(
+        r#"<html><body style="margin: 0; background: #cc3366;"></body></html>"#,
+        Config::new().size(40, 20),
+    )
+    .expect("render should succeed");
+    let html = "<html><body><p>Hello</p></body></html>";
+
+    for fit in [
+        BackgroundFit::Cover,
+        BackgroundFit::Contain,
+        BackgroundFit::Center,
+    ] {
+        let config = Config::new()
+            .size(200, 100)
+            .format(OutputFormat::Pdf)
+            .background_image(image.clone(), fit);
+        let pdf = render(html, config).expect("render should succeed");
+        assert!(pdf_contains(&pdf, b"/Subtype /Image"), "{fit:?}");
+        let content = page_content(&pdf);
+        assert!(
+            content.contains(" Do"),
+            "{fit:?} should draw the image: {content}"
+        );
+    }
+
+    // Contain scales the 40x20 image to fill the page; Center keeps its size
+    let placed = |fit| {
+        let config = Config::new()
+            .size(200, 100)
+            .format(OutputFormat::Pdf)
+            .background_image(image.clone(), fit);
+        page_content(&render(html, config).unwrap())
+    };
+    assert!(placed(BackgroundFit::Contain).contains("200 0 0 100 0 0 cm\n/x0 Do"));
+    assert!(placed(BackgroundFit::Center).contains("40 0 0 20 80 40 cm\n/x0 Do"));
+
+    // Tiling fills the page with a pattern
+    let config = Config::new()
+        .size(200, 100)
+        .format(OutputFormat::Pdf)
+        .background_image(image, BackgroundFit::Tile);
+    let pdf = render(html, config).expect("render should succeed");
+    assert!(pdf_contains(&pdf, b"/PatternType 1"));
+    assert!(page_content(&pdf).contains("/Pattern cs"));
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_element_background_image_position() {
+    // A 20x20 input image, rendered with the PNG backend
+    let image = render(
+        r#"<html><body style="margin: 0; background: #cc3366;"></body></html>"#,
+        Config::new().size(20, 20),
+    )
+    .expect("render should succeed");
+    let content = |background: &str| {
+        let html = format!(
+            r#"<html><body style="margin: 0;">
+                <div style="margin: 10px; width: 100px; height: 50px; border: 2px solid blue;
+                    background: url(data:image/png;base64,{}) {background};"></div>
+            </body></html>"#,
+            base64(&image)
+        );
+        let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+        page_content(&render(&html, config).expect("render should succeed"))
+    };
+
+    // Flush with the bottom-right corner of the 100x50 padding box at
+    // (12, 12): its top-left at (92, 42), 38pt up from the page's bottom
+    assert!(content("no-repeat bottom right").contains("20 0 0 20 92 38 cm\n/x0 Do"));
+    // Centered in the 80x30 left over, at (52, 27)
+    assert!(content("no-repeat center").contains("20 0 0 20 52 53 cm\n/x0 Do"));
+
+    // Repeating along x fills the border box's width, in the image's row
+    let row = content("repeat-x bottom right");
+    assert!(row.contains("/Pattern cs"));
+    assert!(row.contains("10 42 m\n114 42 l\n114 62 l"), "{row}");
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_first_background_layer_is_drawn_on_top() {
+    let image = render(
+        r#"<html><body style="margin: 0; background: #cc3366;"></body></html>"#,
+        Config::new().size(20, 20),
+    )
+    .expect("render should succeed");
+    let html = format!(
+        r#"<html><body style="margin: 0;">
+            <div style="width: 100px; height: 100px;
+                background: url(data:image/png;base64,{}) no-repeat, linear-gradient(red, blue);"></div>
+        </body></html>"#,
+        base64(&image)
+    );
+    let config = Config::new().size(200, 200).format(OutputFormat::Pdf);
+    let content = page_content(&render(&html, config).expect("render should succeed"));
+
+    let gradient = content.find("/p0 scn").expect("gradient should be drawn");
+    let image = content.find("/x0 Do").expect("image should be drawn");
+    assert!(gradient < image, "{content}");
+}
+
+/// The `/Interpolate` flag of each image XObject in a PDF
+#[cfg(feature = "png")]
+fn image_interpolation(data: &[u8]) -> Vec<bool> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    doc.objects
+        .values()
+        .filter_map(|object| match object {
+            pdf_extract::Object::Stream(stream) => Some(&stream.dict),
+            _ => None,
+        })
+        .filter(|dict| {
+            dict.get(b"Subtype")
+                .and_then(|subtype| subtype.as_name())
+                .is_ok_and(|subtype| subtype == b"Image")
+        })
+        .map(|dict| {
+            dict.get(b"Interpolate")
+                .and_then(|interpolate| interpolate.as_bool())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_image_rendering_pixelated_disables_interpolation() {
+    let image = render(
+        r#"<html><body style="margin: 0; background: #cc3366;"></body></html>"#,
+        Config::new().size(20, 20),
+    )
+    .expect("render should succeed");
+    let interpolation = |rendering: &str, conformance: Option<PdfA>| {
+        let html = format!(
+            r#"<html><body style="margin: 0;">
+                <div style="width: 100px; height: 100px; image-rendering: {rendering};
+                    background: url(data:image/png;base64,{}) no-repeat;
+                    background-size: cover;"></div>
+            </body></html>"#,
+            base64(&image)
+        );
+        let config = Config::new()
+            .size(200, 200)
+            .format(OutputFormat::Pdf)
+            .pdf_conformance(conformance);
+        image_interpolation(&render(&html, config).expect("render should succeed"))
+    };
+
+    assert_eq!(interpolation("auto", None), vec![true]);
+    assert_eq!(interpolation("pixelated", None), vec![false]);
+    assert_eq!(interpolation("crisp-edges", None), vec![false]);
+    // PDF/A forbids interpolation outright
+    assert_eq!(interpolation("auto", Some(PdfA::A2b)), vec![false]);
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_max_decoded_image_dimension_downscales_background_image() {
+    use hyper_render::BackgroundFit;
+
+    let image = render(
+        r#"<html><body style="margin: 0; background: linear-gradient(to right, #cc3366, #3366cc);"></body></html>"#,
+        Config::new().size(2000, 1000),
+    )
+    .expect("render should succeed");
+    let config = Config::new()
+        .size(200, 100)
+        .format(OutputFormat::Pdf)
+        .background_image(image, BackgroundFit::Contain);
+
+    let full = render("<html><body></body></html>", config.clone()).unwrap();
+    assert!(pdf_contains(&full, b"/Width 2000"));
+
+    let downscaled = render(
+        "<html><body></body></html>",
+        config.max_decoded_image_dimension(400),
+    )
+    .unwrap();
+    assert!(pdf_contains(&downscaled, b"/Width 400"));
+    assert!(pdf_contains(&downscaled, b"/Height 200"));
+    assert!(
+        downscaled.len() < full.len(),
+        "downscaled PDF ({} bytes) should be smaller than the original ({} bytes)",
+        downscaled.len(),
+        full.len()
+    );
+    // Still drawn over the whole page
+    assert!(page_content(&downscaled).contains("200 0 0 100 0 0 cm\n/x0 Do"));
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_max_decoded_image_dimension_skips_element_background_image() {
+    let image = render(
+        r#"<html><body style="margin: 0; background: linear-gradient(to right, #cc3366, #3366cc);"></body></html>"#,
+        Config::new().size(800, 400),
+    )
+    .expect("render should succeed");
+    let html = format!(
+        r#"<html><body style="margin: 0;">
+            <div style="width: 800px; height: 400px;
+                background: url(data:image/png;base64,{}) no-repeat;"></div>
+        </body></html>"#,
+        base64(&image)
+    );
+    let config = Config::new().size(800, 400).format(OutputFormat::Pdf);
+
+    let full = render(&html, config.clone()).unwrap();
+    assert!(pdf_contains(&full, b"/Width 800"));
+
+    let limited = render(&html, config.max_decoded_image_dimension(200)).unwrap();
+    assert!(!pdf_contains(&limited, b"/Width"));
+    assert!(!page_content(&limited).contains("/x0 Do"));
+}
+
+#[test]
+fn test_pdf_form_control_values() {
+    let html = r#"<html><body>
+        <input value="Hello">
+        <textarea>World</textarea>
+    </body></html>"#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("Hello"), "got {text:?}");
+    assert!(text.contains("World"), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_autosize_text_shrinks_overflowing_text() {
+    let html = r#"<html><body>
+        <div class="label" style="width: 100px; font-size: 20px;">Supercalifragilistic</div>
+        <div class="label" style="width: 300px; font-size: 20px;">Short</div>
+    </body></html>"#;
+    let font_sizes = |config: Config| -> Vec<f32> {
+        let bytes = render(html, config.format(OutputFormat::Pdf)).expect("render should succeed");
+        page_content(&bytes)
+            .lines()
+            .filter_map(|line| line.strip_suffix(" Tf")?.rsplit(' ').next()?.parse().ok())
+            .collect()
+    };
+
+    assert_eq!(font_sizes(Config::new()), vec![20.0, 20.0]);
+
+    // The long word is shrunk into its box, while the short one already fits
+    let sizes = font_sizes(Config::new().autosize_text(".label"));
+    assert_eq!(sizes.len(), 2, "got {sizes:?}");
+    assert!(sizes[0] < 12.0 && sizes[0] > 8.0, "got {sizes:?}");
+    assert_eq!(sizes[1], 20.0);
+}
+
+#[test]
+fn test_pdf_autosize_text_invalid_selector() {
+    let config = Config::new().format(OutputFormat::Pdf).autosize_text("[[");
+    let result = render("<p>Hello</p>", config);
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("autosize_text")),
+        "got {:?}",
+        result.map(|_| ())
+    );
+}
+
+#[test]
+fn test_pdf_list_markers() {
+    let list = |style: &str| {
+        format!(
+            r#"<html><body>
+                <ul style="{style}"><li>Apple</li><li>Pear</li></ul>
+                <ol style="{style}"><li>One</li></ol>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    let text = |html: String| {
+        let bytes = render(&html, config.clone()).expect("render should succeed");
+        pdf_extract::extract_text_from_mem(&bytes).expect("should extract text")
+    };
+
+    let default = text(list(""));
+    assert!(default.contains("• Apple"), "got {default:?}");
+    assert!(default.contains("1."), "got {default:?}");
+
+    for style in ["list-style: none", "list-style-type: none"] {
+        let suppressed = text(list(style));
+        assert!(suppressed.contains("Apple"), "got {suppressed:?}");
+        assert!(
+            !suppressed.contains('•') && !suppressed.contains("1."),
+            "{style} should suppress markers, got {suppressed:?}"
+        );
+    }
+}
+
+#[test]
+fn test_pdf_decimal_leading_zero_markers() {
+    let html = r#"<html><head><style>
+        ol { list-style-type: decimal-leading-zero; }
+    </style></head><body>
+        <ol><li>One</li><li>Two</li></ol>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+
+    let first = text.find("01.").expect("first marker should be 01.");
+    let second = text.find("02.").expect("second marker should be 02.");
+    assert!(first < text.find("One").unwrap(), "got {text:?}");
+    assert!(
+        first < second && second < text.find("Two").unwrap(),
+        "got {text:?}"
+    );
+}
+
+#[test]
+fn test_pdf_long_reversed_list_markers() {
+    let items: String = (0..500).map(|i| format!("<li>Item{i}</li>")).collect();
+    let html = format!(
+        r#"<html><body><ol reversed style="list-style-type: lower-roman">{items}</ol></body></html>"#
+    );
+    let config = Config::new().format(OutputFormat::Pdf).auto_height(true);
+    let bytes = render(&html, config).expect("render should succeed");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+
+    // Counting down from the number of items
+    for line in ["d. Item0", "cdl. Item50", "i. Item499"] {
+        assert!(
+            text.lines()
+                .any(|drawn| drawn.split_whitespace().eq(line.split_whitespace())),
+            "expected {line:?} in {text:?}"
+        );
+    }
+}
+
+#[test]
+fn test_pdf_counter_styles() {
+    let html = r#"<html><head><style>
+        @counter-style stars { system: symbolic; symbols: "*"; suffix: ") "; }
+        .greek { list-style-type: lower-greek; }
+        .stars { list-style: stars inside; }
+    </style></head><body>
+        <ol class="greek" start="2"><li>Beta</li></ol>
+        <ol style="list-style-type: upper-roman" reversed><li>Four</li><li>Three</li><li>Two</li><li>One</li></ol>
+        <ul class="stars"><li>Once</li><li>Twice</li></ul>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+
+    for marker in ["β.", "IV.", "III.", "II.", "I.", "*)", "**)"] {
+        assert!(text.contains(marker), "missing {marker:?} in {text:?}");
+    }
+    assert!(!text.contains('□'), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_counter_styles_out_of_range_fall_back_to_decimal() {
+    let html = r#"<html><head><style>
+        @counter-style padded { system: numeric; symbols: "0" "1"; pad: 200000000 "0"; }
+        @counter-style stars { system: symbolic; symbols: "*"; }
+    </style></head><body>
+        <ol start="9223372036854775807" style="list-style-type: lower-greek"><li>Last</li><li>Saturated</li></ol>
+        <ol start="1000000000" style="list-style-type: upper-roman"><li>Billion</li></ol>
+        <ol style="list-style-type: padded"><li>Padded</li></ol>
+        <ol start="1000000" style="list-style-type: stars"><li>Starry</li></ol>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+
+    // Roman numerals stop at 3999, and symbolic markers at a bounded length
+    for marker in ["1000000000.", "1000000."] {
+        assert!(text.contains(marker), "missing {marker:?} in {text:?}");
+    }
+    assert!(!text.contains("MMM"), "got {text:?}");
+    // Padding is capped rather than taking the whole document
+    let padding = text.matches('0').count();
+    assert!(padding < 100, "got {padding} zeros");
+}
+
+#[test]
+fn test_pdf_marker_pseudo_element_colors_bullets() {
+    let html = r#"<html><head><style>
+        body { color: black; }
+        li::marker { color: red; }
+    </style></head><body>
+        <ul><li>Apple</li><li>Pear</li></ul>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    let content = page_content(&bytes);
+
+    assert_eq!(
+        content.matches("1 0 0 rg\nBT").count(),
+        2,
+        "each bullet should be filled red:\n{}",
+        content
+    );
+    assert_eq!(
+        content.matches("0 0 0 rg\nBT").count(),
+        2,
+        "the item text should keep its black fill:\n{}",
+        content
+    );
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    let bullet = text.find('•').expect("bullets should still be drawn");
+    assert!(bullet < text.find("Apple").unwrap(), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_generate_outline_from_headings() {
+    let html = r#"<html><body>
+        <h1>Introduction</h1>
+        <h2>Background</h2>
+        <h3>Prior   work</h3>
+        <h2>Method</h2>
+        <h4 style="display: none;">Hidden</h4>
+        <h1>Results</h1>
+    </body></html>"#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config.clone()).expect("render should succeed");
+    assert!(!pdf_contains(&bytes, b"/Outlines"));
+
+    let bytes = render(html, config.generate_outline(true)).expect("render should succeed");
+    assert!(pdf_contains(&bytes, b"/Outlines"));
+    let doc = pdf_extract::Document::load_mem(&bytes).expect("should parse PDF");
+    let toc = doc.get_toc().expect("should read outline");
+    let entries: Vec<(usize, &str)> = toc
+        .toc
+        .iter()
+        .map(|entry| (entry.level, entry.title.as_str()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (1, "Introduction"),
+            (2, "Background"),
+            (3, "Prior work"),
+            (2, "Method"),
+            (1, "Results"),
+        ]
+    );
+}
+
+#[test]
+fn test_pdf_toc_page_links_to_headings() {
+    let html = r#"<html><body>
+        <h1>Introduction</h1>
+        <p>Text</p>
+        <h2>Details &amp; more</h2>
+        <h3 style="display: none;">Hidden</h3>
+    </body></html>"#;
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .generate_outline(true);
+
+    let output = render_detailed(html, config.clone()).expect("render should succeed");
+    assert_eq!(output.page_count, 1);
+    let output = render_detailed(html, config.toc(true)).expect("render should succeed");
+    assert_eq!(output.page_count, 2);
+
+    let doc = pdf_extract::Document::load_mem(&output.bytes).expect("should parse PDF");
+    let pages = doc.get_pages();
+    assert_eq!(pages.len(), 2);
+    let (toc_page, content_page) = (pages[&1], pages[&2]);
+
+    // The first page lists the headings with the page they are on
+    let text = pdf_extract::extract_text_from_mem(&output.bytes).expect("should extract text");
+    let listed: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    assert_eq!(
+        &listed[..3],
+        &["Contents", "Introduction 2", "Details & more 2"]
+    );
+
+    // Each entry links to its heading on the second page
+    let annotations = doc
+        .get_dictionary(toc_page)
+        .and_then(|page| page.get(b"Annots"))
+        .and_then(|annots| annots.as_array())
+        .expect("table of contents should have annotations");
+    let mut titles = Vec::new();
+    for annotation in annotations {
+        let annotation = doc
+            .get_dictionary(annotation.as_reference().expect("annotation reference"))
+            .expect("annotation");
+        assert_eq!(
+            annotation.get(b"Subtype").unwrap().as_name().unwrap(),
+            b"Link"
+        );
+        let destination = doc
+            .get_object(annotation.get(b"Dest").unwrap().as_reference().unwrap())
+            .and_then(|dest| dest.as_array())
+            .expect("link destination");
+        assert_eq!(destination[0].as_reference().unwrap(), content_page);
+        titles.push(
+            annotation
+                .get(b"Contents")
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_vec(),
+        );
+    }
+    assert_eq!(
+        titles,
+        vec![b"Introduction".to_vec(), b"Details & more".to_vec()]
+    );
+    assert!(doc
+        .get_dictionary(content_page)
+        .is_ok_and(|page| page.get(b"Annots").is_err()));
+
+    // The outline moves to the second page along with the headings
+    let outline = doc.get_toc().expect("should read outline");
+    assert!(outline.toc.iter().all(|entry| entry.page == 2));
+}
+
+#[test]
+fn test_pdf_fonts_are_subset() {
+    let html = r#"<html><body><p style="font-size: 72px;">Hi</p></body></html>"#;
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("render should succeed");
+
+    // Subset fonts are named with a six-letter tag, e.g. /ABCDEF+DejaVuSans
+    let text = String::from_utf8_lossy(&bytes);
+    let base_font = text
+        .split("/BaseFont /")
+        .nth(1)
+        .expect("should embed a font");
+    let (tag, _) = base_font.split_once('+').expect("font should be subset");
+    assert!(
+        tag.len() == 6 && tag.bytes().all(|b| b.is_ascii_uppercase()),
+        "got tag {tag:?}"
+    );
+    // Only the two glyphs used are embedded, far smaller than any full face
+    assert!(bytes.len() < 20_000, "got {} bytes", bytes.len());
+}
+
+#[test]
+fn test_pdf_rtl_table_columns_run_right_to_left() {
+    let table = |dir: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <table dir="{dir}" style="width: 400px;">
+                    <tr><td>First</td><td>Second</td><td>Third</td></tr>
+                </table>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    let first_x = |html: String, c: &str| {
+        let bytes = render(&html, config.clone()).expect("render should succeed");
+        char_positions(&bytes)
+            .into_iter()
+            .find(|(ch, _, _)| ch == c)
+            .map(|(_, x, _)| x)
+            .expect("character should be drawn")
+    };
+
+    let ltr = (first_x(table("ltr"), "F"), first_x(table("ltr"), "T"));
+    assert!(ltr.0 < 50.0 && ltr.1 > 250.0, "got {ltr:?}");
+
+    // The first logical column is on the right
+    let rtl = (first_x(table("rtl"), "F"), first_x(table("rtl"), "T"));
+    assert!(rtl.0 > 300.0 && rtl.1 < 150.0, "got {rtl:?}");
+}
+
+/// A minimal ICC profile: a header declaring `color_space` and an empty tag table.
+fn test_icc_profile(color_space: &[u8; 4]) -> Vec<u8> {
+    let mut profile = vec![0; 132];
+    profile[0..4].copy_from_slice(&132u32.to_be_bytes());
+    profile[12..16].copy_from_slice(b"prtr");
+    profile[16..20].copy_from_slice(color_space);
+    profile[20..24].copy_from_slice(b"Lab ");
+    profile[36..40].copy_from_slice(b"acsp");
+    profile
+}
+
+#[test]
+fn test_pdf_icc_profile_output_intent() {
+    let profile = test_icc_profile(b"CMYK");
+    for version in [PdfVersion::Pdf14, PdfVersion::Pdf17, PdfVersion::Pdf20] {
+        let config = Config::new()
+            .format(OutputFormat::Pdf)
+            .pdf_version(version)
+            .icc_profile(profile.clone());
+        let bytes = render("<p>Hello</p>", config).expect("render should succeed");
+
+        assert!(pdf_contains(&bytes, &profile), "{version:?}");
+        let doc = pdf_extract::Document::load_mem(&bytes).expect("should parse PDF");
+        let catalog = doc.catalog().expect("should have a catalog");
+        let intents = catalog
+            .get(b"OutputIntents")
+            .and_then(|intents| intents.as_array())
+            .expect("catalog should list output intents");
+        let intent = doc
+            .get_dictionary(intents[0].as_reference().expect("should be a reference"))
+            .expect("should resolve the output intent");
+        let stream = doc
+            .get_object(
+                intent
+                    .get(b"DestOutputProfile")
+                    .and_then(|profile| profile.as_reference())
+                    .expect("should reference the profile"),
+            )
+            .and_then(|object| object.as_stream())
+            .expect("should resolve the profile stream");
+        assert_eq!(stream.content, profile);
+        assert_eq!(stream.dict.get(b"N").and_then(|n| n.as_i64()).ok(), Some(4));
+        assert_eq!(
+            intent.get(b"S").and_then(|s| s.as_name()).ok(),
+            Some(&b"GTS_PDFA1"[..])
+        );
+
+        // The rest of the document is still reachable through the new catalog
+        assert_eq!(doc.get_pages().len(), 1);
+        let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+        assert!(text.contains("Hello"), "got {text:?}");
+    }
+}
+
+#[test]
+fn test_pdf_rgb_icc_profile_tags_content() {
+    let profile = test_icc_profile(b"RGB ");
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .icc_profile(profile.clone());
+    let bytes =
+        render(r#"<p style="color: #c00;">Hello</p>"#, config).expect("render should succeed");
+
+    // The page draws device RGB in the profile's space
+    let doc = pdf_extract::Document::load_mem(&bytes).expect("should parse PDF");
+    let mut tagged = 0;
+    for (_, page_id) in doc.get_pages() {
+        let (resources, _) = doc
+            .get_page_resources(page_id)
+            .expect("should resolve page resources");
+        let resources = resources.expect("page should have resources");
+        let default_rgb = resources
+            .get(b"ColorSpace")
+            .and_then(|spaces| spaces.as_dict())
+            .and_then(|spaces| spaces.get(b"DefaultRGB"))
+            .and_then(|space| space.as_array())
+            .expect("page should set DefaultRGB");
+        assert_eq!(default_rgb[0].as_name().ok(), Some(&b"ICCBased"[..]));
+        let stream = doc
+            .get_object(
+                default_rgb[1]
+                    .as_reference()
+                    .expect("should be a reference"),
+            )
+            .and_then(|object| object.as_stream())
+            .expect("should resolve the profile stream");
+        assert_eq!(stream.content, profile);
+        assert_eq!(stream.dict.get(b"N").and_then(|n| n.as_i64()).ok(), Some(3));
+        tagged += 1;
+    }
+    assert_eq!(tagged, 1);
+
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("Hello"), "got {text:?}");
+}
+
+#[test]
+fn test_pdf_tab_size_sets_tab_stops() {
+    let html = |tab_size: &str| {
+        format!(
+            "<html><body style=\"margin: 0;\">\
+             <pre style=\"tab-size: {};\">a\tb</pre>\
+             </body></html>",
+            tab_size
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    let b_x = |tab_size: &str| {
+        let bytes = render(&html(tab_size), config.clone()).expect("should render");
+        char_positions(&bytes)
+            .iter()
+            .find(|(c, _, _)| c == "b")
+            .map(|(_, x, _)| *x)
+            .expect("text after the tab should be drawn")
+    };
+
+    // In a monospace font, a tab after one character ends at the column
+    // given by tab-size; with tab-size: 2 it takes up the one column left
+    let (narrow, medium, wide) = (b_x("2"), b_x("4"), b_x("8"));
+    let column = (medium - narrow) / 2.0;
+    assert!(
+        column > 5.0,
+        "tab-size: 4 should widen the tab, got x={}",
+        medium
+    );
+    assert!(
+        (wide - narrow - 6.0 * column).abs() < 0.5,
+        "tab-size: 8 should put the text after the tab 6 columns past tab-size: 2, \
+         got x={} and x={} with {}px columns",
+        wide,
+        narrow,
+        column
+    );
+    let default = b_x("initial");
+    assert!(
+        (default - wide).abs() < 0.01,
+        "tab-size should default to 8, got x={} instead of {}",
+        default,
+        wide
+    );
+
+    let bytes = render(&html("8"), config).expect("should render");
+    let content = page_content(&bytes);
+    assert!(
+        !content.contains("(\\000\\000"),
+        "tabs should not be drawn as .notdef glyphs:\n{}",
+        content
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_pdf_rasterize_gradients_matches_png() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div style="width: 120px; height: 80px;
+                        background: radial-gradient(circle at 30% 40%, #f00, #00f 40%, #0f0),
+                                    #fff;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().width(200).height(100);
+
+    let png = render(html, config.clone()).expect("PNG should render");
+    let png = image::load_from_memory(&png)
+        .expect("should decode PNG")
+        .into_rgba8();
+    let pdf = render(
+        html,
+        config.format(OutputFormat::Pdf).rasterize_gradients(true),
+    )
+    .expect("PDF should render");
+
+    // The gradient is embedded as an image of the element's border box
+    let images = color_images(&pdf);
+    assert_eq!(images.len(), 1, "PDF should hold the rasterized gradient");
+    let (width, height, pixels) = &images[0];
+    let (width, height) = (*width, *height);
+    assert_eq!((width, height), (120, 80));
+
+    let max_difference = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let i = ((y * width + x) * 3) as usize;
+            let expected = png.get_pixel(x, y).0;
+            (0..3).map(move |c| (pixels[i + c] as i32 - expected[c] as i32).abs())
+        })
+        .max()
+        .unwrap_or(0);
+    assert!(
+        max_difference <= 2,
+        "rasterized gradient should match the PNG render, channels differ by up to {}",
+        max_difference
+    );
+
+    // Without the option, the radial gradient isn't drawn as an image
+    let vector = render(
+        html,
+        Config::new()
+            .width(200)
+            .height(100)
+            .format(OutputFormat::Pdf),
+    )
+    .expect("PDF should render");
+    assert!(color_images(&vector).is_empty());
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_pdf_rasterize_nested_gradients_once() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div style="width: 100px; height: 100px;
+                        background: linear-gradient(rgba(255, 0, 0, 0.5), rgba(255, 0, 0, 0.5));">
+                <div style="width: 50px; height: 50px;
+                            background: linear-gradient(rgba(0, 0, 255, 0.5), rgba(0, 0, 255, 0.5));">
+                </div>
+            </div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(200)
+        .height(200)
+        .format(OutputFormat::Pdf)
+        .rasterize_gradients(true);
+
+    let bytes = render(html, config).expect("PDF should render");
+
+    // Each tile holds only its own element's gradient, so where they
+    // overlap the inner one is composited over the outer one just once
+    let mut images = color_images(&bytes);
+    images.sort_by_key(|(width, _, _)| *width);
+    let [(50, 50, inner), (100, 100, outer)] = &images[..] else {
+        panic!("expected a tile per gradient, got {} images", images.len());
+    };
+    assert!(inner.chunks_exact(3).all(|pixel| pixel == [0, 0, 255]));
+    assert!(outer.chunks_exact(3).all(|pixel| pixel == [255, 0, 0]));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_pdf_flatten_3d_transform_to_image() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div style="width: 200px; height: 100px; background: #00f; color: #fff;
+                        transform: rotateX(45deg);">Tilted</div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(300)
+        .height(200)
+        .format(OutputFormat::Pdf);
+
+    let flattened =
+        render(html, config.clone().flatten_transforms_for_pdf(true)).expect("PDF should render");
+    let images = color_images(&flattened);
+    assert_eq!(
+        images.len(),
+        1,
+        "the transformed element should be an image"
+    );
+
+    // Tilted back, the element keeps its width but is foreshortened
+    let (width, height, pixels) = &images[0];
+    assert_eq!(*width, 200);
+    assert!((65..=76).contains(height), "height {height}");
+    let center = ((height / 2 * width + width / 2) * 3) as usize;
+    assert_eq!(&pixels[center..center + 3], &[0, 0, 255]);
+
+    // Its background and text aren't drawn natively
+    let text = pdf_extract::extract_text_from_mem(&flattened).expect("should extract text");
+    assert!(!text.contains("Tilted"), "got {text:?}");
+    assert!(!page_content(&flattened).contains("0 0 1 rg"));
+
+    // Without the option, the element is drawn natively
+    let native = render(html, config).expect("PDF should render");
+    assert!(color_images(&native).is_empty());
+    let text = pdf_extract::extract_text_from_mem(&native).expect("should extract text");
+    assert!(text.contains("Tilted"), "got {text:?}");
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_pdf_flatten_3d_transforms_capped() {
+    let cells: String = (0..40)
+        .map(|i| {
+            format!(
+                r#"<div style="position: absolute; left: {}px; top: {}px; width: 20px;
+                    height: 20px; background: {color}; color: {color};
+                    transform: rotateX(30deg);">{i}</div>"#,
+                10 + i % 8 * 30,
+                10 + i / 8 * 30,
+                color = format!("rgb(0, {}, 0)", 100 + i),
+            )
+        })
+        .collect();
+    let html = format!(r#"<html><body style="margin: 0;">{cells}</body></html>"#);
+    let config = Config::new()
+        .width(300)
+        .height(200)
+        .format(OutputFormat::Pdf)
+        .flatten_transforms_for_pdf(true);
+
+    let bytes = render(&html, config).expect("PDF should render");
+
+    // The first 32 elements are images, each painted from its own place
+    let mut greens: Vec<u8> = color_images(&bytes)
+        .iter()
+        .map(|(width, height, pixels)| {
+            let center = ((height / 2 * width + width / 2) * 3) as usize;
+            assert_eq!((pixels[center], pixels[center + 2]), (0, 0));
+            pixels[center + 1]
+        })
+        .collect();
+    greens.sort_unstable();
+    assert_eq!(greens, (100..132).collect::<Vec<u8>>());
+
+    // The rest are drawn as their 2D part
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("39"), "got {text:?}");
+    assert!(!text.contains("31"), "got {text:?}");
+}
+
+#[test]
+#[cfg(not(feature = "png"))]
+fn test_pdf_flatten_transforms_needs_png() {
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .flatten_transforms_for_pdf(true);
+
+    let result = render("<html><body><p>Text</p></body></html>", config);
+    assert!(
+        matches!(result, Err(Error::FormatNotEnabled("png"))),
+        "got {result:?}"
+    );
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_pdf_2d_transform_is_native() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <div style="width: 100px; height: 50px; background: #0a0;
+                        transform: rotate(90deg);">Turned</div>
+        </body>
+        </html>
+    "#;
+    let bytes = render(
+        html,
+        Config::new()
+            .width(300)
+            .height(200)
+            .format(OutputFormat::Pdf)
+            .flatten_transforms_for_pdf(true),
+    )
+    .expect("PDF should render");
+    let content = page_content(&bytes);
+
+    // The quarter turn about the center (50, 25) is combined with the
+    // page's flipped y axis
+    let rotation = content
+        .lines()
+        .filter_map(|line| line.strip_suffix(" cm"))
+        .map(|line| {
+            line.split_whitespace()
+                .map(|value| value.parse::<f64>().expect("numeric operand"))
+                .collect::<Vec<_>>()
+        })
+        .find(|m| m[0].abs() < 1e-6 && m[1] == -1.0 && m[2] == -1.0 && m[3].abs() < 1e-6)
+        .unwrap_or_else(|| panic!("no rotation in {content}"));
+    assert_eq!((rotation[4], rotation[5]), (75.0, 225.0));
+
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(text.contains("Turned"), "got {text:?}");
+}
+
+/// Return the `(width, height, RGB pixels)` of every color image in the PDF,
+/// leaving out soft masks.
+#[cfg(feature = "png")]
+fn color_images(data: &[u8]) -> Vec<(u32, u32, Vec<u8>)> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    doc.objects
+        .values()
+        .filter_map(|object| object.as_stream().ok())
+        .filter(|stream| {
+            let name = |key: &[u8]| stream.dict.get(key).and_then(|v| v.as_name()).ok();
+            name(b"Subtype") == Some(b"Image") && name(b"ColorSpace") != Some(b"DeviceGray")
+        })
+        .filter_map(|stream| {
+            let width = stream.dict.get(b"Width").and_then(|w| w.as_i64()).ok()?;
+            let height = stream.dict.get(b"Height").and_then(|h| h.as_i64()).ok()?;
+            let pixels = stream.decompressed_content().ok()?;
+            Some((width as u32, height as u32, pixels))
+        })
+        .collect()
+}
+
+#[test]
+fn test_pdf_mark_background_behind_text() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <p>Plain <mark>highlighted</mark> text</p>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(300)
+        .height(100)
+        .format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+
+    // A yellow rectangle is filled, then the text is drawn over it in black
+    let (_, after_fill) = content
+        .split_once("1 1 0 rg\n")
+        .expect("the <mark> background should be painted yellow");
+    let rect_x: f64 = after_fill
+        .split_whitespace()
+        .next()
+        .and_then(|x| x.parse().ok())
+        .expect("the background should be a path");
+    let (rect, after_rect) = after_fill
+        .split_once("\nf\n")
+        .expect("the background should be filled");
+    assert_eq!(
+        rect.lines().count(),
+        5,
+        "expected a rectangle, got:\n{}",
+        rect
+    );
+    let text = &after_rect[..after_rect.find("TJ").expect("text should follow")];
+    assert!(
+        text.contains("0 0 0 rg"),
+        "highlighted text should be black:\n{}",
+        text
+    );
+    assert!(
+        text.contains(&format!("{} ", rect_x)) && text.contains("Tm"),
+        "the highlighted text should start where its background does:\n{}",
+        text
+    );
+}
+
+#[test]
+fn test_pdf_wrapped_inline_background_per_line() {
+    let html = r#"
+        <html>
+        <body style="margin: 0;">
+            <p style="width: 80px; margin: 0;">
+                <span style="background: yellow; box-decoration-break: clone;">one two three four five six</span>
+            </p>
+        </body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(300)
+        .height(200)
+        .format(OutputFormat::Pdf);
+
+    let bytes = render(html, config).expect("should render");
+    let content = page_content(&bytes);
+
+    // Each yellow fill is followed by its rectangle's top-left corner
+    let tops: Vec<f64> = content
+        .split("1 1 0 rg\n")
+        .skip(1)
+        .filter_map(|fill| fill.split_whitespace().nth(1)?.parse().ok())
+        .collect();
+    let mut lines = tops.clone();
+    lines.dedup_by(|a, b| (*a - *b).abs() < 0.01);
+    assert!(
+        lines.len() >= 3,
+        "each line of the wrapped span should get its own background, got tops {:?}",
+        tops
+    );
+    assert!(
+        lines.windows(2).all(|pair| pair[1] > pair[0]),
+        "backgrounds should be on successive lines, got tops {:?}",
+        tops
+    );
+}
+
+#[test]
+fn test_pdf_sanitize_strips_script_text() {
+    let html = r#"
+        <html>
+        <head><style onload="steal()">script { display: block; }</style></head>
+        <body>
+            <p onclick="steal()">Visible</p>
+            <script>var x="secret"</script>
+            <svg width="10" height="10"><script>var y="hidden"</script></svg>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().format(OutputFormat::Pdf);
+    let text = |config: Config| {
+        let bytes = render(html, config).expect("should render");
+        pdf_extract::extract_text_from_mem(&bytes).expect("should extract text")
+    };
+
+    // Author CSS can lay out and paint a script's source
+    let unsanitized = text(config.clone());
+    assert!(unsanitized.contains("secret"), "got {:?}", unsanitized);
+
+    let sanitized = text(config.sanitize(true));
+    assert!(sanitized.contains("Visible"), "got {:?}", sanitized);
+    assert!(
+        !sanitized.contains("secret") && !sanitized.contains("hidden"),
+        "script text should not be rendered, got {:?}",
+        sanitized
+    );
+}
+
+/// Page label dictionaries of the PDF's `/PageLabels` number tree, by the
+/// index of the page each starts at, as `(style, prefix, first number)`.
+fn page_labels(data: &[u8]) -> Option<Vec<(i64, String, String, i64)>> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let catalog = doc.catalog().expect("should have a catalog");
+    let tree = catalog.get_deref(b"PageLabels", &doc).ok()?;
+    let nums = tree
+        .as_dict()
+        .and_then(|tree| tree.get(b"Nums"))
+        .and_then(|nums| nums.as_array())
+        .expect("page labels should have /Nums");
+    let labels = nums
+        .chunks(2)
+        .map(|entry| {
+            let label = doc
+                .dereference(&entry[1])
+                .and_then(|(_, label)| label.as_dict())
+                .expect("should be a page label");
+            let text = |key: &[u8]| {
+                label
+                    .get(key)
+                    .map(|value| match value {
+                        pdf_extract::Object::Name(name) => String::from_utf8_lossy(name).into(),
+                        pdf_extract::Object::String(bytes, _) => {
+                            String::from_utf8_lossy(bytes).into()
+                        }
+                        other => format!("{other:?}"),
+                    })
+                    .unwrap_or_default()
+            };
+            let first = label.get(b"St").and_then(|st| st.as_i64()).unwrap_or(1);
+            (entry[0].as_i64().unwrap(), text(b"S"), text(b"P"), first)
+        })
+        .collect();
+    Some(labels)
+}
+
+#[test]
+fn test_pdf_page_labels() {
+    let html = "<html><body><h1>Preface</h1></body></html>";
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(html, config.clone()).expect("render should succeed");
+    assert_eq!(page_labels(&bytes), None);
+
+    let labels = vec![
+        PageLabelRange::new(0, PageLabelStyle::RomanLower),
+        PageLabelRange::new(2, PageLabelStyle::Decimal),
+    ];
+    let bytes = render(html, config.clone().page_labels(labels)).expect("render should succeed");
+    assert_eq!(
+        page_labels(&bytes),
+        Some(vec![(0, "r".to_string(), String::new(), 1)])
+    );
+
+    let labels = vec![PageLabelRange::new(0, PageLabelStyle::LettersUpper)
+        .prefix("Appendix ")
+        .first_number(3)];
+    let bytes = render(html, config.clone().page_labels(labels)).expect("render should succeed");
+    assert_eq!(
+        page_labels(&bytes),
+        Some(vec![(0, "A".to_string(), "Appendix ".to_string(), 3)])
+    );
+
+    // The output's only page comes before a range starting later
+    let labels = vec![PageLabelRange::new(1, PageLabelStyle::Decimal)];
+    let bytes = render(html, config.page_labels(labels)).expect("render should succeed");
+    assert_eq!(page_labels(&bytes), None);
+}
+
+/// Fill colors of the paths (not text) in a page content stream.
+fn path_fills(content: &str) -> Vec<&str> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .windows(2)
+        .filter(|pair| pair[0].ends_with(" rg") && pair[1].ends_with(" m"))
+        .map(|pair| pair[0])
+        .collect()
+}
+
+#[test]
+fn test_pdf_text_decoration_defaults_to_text_color() {
+    let html = r#"<html><body style="margin: 0;">
+        <p style="color: #f00; text-decoration: underline; font-size: 20px;">Hello</p>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+
+    // Page background, then the underline in the text's red
+    let content = page_content(&bytes);
+    assert_eq!(path_fills(&content), vec!["1 1 1 rg", "1 0 0 rg"]);
+    let underline = content
+        .split("1 0 0 rg\n0 ")
+        .nth(1)
+        .expect("should draw an underline");
+    let top: f32 = underline.split(' ').next().unwrap().parse().unwrap();
+    let text_baseline = 39.0;
+    assert!(
+        top > text_baseline - 1.0 && top < text_baseline + 5.0,
+        "{top}"
+    );
+}
+
+#[test]
+fn test_pdf_text_decoration_color() {
+    let html = r#"<html><body>
+        <p style="color: #00f; text-decoration: line-through #0f0;">Struck</p>
+        <p style="color: #00f; text-decoration: underline; text-decoration-color: currentColor;">Blue</p>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+
+    let content = page_content(&bytes);
+    assert_eq!(
+        path_fills(&content),
+        vec!["1 1 1 rg", "0 1 0 rg", "0 0 1 rg"]
+    );
+}
+
+#[test]
+fn test_pdf_text_decoration_propagates_to_descendants() {
+    // The outer underline keeps the paragraph's red under the blue `<b>`
+    let html = r#"<html><body>
+        <p style="color: #f00; text-decoration: underline;">Hello <b style="color: #00f;">world</b></p>
+    </body></html>"#;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    assert_eq!(
+        path_fills(&page_content(&bytes)),
+        vec!["1 1 1 rg", "1 0 0 rg", "1 0 0 rg"]
+    );
+
+    let html = r##"<html><body>
+        <a href="#top" style="color: #0f0;"><span>Link</span></a>
+        <div style="text-decoration: underline;"><span style="display: inline-block;">Box</span></div>
+    </body></html>"##;
+    let bytes =
+        render(html, Config::new().format(OutputFormat::Pdf)).expect("render should succeed");
+    // Nothing crosses into the inline-block
+    assert_eq!(
+        path_fills(&page_content(&bytes)),
+        vec!["1 1 1 rg", "0 1 0 rg"]
+    );
+}
+
+/// The text drawn on each baseline of the PDF, top to bottom, trimmed.
+fn text_lines(data: &[u8]) -> Vec<String> {
+    let mut lines: Vec<(f64, String)> = Vec::new();
+    for (char, _, y) in char_positions(data) {
+        match lines
+            .iter_mut()
+            .find(|(line_y, _)| (line_y - y).abs() < 1.0)
+        {
+            Some((_, line)) => line.push_str(&char),
+            None => lines.push((y, char)),
+        }
+    }
+    lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+    lines
+        .into_iter()
+        .map(|(_, line)| line.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn test_pdf_line_clamp_truncates_with_ellipsis() {
+    let lines: Vec<String> = (1..=10).map(|i| format!("Line {i}")).collect();
+    let html = |clamp: &str| {
+        format!(
+            r#"<html><body><div style="{clamp}">{}</div></body></html>"#,
+            lines.join("<br>")
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let bytes = render(&html(""), config.clone()).expect("render should succeed");
+    assert_eq!(text_lines(&bytes), lines);
+
+    for clamp in [
+        "line-clamp: 3",
+        "-webkit-line-clamp: 3; display: -webkit-box",
+    ] {
+        let bytes = render(&html(clamp), config.clone()).expect("render should succeed");
+        assert_eq!(
+            text_lines(&bytes),
+            vec!["Line 1", "Line 2", "Line 3\u{2026}"],
+            "{clamp}"
+        );
+    }
+
+    // Text that fits within the clamp is left alone
+    let bytes = render(&html("line-clamp: 10"), config).expect("render should succeed");
+    assert_eq!(text_lines(&bytes), lines);
+}
+
+#[test]
+fn test_pdf_unparsed_properties_apply_from_stylesheets() {
+    let config = Config::new().format(OutputFormat::Pdf);
+    let html = r#"<html><head><style>
+            .skip { content-visibility: hidden; }
+            .clamp { -webkit-line-clamp: 2; }
+        </style></head><body>
+            <div>Shown</div>
+            <div class="skip">Skipped</div>
+            <div class="clamp">Line 1<br>Line 2<br>Line 3</div>
+            <div class="clamp"><div>Nested 1<br>Nested 2<br>Nested 3</div></div>
+        </body></html>"#;
+    let bytes = render(html, config.clone()).expect("render should succeed");
+    // The clamp doesn't inherit into the nested block
+    assert_eq!(
+        text_lines(&bytes),
+        vec![
+            "Shown",
+            "Line 1",
+            "Line 2\u{2026}",
+            "Nested 1",
+            "Nested 2",
+            "Nested 3"
+        ]
+    );
+
+    // A rule's tab-size matches an inline one, and inherits
+    let b_x = |html: &str| {
+        char_positions(&render(html, config.clone()).expect("render should succeed"))
+            .into_iter()
+            .find(|(c, _, _)| c == "b")
+            .map(|(_, x, _)| x)
+            .expect("text after the tab should be drawn")
+    };
+    let inline = b_x("<pre style=\"tab-size: 2;\">a\tb</pre>");
+    let rule = b_x("<style>body { tab-size: 2; }</style><pre>a\tb</pre>");
+    let default = b_x("<pre>a\tb</pre>");
+    assert_eq!(rule, inline);
+    assert!(rule < default, "{rule} should be left of {default}");
+}
+
+#[test]
+fn test_pdf_line_clamp_makes_room_for_ellipsis() {
+    let html = |clamp: &str| {
+        format!(
+            r#"<html><body><div style="width: 200px; font-size: 16px; {clamp}">
+                one two three four five six seven eight nine ten eleven twelve
+                thirteen fourteen fifteen sixteen seventeen eighteen nineteen twenty
+            </div></body></html>"#
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+
+    let full = text_lines(&render(&html(""), config.clone()).expect("render should succeed"));
+    let clamped =
+        text_lines(&render(&html("line-clamp: 2"), config).expect("render should succeed"));
+    assert!(full.len() > 2, "{full:?}");
+    assert_eq!(clamped.len(), 2, "{clamped:?}");
+    assert_eq!(clamped[0], full[0]);
+
+    // The wrapped second line loses its last word to fit the ellipsis
+    let second = clamped[1]
+        .strip_suffix('\u{2026}')
+        .expect("should end in an ellipsis");
+    assert!(full[1].starts_with(second.trim_end()), "{second:?}");
+    assert!(second.trim_end().len() < full[1].len(), "{second:?}");
+}
+
+#[test]
+fn test_pdf_soft_hyphen_and_wbr_break_opportunities() {
+    let html = |width: u32, word: &str| {
+        format!(
+            r#"<html><body><p style="width: {width}px; font-size: 20px;">{word} word</p></body></html>"#
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    // Soft hyphens and `<wbr>` draw nothing themselves
+    let lines = |html: String| -> Vec<String> {
+        text_lines(&render(&html, config.clone()).expect("render should succeed"))
+            .into_iter()
+            .map(|line| line.replace(['\u{AD}', '\u{200B}', ' '], ""))
+            .collect()
+    };
+
+    // A word broken at a soft hyphen shows a hyphen there, and only there
+    assert_eq!(
+        lines(html(120, "Super&shy;cali&shy;fragilistic")),
+        vec!["Supercali\u{2010}", "fragilistic", "word"]
+    );
+    assert_eq!(
+        lines(html(400, "Super&shy;cali&shy;fragilistic")),
+        vec!["Supercalifragilisticword"]
+    );
+
+    // `<wbr>` breaks without a hyphen
+    assert_eq!(
+        lines(html(120, "Super<wbr>cali<wbr>fragilistic")),
+        vec!["Supercali", "fragilistic", "word"]
+    );
+}
+
+#[test]
+fn test_pdf_lang_selects_hyphenation_patterns() {
+    let html = r#"<html><body>
+        <p style="width: 110px; font-size: 20px; hyphens: auto;">Rechtsschutzversicherung</p>
+    </body></html>"#;
+    let first_line = |config: Config| -> String {
+        let bytes = render(html, config.format(OutputFormat::Pdf)).expect("render should succeed");
+        text_lines(&bytes)[0].replace(['\u{AD}', ' '], "")
+    };
+
+    // German breaks after "Rechts", English patterns after "Rechtss"
+    assert_eq!(first_line(Config::new().lang("de")), "Rechts\u{2010}");
+    assert_eq!(first_line(Config::new().lang("en")), "Rechtss\u{2010}");
+    // Without a language there are no patterns to hyphenate with
+    assert_eq!(first_line(Config::new()), "Rechtsschutzversicherung");
+}
+
+#[test]
+fn test_pdf_all_unset_and_revert() {
+    let html = |reset: &str| {
+        format!(
+            r#"<html><head><style>
+                h1 {{ color: #f00; font-size: 40px; background: #ff0; }}
+                h1.reset {{ {reset} }}
+            </style></head>
+            <body style="color: #00f; font-size: 12px;"><h1 class="reset">Hello</h1></body></html>"#
+        )
+    };
+    let config = Config::new().format(OutputFormat::Pdf);
+    // The text's fill color and font size, and whether the yellow background is drawn
+    let text_style = |reset: &str| {
+        let bytes = render(&html(reset), config.clone()).expect("render should succeed");
+        let content = page_content(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
+        let text = lines
+            .iter()
+            .position(|line| *line == "BT")
+            .expect("should draw text");
+        let fill = lines[..text]
+            .iter()
+            .rev()
+            .find(|line| line.ends_with(" rg"))
+            .unwrap()
+            .to_string();
+        let size = lines[text..]
+            .iter()
+            .find(|line| line.ends_with(" Tf"))
+            .and_then(|line| line.split(' ').nth(1))
+            .unwrap()
+            .to_string();
+        (fill, size, path_fills(&content).contains(&"1 1 0 rg"))
+    };
+    let style =
+        |fill: &str, size: &str, background: bool| (fill.to_string(), size.to_string(), background);
+
+    assert_eq!(text_style(""), style("1 0 0 rg", "40", true));
+    // Inherited properties come from the parent, the rest are reset
+    assert_eq!(text_style("all: unset"), style("0 0 1 rg", "12", false));
+    assert_eq!(text_style("all: initial"), style("0 0 0 rg", "16", false));
+    // Author rules are rolled back to the user agent's `h1` style
+    assert_eq!(text_style("all: revert"), style("0 0 1 rg", "24", false));
+}
+
+/// Number of glyphs drawn by the PDF's text showing operators, assuming
+/// two-byte glyph ids.
+fn drawn_glyphs(data: &[u8]) -> usize {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let string_len = |object: &pdf_extract::Object| match object {
+        pdf_extract::Object::String(bytes, _) => bytes.len() / 2,
+        _ => 0,
+    };
+    doc.get_pages()
+        .into_values()
+        .flat_map(|page_id| {
+            doc.get_and_decode_page_content(page_id)
+                .expect("should decode page content")
+                .operations
+        })
+        .map(|operation| match operation.operator.as_str() {
+            "Tj" => operation.operands.iter().map(string_len).sum(),
+            "TJ" => operation
+                .operands
+                .iter()
+                .filter_map(|operand| operand.as_array().ok())
+                .flatten()
+                .map(string_len)
+                .sum(),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[test]
+fn test_pdf_ligatures_toggle() {
+    let paragraph =
+        |text: &str| format!(r#"<p style="font-family: 'Open Sans'; margin: 0;">{text}</p>"#);
+    let fonts = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/fonts");
+    let on = Config::new()
+        .format(OutputFormat::Pdf)
+        .system_fonts(false)
+        .fonts_dir(fonts);
+    let off = on.clone().ligatures(false);
+
+    let with = render(&paragraph("ffi"), on.clone()).expect("should render with ligatures");
+    assert_eq!(
+        drawn_glyphs(&with),
+        1,
+        "ffi should shape as one ligature glyph"
+    );
+
+    let without = render(&paragraph("ffi"), off.clone()).expect("should render without ligatures");
+    assert!(
+        drawn_glyphs(&without) >= 3,
+        "ffi should shape as separate glyphs, drew {}",
+        drawn_glyphs(&without)
+    );
+    // The non-joiners keeping them apart stay out of the text layer
+    let text = pdf_extract::extract_text_from_mem(&without).expect("should extract text");
+    assert_eq!(text.trim(), "ffi");
+
+    // Text that forms no ligatures is left as it is
+    let plain = paragraph("code ffi");
+    assert_eq!(
+        drawn_glyphs(&render(&plain, off).unwrap()) - drawn_glyphs(&without),
+        drawn_glyphs(&render(&plain, on).unwrap()) - drawn_glyphs(&with),
+    );
+}
+
+/// The font size and glyph string of the text-showing operators on the
+/// PDF's pages, in drawing order, with consecutive ones at the same size
+/// joined.
+fn shown_glyphs(data: &[u8]) -> Vec<(f32, Vec<u8>)> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let mut size = 0.0;
+    let mut shown: Vec<(f32, Vec<u8>)> = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let content = doc
+            .get_and_decode_page_content(page_id)
+            .expect("should decode page content");
+        for operation in content.operations {
+            let strings = match operation.operator.as_str() {
+                "Tf" => {
+                    size = operation.operands[1].as_float().expect("font size");
+                    continue;
+                }
+                "Tj" => operation.operands,
+                "TJ" => operation.operands[0]
+                    .as_array()
+                    .expect("TJ operand")
+                    .clone(),
+                _ => continue,
+            };
+            let glyphs = strings
+                .iter()
+                .filter_map(|operand| operand.as_str().ok())
+                .flatten()
+                .copied()
+                .collect();
+            match shown.last_mut() {
+                Some((last_size, last)) if *last_size == size => last.extend(glyphs),
+                _ => shown.push((size, glyphs)),
+            }
+        }
+    }
+    shown
+}
+
+/// The `/ActualText` of each marked-content sequence on the PDF's pages,
+/// which stands for the glyphs whose own mapping to text is taken.
+fn actual_texts(data: &[u8]) -> Vec<String> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let mut texts = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let content = doc
+            .get_and_decode_page_content(page_id)
+            .expect("should decode page content");
+        for operation in content.operations {
+            let text = match operation.operator.as_str() {
+                "BDC" => operation.operands.get(1).and_then(|properties| {
+                    properties
+                        .as_dict()
+                        .ok()?
+                        .get(b"ActualText")
+                        .ok()?
+                        .as_str()
+                        .ok()
+                }),
+                _ => None,
+            };
+            // Text strings are UTF-16 after a byte order mark, or else
+            // ASCII here
+            texts.extend(text.map(|text| match text.strip_prefix(&[0xFE, 0xFF]) {
+                Some(utf16) => {
+                    let units: Vec<u16> = utf16
+                        .chunks(2)
+                        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                        .collect();
+                    String::from_utf16_lossy(&units)
+                }
+                None => String::from_utf8_lossy(text).into_owned(),
+            }));
+        }
+    }
+    texts
+}
+
+#[test]
+fn test_pdf_font_variant_small_caps() {
+    let html = r#"<html><body style="font-size: 20px;">
+        <p style="font-variant: small-caps;">Hello</p>
+        <p>ELLO hello</p>
+    </body></html>"#;
+    let bytes = render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+
+    // The capital H keeps the text's size; the lowercase letters are drawn
+    // with the capitals' glyphs at a smaller size
+    let shown = shown_glyphs(&bytes);
+    assert_eq!(shown.len(), 3, "{shown:?}");
+    let (capital_size, _) = &shown[0];
+    let (small_size, small) = &shown[1];
+    let (_, plain) = &shown[2];
+    assert_eq!(*capital_size, 20.0);
+    assert!(
+        (*small_size - 14.0).abs() < 0.01,
+        "small caps at {small_size}px"
+    );
+    assert_eq!(small[..], plain[..8], "should draw capital glyphs");
+    assert_ne!(
+        small[..],
+        plain[plain.len() - 8..],
+        "shouldn't draw lowercase glyphs"
+    );
+
+    // The text layer reads the text as it was written: the capitals' glyphs
+    // read as the lowercase letters they were first drawn for, and as
+    // capitals through `/ActualText` where they are drawn for those
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(
+        text.replace(char::is_whitespace, "").starts_with("Hello"),
+        "got {text:?}"
+    );
+    assert_eq!(actual_texts(&bytes).concat(), "ELLO");
+}
+
+#[test]
+fn test_pdf_font_variant_small_caps_text_layer_keeps_expanded_letters() {
+    // ß is set as two capitals, which read back as the one letter
+    let html = r#"<p style="font-variant: small-caps; hyphens: auto;" lang="de">Straße</p>"#;
+    let bytes = render(html, Config::new().format(OutputFormat::Pdf)).expect("should render");
+
+    let shown = shown_glyphs(&bytes);
+    assert_eq!(
+        shown
+            .iter()
+            .map(|(_, glyphs)| glyphs.len() / 2)
+            .sum::<usize>(),
+        7,
+        "should draw S, TRA, SS and E: {shown:?}"
+    );
+    let text = pdf_extract::extract_text_from_mem(&bytes).expect("should extract text");
+    assert!(
+        text.replace(char::is_whitespace, "").starts_with("Stra"),
+        "got {text:?}"
+    );
+    assert_eq!(actual_texts(&bytes), ["ß", ""]);
+}
+
+/// `font` with its GSUB table replaced by one whose `feature` substitutes
+/// each glyph of `substitutions` for another.
+fn with_single_substitutions(
+    font: &[u8],
+    feature: &[u8; 4],
+    substitutions: &[(u16, u16)],
+) -> Vec<u8> {
+    let mut substitutions = substitutions.to_vec();
+    substitutions.sort();
+    let count = substitutions.len() as u16;
+    let mut gsub = Vec::new();
+    let push = |gsub: &mut Vec<u8>, values: &[u16]| {
+        for value in values {
+            gsub.extend(value.to_be_bytes());
+        }
+    };
+    // Header, then a script list enabling feature 0 by default, a feature
+    // list holding it and a lookup list with its single substitution
+    push(&mut gsub, &[1, 0, 10, 30, 44]);
+    push(&mut gsub, &[1]);
+    gsub.extend(b"DFLT");
+    push(&mut gsub, &[8, 4, 0, 0, 0xFFFF, 1, 0, 1]);
+    gsub.extend(feature);
+    push(&mut gsub, &[8, 0, 1, 0, 1, 4, 1, 0, 1, 8]);
+    push(&mut gsub, &[2, 6 + 2 * count, count]);
+    push(
+        &mut gsub,
+        &substitutions.iter().map(|&(_, to)| to).collect::<Vec<_>>(),
+    );
+    push(&mut gsub, &[1, count]);
+    push(
+        &mut gsub,
+        &substitutions
+            .iter()
+            .map(|&(from, _)| from)
+            .collect::<Vec<_>>(),
+    );
+
+    let table_count = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let mut tables: Vec<([u8; 4], &[u8])> = (0..table_count)
+        .map(|index| {
+            let record = &font[12 + 16 * index..28 + 16 * index];
+            let field = |at: usize| u32::from_be_bytes(record[at..at + 4].try_into().unwrap());
+            let (offset, len) = (field(8) as usize, field(12) as usize);
+            (record[..4].try_into().unwrap(), &font[offset..offset + len])
+        })
+        .filter(|(tag, _)| tag != b"GSUB")
+        .collect();
+    tables.push((*b"GSUB", &gsub));
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let mut out = font[..12].to_vec();
+    out[4..6].copy_from_slice(&(tables.len() as u16).to_be_bytes());
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        out.extend(tag);
+        out.extend(0u32.to_be_bytes());
+        out.extend((offset as u32).to_be_bytes());
+        out.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        out.extend(*data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+#[test]
+fn test_pdf_font_variant_numeric_oldstyle_nums() {
+    use skrifa::MetadataProvider;
+
+    // Open Sans has no old-style figures, so give it a 1 set as an A
+    let font = wuff::decompress_woff2(include_bytes!("../assets/fonts/OpenSans-Regular.woff2"))
+        .expect("should decompress");
+    let glyph = |c: char| {
+        let font = skrifa::FontRef::new(&font).expect("should parse font");
+        font.charmap().map(c).expect("should map").to_u32() as u16
+    };
+    let patched = with_single_substitutions(&font, b"onum", &[(glyph('1'), glyph('A'))]);
+    let dir = std::env::temp_dir().join(format!("hyper-render-onum-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("OpenSans-Onum.ttf"), patched).unwrap();
+    let config = Config::new()
+        .format(OutputFormat::Pdf)
+        .system_fonts(false)
+        .fonts_dir(&dir);
+    let shown = |numeric: &str| {
+        let html = format!(
+            r#"<style>p {{ font-variant-numeric: {numeric}; }}</style><p style="font-family: 'Open Sans';">1A</p>"#
+        );
+        let bytes = render(&html, config.clone()).expect("should render");
+        (shown_glyphs(&bytes).remove(0).1, actual_texts(&bytes))
+    };
+    let (oldstyle, oldstyle_texts) = shown("oldstyle-nums");
+    let (lining, lining_texts) = shown("lining-nums");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(oldstyle[..2], oldstyle[2..4], "should draw the substitute");
+    assert_ne!(lining[..2], lining[2..4], "should draw the font's own 1");
+    // The substitute reads as the 1 it was first drawn for, and the A as
+    // itself
+    assert_eq!(oldstyle_texts, ["A"]);
+    assert!(lining_texts.is_empty());
+}
+
+#[test]
+fn test_pdf_debug_layout_outlines_boxes() {
+    let html = r#"<html><body style="margin: 8px;">
+        <div style="border: 2px solid #ccc; padding: 4px;">Hello</div>
+    </body></html>"#;
+    let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+    let strokes = |bytes: &[u8]| {
+        page_content(bytes)
+            .lines()
+            .filter(|line| *line == "S")
+            .count()
+    };
+
+    let normal = render(html, config.clone()).expect("should render");
+    assert_eq!(strokes(&normal), 0);
+
+    // html and body border boxes, body margin box, and the div's border,
+    // padding and content boxes
+    let debug = render(html, config.debug_layout(true)).expect("should render");
+    assert_eq!(strokes(&debug), 6, "{}", page_content(&debug));
+}
+
+/// The font size and text matrix `y` of each text object on the PDF's
+/// pages, in drawing order. Pages are drawn top-down, so `y` grows down
+/// the page.
+fn text_baselines(data: &[u8]) -> Vec<(f32, f32)> {
+    let doc = pdf_extract::Document::load_mem(data).expect("should parse PDF");
+    let mut size = 0.0;
+    let mut baselines = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let content = doc
+            .get_and_decode_page_content(page_id)
+            .expect("should decode page content");
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Tf" => size = operation.operands[1].as_float().expect("font size"),
+                "Tm" => baselines.push((size, operation.operands[5].as_float().expect("y"))),
+                _ => {}
+            }
+        }
+    }
+    baselines
+}
+
+#[test]
+fn test_pdf_table_caption_side() {
+    // The caption is set at 10px and the cells at 20px to tell them apart
+    let baselines = |side: &str| {
+        let html = format!(
+            r#"<html><body style="margin: 0; font-size: 20px;">
+                <table style="caption-side: {side};">
+                    <caption style="font-size: 10px;">Caption</caption>
+                    <tr><td>Cell</td></tr>
+                </table>
+            </body></html>"#
+        );
+        let config = Config::new().size(200, 100).format(OutputFormat::Pdf);
+        text_baselines(&render(&html, config).expect("render should succeed"))
+    };
+    let caption_and_cell = |side| {
+        let runs = baselines(side);
+        let y = |size| {
+            runs.iter()
+                .find(|(run_size, _)| *run_size == size)
+                .map(|(_, y)| *y)
+                .unwrap_or_else(|| panic!("no {size}px text with caption-side: {side}: {runs:?}"))
+        };
+        (y(10.0), y(20.0))
+    };
+
+    let (caption, cell) = caption_and_cell("top");
+    assert!(
+        caption < cell,
+        "top caption at {caption} should be above the cell at {cell}"
+    );
+    let (caption, cell) = caption_and_cell("bottom");
+    assert!(
+        caption > cell,
+        "bottom caption at {caption} should be below the cell at {cell}"
+    );
+}
+
+#[test]
+fn test_pdf_table_caption_keeps_table_selectors() {
+    // The table stays in place, so selectors through its parent and rows
+    // still match, as does its sibling on the side without a caption
+    let html = r#"<html><head><style>
+            body > table { background: #ff0000; }
+            table > tbody > tr > td { background: #0000ff; }
+            table + p { background: #00ff00; }
+        </style></head>
+        <body style="margin: 0;">
+            <table style="border-spacing: 0;">
+                <caption>Top</caption>
+                <tr><td style="width: 50px; height: 20px; padding: 0;"></td></tr>
+            </table>
+            <p style="margin: 0; height: 10px;"></p>
+        </body></html>"#;
+    let config = Config::new().size(200, 200).format(OutputFormat::Pdf);
+    let pdf = render(html, config).expect("render should succeed");
+    let content = page_content(&pdf);
+
+    for (color, selector) in [
+        ("1 0 0 rg", "body > table"),
+        ("0 0 1 rg", "table > tbody > tr > td"),
+        ("0 1 0 rg", "table + p"),
+    ] {
+        assert!(
+            content.contains(color),
+            "`{selector}` should still match with a caption: {content}"
+        );
+    }
+}
@@ -2,7 +2,12 @@
 
 #![cfg(feature = "png")]
 
-use hyper_render::{render, render_to_png, Config, OutputFormat};
+mod common;
+
+use common::base64;
+use hyper_render::{
+    render, render_detailed, render_to_png, Config, Error, OutputFormat, PixelMode,
+};
 
 /// PNG header magic bytes
 const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
@@ -30,6 +35,24 @@ fn parse_png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
     Some((width, height))
 }
 
+/// Decode PNG bytes into (width, height, RGBA pixels).
+fn decode_png(data: &[u8]) -> (u32, u32, Vec<u8>) {
+    let mut decoder = png::Decoder::new(data);
+    // Expand indexed images to RGBA
+    decoder.set_transformations(png::Transformations::ALPHA);
+    let mut reader = decoder.read_info().expect("should read PNG header");
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).expect("should decode PNG");
+    buf.truncate(info.buffer_size());
+    (info.width, info.height, buf)
+}
+
+/// Get the RGBA value of the pixel at (x, y).
+fn pixel_at(pixels: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+    let i = ((y * width + x) * 4) as usize;
+    [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+}
+
 #[test]
 fn test_png_basic_render() {
     let html = "<html><body><h1>Hello</h1></body></html>";
@@ -73,6 +96,25 @@ fn test_png_dimensions_match_config() {
     assert_eq!(height, 480, "PNG height should match config");
 }
 
+#[test]
+fn test_png_aspect_ratio_derives_height() {
+    // Content taller than the card is cropped, and `100vh` fills it
+    let html = r#"<html><body style="margin: 0;">
+        <div style="height: 100vh; background: #00f;"></div>
+        <div style="height: 2000px;"></div>
+    </body></html>"#;
+    let config = Config::new()
+        .width(1200)
+        .height(100)
+        .aspect_ratio(1200.0 / 630.0);
+
+    let output = render_detailed(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&output.bytes);
+    assert_eq!((width, height), (1200, 630));
+    assert_eq!((output.width, output.height), (1200, 630));
+    assert_eq!(pixel_at(&pixels, width, 600, 629), [0, 0, 255, 255]);
+}
+
 #[test]
 fn test_png_scale_factor_doubles_dimensions() {
     let html = "<html><body></body></html>";
@@ -128,6 +170,21 @@ fn test_png_transparent_background() {
     );
 }
 
+#[test]
+fn test_png_colors_are_srgb_with_straight_alpha() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="height: 10px; background: #808080;"></div>
+        <div style="height: 10px; background: rgba(255, 0, 0, 0.5);"></div>
+    </body></html>"#;
+    let config = Config::new().size(20, 20).transparent();
+
+    let (width, _, pixels) = decode_png(&render(html, config).expect("should render"));
+    // Mid-gray keeps its sRGB value, with no gamma shift
+    assert_eq!(pixel_at(&pixels, width, 5, 5), [128, 128, 128, 255]);
+    // Translucent colors are not premultiplied by their alpha
+    assert_eq!(pixel_at(&pixels, width, 5, 15), [255, 0, 0, 128]);
+}
+
 #[test]
 fn test_png_custom_background() {
     let html = "<html><body></body></html>";
@@ -212,3 +269,1283 @@ fn test_png_minimum_dimensions() {
     assert_eq!(width, Config::MIN_DIMENSION);
     assert_eq!(height, Config::MIN_DIMENSION);
 }
+
+#[test]
+fn test_png_min_height_100vh_fills_viewport() {
+    let html = r#"
+        <html>
+        <head><style>
+            body {
+                margin: 0;
+                padding: 40px;
+                min-height: 100vh;
+                background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            }
+        </style></head>
+        <body><p>Full bleed</p></body>
+        </html>
+    "#;
+
+    for scale in [1.0, 2.0] {
+        let config = Config::new().width(200).height(150).scale(scale);
+        let bytes = render(html, config).expect("render should succeed");
+        let (width, height, pixels) = decode_png(&bytes);
+
+        // Every pixel of the bottom row should be covered by the body background
+        for x in 0..width {
+            let [_, _, _, a] = pixel_at(&pixels, width, x, height - 1);
+            assert_eq!(
+                a, 255,
+                "bottom row pixel {} unpainted at scale {}",
+                x, scale
+            );
+        }
+    }
+}
+
+#[test]
+fn test_png_theme_css_follows_color_scheme() {
+    use hyper_render::ColorScheme;
+
+    let html = r#"
+        <html>
+        <head><style>body { margin: 0; height: 100vh; background: gray; }</style></head>
+        <body></body>
+        </html>
+    "#;
+
+    let themed = |scheme| {
+        Config::new()
+            .width(Config::MIN_DIMENSION)
+            .height(Config::MIN_DIMENSION)
+            .color_scheme(scheme)
+            .theme_css(
+                "body { background: rgb(255, 0, 0); }",
+                "body { background: rgb(0, 0, 255); }",
+            )
+    };
+
+    for (scheme, expected) in [
+        (ColorScheme::Light, [255, 0, 0, 255]),
+        (ColorScheme::Dark, [0, 0, 255, 255]),
+    ] {
+        let bytes = render(html, themed(scheme)).expect("render should succeed");
+        let (width, _, pixels) = decode_png(&bytes);
+        assert_eq!(
+            pixel_at(&pixels, width, 5, 5),
+            expected,
+            "theme stylesheet should apply for {:?}",
+            scheme
+        );
+    }
+}
+
+#[test]
+fn test_png_crop_margins() {
+    // A red 20px frame around a blue centre; cropping 10px leaves blue edges
+    // inside a red 10px border
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: red;">
+            <div style="margin: 20px; width: 60px; height: 60px; background: blue;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(100, 100).crop_margins(10, 10, 10, 10);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!(
+        (width, height),
+        (80, 80),
+        "crop_margins should remove 10px per side"
+    );
+    assert_eq!(pixel_at(&pixels, width, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 10, 10), [0, 0, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 69, 69), [0, 0, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 79, 79), [255, 0, 0, 255]);
+}
+
+#[test]
+fn test_png_crop_margins_larger_than_image_fails() {
+    let config = Config::new().size(100, 100).crop_margins(0, 60, 0, 40);
+
+    let result = render("<p>Hi</p>", config);
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(_))),
+        "cropping the whole width should fail"
+    );
+}
+
+#[test]
+fn test_crop_margins_rejected_for_other_formats() {
+    let config = Config::new().crop_margins(10, 10, 10, 10);
+    assert!(config.validate().is_ok());
+    for format in [
+        OutputFormat::Pdf,
+        OutputFormat::Svg,
+        OutputFormat::Gif,
+        OutputFormat::Tiff,
+    ] {
+        let result = config.clone().format(format).validate();
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(_))),
+            "crop_margins should be rejected for {format}"
+        );
+    }
+}
+
+#[test]
+fn test_paint_into_scene_matches_render() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use hyper_render::anyrender::render_to_buffer;
+    use hyper_render::paint_into_scene;
+
+    let html = r#"<html><body style="margin: 0; background: #336699;"><h1 style="color: white;">Hi</h1></body></html>"#;
+    let config = Config::new().size(100, 80);
+
+    let (_, _, expected) =
+        decode_png(&render(html, config.clone()).expect("render should succeed"));
+    let mut size = (0, 0);
+    let painted = render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| {
+            size = paint_into_scene(html, config.clone(), scene, 0.0, 0.0)
+                .expect("paint should succeed");
+        },
+        100,
+        80,
+    );
+    assert_eq!(size, (100, 80));
+    assert!(painted == expected, "scene output should match render()");
+}
+
+#[test]
+fn test_paint_into_scene_at_offset() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use hyper_render::anyrender::render_to_buffer;
+    use hyper_render::paint_into_scene;
+
+    let html = r#"<html><body style="margin: 0; background: red;"></body></html>"#;
+    let config = Config::new().size(20, 20);
+
+    // Two documents side by side; the rest of the scene stays transparent
+    let pixels = render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| {
+            paint_into_scene(html, config.clone(), scene, 0.0, 0.0).expect("paint should succeed");
+            paint_into_scene(html, config.clone(), scene, 40.0, 10.0)
+                .expect("paint should succeed");
+        },
+        60,
+        40,
+    );
+    assert_eq!(pixel_at(&pixels, 60, 5, 5), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, 60, 30, 5), [0, 0, 0, 0]);
+    assert_eq!(pixel_at(&pixels, 60, 45, 5), [0, 0, 0, 0]);
+    assert_eq!(pixel_at(&pixels, 60, 45, 15), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, 60, 45, 35), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_png_auto_height_includes_last_child_margin() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="height: 100px; margin-bottom: 50px; background: red;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().width(100).auto_height(true);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!(height, 150, "the 50px bottom margin should not be cropped");
+    assert_eq!(pixel_at(&pixels, width, 50, 99), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 50, 149), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_png_auto_height_includes_overflowing_content() {
+    // The child overflows the fixed-height body
+    let html = r#"
+        <html>
+        <body style="margin: 0; height: 10px;">
+            <div style="height: 100px; background: red;"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().width(100).auto_height(true);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!(height, 100, "overflowing content should extend the output");
+    assert_eq!(pixel_at(&pixels, width, 50, 99), [255, 0, 0, 255]);
+}
+
+#[test]
+fn test_png_css_zoom_scales_element() {
+    // Stylo folds `zoom` into computed lengths, on top of the device scale
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="zoom: 2; width: 20px; height: 10px; background: blue;"></div>
+        </body>
+        </html>
+    "#;
+
+    for scale in [1.0, 2.0] {
+        let config = Config::new().size(100, 50).scale(scale);
+        let bytes = render_to_png(html, config).expect("render should succeed");
+        let (width, _, pixels) = decode_png(&bytes);
+
+        let edge = |css_px: f32| (css_px * scale) as u32;
+        assert_eq!(
+            pixel_at(&pixels, width, edge(39.0), edge(19.0)),
+            [0, 0, 255, 255],
+            "zoomed box should cover 40x20 CSS pixels at scale {}",
+            scale
+        );
+        assert_eq!(
+            pixel_at(&pixels, width, edge(41.0), edge(5.0)),
+            [255, 255, 255, 255],
+            "zoomed box should end at x = 40 at scale {}",
+            scale
+        );
+        assert_eq!(
+            pixel_at(&pixels, width, edge(5.0), edge(21.0)),
+            [255, 255, 255, 255],
+            "zoomed box should end at y = 20 at scale {}",
+            scale
+        );
+    }
+}
+
+#[test]
+fn test_png_lang_overrides_document_language() {
+    let html = r#"
+        <html lang="en">
+        <head><style>
+            body { margin: 0; height: 100vh; background: rgb(255, 0, 0); }
+            html[lang="de"] body { background: rgb(0, 0, 255); }
+        </style></head>
+        <body></body>
+        </html>
+    "#;
+    let config = Config::new()
+        .width(Config::MIN_DIMENSION)
+        .height(Config::MIN_DIMENSION)
+        .lang("de");
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+    assert_eq!(
+        pixel_at(&pixels, width, 5, 5),
+        [0, 0, 255, 255],
+        "Config::lang should replace <html lang>"
+    );
+}
+
+#[test]
+fn test_png_overlay_draws_after_document() {
+    use hyper_render::OverlayTarget;
+
+    let html = r#"<html><body style="margin: 0; height: 100vh; background: blue;"></body></html>"#;
+    let config = Config::new().size(40, 30).scale(2.0).overlay(|target| {
+        if let OverlayTarget::Raster {
+            pixels,
+            width,
+            height,
+        } = target
+        {
+            // A red mark in the bottom-right corner
+            for y in height - 4..height {
+                for x in width - 4..width {
+                    let i = ((y * width + x) * 4) as usize;
+                    pixels[i..i + 4].copy_from_slice(&[255, 0, 0, 255]);
+                }
+            }
+        }
+    });
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!((width, height), (80, 60));
+    assert_eq!(pixel_at(&pixels, width, 79, 59), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 76, 56), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 75, 55), [0, 0, 255, 255]);
+}
+
+#[test]
+fn test_png_backdrop_filter_blurs_content_behind_panel() {
+    let page = |panel_style: &str| {
+        format!(
+            r#"<html><body style="margin: 0; background: white;">
+                <p style="margin: 0; font-size: 32px; line-height: 40px; color: black;">HHHH</p>
+                <p style="margin: 0; font-size: 32px; line-height: 40px; color: black;">HHHH</p>
+                <div style="position: absolute; left: 0; top: 0; width: 200px; height: 40px;
+                            background: rgba(255, 255, 255, 0.25); {panel_style}"></div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().size(200, 80);
+    let darkest = |bytes: &[u8], rows: std::ops::Range<u32>| {
+        let (width, _, pixels) = decode_png(bytes);
+        rows.flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixel_at(&pixels, width, x, y)[0])
+            .min()
+            .unwrap()
+    };
+
+    let sharp = render(&page(""), config.clone()).expect("render should succeed");
+    let blurred =
+        render(&page("backdrop-filter: blur(4px);"), config).expect("render should succeed");
+
+    // Behind the panel, glyph strokes are only lightened without the filter
+    // but smeared into gray with it
+    let sharp_dark = darkest(&sharp, 0..40);
+    let blurred_dark = darkest(&blurred, 0..40);
+    assert!(
+        sharp_dark < 80,
+        "sharp text should stay dark, got {sharp_dark}"
+    );
+    assert!(
+        blurred_dark > sharp_dark + 40,
+        "blurred text should lose contrast, got {blurred_dark} vs {sharp_dark}"
+    );
+
+    // Text outside the panel is untouched
+    assert_eq!(darkest(&sharp, 40..80), darkest(&blurred, 40..80));
+    assert!(darkest(&blurred, 40..80) < 40);
+}
+
+#[test]
+fn test_png_backdrop_filter_huge_blur_is_capped() {
+    let page = |radius: &str| {
+        format!(
+            r#"<html><body style="margin: 0; background: white;">
+                <div style="width: 50px; height: 60px; background: black;"></div>
+                <div style="position: absolute; left: 0; top: 0; width: 100px; height: 60px;
+                            backdrop-filter: blur({radius});"></div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().size(100, 60);
+
+    // Past the cap every blur is the same, and a huge one still finishes
+    let started = std::time::Instant::now();
+    let huge = render(&page("100000px"), config.clone()).expect("render should succeed");
+    assert!(
+        started.elapsed() < std::time::Duration::from_secs(10),
+        "huge blur took {:?}",
+        started.elapsed()
+    );
+    let capped = render(&page("1000px"), config).expect("render should succeed");
+    assert_eq!(huge, capped);
+
+    // Still blurred: the black half bleeds into the white one
+    let (width, _, pixels) = decode_png(&huge);
+    let [r, _, _, _] = pixel_at(&pixels, width, 75, 30);
+    assert!((1..255).contains(&r), "{r}");
+}
+
+#[test]
+fn test_png_rotate_output_quarter_turn() {
+    use hyper_render::Rotation;
+
+    // A red block in the top-left corner of a 100x50 page
+    let html = r#"<html><body style="margin: 0; background: white;">
+        <div style="width: 20px; height: 10px; background: red;"></div>
+    </body></html>"#;
+    let config = Config::new().size(100, 50).rotate_output(Rotation::Deg90);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!((width, height), (50, 100));
+
+    // Rotated clockwise, the block is in the top-right corner, 10 wide and 20 tall
+    assert_eq!(pixel_at(&pixels, width, 45, 15), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 45, 25), [255, 255, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 35, 15), [255, 255, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 5, 5), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_png_rotate_output_half_turn_keeps_dimensions() {
+    use hyper_render::Rotation;
+
+    let html = r#"<html><body style="margin: 0; background: white;">
+        <div style="width: 20px; height: 10px; background: red;"></div>
+    </body></html>"#;
+    let config = Config::new().size(100, 50).rotate_output(Rotation::Deg180);
+
+    let bytes = render(html, config).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&bytes);
+    assert_eq!((width, height), (100, 50));
+    assert_eq!(pixel_at(&pixels, width, 95, 45), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 5, 5), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_png_indexed_color_type_shrinks_flat_render() {
+    use hyper_render::PngColorType;
+
+    let html = r#"<html><body style="margin: 0; background: #3366cc;">
+        <div style="margin: 20px; width: 100px; height: 60px; background: #ffcc00;"></div>
+    </body></html>"#;
+    let config = Config::new().size(300, 200);
+
+    let rgba = render(html, config.clone()).expect("render should succeed");
+    let indexed = render(
+        html,
+        config.png_color_type(PngColorType::Indexed { dither: false }),
+    )
+    .expect("render should succeed");
+
+    assert!(
+        indexed.len() < rgba.len(),
+        "indexed PNG ({} bytes) should be smaller than RGBA ({} bytes)",
+        indexed.len(),
+        rgba.len()
+    );
+    // Color type 3 (indexed) in the IHDR chunk
+    assert_eq!(indexed[25], 3);
+
+    let (width, height, pixels) = decode_png(&indexed);
+    assert_eq!((width, height), (300, 200));
+    assert_eq!(pixel_at(&pixels, width, 5, 5), [0x33, 0x66, 0xcc, 255]);
+    assert_eq!(pixel_at(&pixels, width, 50, 50), [0xff, 0xcc, 0x00, 255]);
+}
+
+#[test]
+fn test_png_indexed_color_type_quantizes_gradients() {
+    use hyper_render::PngColorType;
+
+    // Each half of the gradient steps through ~256 colors
+    let html = r#"<html><body style="margin: 0; height: 100vh;
+        background: linear-gradient(to right, red, lime, blue);"></body></html>"#;
+
+    for dither in [false, true] {
+        let config = Config::new()
+            .size(600, 20)
+            .png_color_type(PngColorType::Indexed { dither });
+        let bytes = render(html, config).expect("render should succeed");
+        let (width, height, pixels) = decode_png(&bytes);
+        assert_eq!((width, height), (600, 20));
+        assert_eq!(bytes[25], 3);
+
+        // The palette still covers the middle of the gradient
+        let [r, g, b, a] = pixel_at(&pixels, width, 300, 10);
+        assert!(
+            r < 40 && g > 200 && b < 40 && a == 255,
+            "got {r} {g} {b} {a}"
+        );
+    }
+}
+
+#[test]
+fn test_png_svg_use_references_sprite_symbol() {
+    // A hidden sprite sheet, and an icon using it in the inherited text color
+    let html = r##"<html><body style="margin: 0; background: white;">
+        <svg style="display: none;">
+            <symbol id="square" viewBox="0 0 10 10">
+                <rect width="10" height="10" fill="currentColor"/>
+            </symbol>
+        </svg>
+        <div style="color: blue;">
+            <svg width="40" height="40" style="display: block; width: 40px; height: 40px;">
+                <use href="#square" x="10" y="10" width="20" height="20"/>
+            </svg>
+        </div>
+    </body></html>"##;
+
+    let bytes = render(html, Config::new().size(60, 60)).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+
+    // The symbol is drawn at the use element's position and size
+    assert_eq!(pixel_at(&pixels, width, 11, 11), [0, 0, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 28, 28), [0, 0, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 5, 5), [255, 255, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 35, 20), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_png_svg_xlink_use_references_sprite_with_own_defs() {
+    // A symbol filled through a gradient of its own, used by xlink:href
+    // from two icons
+    let html = r##"<html><body style="margin: 0; background: white;">
+        <svg style="display: none;" xmlns:xlink="http://www.w3.org/1999/xlink">
+            <symbol id="bar" viewBox="0 0 10 10">
+                <linearGradient id="fade"><stop offset="0" stop-color="lime"/><stop offset="1" stop-color="lime"/></linearGradient>
+                <rect width="10" height="10" fill="url(#fade)"/>
+            </symbol>
+        </svg>
+        <svg width="20" height="20" style="display: block;">
+            <use xlink:href="#bar" width="20" height="20"/>
+        </svg>
+        <svg width="20" height="20" style="display: block;">
+            <use href="#bar" width="20" height="20"/>
+        </svg>
+    </body></html>"##;
+
+    let bytes = render(html, Config::new().size(20, 40)).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+
+    assert_eq!(pixel_at(&pixels, width, 10, 10), [0, 255, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 10, 30), [0, 255, 0, 255]);
+}
+
+#[test]
+fn test_png_svg_current_color_through_nested_groups() {
+    // Icons colored by `color` set several groups up, inside and outside
+    // the SVG
+    let html = r##"<html><body style="margin: 0; background: white;">
+        <div style="color: blue;">
+            <svg width="40" height="20" viewBox="0 0 40 20" style="display: block;">
+                <g color="red"><g><g><path d="M0 0h10v20H0z" fill="currentColor"/></g></g></g>
+                <g color="red"><g color="lime"><rect x="10" width="10" height="20" fill="currentColor"/></g></g>
+                <g color="red"><g style="color: #ff00ff;"><rect x="20" width="10" height="20" stroke="none" fill="currentColor"/></g></g>
+                <rect x="30" width="10" height="20" fill="currentColor"/>
+            </svg>
+        </div>
+    </body></html>"##;
+
+    let bytes = render(html, Config::new().size(40, 20)).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+
+    assert_eq!(pixel_at(&pixels, width, 5, 10), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 15, 10), [0, 255, 0, 255]);
+    assert_eq!(pixel_at(&pixels, width, 25, 10), [255, 0, 255, 255]);
+    // Outside the groups the color inherited into the SVG still applies
+    assert_eq!(pixel_at(&pixels, width, 35, 10), [0, 0, 255, 255]);
+}
+
+/// Encode straight RGBA8 pixels as a PNG, for use as an input image.
+fn encode_test_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().expect("should write PNG header");
+    writer
+        .write_image_data(pixels)
+        .expect("should write PNG data");
+    writer.finish().expect("should finish PNG");
+    bytes
+}
+
+#[test]
+fn test_png_background_image_fits() {
+    use hyper_render::BackgroundFit;
+
+    // 20x10: red on the left half, blue on the right
+    let pixels: Vec<u8> = (0..10 * 20)
+        .flat_map(|i| match i % 20 < 10 {
+            true => [255, 0, 0, 255],
+            false => [0, 0, 255, 255],
+        })
+        .collect();
+    let image = encode_test_png(20, 10, &pixels);
+    let html = r#"<html><body style="margin: 0;">
+        <div style="width: 10px; height: 10px; background: lime;"></div>
+    </body></html>"#;
+    let render_fit = |fit| {
+        let config = Config::new()
+            .size(100, 100)
+            .background_image(image.clone(), fit);
+        let (width, _, pixels) = decode_png(&render(html, config).expect("render should succeed"));
+        move |x, y| pixel_at(&pixels, width, x, y)
+    };
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    // Cover scales to 200x100, cropping the sides
+    let cover = render_fit(BackgroundFit::Cover);
+    assert_eq!(cover(25, 50), RED);
+    assert_eq!(cover(75, 50), BLUE);
+    assert_eq!(cover(25, 95), RED);
+    // Page content is drawn over the image
+    assert_eq!(cover(5, 5), [0, 255, 0, 255]);
+
+    // Contain scales to 100x50, centered vertically
+    let contain = render_fit(BackgroundFit::Contain);
+    assert_eq!(contain(25, 50), RED);
+    assert_eq!(contain(75, 50), BLUE);
+    assert_eq!(contain(50, 15), CLEAR);
+    assert_eq!(contain(50, 85), CLEAR);
+
+    // Center keeps the natural size
+    let center = render_fit(BackgroundFit::Center);
+    assert_eq!(center(43, 50), RED);
+    assert_eq!(center(56, 50), BLUE);
+    assert_eq!(center(30, 50), CLEAR);
+    assert_eq!(center(50, 40), CLEAR);
+
+    // Tile repeats the natural size from the origin
+    let tile = render_fit(BackgroundFit::Tile);
+    assert_eq!(tile(25, 15), RED);
+    assert_eq!(tile(35, 15), BLUE);
+    assert_eq!(tile(85, 95), RED);
+    assert_eq!(tile(95, 95), BLUE);
+}
+
+#[test]
+fn test_png_background_image_invalid_data() {
+    use hyper_render::BackgroundFit;
+
+    let config = Config::new().background_image(b"not an image".to_vec(), BackgroundFit::Cover);
+    let result = render("<html><body></body></html>", config);
+    assert!(
+        matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("background image")),
+        "got {:?}",
+        result.map(|bytes| bytes.len())
+    );
+}
+
+#[test]
+fn test_png_max_decoded_image_dimension_keeps_drawn_size() {
+    use hyper_render::BackgroundFit;
+
+    let image = encode_test_png(40, 20, &[255, 0, 0, 255].repeat(40 * 20));
+    let config = Config::new()
+        .size(100, 100)
+        .background_image(image, BackgroundFit::Center)
+        .max_decoded_image_dimension(8);
+    let bytes = render("<html><body></body></html>", config).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+
+    // Decoded at 8x4 but still drawn over the natural 40x20 at (30, 40)
+    for (x, y) in [(32, 42), (50, 50), (68, 58)] {
+        assert_eq!(
+            pixel_at(&pixels, width, x, y),
+            [255, 0, 0, 255],
+            "({x}, {y})"
+        );
+    }
+    for (x, y) in [(28, 50), (72, 50), (50, 38), (50, 62)] {
+        assert_eq!(pixel_at(&pixels, width, x, y), [0, 0, 0, 0], "({x}, {y})");
+    }
+}
+
+#[test]
+fn test_png_max_decoded_image_dimension_skips_document_images() {
+    use hyper_render::{render_with_warnings, Warning};
+
+    let image = base64(&encode_test_png(40, 20, &[0, 0, 0, 255].repeat(40 * 20)));
+    let html = format!(
+        r#"<html><body style="margin: 0;">
+            <div style="font-size: 0;"><img src="data:image/png;base64,{image}"></div>
+            <div style="width: 40px; height: 20px;
+                background: url(data:image/png;base64,{image}) no-repeat;"></div>
+            <div style="width: 10px; height: 10px; background: lime;"></div>
+        </body></html>"#
+    );
+    let config = Config::new().size(100, 100).max_decoded_image_dimension(8);
+
+    let (bytes, warnings) =
+        render_with_warnings(&html, config.clone()).expect("render should succeed");
+    let skipped = Warning::ImageTooLarge {
+        width: 40,
+        height: 20,
+        limit: 8,
+    };
+    assert!(!warnings.is_empty());
+    assert!(
+        warnings.iter().all(|warning| *warning == skipped),
+        "{warnings:?}"
+    );
+    let (width, _, pixels) = decode_png(&bytes);
+    // Neither image is drawn, and the skipped <img> takes no space
+    assert_eq!(pixel_at(&pixels, width, 20, 10), [0, 0, 0, 0]);
+    assert_eq!(pixel_at(&pixels, width, 5, 25), [0, 255, 0, 255]);
+
+    // Images within the limit are decoded as usual
+    let (_, warnings) = render_with_warnings(&html, config.clone().max_decoded_image_dimension(40))
+        .expect("render should succeed");
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let result = render(&html, config.strict(true));
+    assert!(
+        matches!(result, Err(Error::Strict(Warning::ImageTooLarge { .. }))),
+        "strict mode should fail on oversized images"
+    );
+}
+
+#[test]
+fn test_png_data_uri_image_takes_intrinsic_size() {
+    // A 20x30 image, whose size is only known once the data URI is loaded
+    // after the first layout pass
+    let image = encode_test_png(20, 30, &[0, 0, 255, 255].repeat(20 * 30));
+    let html = format!(
+        r#"<html><body style="margin: 0;">
+            <div style="font-size: 0;"><img src="data:image/png;base64,{}"></div>
+            <div style="width: 10px; height: 10px; background: lime;"></div>
+        </body></html>"#,
+        base64(&image)
+    );
+    let bytes = render(&html, Config::new().size(100, 100)).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&bytes);
+
+    assert_eq!(pixel_at(&pixels, width, 10, 15), [0, 0, 255, 255]);
+    assert_eq!(pixel_at(&pixels, width, 25, 15), [0, 0, 0, 0]);
+    // The div is pushed below the image
+    assert_eq!(pixel_at(&pixels, width, 5, 35), [0, 255, 0, 255]);
+}
+
+#[test]
+fn test_png_resolve_timestamp_samples_animation() {
+    let html = r#"<html><head><style>
+        @keyframes fade { from { background: red; } to { background: blue; } }
+        body { margin: 0; }
+        div { width: 40px; height: 40px; animation: fade 1s linear forwards; }
+    </style></head><body><div></div></body></html>"#;
+    let color_at = |seconds: f64| {
+        let config = Config::new().size(100, 100).resolve_timestamp(seconds);
+        let (width, _, pixels) = decode_png(&render(html, config).expect("render should succeed"));
+        pixel_at(&pixels, width, 20, 20)
+    };
+
+    assert_eq!(color_at(0.0), [255, 0, 0, 255]);
+    let [r, _, b, _] = color_at(0.5);
+    assert!(r > 64 && b > 64, "half-way should be a mix, got {r} {b}");
+    assert_eq!(color_at(2.0), [0, 0, 255, 255]);
+}
+
+#[test]
+fn test_png_overflow_scroll_reserves_no_scrollbar_gutter() {
+    // Scrollable boxes render like a screenshot with overlay scrollbars:
+    // no gutter is reserved and no scrollbar is painted
+    let html = r#"<html><body style="margin: 0; background: white;">
+        <div style="width: 100px; height: 50px; overflow: scroll;">
+            <div style="height: 200px; background: rgb(255, 0, 0);"></div>
+        </div>
+    </body></html>"#;
+    let config = Config::new().size(200, 100).format(OutputFormat::Png);
+
+    let png = render(html, config).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&png);
+    for x in [0, 50, 99] {
+        for y in [0, 49] {
+            assert_eq!(
+                pixel_at(&pixels, width, x, y),
+                [255, 0, 0, 255],
+                "at ({x}, {y})"
+            );
+        }
+    }
+    assert_eq!(pixel_at(&pixels, width, 100, 25), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_png_resolution_media_query_follows_scale() {
+    let html = r#"<html><head><style>
+        body { margin: 0; background: rgb(255, 0, 0); }
+        @media (min-resolution: 2dppx) { body { background: rgb(0, 0, 255); } }
+    </style></head><body></body></html>"#;
+    let background = |scale: f32| {
+        let config = Config::new().size(20, 20).scale(scale);
+        let png = render(html, config).expect("render should succeed");
+        let (width, _, pixels) = decode_png(&png);
+        pixel_at(&pixels, width, 1, 1)
+    };
+
+    assert_eq!(background(1.0), [255, 0, 0, 255]);
+    assert_eq!(background(1.5), [255, 0, 0, 255]);
+    assert_eq!(background(2.0), [0, 0, 255, 255]);
+    assert_eq!(background(3.0), [0, 0, 255, 255]);
+}
+
+#[test]
+fn test_png_background_image_contain_letterboxes_without_distortion() {
+    use hyper_render::BackgroundFit;
+
+    // 2:1 content fitted into a 1:1 page keeps its aspect ratio: it spans
+    // the full width at half the height, with empty bands above and below
+    let pixels: Vec<u8> = (0..10 * 20)
+        .flat_map(|i| match i % 20 < 10 {
+            true => [255, 0, 0, 255],
+            false => [0, 0, 255, 255],
+        })
+        .collect();
+    let image = encode_test_png(20, 10, &pixels);
+    let config = Config::new()
+        .size(100, 100)
+        .background_image(image, BackgroundFit::Contain);
+    let png = render("<html><body></body></html>", config).expect("render should succeed");
+    let (width, _, pixels) = decode_png(&png);
+    let at = |x, y| pixel_at(&pixels, width, x, y);
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    // Away from the red/blue edge, which the scaling filter blends
+    for x in [0, 40] {
+        assert_eq!(at(x, 25), RED, "at ({x}, 25)");
+        assert_eq!(at(x, 74), RED, "at ({x}, 74)");
+    }
+    for x in [60, 99] {
+        assert_eq!(at(x, 25), BLUE, "at ({x}, 25)");
+        assert_eq!(at(x, 74), BLUE, "at ({x}, 74)");
+    }
+    for x in [0, 50, 99] {
+        assert_eq!(at(x, 23), CLEAR, "at ({x}, 23)");
+        assert_eq!(at(x, 76), CLEAR, "at ({x}, 76)");
+    }
+}
+
+/// Width and height of the blue (`#00f`) pixels of a PNG, from the origin.
+fn blue_extent(png: &[u8]) -> (u32, u32) {
+    let (width, height, pixels) = decode_png(png);
+    let mut extent = (0, 0);
+    for y in 0..height {
+        for x in 0..width {
+            if pixel_at(&pixels, width, x, y) == [0, 0, 255, 255] {
+                extent = (extent.0.max(x + 1), extent.1.max(y + 1));
+            }
+        }
+    }
+    extent
+}
+
+#[test]
+fn test_png_fit_content_width_shrinks_to_contents() {
+    let html = |style: &str| {
+        format!(
+            r#"<html><body style="margin: 0;">
+                <div style="{style}; background: #00f; font-size: 20px; line-height: 30px;">Hello wide world</div>
+            </body></html>"#
+        )
+    };
+    let config = Config::new().width(400).height(200);
+    let extent = |style: &str| {
+        blue_extent(&render(&html(style), config.clone()).expect("render should succeed"))
+    };
+
+    // An inline-block shrinks to its contents, as do the intrinsic keywords
+    let (content_width, _) = extent("display: inline-block");
+    assert!(content_width > 50 && content_width < 300, "{content_width}");
+    for width in ["fit-content", "max-content"] {
+        assert_eq!(
+            extent(&format!("width: {width}")),
+            (content_width, 30),
+            "{width}"
+        );
+    }
+    assert_eq!(extent("width: auto"), (400, 30));
+
+    // Narrower widths wrap the text onto a line per word
+    let (min_width, min_height) = extent("width: min-content");
+    assert!(min_width < content_width / 2, "{min_width}");
+    assert_eq!(min_height, 90);
+    assert_eq!(extent("width: fit-content(100px)"), (100, 90));
+    assert_eq!(extent("max-width: min-content"), (min_width, 90));
+
+    // Padding of a content-box element is added to the fitted width
+    assert_eq!(
+        extent("width: fit-content; padding: 0 10px"),
+        (content_width + 20, 30)
+    );
+}
+
+#[test]
+fn test_png_fit_content_in_narrow_viewport_wraps_with_auto_height() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="width: fit-content; background: #00f; font-size: 20px; line-height: 30px;">
+            Hello wide world and a good deal more text that wraps
+        </div>
+    </body></html>"#;
+    let config = Config::new().width(200).height(20).auto_height(true);
+
+    // Contents wider than the viewport fill it and wrap, and the capture
+    // grows to hold every line
+    let png = render(html, config).expect("render should succeed");
+    let (_, height) = parse_png_dimensions(&png).expect("should parse PNG");
+    let (width, blue_height) = blue_extent(&png);
+    assert_eq!(width, 200);
+    assert!(blue_height > 60, "{blue_height}");
+    assert_eq!(height, blue_height);
+}
+
+/// The card from `examples/simple.rs`.
+const SIMPLE_EXAMPLE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <style>
+        body {
+            font-family: system-ui, -apple-system, sans-serif;
+            padding: 40px;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            min-height: 100vh;
+            margin: 0;
+        }
+        .card {
+            background: white;
+            border-radius: 16px;
+            padding: 32px;
+            max-width: 500px;
+            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
+        }
+        h1 { color: #1a1a2e; margin: 0 0 16px 0; font-size: 28px; }
+        p { color: #4a4a68; line-height: 1.6; margin: 0 0 24px 0; }
+        .badge {
+            display: inline-block;
+            background: #667eea;
+            color: white;
+            padding: 8px 16px;
+            border-radius: 20px;
+            font-size: 14px;
+            font-weight: 500;
+        }
+    </style>
+</head>
+<body>
+    <div class="card">
+        <h1>Hello, hyper-render!</h1>
+        <p>
+            This HTML was rendered to an image without using Chromium.
+            The rendering is powered by Blitz, Stylo, and Taffy.
+        </p>
+        <span class="badge">Pure Rust</span>
+    </div>
+</body>
+</html>"#;
+
+#[test]
+fn test_png_capture_crops_to_element() {
+    let config = Config::new().size(800, 600);
+    const BADGE: [u8; 4] = [0x66, 0x7e, 0xea, 255];
+
+    // The badge's extent on the full page, within the white card: the
+    // rows its fill reaches, and the pixels it tints in those rows
+    let png = render(SIMPLE_EXAMPLE, config.clone()).expect("render should succeed");
+    let (width, height, pixels) = decode_png(&png);
+    let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+    for y in 72..height {
+        if (72..500).any(|x| pixel_at(&pixels, width, x, y) == BADGE) {
+            (top, bottom) = (top.min(y), bottom.max(y + 1));
+        }
+    }
+    for y in top..bottom {
+        for x in 72..500 {
+            if pixel_at(&pixels, width, x, y) != [255, 255, 255, 255] {
+                (left, right) = (left.min(x), right.max(x + 1));
+            }
+        }
+    }
+    assert!(right > left && bottom > top, "badge should be painted");
+
+    let output = render_detailed(SIMPLE_EXAMPLE, config.clone().capture(".badge"))
+        .expect("render should succeed");
+    let (captured_width, captured_height, captured) = decode_png(&output.bytes);
+    assert_eq!(
+        (output.width, output.height),
+        (captured_width, captured_height)
+    );
+    assert!(
+        captured_width.abs_diff(right - left) <= 1,
+        "{captured_width}"
+    );
+    assert!(
+        captured_height.abs_diff(bottom - top) <= 1,
+        "{captured_height}"
+    );
+    let center = pixel_at(&captured, captured_width, captured_width / 2, 2);
+    assert_eq!(center, BADGE);
+    assert_eq!(
+        captured,
+        crop(&pixels, width, left, top, captured_width, captured_height)
+    );
+
+    // Padding keeps the white card around the badge
+    let png = render(SIMPLE_EXAMPLE, config.capture(".badge").capture_padding(5))
+        .expect("render should succeed");
+    let (padded_width, padded_height, padded) = decode_png(&png);
+    assert_eq!(
+        (padded_width, padded_height),
+        (captured_width + 10, captured_height + 10)
+    );
+    assert_eq!(pixel_at(&padded, padded_width, 2, 2), [255, 255, 255, 255]);
+    assert_eq!(pixel_at(&padded, padded_width, padded_width / 2, 7), BADGE);
+}
+
+/// The `width` x `height` pixels at `(x, y)` of an RGBA image.
+fn crop(pixels: &[u8], stride: u32, x: u32, y: u32, width: u32, height: u32) -> Vec<u8> {
+    (y..y + height)
+        .flat_map(|row| {
+            let start = ((row * stride + x) * 4) as usize;
+            pixels[start..start + width as usize * 4].iter().copied()
+        })
+        .collect()
+}
+
+#[test]
+fn test_png_capture_below_the_viewport() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="height: 300px;"></div>
+        <div id="target" style="width: 50px; height: 40px; background: #00f;"></div>
+    </body></html>"#;
+
+    let png = render(
+        html,
+        Config::new().size(200, 100).capture("#target").scale(2.0),
+    )
+    .expect("render should succeed");
+    let (width, height, pixels) = decode_png(&png);
+    assert_eq!((width, height), (100, 80));
+    assert!(pixels
+        .chunks_exact(4)
+        .all(|pixel| pixel == [0, 0, 255, 255]));
+}
+
+#[test]
+fn test_png_capture_without_match_fails() {
+    let html = "<html><body><p>Hello</p></body></html>";
+    for selector in [".missing", "p[", "head"] {
+        let result = render(html, Config::new().capture(selector));
+        assert!(
+            matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("capture")),
+            "{selector}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_png_capture_overhang_keeps_content_past_the_left_edge() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="position: absolute; left: -20px; top: 10px; width: 60px; height: 30px; background: #00f;"></div>
+        <div style="position: absolute; left: 195px; top: 50px; width: 15px; height: 10px; background: #f00;"></div>
+    </body></html>"#;
+    let config = Config::new().size(200, 100);
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+
+    // Without the option both boxes are cut off at the page's edges
+    let (width, height, pixels) = decode_png(&render(html, config.clone()).unwrap());
+    assert_eq!((width, height), (200, 100));
+    assert_eq!(pixel_at(&pixels, width, 0, 20), BLUE);
+    assert_eq!(pixel_at(&pixels, width, 40, 20), [0, 0, 0, 0]);
+    assert_eq!(pixel_at(&pixels, width, 199, 55), RED);
+
+    let output = render_detailed(html, config.capture_overhang(true)).expect("should render");
+    let (width, height, pixels) = decode_png(&output.bytes);
+    assert_eq!((width, height), (230, 100));
+    assert_eq!((output.width, output.height), (width, height));
+    // Both boxes are whole, with the page shifted right to make room
+    for x in 0..60 {
+        assert_eq!(pixel_at(&pixels, width, x, 20), BLUE, "x = {x}");
+    }
+    assert_eq!(pixel_at(&pixels, width, 60, 20), [0, 0, 0, 0]);
+    assert_eq!(pixel_at(&pixels, width, 10, 5), [0, 0, 0, 0]);
+    for x in 215..230 {
+        assert_eq!(pixel_at(&pixels, width, x, 55), RED, "x = {x}");
+    }
+    assert_eq!(pixel_at(&pixels, width, 214, 55), [0, 0, 0, 0]);
+}
+
+/// The pixel density in a PNG's `pHYs` chunk, in dots per inch.
+fn png_dpi(data: &[u8]) -> Option<f32> {
+    let reader = png::Decoder::new(data)
+        .read_info()
+        .expect("should read PNG header");
+    let dims = reader.info().pixel_dims?;
+    assert_eq!(dims.unit, png::Unit::Meter);
+    assert_eq!(dims.xppu, dims.yppu);
+    Some(dims.xppu as f32 * 0.0254)
+}
+
+#[test]
+fn test_png_pixel_mode_and_dpi() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="width: 100px; height: 100px; background: #00f;"></div>
+    </body></html>"#;
+    let config = Config::new().size(200, 200);
+
+    let png = render(html, config.clone()).expect("render should succeed");
+    assert_eq!(png_dpi(&png), None);
+    assert_eq!(blue_extent(&png), (100, 100));
+
+    // CSS pixels stay device pixels at any DPI, which is only recorded
+    let css_px = config.clone().dpi(300.0);
+    assert_eq!(css_px.pixel_mode, PixelMode::CssPx);
+    let output = render_detailed(html, css_px).expect("render should succeed");
+    assert_eq!(parse_png_dimensions(&output.bytes), Some((200, 200)));
+    assert_eq!(blue_extent(&output.bytes), (100, 100));
+    assert_eq!(output.scale, 1.0);
+    assert!((png_dpi(&output.bytes).unwrap() - 300.0).abs() < 0.01);
+
+    // Physical pixels scale CSS pixels by DPI / 96, on top of the scale
+    let physical = config.dpi(192.0).pixel_mode(PixelMode::Physical);
+    let output = render_detailed(html, physical.clone()).expect("render should succeed");
+    assert_eq!(parse_png_dimensions(&output.bytes), Some((400, 400)));
+    assert_eq!(blue_extent(&output.bytes), (200, 200));
+    assert_eq!(output.scale, 2.0);
+    let png = render(html, physical.scale(1.5)).expect("render should succeed");
+    assert_eq!(blue_extent(&png), (300, 300));
+}
+
+#[test]
+fn test_png_pixel_snap_keeps_hairlines_crisp() {
+    // The border starts half way into a pixel
+    let html = r#"<html><body style="margin: 0; background: white;">
+        <div style="height: 7.5px;"></div>
+        <div style="border-top: 1px solid black;"></div>
+    </body></html>"#;
+    let border_rows = |config: Config| {
+        let (width, height, pixels) =
+            decode_png(&render(html, config).expect("render should succeed"));
+        (0..height)
+            .map(|y| pixel_at(&pixels, width, 20, y))
+            .filter(|&pixel| pixel != [255, 255, 255, 255])
+            .collect::<Vec<_>>()
+    };
+
+    for scale in [1.0, 1.5] {
+        let config = Config::new().size(40, 20).scale(scale);
+        let blurred = border_rows(config.clone());
+        assert_eq!(blurred.len(), 2, "at {scale}x: {blurred:?}");
+
+        let snapped = border_rows(config.pixel_snap(true));
+        assert_eq!(snapped, vec![[0, 0, 0, 255]], "at {scale}x");
+    }
+}
+
+#[test]
+fn test_png_fragment_mode_has_no_body_margin() {
+    let html = r#"<div style="width: 20px; height: 10px; background: #00f;">x</div>"#;
+    let config = Config::new().size(100, 50);
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    // As a document, the fragment is offset by the body's 8px margin
+    let (width, _, pixels) = decode_png(&render(html, config.clone()).unwrap());
+    assert_ne!(pixel_at(&pixels, width, 0, 0), BLUE);
+    assert_eq!(pixel_at(&pixels, width, 27, 17), BLUE);
+
+    let (width, _, pixels) = decode_png(&render(html, config.clone().fragment_mode(true)).unwrap());
+    assert_eq!(pixel_at(&pixels, width, 0, 0), BLUE);
+    assert_eq!(pixel_at(&pixels, width, 19, 9), BLUE);
+    assert_ne!(pixel_at(&pixels, width, 20, 0), BLUE);
+
+    // The fragment's own styles still win
+    let styled = format!("<style>body {{ margin: 4px; }}</style>{html}");
+    let (width, _, pixels) = decode_png(&render(&styled, config.fragment_mode(true)).unwrap());
+    assert_ne!(pixel_at(&pixels, width, 3, 3), BLUE);
+    assert_eq!(pixel_at(&pixels, width, 4, 4), BLUE);
+}
+
+#[test]
+fn test_png_debug_layout_outlines_boxes() {
+    let html = r#"<html><body style="margin: 0;">
+        <div style="position: absolute; left: 20px; top: 20px; width: 100px; height: 40px;
+                    border: 4px solid #ccc; padding: 10px;"></div>
+    </body></html>"#;
+    let config = Config::new().size(200, 120);
+    // Border, padding and content box outlines along the box's middle row
+    let edges = [
+        (20, [212, 172, 13, 255]),
+        (24, [39, 174, 96, 255]),
+        (34, [41, 128, 185, 255]),
+    ];
+
+    let (width, _, pixels) = decode_png(&render(html, config.clone()).unwrap());
+    for (x, color) in edges {
+        assert_ne!(pixel_at(&pixels, width, x, 50), color, "x = {x}");
+    }
+
+    let (width, _, pixels) = decode_png(&render(html, config.debug_layout(true)).unwrap());
+    for (x, color) in edges {
+        assert_eq!(pixel_at(&pixels, width, x, 50), color, "x = {x}");
+    }
+    // Inside the content box is left as painted
+    assert_eq!(pixel_at(&pixels, width, 60, 50), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_png_image_rendering_pixelated_samples_nearest_pixel() {
+    use hyper_render::BackgroundFit;
+
+    // A 2x2 checkerboard scaled up to 64x64
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    let image = encode_test_png(2, 2, &[BLACK, WHITE, WHITE, BLACK].concat());
+    let quadrant = |x: u32, y: u32| match (x < 32) == (y < 32) {
+        true => BLACK,
+        false => WHITE,
+    };
+    let config = Config::new().size(64, 64);
+    let element = |rendering: &str| {
+        let html = format!(
+            r#"<html><body style="margin: 0; font-size: 0;">
+                <img src="data:image/png;base64,{}" width="64" height="64"
+                    style="image-rendering: {rendering};">
+            </body></html>"#,
+            base64(&image)
+        );
+        decode_png(&render(&html, config.clone()).expect("render should succeed")).2
+    };
+    let page = |rendering: &str| {
+        let html = format!(r#"<html style="image-rendering: {rendering};"></html>"#);
+        let config = config
+            .clone()
+            .background_image(image.clone(), BackgroundFit::Cover);
+        decode_png(&render(&html, config).expect("render should succeed")).2
+    };
+
+    for pixels in [element("pixelated"), page("pixelated"), page("crisp-edges")] {
+        for y in 0..64 {
+            for x in 0..64 {
+                assert_eq!(pixel_at(&pixels, 64, x, y), quadrant(x, y), "({x}, {y})");
+            }
+        }
+    }
+    // Smoothing blends the quadrants where they meet
+    for pixels in [element("auto"), page("auto")] {
+        let [r, ..] = pixel_at(&pixels, 64, 31, 32);
+        assert!(r > 0 && r < 255, "{r}");
+    }
+}
+
+#[test]
+fn test_png_background_position_keywords() {
+    // A red 20x10 image in the 100x50 padding box of a bordered box at (10, 10)
+    let image = base64(&encode_test_png(20, 10, &[255, 0, 0, 255].repeat(20 * 10)));
+    let render_position = |position: &str| {
+        let html = format!(
+            r#"<html><body style="margin: 0;">
+                <div style="margin: 10px; width: 100px; height: 50px; border: 2px solid blue;
+                    background: url(data:image/png;base64,{image}) no-repeat {position};"></div>
+            </body></html>"#
+        );
+        let config = Config::new().size(200, 100);
+        let (width, _, pixels) = decode_png(&render(&html, config).expect("render should succeed"));
+        move |x, y| pixel_at(&pixels, width, x, y)
+    };
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    // Flush with the bottom-right corner of the padding box, at (92, 52)
+    let bottom_right = render_position("bottom right");
+    for (x, y) in [(92, 52), (111, 52), (92, 61), (111, 61)] {
+        assert_eq!(bottom_right(x, y), RED, "({x}, {y})");
+    }
+    for (x, y) in [(91, 56), (100, 51), (12, 12)] {
+        assert_eq!(bottom_right(x, y), CLEAR, "({x}, {y})");
+    }
+
+    // Percentages line up the image's point with the box's: 50% of the
+    // 80x40 left over puts it at (52, 32)
+    let center = render_position("center");
+    for (x, y) in [(52, 32), (71, 41)] {
+        assert_eq!(center(x, y), RED, "({x}, {y})");
+    }
+    for (x, y) in [(51, 36), (72, 36), (60, 31), (60, 42)] {
+        assert_eq!(center(x, y), CLEAR, "({x}, {y})");
+    }
+
+    // Keyword offsets count inwards from the named edge
+    let offset = render_position("right 5px top 3px");
+    for (x, y) in [(87, 15), (106, 24)] {
+        assert_eq!(offset(x, y), RED, "({x}, {y})");
+    }
+    for (x, y) in [(107, 20), (95, 14)] {
+        assert_eq!(offset(x, y), CLEAR, "({x}, {y})");
+    }
+}
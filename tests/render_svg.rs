@@ -0,0 +1,75 @@
+//! Integration tests for SVG rendering.
+
+#![cfg(feature = "svg")]
+
+use hyper_render::{render, render_to_svg_string, Config, OutputFormat};
+
+/// Parse SVG markup, panicking if it is not a valid standalone document.
+fn parse_svg(svg: &str) -> usvg::Tree {
+    usvg::Tree::from_str(svg, &usvg::Options::default())
+        .unwrap_or_else(|e| panic!("output should be valid SVG: {}", e))
+}
+
+#[test]
+fn test_svg_string_has_view_box() {
+    let html = "<html><body><h1>Hello</h1></body></html>";
+    let config = Config::new().width(400).height(300);
+
+    let svg = render_to_svg_string(html, config).expect("render should succeed");
+    assert!(svg.starts_with("<svg"), "output should start with <svg");
+    assert!(
+        svg.contains(r#"viewBox="0 0 400 300""#),
+        "viewBox should match the config size"
+    );
+
+    let tree = parse_svg(&svg);
+    assert_eq!(tree.size().width(), 400.0);
+    assert_eq!(tree.size().height(), 300.0);
+}
+
+#[test]
+fn test_svg_view_box_is_scaled() {
+    let html = "<html><body><p>Retina</p></body></html>";
+    let config = Config::new().width(200).height(100).scale(2.0);
+
+    let svg = render_to_svg_string(html, config).expect("render should succeed");
+    assert!(
+        svg.contains(r#"width="400" height="200" viewBox="0 0 400 200""#),
+        "SVG should be sized in device pixels"
+    );
+}
+
+#[test]
+fn test_svg_format_enum_matches_string() {
+    let html = r#"<html><body style="background: navy;"><p>Same</p></body></html>"#;
+    let config = Config::new().width(200).height(100);
+
+    let bytes = render(html, config.clone().format(OutputFormat::Svg)).expect("render");
+    let svg = render_to_svg_string(html, config).expect("render");
+    assert_eq!(bytes, svg.into_bytes());
+}
+
+#[test]
+fn test_svg_styled_content() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: linear-gradient(90deg, #667eea, #764ba2);">
+            <div style="margin: 20px; padding: 16px; background: white; border-radius: 8px;
+                        border: 2px solid #333; box-shadow: 0 4px 12px rgba(0,0,0,0.3);
+                        overflow: hidden; opacity: 0.9;">
+                <h1 style="color: navy;">Styled</h1>
+                <p>Text with <em>emphasis</em></p>
+            </div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().width(400).height(300);
+
+    let svg = render_to_svg_string(html, config).expect("render should succeed");
+    parse_svg(&svg);
+    assert!(svg.contains("<linearGradient"), "gradient background");
+    assert!(svg.contains("<clipPath"), "clipped layer");
+    assert!(svg.contains("feGaussianBlur"), "blurred box shadow");
+    assert!(svg.contains("fill=\"#000080\""), "navy heading text");
+    assert!(svg.contains("<use "), "text glyphs");
+}
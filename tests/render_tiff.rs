@@ -0,0 +1,91 @@
+//! Integration tests for TIFF rendering.
+
+#![cfg(feature = "tiff")]
+
+use hyper_render::{render, render_detailed, Config, OutputFormat};
+use std::io::Cursor;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::ColorType;
+
+/// Decode every image directory of a TIFF into (width, height, RGBA pixels).
+fn decode_tiff(data: &[u8]) -> Vec<(u32, u32, Vec<u8>)> {
+    let mut decoder = Decoder::new(Cursor::new(data)).expect("should read TIFF header");
+    let mut pages = Vec::new();
+    loop {
+        assert_eq!(
+            decoder.colortype().expect("should read color type"),
+            ColorType::RGBA(8)
+        );
+        let (width, height) = decoder.dimensions().expect("should read dimensions");
+        let DecodingResult::U8(pixels) = decoder.read_image().expect("should decode image") else {
+            panic!("expected 8-bit samples");
+        };
+        pages.push((width, height, pixels));
+
+        if !decoder.more_images() {
+            break;
+        }
+        decoder.next_image().expect("should read next directory");
+    }
+    pages
+}
+
+#[test]
+fn test_tiff_single_page_has_one_directory() {
+    let html = r#"
+        <html>
+        <body style="margin: 0; background: white;">
+            <div style="width: 20px; height: 20px; background: rgb(255, 0, 0);"></div>
+        </body>
+        </html>
+    "#;
+    let config = Config::new().size(64, 48).format(OutputFormat::Tiff);
+
+    let bytes = render(html, config).expect("render should succeed");
+    assert!(
+        bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*"),
+        "output should start with a TIFF header"
+    );
+
+    let pages = decode_tiff(&bytes);
+    assert_eq!(
+        pages.len(),
+        1,
+        "single-page content should give one directory"
+    );
+    let (width, height, pixels) = &pages[0];
+    assert_eq!((*width, *height), (64, 48));
+    assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+    let i = (10 * 64 + 40) * 4;
+    assert_eq!(&pixels[i..i + 4], &[255, 255, 255, 255]);
+}
+
+#[test]
+fn test_tiff_keeps_alpha_and_scale() {
+    let config = Config::new()
+        .size(32, 16)
+        .scale(2.0)
+        .transparent()
+        .format(OutputFormat::Tiff);
+
+    let output = render_detailed("<html><body></body></html>", config).expect("should render");
+    assert_eq!(output.page_count, 1);
+
+    let pages = decode_tiff(&output.bytes);
+    let (width, height, pixels) = &pages[0];
+    assert_eq!((*width, *height), (64, 32));
+    assert_eq!(pixels[3], 0, "transparent background should keep alpha");
+}
+
+#[test]
+fn test_tiff_alpha_is_unassociated() {
+    let html = r#"<html><body style="margin: 0; background: rgba(255, 0, 0, 0.5);"></body></html>"#;
+    let config = Config::new()
+        .size(16, 16)
+        .transparent()
+        .format(OutputFormat::Tiff);
+
+    let bytes = render(html, config).expect("should render");
+    let (_, _, pixels) = &decode_tiff(&bytes)[0];
+    assert_eq!(&pixels[..4], &[255, 0, 0, 128]);
+}
@@ -0,0 +1,201 @@
+//! Integration tests for render warnings and strict mode.
+
+#![cfg(feature = "png")]
+
+use hyper_render::{render, render_with_warnings, Config, Error, ResourcePolicy, Warning};
+
+const TALL_HTML: &str = r#"
+    <html>
+    <body style="margin: 0;">
+        <div style="height: 500px; background: red;"></div>
+    </body>
+    </html>
+"#;
+
+#[test]
+fn test_overflow_guard_reports_clipped_content() {
+    let config = Config::new().width(200).height(100).overflow_guard(true);
+
+    let (bytes, warnings) = render_with_warnings(TALL_HTML, config).expect("should render");
+    assert!(!bytes.is_empty(), "output should still be produced");
+    assert_eq!(
+        warnings,
+        vec![Warning::ContentClipped {
+            overflow_x: 0,
+            overflow_y: 400,
+        }]
+    );
+}
+
+#[test]
+fn test_overflow_guard_disabled_by_default() {
+    let config = Config::new().width(200).height(100);
+
+    let (_, warnings) = render_with_warnings(TALL_HTML, config).expect("should render");
+    assert!(warnings.is_empty(), "no warnings without overflow_guard");
+}
+
+#[test]
+fn test_overflow_guard_fitting_content() {
+    let html = r#"<html><body style="margin: 0;"><div style="height: 50px;"></div></body></html>"#;
+    let config = Config::new().width(200).height(100).overflow_guard(true);
+
+    let (_, warnings) = render_with_warnings(html, config).expect("should render");
+    assert!(warnings.is_empty(), "fitting content should not warn");
+}
+
+#[test]
+fn test_overflow_guard_ignores_height_with_auto_height() {
+    let config = Config::new()
+        .width(200)
+        .height(100)
+        .auto_height(true)
+        .overflow_guard(true);
+
+    let (_, warnings) = render_with_warnings(TALL_HTML, config).expect("should render");
+    assert!(warnings.is_empty(), "auto_height output fits its content");
+}
+
+#[test]
+fn test_overflow_guard_horizontal() {
+    let html = r#"<html><body style="margin: 0;"><div style="width: 300px; height: 10px;"></div></body></html>"#;
+    let config = Config::new().width(200).height(100).overflow_guard(true);
+
+    let (_, warnings) = render_with_warnings(html, config).expect("should render");
+    assert_eq!(
+        warnings,
+        vec![Warning::ContentClipped {
+            overflow_x: 100,
+            overflow_y: 0,
+        }]
+    );
+}
+
+#[test]
+fn test_strict_turns_warning_into_error() {
+    let config = Config::new()
+        .width(200)
+        .height(100)
+        .overflow_guard(true)
+        .strict(true);
+
+    let result = render(TALL_HTML, config);
+    assert!(
+        matches!(result, Err(Error::Strict(Warning::ContentClipped { .. }))),
+        "strict mode should fail on clipping"
+    );
+}
+
+/// HTML with a 50x50 image at the top-left whose data URI is `payload` bytes of base64.
+fn data_uri_html(payload: usize) -> String {
+    format!(
+        r#"<html><body style="margin: 0; background: white;"><img width="50" height="50" src="data:image/png;base64,{}"></body></html>"#,
+        "A".repeat(payload)
+    )
+}
+
+#[test]
+fn test_max_resource_bytes_skips_oversized_data_uri() {
+    let html = data_uri_html(4096);
+    let config = Config::new().max_resource_bytes(1024);
+
+    let (bytes, warnings) = render_with_warnings(&html, config).expect("should render");
+    assert_eq!(
+        warnings,
+        vec![Warning::ResourceTooLarge {
+            element: "img".to_string(),
+            attribute: "src".to_string(),
+            bytes: "data:image/png;base64,".len() + 4096,
+            limit: 1024,
+        }]
+    );
+
+    // The image area shows the body background
+    let decoder = png::Decoder::new(bytes.as_slice());
+    let mut reader = decoder.read_info().expect("should read PNG header");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).expect("should decode PNG");
+    assert_eq!(&pixels[..4], &[255, 255, 255, 255]);
+}
+
+#[test]
+fn test_max_resource_bytes_allows_small_data_uri() {
+    let html = data_uri_html(16);
+    let config = Config::new().max_resource_bytes(1024);
+
+    let (_, warnings) = render_with_warnings(&html, config).expect("should render");
+    assert!(warnings.is_empty(), "data URIs under the limit are kept");
+}
+
+#[test]
+fn test_max_resource_bytes_strict() {
+    let config = Config::new().max_resource_bytes(1024).strict(true);
+
+    let result = render(&data_uri_html(4096), config);
+    assert!(
+        matches!(result, Err(Error::Strict(Warning::ResourceTooLarge { .. }))),
+        "strict mode should fail on oversized resources"
+    );
+}
+
+const UNREACHABLE_IMAGE_HTML: &str = r#"
+    <html>
+    <body style="margin: 0;">
+        <img width="50" height="50" src="http://unreachable.invalid/logo.png">
+    </body>
+    </html>
+"#;
+
+#[test]
+fn test_best_effort_warns_on_unreachable_image() {
+    let config = Config::new().resource_policy(ResourcePolicy::BestEffort);
+
+    let (_, warnings) =
+        render_with_warnings(UNREACHABLE_IMAGE_HTML, config).expect("should render");
+    assert!(
+        matches!(
+            warnings.as_slice(),
+            [Warning::ResourceNotLoaded { url, .. }] if url == "http://unreachable.invalid/logo.png"
+        ),
+        "unexpected warnings: {:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_strict_resource_policy_fails_on_unreachable_image() {
+    let config = Config::new().fail_on_network(true);
+
+    let result = render(UNREACHABLE_IMAGE_HTML, config);
+    assert!(
+        matches!(&result, Err(Error::Network { url, .. }) if url == "http://unreachable.invalid/logo.png"),
+        "expected a network error, got {:?}",
+        result.map(|bytes| bytes.len())
+    );
+}
+
+#[test]
+fn test_resource_policy_covers_stylesheets_and_background_images() {
+    let html = r#"
+        <html>
+        <head><link rel="stylesheet" href="https://unreachable.invalid/site.css"></head>
+        <body style="background-image: url(https://unreachable.invalid/bg.png);"></body>
+        </html>
+    "#;
+
+    let (_, warnings) = render_with_warnings(html, Config::new()).expect("should render");
+    let urls: Vec<&str> = warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            Warning::ResourceNotLoaded { url, .. } => Some(url.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://unreachable.invalid/site.css",
+            "https://unreachable.invalid/bg.png"
+        ]
+    );
+}